@@ -0,0 +1,245 @@
+//! Serves the mock fixtures over HTTP for developing providers and retry logic end-to-end
+//!
+//! ```sh
+//! cargo run --example dev-server -- --latency 250 --error-rate 0.2 --error-status 429
+//! ```
+use clap::{Arg, ArgAction, Command};
+use owo_colors::OwoColorize;
+use rand::Rng;
+use std::{
+    error::Error,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::sleep,
+};
+
+#[derive(Debug)]
+struct DevServerOptions {
+    fixtures: PathBuf,
+    latency: u64,
+    error_rate: f64,
+    error_status: u16,
+}
+
+fn parse_error_rate(value: &str) -> Result<f64, String> {
+    let rate = value
+        .parse::<f64>()
+        .map_err(|_| format!("{} is not a number", value))?;
+
+    match (0.0..=1.0).contains(&rate) {
+        true => Ok(rate),
+        false => Err(String::from("Error rate must be between 0.0 and 1.0")),
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("html") => "text/html",
+        Some("gif") => "image/gif",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("mp4") => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Maps a request target onto a file inside the fixture directory, rejecting path traversal
+fn resolve_fixture(fixtures: &Path, target: &str) -> Option<PathBuf> {
+    let path = target.split('?').next().unwrap_or_default();
+    let relative = Path::new(path.trim_start_matches('/'));
+
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let file = fixtures.join(relative);
+    file.is_file().then_some(file)
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), std::io::Error> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        status_text(status),
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    options: Arc<DevServerOptions>,
+) -> Result<(), std::io::Error> {
+    let mut buf = vec![0u8; 8192];
+    let mut read = 0;
+
+    // Only the request head is of interest, bodies are ignored
+    loop {
+        let n = stream.read(&mut buf[read..]).await?;
+        read += n;
+        if n == 0 || buf[..read].windows(4).any(|w| w == b"\r\n\r\n") || read == buf.len() {
+            break;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_owned();
+    let target = request_line.next().unwrap_or_default().to_owned();
+
+    if options.latency > 0 {
+        sleep(Duration::from_millis(options.latency)).await;
+    }
+
+    let inject_error = options.error_rate > 0.0 && rand::thread_rng().gen_bool(options.error_rate);
+
+    let status = match (method.as_str(), inject_error) {
+        (_, true) => {
+            let body = format!("Injected {} error", options.error_status);
+            write_response(
+                &mut stream,
+                options.error_status,
+                "text/plain",
+                body.as_bytes(),
+            )
+            .await?;
+            options.error_status
+        }
+        ("GET", false) => match resolve_fixture(&options.fixtures, &target) {
+            Some(file) => {
+                let body = tokio::fs::read(&file).await?;
+                write_response(&mut stream, 200, content_type(&file), &body).await?;
+                200
+            }
+            None => {
+                write_response(&mut stream, 404, "text/plain", b"Fixture not found").await?;
+                404
+            }
+        },
+        _ => {
+            write_response(&mut stream, 400, "text/plain", b"Only GET is supported").await?;
+            400
+        }
+    };
+
+    let status = match status {
+        200 => status.green().to_string(),
+        _ => status.red().to_string(),
+    };
+    println!("{} {} {}", method.bold(), target, status);
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let matches = Command::new("dev-server")
+        .about("Serves mock fixtures over HTTP with latency and error injection")
+        .arg(
+            Arg::new("port")
+                .short('p')
+                .long("port")
+                .long_help("Port to listen on")
+                .value_parser(clap::value_parser!(u16))
+                .default_value("8080")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("fixtures")
+                .long("fixtures")
+                .long_help("Directory the fixtures are served from")
+                .value_name("PATH")
+                .default_value("tests/mocks")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("latency")
+                .long("latency")
+                .long_help("Latency in milliseconds added to every response")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("0")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("error-rate")
+                .long("error-rate")
+                .long_help("Share of requests answered with --error-status [0.0-1.0]")
+                .value_parser(parse_error_rate)
+                .default_value("0")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("error-status")
+                .long("error-status")
+                .long_help("Status code used for injected errors")
+                .value_parser(clap::value_parser!(u16).range(400..=599))
+                .default_value("500")
+                .action(ArgAction::Set),
+        )
+        .get_matches();
+
+    let port = *matches.get_one::<u16>("port").unwrap();
+    let options = Arc::new(DevServerOptions {
+        fixtures: PathBuf::from(matches.get_one::<String>("fixtures").unwrap()),
+        latency: *matches.get_one::<u64>("latency").unwrap(),
+        error_rate: *matches.get_one::<f64>("error-rate").unwrap(),
+        error_status: *matches.get_one::<u16>("error-status").unwrap(),
+    });
+
+    if !options.fixtures.is_dir() {
+        return Err(format!("Fixture directory {} not found", options.fixtures.display()).into());
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    println!(
+        "Serving {} on {}",
+        options.fixtures.display().bold(),
+        format!("http://127.0.0.1:{}", port).bold()
+    );
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let options = Arc::clone(&options);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, options).await {
+                eprintln!("Connection error: {}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,22 @@
+use super::crawl::{crawl_and_download, get_unsorted_command, CrawlSource};
+use crate::{cli::CliAccountCommand, utils::state::SharedState};
+use std::{error::Error, sync::Arc};
+use tokio::sync::Mutex;
+
+pub async fn handle_account_command(
+    cmd: CliAccountCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliAccountCommand {
+        listing,
+        credentials,
+        options,
+    } = cmd;
+
+    // Every request of the crawl is sent with the token of this account
+    shared_state.lock().await.reddit_account = Some(credentials.clone());
+
+    let cmd = get_unsorted_command(credentials.username, options);
+    crawl_and_download(CrawlSource::Account(listing), cmd, client, shared_state).await
+}
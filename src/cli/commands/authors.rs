@@ -0,0 +1,132 @@
+use crate::{
+    cli::{AuthorSource, CliAuthorsCommand},
+    clients::{self, api_types::reddit::submitted_response::RedditSubmittedResponse, ArchiveQuery},
+    reddit_parser::RedditPostParser,
+    utils::{self, state::SharedState},
+};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter},
+    sync::Arc,
+};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{info, warn};
+
+// Fetched pages that may wait for the parser before fetching pauses
+const PAGE_BUFFER: usize = 4;
+
+/// Crawls a subreddit or search and lists the authors of its posts, nothing is downloaded
+pub async fn handle_authors_command(
+    cmd: CliAuthorsCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliAuthorsCommand {
+        source,
+        listing,
+        format,
+        file,
+    } = cmd;
+    let options = &listing.options;
+    let resource = listing.resource.as_str();
+
+    let reddit_client = clients::RedditClient::default();
+    let reddit_parser = RedditPostParser {
+        preview_size: options.preview_size,
+        fallback_ytdlp: options.fallback_ytdlp,
+    };
+
+    let mock_responses = match &options.mock {
+        Some(mock_file) => {
+            let file = fs::read_to_string(mock_file)
+                .map_err(|e| format!("Failed to read mock file: {}", e))?;
+
+            Some(
+                serde_json::from_str::<Vec<RedditSubmittedResponse>>(&file)
+                    .map_err(|e| format!("Failed to parse mock file: {}", e))?,
+            )
+        }
+        None => None,
+    };
+
+    let archive = match (source, options.backend.archive()) {
+        (AuthorSource::Subreddit, Some(archive)) if !clients::is_combined_subreddit(resource) => {
+            Some(archive)
+        }
+        (_, Some(archive)) => {
+            warn!(
+                "[BACKEND] {} only indexes single subreddits, fetching {} from Reddit",
+                archive, resource
+            );
+            None
+        }
+        (_, None) => None,
+    };
+
+    let (page_tx, mut page_rx) = mpsc::channel::<RedditSubmittedResponse>(PAGE_BUFFER);
+
+    let listing = &listing;
+    let fetch = async move {
+        match mock_responses {
+            Some(responses) => {
+                for response in responses {
+                    let _ = page_tx.send(response).await;
+                }
+                Ok(())
+            }
+            None => match (source, archive) {
+                (AuthorSource::Subreddit, Some(archive)) => clients::get_archive_submissions(
+                    client,
+                    shared_state,
+                    archive,
+                    ArchiveQuery::Subreddit(resource),
+                    options,
+                    &page_tx,
+                )
+                .await
+                .map_err(clients::RedditProviderError::from),
+                (AuthorSource::Subreddit, _) => {
+                    reddit_client
+                        .get_subreddit_submissions(client, shared_state, listing, options, &page_tx)
+                        .await
+                }
+                (AuthorSource::Search, _) => {
+                    reddit_client
+                        .get_search_submissions(client, shared_state, listing, options, &page_tx)
+                        .await
+                }
+            },
+        }
+    };
+
+    // Only posts passing the filters count, like in a crawl that downloads them
+    let collect = async {
+        let mut posts = Vec::new();
+        while let Some(page) = page_rx.recv().await {
+            posts.extend(
+                reddit_parser
+                    .parse(&page)
+                    .into_iter()
+                    .filter(|p| options.filter.matches(p)),
+            );
+        }
+        posts
+    };
+
+    let (fetched, posts) = tokio::join!(fetch, collect);
+    fetched?;
+
+    let ranks = utils::get_author_ranks(&posts);
+
+    match file {
+        Some(file) => {
+            utils::write_author_list(BufWriter::new(File::create(&file)?), &ranks, format)?;
+            info!("Wrote {} authors to {}", ranks.len(), file);
+        }
+        // Stdout only gets the list, so it can be piped into a batch file
+        None => utils::write_author_list(io::stdout().lock(), &ranks, format)?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use super::{
+    crawl::{crawl_and_download, CrawlSource},
+    handle_domain_command, handle_search_command, handle_user_command,
+};
+use crate::{
+    cli::{
+        CliBatchCommand, CliRedditCommand, CliSharedOptions, RedditCategoryFilter,
+        RedditTimeframeFilter,
+    },
+    clients::RedditProviderError,
+    utils::{self, state::SharedState, BatchTarget, BatchTargetKind, ProgressMode, SyncProgress},
+};
+use futures_util::{stream, StreamExt};
+use std::{error::Error, fs, path::Path, sync::Arc, time::Duration};
+use tokio::{
+    sync::{Mutex, Semaphore},
+    time::sleep,
+};
+use tracing::{info, warn};
+
+// Pause before retrying a target that was rate limited by Reddit
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+
+pub async fn handle_batch_command(
+    cmd: CliBatchCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliBatchCommand {
+        ref path,
+        ref category,
+        ref timeframe,
+        parallel_targets,
+        ref options,
+    } = cmd;
+
+    let targets = read_batch_targets(path)?;
+    let failed = run_batch_targets(
+        &targets,
+        category,
+        timeframe,
+        options,
+        parallel_targets,
+        client,
+        shared_state,
+    )
+    .await;
+
+    info!("Finished {} targets - {} failed", targets.len(), failed);
+
+    Ok(())
+}
+
+/// Reads a line based or TOML (`.toml`) batch file
+pub fn read_batch_targets(path: &str) -> Result<Vec<BatchTarget>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read batch file {}: {}", path, e))?;
+
+    let targets = match Path::new(path).extension().is_some_and(|e| e == "toml") {
+        true => utils::parse_batch_targets_toml(&content)?,
+        false => utils::parse_batch_targets(&content)?,
+    };
+
+    Ok(targets)
+}
+
+/// Crawls the targets, `parallel_targets` of them at once, and returns how many of them failed
+pub async fn run_batch_targets(
+    targets: &[BatchTarget],
+    category: &RedditCategoryFilter,
+    timeframe: &RedditTimeframeFilter,
+    options: &CliSharedOptions,
+    parallel_targets: usize,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> usize {
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    if parallel_targets > 1 {
+        // `--tasks` limits the downloads of all targets together
+        shared_state.lock().await.download_slots =
+            Some(Arc::new(Semaphore::new(options.concurrency as usize)));
+    }
+
+    // JSON mode reports every crawl as events instead
+    let sync_progress = (options.progress != ProgressMode::Hidden)
+        .then(|| Arc::new(SyncProgress::new(targets.len() as u64)));
+    shared_state.lock().await.sync_progress = sync_progress.clone();
+
+    let outcomes = stream::iter(targets.iter().enumerate())
+        .map(|(i, target)| {
+            let shutdown = &shutdown;
+            let sync_progress = &sync_progress;
+            async move {
+                if shutdown.is_requested() {
+                    return TargetOutcome::Skipped;
+                }
+
+                match sync_progress {
+                    Some(sync_progress) => sync_progress.start_resource(&target.to_string()),
+                    None => info!("[{}/{}] {}", i + 1, targets.len(), target),
+                }
+
+                let (result, received_bytes, downloads_failed) = match parallel_targets {
+                    1 => {
+                        let before = get_crawl_totals(shared_state).await;
+                        let result = crawl_target(
+                            target,
+                            category,
+                            timeframe,
+                            options,
+                            client,
+                            shared_state,
+                        )
+                        .await;
+                        let after = get_crawl_totals(shared_state).await;
+                        (result, after.0 - before.0, after.1 - before.1)
+                    }
+                    // Every target crawls with a state of its own, see `SharedState::fork`
+                    _ => {
+                        let (fork, forked_bytes) = {
+                            let ss = shared_state.lock().await;
+                            (Arc::new(Mutex::new(ss.fork())), ss.received_bytes)
+                        };
+                        let result =
+                            crawl_target(target, category, timeframe, options, client, &fork).await;
+                        let fork = fork.lock().await;
+                        shared_state.lock().await.join(&fork, forked_bytes);
+                        (
+                            result,
+                            fork.received_bytes.saturating_sub(forked_bytes),
+                            fork.downloads_failed,
+                        )
+                    }
+                };
+
+                if let Some(sync_progress) = sync_progress {
+                    let summary = match &result {
+                        Ok(()) => format!(
+                            "{:.2} MB, {} failed",
+                            received_bytes as f64 / 1024.0 / 1024.0,
+                            downloads_failed
+                        ),
+                        Err(e) => format!("failed - {}", e),
+                    };
+                    sync_progress.finish_resource(&target.to_string(), &summary);
+                }
+
+                match result {
+                    Ok(()) => TargetOutcome::Crawled,
+                    Err(e) => {
+                        warn!("[TARGET_FAILED] {} - {}", target, e);
+                        TargetOutcome::Failed
+                    }
+                }
+            }
+        })
+        .buffer_unordered(parallel_targets.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    shared_state.lock().await.download_slots = None;
+    shared_state.lock().await.sync_progress = None;
+    if let Some(sync_progress) = &sync_progress {
+        sync_progress.post_report();
+    }
+
+    let skipped = outcomes
+        .iter()
+        .filter(|o| **o == TargetOutcome::Skipped)
+        .count();
+    if skipped > 0 {
+        info!("[INTERRUPTED] Skipped the remaining {} targets", skipped);
+    }
+
+    outcomes
+        .iter()
+        .filter(|o| **o == TargetOutcome::Failed)
+        .count()
+}
+
+/// Bytes received and downloads failed so far, the difference is what a target added
+async fn get_crawl_totals(shared_state: &Arc<Mutex<SharedState>>) -> (u64, u64) {
+    let ss = shared_state.lock().await;
+    (ss.received_bytes, ss.downloads_failed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetOutcome {
+    Crawled,
+    Failed,
+    // Not started because of Ctrl-C
+    Skipped,
+}
+
+/// Crawls a target, once more after a cooldown when it was rate limited
+async fn crawl_target(
+    target: &BatchTarget,
+    category: &RedditCategoryFilter,
+    timeframe: &RedditTimeframeFilter,
+    options: &CliSharedOptions,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let get_cmd = || CliRedditCommand {
+        resource: target.resource.clone(),
+        category: category.clone(),
+        timeframe: timeframe.clone(),
+        deep: false,
+        crawl_authors: false,
+        max_authors: None,
+        options: options.clone(),
+    };
+
+    let result = handle_target(target, get_cmd(), client, shared_state).await;
+
+    match result {
+        Err(e) if matches!(e.downcast_ref(), Some(RedditProviderError::TooManyRequests)) => {
+            info!(
+                "Rate limited by Reddit, retrying {} in {}s",
+                target,
+                RATE_LIMIT_COOLDOWN.as_secs()
+            );
+            sleep(RATE_LIMIT_COOLDOWN).await;
+            handle_target(target, get_cmd(), client, shared_state).await
+        }
+        result => result,
+    }
+}
+
+async fn handle_target(
+    target: &BatchTarget,
+    cmd: CliRedditCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    // Every target has its own cache, tokens and throttling carry over between targets
+    {
+        let mut ss = shared_state.lock().await;
+        let fresh = SharedState::default();
+        ss.file_cache_path = fresh.file_cache_path;
+        ss.file_cache = fresh.file_cache;
+    }
+
+    match target.kind {
+        BatchTargetKind::User => handle_user_command(cmd, client, shared_state).await,
+        // Crawled directly, `--crawl-authors` runs batches of its own
+        BatchTargetKind::Subreddit => {
+            crawl_and_download(CrawlSource::Subreddit, cmd, client, shared_state).await
+        }
+        BatchTargetKind::Search => handle_search_command(cmd, client, shared_state).await,
+        BatchTargetKind::Domain => handle_domain_command(cmd, client, shared_state).await,
+    }
+}
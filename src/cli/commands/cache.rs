@@ -0,0 +1,121 @@
+use crate::{
+    cli::CliCacheCommand,
+    utils::{
+        self,
+        state::{
+            find_cache_files, get_cache_from_serde_value, FileCacheLatest, FileCacheVersion,
+            PartialFileCache,
+        },
+        CacheAction, CacheStats,
+    },
+};
+use std::{error::Error, fs, path::Path, str::FromStr};
+use tracing::info;
+
+pub fn handle_cache_command(cmd: CliCacheCommand) -> Result<(), Box<dyn Error>> {
+    let CliCacheCommand { action, path, deep } = cmd;
+    let root = Path::new(&path);
+
+    if action == CacheAction::Rebuild {
+        return rebuild_cache_file(root);
+    }
+
+    let cache_files = find_cache_files(root)
+        .map_err(|e| format!("Failed to read output directory {}: {}", path, e))?;
+
+    if cache_files.is_empty() {
+        info!("No cached resources found in {}", path);
+        return Ok(());
+    }
+
+    let mut total = CacheStats::default();
+    let mut changed = 0;
+
+    for cache_file in &cache_files {
+        let folder = cache_file.parent().unwrap_or(root);
+        let resource = folder.strip_prefix(root).unwrap_or(folder).display();
+
+        let value = serde_json::from_str::<serde_json::Value>(&fs::read_to_string(cache_file)?)?;
+        let version = serde_json::from_value::<PartialFileCache>(value.clone())?.version;
+        let mut file_cache = get_cache_from_serde_value(value)
+            .map_err(|e| format!("Failed to read {}: {}", cache_file.display(), e))?;
+
+        let count = match action {
+            CacheAction::Stats => {
+                let stats = utils::get_cache_stats(folder, &file_cache);
+                info!(
+                    "{} - {} cached, {} downloaded ({:.2} MB), {} failed, {} missing",
+                    resource,
+                    stats.entries,
+                    stats.downloaded,
+                    stats.bytes as f64 / 1024.0 / 1024.0,
+                    stats.failed,
+                    stats.missing
+                );
+                total += stats;
+                continue;
+            }
+            CacheAction::Verify => utils::verify_cache(folder, &mut file_cache, deep),
+            CacheAction::Prune => utils::prune_cache(&mut file_cache),
+            CacheAction::Migrate => match version {
+                FileCacheVersion::Latest => 0,
+                FileCacheVersion::V1 => 1,
+            },
+            CacheAction::Rebuild => unreachable!("Rebuilt before reading the caches"),
+        };
+
+        if count == 0 {
+            continue;
+        }
+        fs::write(cache_file, serde_json::to_string(&file_cache)?)?;
+        changed += 1;
+
+        match action {
+            CacheAction::Verify => info!(
+                "{} - marked {} missing or corrupt files as failed",
+                resource, count
+            ),
+            CacheAction::Prune => info!("{} - removed {} failed entries", resource, count),
+            _ => info!("{} - upgraded to the latest cache version", resource),
+        }
+    }
+
+    match action {
+        CacheAction::Stats => info!(
+            "{} resources - {} cached, {} downloaded ({:.2} MB), {} failed, {} missing",
+            cache_files.len(),
+            total.entries,
+            total.downloaded,
+            total.bytes as f64 / 1024.0 / 1024.0,
+            total.failed,
+            total.missing
+        ),
+        _ => info!("Updated {} of {} caches", changed, cache_files.len()),
+    }
+
+    Ok(())
+}
+
+/// Adds the downloaded files of a folder to its cache, creating it when it was lost
+fn rebuild_cache_file(folder: &Path) -> Result<(), Box<dyn Error>> {
+    let cache_file = folder.join("cache.json");
+    let mut file_cache = match cache_file.is_file() {
+        true => FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)
+            .map_err(|e| format!("Failed to read {}: {}", cache_file.display(), e))?,
+        false => FileCacheLatest::default(),
+    };
+
+    let added = utils::rebuild_cache(folder, &mut file_cache)
+        .map_err(|e| format!("Failed to read folder {}: {}", folder.display(), e))?;
+    if added == 0 {
+        info!(
+            "No files named after the default file scheme found in {}",
+            folder.display()
+        );
+        return Ok(());
+    }
+
+    fs::write(&cache_file, serde_json::to_string(&file_cache)?)?;
+    info!("{} - added {} files to the cache", folder.display(), added);
+    Ok(())
+}
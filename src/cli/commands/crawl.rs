@@ -0,0 +1,724 @@
+use crate::{
+    cli::{
+        CliRedditCommand, CliSharedOptions, RedditAccountListing, RedditCategoryFilter,
+        RedditTimeframeFilter,
+    },
+    clients::{
+        self, api_types::reddit::submitted_response::RedditSubmittedResponse, ArchiveQuery,
+        RedgifsUserResponse,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditPostParser},
+    utils::{
+        self, download_crawler_post, emit_event,
+        state::{DownloadStats, FileCacheLatest, LastDownloadStatus, ResourceStatus, SharedState},
+        CrawlEvent, CrawlSummary, DownloadProgress, OutputMode,
+    },
+};
+use anyhow::anyhow;
+use owo_colors::OwoColorize;
+use std::{error::Error, fs, mem, path::Path, str::FromStr, sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, oneshot, Mutex, Semaphore},
+    task::JoinSet,
+    time::sleep,
+};
+use tracing::{info, warn};
+
+// Fetched pages that may wait for the downloader before fetching pauses
+const PAGE_BUFFER: usize = 4;
+
+/// Listing a crawl pages through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrawlSource {
+    User,
+    Subreddit,
+    Search,
+    Domain,
+    /// Posts the authenticated account saved or upvoted
+    Account(RedditAccountListing),
+    RedgifsUser,
+}
+
+impl CrawlSource {
+    fn stem(&self, resource: &str) -> String {
+        match self {
+            CrawlSource::User => format!("user/{}", resource),
+            CrawlSource::Subreddit => format!("subreddit/{}", resource),
+            CrawlSource::Search => format!("search/{}", resource),
+            CrawlSource::Domain => format!("domain/{}", resource),
+            CrawlSource::Account(listing) => format!("{}/{}", listing, resource),
+            CrawlSource::RedgifsUser => format!("redgifs/{}", resource),
+        }
+    }
+
+    fn spinner_text(&self, resource: &str) -> String {
+        match self {
+            CrawlSource::User => format!("Fetching posts from {}{}", "/u/".bold(), resource.bold()),
+            CrawlSource::Subreddit => {
+                format!("Fetching posts from {}{}", "/r/".bold(), resource.bold())
+            }
+            CrawlSource::Search => format!("Fetching posts for search term {}", resource.bold()),
+            CrawlSource::Domain => format!("Fetching posts linking to {}", resource.bold()),
+            CrawlSource::Account(listing) => format!(
+                "Fetching {} posts of {}{}",
+                listing,
+                "/u/".bold(),
+                resource.bold()
+            ),
+            CrawlSource::RedgifsUser => {
+                format!(
+                    "Fetching gifs from {}{}",
+                    "redgifs/".bold(),
+                    resource.bold()
+                )
+            }
+        }
+    }
+
+    fn label(&self, resource: &str) -> String {
+        match self {
+            CrawlSource::User => format!("The user, {}", resource),
+            CrawlSource::Subreddit => format!("The subreddit, {}", resource),
+            CrawlSource::Search => format!("The search term, {}", resource),
+            CrawlSource::Domain => format!("The domain, {}", resource),
+            CrawlSource::Account(_) => format!("The account, {}", resource),
+            CrawlSource::RedgifsUser => format!("The Redgifs user, {}", resource),
+        }
+    }
+}
+
+/// A fetched page, parsed into posts by the downloader
+enum CrawlPage {
+    Reddit(RedditSubmittedResponse),
+    // A profile is sent at once, its galleries span several pages
+    Redgifs(Vec<RedgifsUserResponse>),
+}
+
+impl From<RedditSubmittedResponse> for CrawlPage {
+    fn from(response: RedditSubmittedResponse) -> Self {
+        CrawlPage::Reddit(response)
+    }
+}
+
+/// Command of a listing without categories, e.g. saved posts or a Redgifs profile
+pub fn get_unsorted_command(resource: String, options: CliSharedOptions) -> CliRedditCommand {
+    CliRedditCommand {
+        resource,
+        category: RedditCategoryFilter::New,
+        timeframe: RedditTimeframeFilter::All,
+        deep: false,
+        crawl_authors: false,
+        max_authors: None,
+        options,
+    }
+}
+
+/// Crawls a listing and downloads its posts while later pages are still fetched
+pub async fn crawl_and_download(
+    source: CrawlSource,
+    cmd: CliRedditCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliRedditCommand {
+        ref resource,
+        ref options,
+        ..
+    } = cmd;
+
+    let (tx, mut rx) = oneshot::channel::<bool>();
+    let reddit_client = clients::RedditClient::default();
+    let reddit_parser = RedditPostParser {
+        preview_size: options.preview_size,
+        fallback_ytdlp: options.fallback_ytdlp,
+    };
+
+    // Batch and watch runs draw every crawl below their aggregate bar
+    let sync_progress = shared_state.lock().await.sync_progress.clone();
+    let mut spinner = match &sync_progress {
+        Some(sync_progress) => {
+            utils::CrawlSpinner::new_in(source.spinner_text(resource), sync_progress)
+        }
+        None => utils::CrawlSpinner::new(source.spinner_text(resource), options.output_mode),
+    };
+
+    let stem = source.stem(resource);
+
+    if options.output_mode == OutputMode::Json {
+        emit_event(&CrawlEvent::CrawlStarted { resource: &stem });
+    }
+    shared_state.lock().await.run_report = utils::RunReport::new(&stem);
+    let output_folder = utils::get_output_folder(&options.output, &stem);
+
+    if !options.dry_run {
+        utils::prepare_output_folder(&output_folder)?;
+    }
+
+    let file_cache_path = format!("{}/cache.json", output_folder);
+
+    if Path::new(&file_cache_path).exists() {
+        let file_cache = fs::read_to_string(&file_cache_path)
+            .map_err(|e| format!("Failed to read {}: {}", file_cache_path, e))?;
+        let file_cache = FileCacheLatest::from_str(&file_cache)?;
+
+        let mut ss = shared_state.lock().await;
+        ss.file_cache_path = Some(file_cache_path.clone());
+        ss.file_cache = file_cache.clone();
+
+        if file_cache.status.resource != ResourceStatus::Active {
+            if options.force {
+                info!(
+                    "[FLAG] Crawling {} although it has been marked as {} in cache",
+                    &stem, file_cache.status.resource
+                );
+                // Detected again if it is still unavailable
+                ss.file_cache.status.resource = ResourceStatus::Active;
+            } else {
+                ss.file_cache.status.last_download = LastDownloadStatus::Success;
+                ss.resources_gone += 1;
+                ss.write_file_cache(&file_cache_path)?;
+                spinner.fail(&format!(
+                    "{} has been marked as {} in cache. Skipping download",
+                    source.label(resource),
+                    file_cache.status.resource
+                ));
+                return Ok(());
+            }
+        }
+    }
+
+    let archive = match (source, options.backend.archive()) {
+        // Saved posts are private and Redgifs profiles are not archived
+        (CrawlSource::Account(_) | CrawlSource::RedgifsUser, _) => None,
+        (CrawlSource::Search | CrawlSource::Domain, Some(archive)) => {
+            warn!(
+                "[BACKEND] {} only indexes users and subreddits, fetching {} from Reddit",
+                archive, stem
+            );
+            None
+        }
+        // Front page and multi-subreddit listings are only served by Reddit
+        (CrawlSource::Subreddit, Some(archive)) if clients::is_combined_subreddit(resource) => {
+            warn!(
+                "[BACKEND] {} only indexes single subreddits, fetching {} from Reddit",
+                archive, stem
+            );
+            None
+        }
+        (_, archive) => archive,
+    };
+
+    // Mock pages are read up front so a broken mock file fails before anything is fetched
+    let mock_responses = match &options.mock {
+        Some(mock_file) => {
+            info!("[FLAG] Mock mode enabled");
+
+            let file = fs::read_to_string(mock_file)
+                .map_err(|e| format!("Failed to read mock file: {}", e))?;
+
+            Some(match source {
+                CrawlSource::RedgifsUser => vec![CrawlPage::Redgifs(
+                    serde_json::from_str::<Vec<RedgifsUserResponse>>(&file)
+                        .expect("Failed to parse mock file"),
+                )],
+                _ => serde_json::from_str::<Vec<RedditSubmittedResponse>>(&file)
+                    .expect("Failed to parse mock file")
+                    .into_iter()
+                    .map(CrawlPage::Reddit)
+                    .collect(),
+            })
+        }
+        None => None,
+    };
+
+    let download_stats: Arc<Mutex<DownloadStats>> = Arc::new(Mutex::new(DownloadStats::default()));
+    let download_progress: Arc<Mutex<DownloadProgress>> =
+        Arc::new(Mutex::new(match &sync_progress {
+            Some(sync_progress) => DownloadProgress::new_in(sync_progress, 0, options.progress),
+            None => DownloadProgress::with_mode(0, options.progress),
+        }));
+    // Downloads run while the spinner is still fetching pages, the bar is shown afterwards
+    download_progress.lock().await.hide();
+    shared_state.lock().await.byte_progress = Some(download_progress.lock().await.byte_progress());
+
+    // Concurrent batch targets share their download slots
+    let semaphore = shared_state
+        .lock()
+        .await
+        .download_slots
+        .clone()
+        .unwrap_or_else(|| Arc::new(Semaphore::new(options.concurrency as usize)));
+    let shutdown = shared_state.lock().await.shutdown.clone();
+    // Downloads recorded so far survive a crash of a long crawl
+    let _checkpoints = (!(options.dry_run || options.skip)).then(|| {
+        utils::CacheCheckpoints::spawn(shared_state, &file_cache_path, options.checkpoint)
+    });
+
+    let clockwork_dp = Arc::clone(&download_progress);
+    // Updates the progress bar so it runs smoothly
+    let clockwork_orange = tokio::spawn(async move {
+        loop {
+            if rx.try_recv().is_ok() {
+                break;
+            }
+            clockwork_dp.lock().await.control.tick();
+            sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    // Pages stream from the fetcher into the downloader, so downloads start with the first page
+    let (page_tx, page_rx) = mpsc::channel::<CrawlPage>(PAGE_BUFFER);
+
+    let (cmd, reddit_client) = (&cmd, &reddit_client);
+    let fetch = async move {
+        match mock_responses {
+            Some(pages) => {
+                for page in pages {
+                    let _ = page_tx.send(page).await;
+                }
+                Ok(())
+            }
+            None => match (source, archive) {
+                (CrawlSource::User, Some(archive)) => clients::get_archive_submissions(
+                    client,
+                    shared_state,
+                    archive,
+                    ArchiveQuery::Author(resource),
+                    options,
+                    &page_tx,
+                )
+                .await
+                .map_err(clients::RedditProviderError::from),
+                (CrawlSource::Subreddit, Some(archive)) => clients::get_archive_submissions(
+                    client,
+                    shared_state,
+                    archive,
+                    ArchiveQuery::Subreddit(resource),
+                    options,
+                    &page_tx,
+                )
+                .await
+                .map_err(clients::RedditProviderError::from),
+                (CrawlSource::Subreddit, None) if cmd.deep => {
+                    reddit_client
+                        .get_subreddit_deep_submissions(
+                            client,
+                            shared_state,
+                            cmd,
+                            options,
+                            &page_tx,
+                        )
+                        .await
+                }
+                (CrawlSource::User, None) => {
+                    reddit_client
+                        .get_user_submissions(client, shared_state, cmd, options, &page_tx)
+                        .await
+                }
+                (CrawlSource::Subreddit, _) => {
+                    reddit_client
+                        .get_subreddit_submissions(client, shared_state, cmd, options, &page_tx)
+                        .await
+                }
+                (CrawlSource::Search, _) => {
+                    reddit_client
+                        .get_search_submissions(client, shared_state, cmd, options, &page_tx)
+                        .await
+                }
+                (CrawlSource::Domain, _) => {
+                    reddit_client
+                        .get_domain_submissions(client, shared_state, cmd, options, &page_tx)
+                        .await
+                }
+                (CrawlSource::Account(listing), _) => {
+                    reddit_client
+                        .get_account_submissions(
+                            client,
+                            shared_state,
+                            cmd,
+                            &listing,
+                            options,
+                            &page_tx,
+                        )
+                        .await
+                }
+                (CrawlSource::RedgifsUser, _) => {
+                    let responses = clients::get_redgifs_user_gifs(
+                        client,
+                        shared_state,
+                        resource,
+                        options.limit,
+                    )
+                    .await?;
+                    let _ = page_tx.send(CrawlPage::Redgifs(responses)).await;
+                    Ok(())
+                }
+            },
+        }
+    };
+
+    // Ctrl-C stops fetching right away, even while waiting out a rate limit
+    let fetch = async {
+        tokio::select! {
+            fetched = fetch => fetched,
+            _ = shutdown.wait() => Ok(()),
+        }
+    };
+
+    let download = async {
+        let mut responses: Vec<RedditSubmittedResponse> = Vec::new();
+        let mut posts: Vec<RedditCrawlerPost> = Vec::new();
+        // Only filled by --dry-run and --skip, which list the posts instead of downloading them
+        let mut posts_to_download: Vec<RedditCrawlerPost> = Vec::new();
+        let mut downloads = JoinSet::new();
+        let mut queued: u64 = 0;
+
+        // Owned by the downloader, so the fetcher stops once it hangs up
+        let mut page_rx = page_rx;
+
+        while let Some(page) = page_rx.recv().await {
+            if !(options.dry_run || options.skip) {
+                let ss = shared_state.lock().await;
+                if ss.size_limits.quota_reached(ss.received_bytes) {
+                    info!(
+                        "[QUOTA] Downloaded {:.2} MB, stopping {}",
+                        ss.received_bytes as f64 / 1024.0 / 1024.0,
+                        stem
+                    );
+                    break;
+                }
+            }
+
+            let page_posts = match &page {
+                CrawlPage::Reddit(response) => reddit_parser.parse(response),
+                CrawlPage::Redgifs(responses) => reddit_parser.parse_redgifs_user(responses),
+            }
+            .into_iter()
+            .filter(|p| options.filter.matches(p))
+            .collect::<Vec<_>>();
+            // Kept for the captions, metadata and comments written afterwards
+            if let CrawlPage::Reddit(response) = page {
+                responses.push(response);
+            }
+
+            let ss = shared_state.lock().await;
+            let page_to_download = page_posts
+                .iter()
+                .filter(|p| {
+                    // Try to find the successfully downloaded post in the cache
+                    !ss.file_cache
+                        .files
+                        .iter()
+                        .any(|f| p.id == f.id && f.success)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            mem::drop(ss);
+            if let Some(authors) = shared_state.lock().await.matched_authors.as_mut() {
+                authors.extend(page_posts.iter().map(|p| p.author.clone()));
+            }
+            posts.extend(page_posts);
+
+            queued += page_to_download.len() as u64;
+            spinner.update_text(format!(
+                "{} - {} queued",
+                source.spinner_text(resource),
+                queued
+            ));
+
+            if options.dry_run || options.skip {
+                posts_to_download.extend(page_to_download);
+                continue;
+            }
+
+            download_progress
+                .lock()
+                .await
+                .inc_length(page_to_download.len() as u64);
+            shared_state
+                .lock()
+                .await
+                .transfer_status
+                .lock()
+                .unwrap()
+                .add_queue_depth(&stem, page_to_download.len() as u64);
+
+            for post in page_to_download {
+                if shutdown.is_requested() {
+                    break;
+                }
+
+                let client = client.clone();
+                let output_folder = output_folder.clone();
+
+                let dp_clone = Arc::clone(&download_progress);
+                let ds_clone = Arc::clone(&download_stats);
+                let ss_clone = Arc::clone(shared_state);
+                let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+                let stem = stem.clone();
+                let file_scheme = options.file_scheme.clone();
+
+                downloads.spawn(async move {
+                    ss_clone
+                        .lock()
+                        .await
+                        .transfer_status
+                        .lock()
+                        .unwrap()
+                        .start_download();
+                    let download = download_crawler_post(
+                        &client,
+                        &ss_clone,
+                        &output_folder,
+                        &post,
+                        &file_scheme,
+                    )
+                    .await
+                    // Recorded as failed, so `retry` tries the post again
+                    .unwrap_or_else(|e| utils::DownloadPostResult::ReceivedFailed(e.to_string()));
+
+                    let bytes = match &download {
+                        utils::DownloadPostResult::ReceivedBytes(bytes, _, _, _) => *bytes,
+                        _ => 0.0,
+                    };
+                    ss_clone
+                        .lock()
+                        .await
+                        .transfer_status
+                        .lock()
+                        .unwrap()
+                        .finish_download(&stem, bytes);
+
+                    let mut dl_stats = ds_clone.lock().await;
+                    match &download {
+                        utils::DownloadPostResult::ReceivedBytes(bytes, _, _, _) => {
+                            dl_stats.files_downloaded += 1;
+                            dl_stats.bytes_downloaded += bytes;
+
+                            let dp = dp_clone.lock().await;
+                            dp.update_progress(
+                                dl_stats.files_downloaded,
+                                dp.total_count,
+                                dl_stats.bytes_downloaded,
+                            );
+                        }
+                        utils::DownloadPostResult::ReceivedNotFound
+                        | utils::DownloadPostResult::ReceivedFailed(_) => {
+                            dl_stats.downloads_failed += 1;
+                        }
+                        utils::DownloadPostResult::ReceivedSkipped(_)
+                        | utils::DownloadPostResult::ReceivedUnhandled => {}
+                    }
+                    mem::drop(dl_stats);
+
+                    if let Some(item) = download.into_cache_item(&post) {
+                        ss_clone.lock().await.file_cache.files.push(item);
+                    }
+                    drop(permit);
+                });
+            }
+        }
+
+        (responses, posts, posts_to_download, downloads)
+    };
+
+    let (fetched, (responses, posts, posts_to_download, mut downloads)) =
+        tokio::join!(fetch, download);
+
+    if let Err(e) = fetched {
+        // Posts of the pages before the error are still downloaded and cached
+        while let Some(joined) = downloads.join_next().await {
+            joined?;
+        }
+        tx.send(true)
+            .map_err(|_| anyhow!("Failed sending to oneshot channel"))?;
+        clockwork_orange.await?;
+
+        // Still returned, so the run exits with the code of a failed authentication
+        if let (CrawlSource::Account(_), clients::RedditProviderError::Unauthorized) = (source, &e)
+        {
+            spinner.fail(&format!(
+                "Reddit rejected the credentials of {}. Check the app and account",
+                resource
+            ));
+            return Err(Box::new(e));
+        }
+
+        let status = match e {
+            clients::RedditProviderError::NotFound => Some(ResourceStatus::Deleted),
+            clients::RedditProviderError::Suspended => Some(ResourceStatus::Suspended),
+            clients::RedditProviderError::Banned => Some(ResourceStatus::Banned),
+            clients::RedditProviderError::Private => Some(ResourceStatus::Private),
+            clients::RedditProviderError::Quarantined => Some(ResourceStatus::Quarantined),
+            _ => None,
+        };
+
+        let mut ss = shared_state.lock().await;
+        // Unavailable resources are recorded so later runs skip them without a request
+        if let Some(status) = status {
+            ss.file_cache.status.resource = status.clone();
+            ss.file_cache.status.last_download = LastDownloadStatus::Success;
+            ss.resources_gone += 1;
+            ss.write_file_cache(&file_cache_path)?;
+            spinner.fail(&format!(
+                "{} is {}. Skipping download - cache updated",
+                source.label(resource),
+                status
+            ));
+            return Ok(());
+        }
+
+        ss.file_cache.status.last_download = match e {
+            clients::RedditProviderError::TooManyRequests => LastDownloadStatus::RateLimit,
+            clients::RedditProviderError::Archive(
+                clients::ArchiveClientError::TooManyRequests(_),
+            ) => LastDownloadStatus::RateLimit,
+            clients::RedditProviderError::Forbidden => LastDownloadStatus::Forbidden,
+            _ => LastDownloadStatus::Error,
+        };
+        ss.write_file_cache(&file_cache_path)?;
+        return Err(Box::new(e));
+    }
+
+    if options.mock.is_none() {
+        let mut ss = shared_state.lock().await;
+        ss.file_cache.status.last_download = LastDownloadStatus::Success;
+        ss.write_file_cache(&file_cache_path)?;
+    }
+
+    let ss = shared_state.lock().await;
+    let total_post_len = match options.dry_run || options.skip {
+        true => posts_to_download.len() as u64,
+        false => download_progress.lock().await.total_count,
+    };
+    spinner.success(&format!(
+        "Done, trying to download {} posts. - cached {}",
+        total_post_len,
+        ss.file_cache.files.len()
+    ));
+    mem::drop(ss);
+
+    if options.dry_run {
+        tx.send(true)
+            .map_err(|_| anyhow!("Failed sending to oneshot channel"))?;
+        match &options.export_urls {
+            Some(export_path) => {
+                utils::export_media_urls(
+                    client,
+                    shared_state,
+                    &posts_to_download,
+                    export_path,
+                    options.concurrency,
+                )
+                .await?
+            }
+            None => {
+                utils::print_dry_run(
+                    client,
+                    &output_folder,
+                    &posts_to_download,
+                    &options.file_scheme,
+                    options.concurrency,
+                    options.format,
+                )
+                .await?
+            }
+        }
+        return Ok(());
+    }
+
+    if options.skip {
+        tx.send(true)
+            .map_err(|_| anyhow!("Failed sending to oneshot channel"))?;
+        info!("[FLAG] Download skipped");
+        return Ok(());
+    }
+
+    download_progress.lock().await.show();
+    while let Some(joined) = downloads.join_next().await {
+        joined?;
+    }
+
+    tx.send(true)
+        .map_err(|_| anyhow!("Failed sending to oneshot channel"))?;
+    let dl_stats = download_stats.lock().await;
+    download_progress.lock().await.post_report(
+        dl_stats.files_downloaded,
+        total_post_len,
+        dl_stats.bytes_downloaded,
+    );
+    if options.output_mode == OutputMode::Json {
+        emit_event(&CrawlEvent::CrawlFinished {
+            resource: &stem,
+            downloaded: dl_stats.files_downloaded,
+            failed: dl_stats.downloads_failed,
+            bytes: dl_stats.bytes_downloaded,
+        });
+    }
+    options
+        .notifier
+        .send(
+            client,
+            &CrawlSummary {
+                resource: stem.to_owned(),
+                downloaded: dl_stats.files_downloaded,
+                failed: dl_stats.downloads_failed,
+                bytes: dl_stats.bytes_downloaded,
+            },
+        )
+        .await;
+
+    clockwork_orange.await?;
+
+    if options.export_captions {
+        utils::write_caption_markdown(&output_folder, &responses, &posts, &options.file_scheme)?;
+    }
+
+    if options.write_metadata {
+        utils::write_post_metadata(&output_folder, &responses, &posts, &options.file_scheme)?;
+    }
+
+    let unsupported = utils::report_unsupported_posts(&output_folder, &responses, &reddit_parser);
+    utils::finish_run_report(
+        shared_state,
+        &output_folder,
+        unsupported,
+        !options.quiet && options.output_mode != OutputMode::Json,
+    )
+    .await;
+
+    // Redgifs gifs have no comments on Reddit
+    if options.with_comments && source != CrawlSource::RedgifsUser && !shutdown.is_requested() {
+        match utils::write_post_comments(
+            client,
+            shared_state,
+            &output_folder,
+            &posts,
+            &options.file_scheme,
+        )
+        .await
+        {
+            Ok(written) => info!("Saved the comments of {} posts", written),
+            // The downloads are still recorded in the cache below
+            Err(e) => warn!("[COMMENTS_FAILED] {}", e),
+        }
+    }
+
+    shared_state
+        .lock()
+        .await
+        .write_file_cache(&file_cache_path)?;
+
+    if shutdown.is_requested() {
+        info!(
+            "[INTERRUPTED] Cached the {} downloaded files of {}, the next run continues from there",
+            dl_stats.files_downloaded, stem
+        );
+    }
+
+    if let Some(format) = options.archive {
+        utils::archive_output_folder(&output_folder, format, options.archive_delete);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::{
+    cli::CliDiffCommand,
+    clients,
+    utils::{
+        self,
+        state::{find_cache_files, FileCacheLatest, SharedState},
+        OutputFormat,
+    },
+};
+use std::{error::Error, fs, path::Path, str::FromStr, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Fetches the archived posts of every cached resource again and lists the ones taken down
+pub async fn handle_diff_command(
+    cmd: CliDiffCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliDiffCommand { path, format } = cmd;
+    let root = Path::new(&path);
+
+    let cache_files = find_cache_files(root)
+        .map_err(|e| format!("Failed to read output directory {}: {}", path, e))?;
+
+    let reddit_client = clients::RedditClient::default();
+    let shutdown = shared_state.lock().await.shutdown.clone();
+    let mut takedowns = Vec::new();
+
+    for cache_file in cache_files {
+        if shutdown.is_requested() {
+            break;
+        }
+
+        let folder = cache_file.parent().unwrap_or(root);
+        let resource = match folder.strip_prefix(root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => folder,
+        }
+        .to_string_lossy()
+        .replace('\\', "/");
+
+        let mut file_cache = FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)?;
+        let ids = utils::get_archived_ids(&file_cache);
+        if ids.is_empty() {
+            continue;
+        }
+
+        // A resource that can not be fetched is left as it is, its posts would all look gone
+        let responses = match reddit_client
+            .get_posts_by_id(client, shared_state, &ids)
+            .await
+        {
+            Ok(responses) => responses,
+            Err(e) => {
+                warn!("[DIFF_FAILED] {} - {}", resource, e);
+                continue;
+            }
+        };
+        let posts = responses
+            .into_iter()
+            .flat_map(|r| r.data.children)
+            .map(|child| child.data)
+            .collect::<Vec<_>>();
+
+        let recorded = file_cache.clone();
+        let found = utils::diff_takedowns(&resource, &mut file_cache, &posts);
+        info!(
+            "{} - checked {} archived posts, {} taken down",
+            resource,
+            ids.len(),
+            found.len()
+        );
+        takedowns.extend(found);
+
+        // The next diff only reports posts taken down after this one
+        if file_cache != recorded {
+            fs::write(&cache_file, serde_json::to_string(&file_cache)?)?;
+        }
+    }
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&takedowns)?),
+        OutputFormat::Table => print!("{}", utils::render_takedowns(&takedowns)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use super::crawl::{crawl_and_download, CrawlSource};
+use crate::{cli::CliRedditCommand, utils::state::SharedState};
+use std::{error::Error, sync::Arc};
+use tokio::sync::Mutex;
+
+pub async fn handle_domain_command(
+    cmd: CliRedditCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    crawl_and_download(CrawlSource::Domain, cmd, client, shared_state).await
+}
@@ -0,0 +1,70 @@
+use crate::{
+    cli::CliExplainCommand,
+    clients::{self, api_types::reddit::submitted_response::RedditSubmittedResponse},
+    reddit_parser::RedditPostParser,
+    utils::{self, state::SharedState},
+};
+use owo_colors::OwoColorize;
+use std::{error::Error, fs, sync::Arc};
+use tokio::sync::Mutex;
+
+pub async fn handle_explain_command(
+    cmd: CliExplainCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliExplainCommand {
+        resource,
+        mock,
+        file_scheme,
+    } = cmd;
+
+    let post_id = clients::extract_post_id(&resource)
+        .ok_or_else(|| format!("Could not extract a post ID from {}", resource))?;
+
+    let response = match mock {
+        Some(mock_file) => {
+            let file = fs::read_to_string(mock_file)
+                .map_err(|e| format!("Failed to read mock file: {}", e))?;
+
+            serde_json::from_str::<Vec<RedditSubmittedResponse>>(&file)
+                .map_err(|e| format!("Failed to parse mock file: {}", e))?
+                .into_iter()
+                .next()
+                .ok_or("Mock file does not contain a response")?
+        }
+        _ => {
+            clients::RedditClient::default()
+                .get_post(client, shared_state, post_id)
+                .await?
+        }
+    };
+
+    let reddit_parser = RedditPostParser::default();
+
+    for child in response.data.children.iter() {
+        let data = &child.data;
+        println!("{} {}", "Post".bold(), data.id.bold());
+        println!("  title: {}", data.title);
+        println!("  url: {}", data.url);
+
+        println!("{}", "Parser".bold());
+        let (posts, trace) = reddit_parser.explain(child);
+        for step in trace {
+            println!("  - {}", step);
+        }
+
+        println!("{}", "Plan".bold());
+        if posts.is_empty() {
+            println!("  {}", "Nothing to download".yellow());
+        }
+        for post in posts.iter() {
+            println!("  {}", utils::get_file_name(post, &file_scheme).green());
+            println!("    provider: {:?}", post.provider);
+            println!("    extension: {}", post.extension);
+            println!("    url: {}", post.url);
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,39 @@
+mod account;
+mod authors;
+mod batch;
+mod cache;
+mod crawl;
+mod diff;
+mod domain;
+mod explain;
+mod post;
+mod rebuild_views;
+mod redgifs_user;
+mod report;
+mod retry;
+mod schedule;
 mod search;
+mod stats;
 mod subreddit;
 mod user;
+mod user_comments;
+mod watch;
+pub use account::handle_account_command;
+pub use authors::handle_authors_command;
+pub use batch::handle_batch_command;
+pub use cache::handle_cache_command;
+pub use diff::handle_diff_command;
+pub use domain::handle_domain_command;
+pub use explain::handle_explain_command;
+pub use post::handle_post_command;
+pub use rebuild_views::handle_rebuild_views_command;
+pub use redgifs_user::handle_redgifs_user_command;
+pub use report::handle_report_command;
+pub use retry::handle_retry_command;
+pub use schedule::handle_schedule_command;
 pub use search::handle_search_command;
+pub use stats::handle_stats_command;
 pub use subreddit::handle_subreddit_command;
 pub use user::handle_user_command;
+pub use user_comments::handle_user_comments_command;
+pub use watch::handle_watch_command;
@@ -0,0 +1,312 @@
+use crate::{
+    cli::CliPostCommand,
+    clients::{self, api_types::reddit::submitted_response::RedditSubmittedResponse},
+    reddit_parser::RedditPostParser,
+    utils::{
+        self, download_crawler_post, emit_event,
+        state::{DownloadStats, FileCacheItemLatest, FileCacheLatest, PostDuplicate, SharedState},
+        CrawlEvent, CrawlSummary, DownloadProgress, OutputMode,
+    },
+};
+use owo_colors::OwoColorize;
+use std::{error::Error, fs, path::Path, str::FromStr, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+pub async fn handle_post_command(
+    cmd: CliPostCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliPostCommand {
+        ref resource,
+        include_duplicates,
+        ref options,
+    } = cmd;
+
+    let post_id = clients::extract_post_id(resource)
+        .ok_or_else(|| format!("Could not extract a post ID from {}", resource))?;
+
+    let reddit_client = clients::RedditClient::default();
+    let reddit_parser = RedditPostParser {
+        preview_size: options.preview_size,
+        fallback_ytdlp: options.fallback_ytdlp,
+    };
+
+    let mut spinner = utils::CrawlSpinner::new(
+        format!("Fetching post {}", post_id.bold()),
+        options.output_mode,
+    );
+
+    let stem = format!("post/{}", post_id);
+
+    if options.output_mode == OutputMode::Json {
+        emit_event(&CrawlEvent::CrawlStarted { resource: &stem });
+    }
+    shared_state.lock().await.run_report = utils::RunReport::new(&stem);
+    let output_folder = utils::get_output_folder(&options.output, &stem);
+
+    if !options.dry_run {
+        utils::prepare_output_folder(&output_folder)?;
+    }
+
+    let file_cache_path = format!("{}/cache.json", output_folder);
+
+    if Path::new(&file_cache_path).exists() {
+        let file_cache = fs::read_to_string(&file_cache_path)?;
+        let file_cache = FileCacheLatest::from_str(&file_cache)?;
+
+        let mut ss = shared_state.lock().await;
+        ss.file_cache_path = Some(file_cache_path.clone());
+        ss.file_cache = file_cache;
+    }
+
+    let response = match &options.mock {
+        Some(mock_file) => {
+            info!("[FLAG] Mock mode enabled");
+
+            let file = fs::read_to_string(mock_file)
+                .map_err(|e| format!("Failed to read mock file: {}", e))?;
+
+            serde_json::from_str::<Vec<RedditSubmittedResponse>>(&file)
+                .expect("Failed to parse mock file")
+                .into_iter()
+                .next()
+                .ok_or("Mock file does not contain a response")?
+        }
+        _ => match reddit_client.get_post(client, shared_state, post_id).await {
+            Ok(response) => response,
+            Err(clients::RedditProviderError::NotFound) => {
+                spinner.fail(&format!("The post, {} could not be found", post_id));
+                return Ok(());
+            }
+            Err(e) => return Err(Box::new(e)),
+        },
+    };
+
+    let duplicates = match include_duplicates {
+        true => reddit_client
+            .get_post_duplicates(client, shared_state, post_id)
+            .await?
+            .data
+            .children
+            .into_iter()
+            .map(|child| PostDuplicate {
+                id: child.data.id,
+                subreddit: child.data.subreddit,
+                author: child.data.author,
+                created_utc: child.data.created_utc,
+                permalink: child.data.permalink,
+            })
+            .collect::<Vec<_>>(),
+        false => Vec::new(),
+    };
+
+    let posts = reddit_parser
+        .parse(&response)
+        .into_iter()
+        .filter(|p| options.filter.matches(p))
+        .collect::<Vec<_>>();
+
+    let ss = shared_state.lock().await;
+    let posts_to_download = posts
+        .iter()
+        .filter(|p| {
+            !ss.file_cache
+                .files
+                .iter()
+                .any(|f| p.id == f.id && p.index == f.index && f.success)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    drop(ss);
+
+    spinner.success(&format!(
+        "Done, trying to download {} files.",
+        posts_to_download.len()
+    ));
+
+    for duplicate in duplicates.iter() {
+        info!(
+            "Also posted to /r/{} by {} - https://www.reddit.com{}",
+            duplicate.subreddit, duplicate.author, duplicate.permalink
+        );
+    }
+
+    if options.dry_run {
+        match &options.export_urls {
+            Some(export_path) => {
+                utils::export_media_urls(
+                    client,
+                    shared_state,
+                    &posts_to_download,
+                    export_path,
+                    options.concurrency,
+                )
+                .await?
+            }
+            None => {
+                utils::print_dry_run(
+                    client,
+                    &output_folder,
+                    &posts_to_download,
+                    &options.file_scheme,
+                    options.concurrency,
+                    options.format,
+                )
+                .await?
+            }
+        }
+        return Ok(());
+    }
+
+    if options.skip {
+        info!("[FLAG] Download skipped");
+        return Ok(());
+    }
+
+    let mut dl_stats = DownloadStats::default();
+    let total_post_len = posts_to_download.len() as u64;
+    let download_progress = DownloadProgress::with_mode(total_post_len, options.progress);
+    shared_state.lock().await.byte_progress = Some(download_progress.byte_progress());
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    // A single post has only a handful of files, so they are downloaded one after another
+    for post in posts_to_download {
+        if shutdown.is_requested() {
+            break;
+        }
+
+        let result = download_crawler_post(
+            client,
+            shared_state,
+            &output_folder,
+            &post,
+            &options.file_scheme,
+        )
+        .await
+        // Recorded as failed, so `retry` tries the post again
+        .unwrap_or_else(|e| utils::DownloadPostResult::ReceivedFailed(e.to_string()));
+
+        match &result {
+            utils::DownloadPostResult::ReceivedBytes(bytes, _, _, _) => {
+                dl_stats.files_downloaded += 1;
+                dl_stats.bytes_downloaded += bytes;
+            }
+            utils::DownloadPostResult::ReceivedNotFound
+            | utils::DownloadPostResult::ReceivedFailed(_) => dl_stats.downloads_failed += 1,
+            utils::DownloadPostResult::ReceivedSkipped(_)
+            | utils::DownloadPostResult::ReceivedUnhandled => {}
+        }
+        let Some(item) = result.into_cache_item(&post) else {
+            continue;
+        };
+
+        shared_state
+            .lock()
+            .await
+            .file_cache
+            .files
+            .push(FileCacheItemLatest {
+                duplicates: duplicates.clone(),
+                ..item
+            });
+
+        download_progress.update_progress(
+            dl_stats.files_downloaded,
+            total_post_len,
+            dl_stats.bytes_downloaded,
+        );
+    }
+
+    download_progress.post_report(
+        dl_stats.files_downloaded,
+        total_post_len,
+        dl_stats.bytes_downloaded,
+    );
+    if options.output_mode == OutputMode::Json {
+        emit_event(&CrawlEvent::CrawlFinished {
+            resource: &stem,
+            downloaded: dl_stats.files_downloaded,
+            failed: dl_stats.downloads_failed,
+            bytes: dl_stats.bytes_downloaded,
+        });
+    }
+    options
+        .notifier
+        .send(
+            client,
+            &CrawlSummary {
+                resource: stem.to_owned(),
+                downloaded: dl_stats.files_downloaded,
+                failed: dl_stats.downloads_failed,
+                bytes: dl_stats.bytes_downloaded,
+            },
+        )
+        .await;
+
+    if options.export_captions {
+        utils::write_caption_markdown(
+            &output_folder,
+            std::slice::from_ref(&response),
+            &posts,
+            &options.file_scheme,
+        )?;
+    }
+
+    if options.write_metadata {
+        utils::write_post_metadata(
+            &output_folder,
+            std::slice::from_ref(&response),
+            &posts,
+            &options.file_scheme,
+        )?;
+    }
+
+    let unsupported = utils::report_unsupported_posts(
+        &output_folder,
+        std::slice::from_ref(&response),
+        &reddit_parser,
+    );
+    utils::finish_run_report(
+        shared_state,
+        &output_folder,
+        unsupported,
+        !options.quiet && options.output_mode != OutputMode::Json,
+    )
+    .await;
+
+    if options.with_comments && !shutdown.is_requested() {
+        match utils::write_post_comments(
+            client,
+            shared_state,
+            &output_folder,
+            &posts,
+            &options.file_scheme,
+        )
+        .await
+        {
+            Ok(written) => info!("Saved the comments of {} posts", written),
+            // The downloads are still recorded in the cache below
+            Err(e) => warn!("[COMMENTS_FAILED] {}", e),
+        }
+    }
+
+    shared_state
+        .lock()
+        .await
+        .write_file_cache(&file_cache_path)?;
+
+    if shutdown.is_requested() {
+        info!(
+            "[INTERRUPTED] Cached the {} downloaded files of {}, the next run continues from there",
+            dl_stats.files_downloaded, stem
+        );
+    }
+
+    if let Some(format) = options.archive {
+        utils::archive_output_folder(&output_folder, format, options.archive_delete);
+    }
+
+    Ok(())
+}
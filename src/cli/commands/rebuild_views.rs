@@ -0,0 +1,31 @@
+use crate::{
+    cli::CliRebuildViewsCommand,
+    utils::{
+        self,
+        state::{find_cache_files, FileCacheLatest},
+    },
+};
+use std::{error::Error, fs, path::Path, str::FromStr};
+use tracing::info;
+
+pub fn handle_rebuild_views_command(cmd: CliRebuildViewsCommand) -> Result<(), Box<dyn Error>> {
+    let CliRebuildViewsCommand { path } = cmd;
+
+    let cache_files = find_cache_files(Path::new(&path))
+        .map_err(|e| format!("Failed to read output directory {}: {}", path, e))?;
+
+    if cache_files.is_empty() {
+        info!("No cached resources found in {}", path);
+        return Ok(());
+    }
+
+    for cache_file in cache_files {
+        let folder = cache_file.parent().unwrap_or(Path::new(&path));
+        let file_cache = FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)?;
+        let linked = utils::rebuild_views(folder, &file_cache)?;
+
+        info!("{} - linked {} files", folder.display(), linked);
+    }
+
+    Ok(())
+}
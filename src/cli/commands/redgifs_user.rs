@@ -0,0 +1,15 @@
+use super::crawl::{crawl_and_download, get_unsorted_command, CrawlSource};
+use crate::{cli::CliRedgifsUserCommand, utils::state::SharedState};
+use std::{error::Error, sync::Arc};
+use tokio::sync::Mutex;
+
+pub async fn handle_redgifs_user_command(
+    cmd: CliRedgifsUserCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliRedgifsUserCommand { resource, options } = cmd;
+
+    let cmd = get_unsorted_command(resource, options);
+    crawl_and_download(CrawlSource::RedgifsUser, cmd, client, shared_state).await
+}
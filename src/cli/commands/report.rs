@@ -0,0 +1,41 @@
+use crate::{
+    cli::CliReportCommand,
+    utils::{
+        self,
+        state::{find_cache_files, FileCacheLatest},
+    },
+};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+    str::FromStr,
+};
+use tracing::info;
+
+pub fn handle_report_command(cmd: CliReportCommand) -> Result<(), Box<dyn Error>> {
+    let CliReportCommand { path, file } = cmd;
+    let root = Path::new(&path);
+
+    let cache_files = find_cache_files(root)
+        .map_err(|e| format!("Failed to read output directory {}: {}", path, e))?;
+
+    let mut rows = Vec::new();
+    for cache_file in cache_files {
+        let folder = cache_file.parent().unwrap_or(root);
+        let file_cache = FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)?;
+        rows.extend(utils::get_report_rows(root, folder, &file_cache));
+    }
+
+    match file {
+        Some(file) => {
+            utils::write_csv_report(BufWriter::new(File::create(&file)?), &rows)?;
+            info!("Wrote {} files to {}", rows.len(), file);
+        }
+        // Stdout only gets the CSV, so the report can be piped
+        None => utils::write_csv_report(io::stdout().lock(), &rows)?,
+    }
+
+    Ok(())
+}
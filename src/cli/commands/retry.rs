@@ -0,0 +1,213 @@
+use crate::{
+    cli::{CliRetryCommand, CliSharedOptions},
+    clients,
+    reddit_parser::{RedditCrawlerPost, RedditPostParser},
+    utils::{
+        self, download_crawler_post,
+        state::{find_cache_files, DownloadStats, FileCacheLatest, SharedState},
+        DownloadProgress,
+    },
+};
+use futures_util::{stream, StreamExt};
+use std::{collections::HashSet, error::Error, fs, path::Path, str::FromStr, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Downloads the failed files of every cached resource below the output directory again
+pub async fn handle_retry_command(
+    cmd: CliRetryCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliRetryCommand { ref options } = cmd;
+    let root = Path::new(&options.output);
+
+    let cache_files = find_cache_files(root)
+        .map_err(|e| format!("Failed to read output directory {}: {}", options.output, e))?;
+
+    let reddit_client = clients::RedditClient::default();
+    let reddit_parser = RedditPostParser {
+        preview_size: options.preview_size,
+        fallback_ytdlp: options.fallback_ytdlp,
+    };
+    let mut total = DownloadStats::default();
+
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    for cache_file in cache_files {
+        if shutdown.is_requested() {
+            break;
+        }
+
+        let folder = cache_file.parent().unwrap_or(root);
+        let output_folder = folder.to_string_lossy().to_string();
+        let resource = folder.strip_prefix(root).unwrap_or(folder).display();
+
+        let file_cache = FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)?;
+        let failed = file_cache
+            .files
+            .iter()
+            .filter(|f| !f.success)
+            .map(|f| (f.id.clone(), f.index))
+            .collect::<HashSet<_>>();
+
+        if failed.is_empty() {
+            continue;
+        }
+
+        let mut ids = failed.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+
+        {
+            let mut ss = shared_state.lock().await;
+            ss.file_cache_path = Some(cache_file.to_string_lossy().to_string());
+            ss.file_cache = file_cache;
+        }
+
+        // The posts are fetched again, so expired media URLs are resolved anew by the downloader
+        let responses = match reddit_client
+            .get_posts_by_id(client, shared_state, &ids)
+            .await
+        {
+            Ok(responses) => responses,
+            Err(e) => {
+                warn!("[RETRY_FAILED] {} - {}", resource, e);
+                continue;
+            }
+        };
+
+        // Galleries are matched per image, so only their failed images are downloaded again
+        let posts = responses
+            .iter()
+            .flat_map(|response| reddit_parser.parse(response))
+            .filter(|p| failed.contains(&(p.id.clone(), p.index)))
+            .collect::<Vec<_>>();
+
+        info!(
+            "{} - retrying {} of {} failed files",
+            resource,
+            posts.len(),
+            failed.len()
+        );
+
+        if options.dry_run {
+            match &options.export_urls {
+                Some(export_path) => {
+                    utils::export_media_urls(
+                        client,
+                        shared_state,
+                        &posts,
+                        export_path,
+                        options.concurrency,
+                    )
+                    .await?
+                }
+                None => {
+                    utils::print_dry_run(
+                        client,
+                        &output_folder,
+                        &posts,
+                        &options.file_scheme,
+                        options.concurrency,
+                        options.format,
+                    )
+                    .await?
+                }
+            }
+            continue;
+        }
+
+        let stats = retry_posts(client, shared_state, &output_folder, posts, options).await;
+        total.files_downloaded += stats.files_downloaded;
+        total.downloads_failed += stats.downloads_failed;
+        total.bytes_downloaded += stats.bytes_downloaded;
+
+        let ss = shared_state.lock().await;
+        fs::write(&cache_file, serde_json::to_string(&ss.file_cache)?)?;
+    }
+
+    info!(
+        "Downloaded {} files ({:.2} MB), {} failed again",
+        total.files_downloaded,
+        total.bytes_downloaded / 1024.0 / 1024.0,
+        total.downloads_failed
+    );
+
+    Ok(())
+}
+
+/// Downloads the posts and replaces their failed cache entries once they succeed
+async fn retry_posts(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    output_folder: &str,
+    posts: Vec<RedditCrawlerPost>,
+    options: &CliSharedOptions,
+) -> DownloadStats {
+    let mut dl_stats = DownloadStats::default();
+    let total_post_len = posts.len() as u64;
+    let download_progress = DownloadProgress::with_mode(total_post_len, options.progress);
+    shared_state.lock().await.byte_progress = Some(download_progress.byte_progress());
+
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    // Running downloads finish after Ctrl-C, so their results still replace the failed entries
+    let mut downloads = stream::iter(posts)
+        .take_until(Box::pin(shutdown.wait()))
+        .map(|post| async move {
+            let result = download_crawler_post(
+                client,
+                shared_state,
+                output_folder,
+                &post,
+                &options.file_scheme,
+            )
+            .await;
+            (post, result)
+        })
+        .buffer_unordered(options.concurrency as usize);
+
+    while let Some((post, result)) = downloads.next().await {
+        match result {
+            Ok(result @ utils::DownloadPostResult::ReceivedBytes(bytes, _, _, _)) => {
+                dl_stats.files_downloaded += 1;
+                dl_stats.bytes_downloaded += bytes;
+
+                let mut ss = shared_state.lock().await;
+                ss.file_cache
+                    .files
+                    .retain(|f| f.id != post.id || f.index != post.index);
+                ss.file_cache.files.extend(result.into_cache_item(&post));
+            }
+            Ok(utils::DownloadPostResult::ReceivedUnhandled)
+            | Ok(utils::DownloadPostResult::ReceivedSkipped(_)) => {}
+            // The failed entry stays, so the file is tried again by the next retry
+            Ok(utils::DownloadPostResult::ReceivedFailed(error)) => {
+                dl_stats.downloads_failed += 1;
+
+                let mut ss = shared_state.lock().await;
+                ss.file_cache
+                    .files
+                    .iter_mut()
+                    .filter(|f| f.id == post.id && f.index == post.index)
+                    .for_each(|f| f.error = Some(error.clone()));
+            }
+            Ok(_) | Err(_) => dl_stats.downloads_failed += 1,
+        }
+
+        download_progress.update_progress(
+            dl_stats.files_downloaded,
+            total_post_len,
+            dl_stats.bytes_downloaded,
+        );
+    }
+
+    download_progress.post_report(
+        dl_stats.files_downloaded,
+        total_post_len,
+        dl_stats.bytes_downloaded,
+    );
+
+    dl_stats
+}
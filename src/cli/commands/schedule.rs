@@ -0,0 +1,86 @@
+use super::batch::run_batch_targets;
+use crate::{
+    cli::{CliScheduleCommand, RedditCategoryFilter, RedditTimeframeFilter},
+    utils::{self, state::SharedState},
+};
+use chrono::{DateTime, Local};
+use std::{error::Error, fs, sync::Arc};
+use tokio::{
+    sync::{mpsc, Mutex},
+    time::sleep,
+};
+use tracing::info;
+
+pub async fn handle_schedule_command(
+    cmd: CliScheduleCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliScheduleCommand {
+        ref path,
+        ref options,
+    } = cmd;
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read schedule file {}: {}", path, e))?;
+    let schedule = utils::parse_schedule_toml(&content)?;
+
+    // Every target has a timer task that queues its crawls once they are due
+    let (tx, mut rx) = mpsc::unbounded_channel::<(usize, DateTime<Local>)>();
+    for (i, scheduled) in schedule.targets.iter().enumerate() {
+        let timer = scheduled.clone();
+        let tx = tx.clone();
+
+        tokio::spawn(async move {
+            while let Some(next_run) = timer.next_run(&Local::now()) {
+                let wait = (next_run - Local::now()).to_std().unwrap_or_default();
+                sleep(wait).await;
+                if tx.send((i, next_run)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        if let Some(next_run) = scheduled.next_run(&Local::now()) {
+            info!(
+                "{} {} - next run at {}",
+                scheduled.target,
+                scheduled.cron.pattern,
+                next_run.format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+    drop(tx);
+
+    let mut last_finished: Vec<Option<DateTime<Local>>> = vec![None; schedule.targets.len()];
+
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    // Targets share the cache state, so due crawls run one after another
+    while let Some((i, due)) = tokio::select! {
+        due = rx.recv() => due,
+        _ = shutdown.wait() => None,
+    } {
+        // A crawl that outlasted its schedule already covered the runs queued meanwhile
+        if last_finished[i].is_some_and(|finished| finished > due) {
+            continue;
+        }
+
+        let target = &schedule.targets[i].target;
+        run_batch_targets(
+            std::slice::from_ref(target),
+            &RedditCategoryFilter::New,
+            &RedditTimeframeFilter::All,
+            options,
+            1,
+            client,
+            shared_state,
+        )
+        .await;
+        last_finished[i] = Some(Local::now());
+
+        sleep(schedule.pause).await;
+    }
+
+    Ok(())
+}
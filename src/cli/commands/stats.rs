@@ -0,0 +1,32 @@
+use crate::{
+    cli::CliStatsCommand,
+    utils::{
+        self,
+        state::{find_cache_files, FileCacheLatest},
+        OutputFormat,
+    },
+};
+use std::{error::Error, fs, path::Path, str::FromStr};
+
+pub fn handle_stats_command(cmd: CliStatsCommand) -> Result<(), Box<dyn Error>> {
+    let CliStatsCommand { path, top, format } = cmd;
+    let root = Path::new(&path);
+
+    let cache_files = find_cache_files(root)
+        .map_err(|e| format!("Failed to read output directory {}: {}", path, e))?;
+
+    let mut rows = Vec::new();
+    for cache_file in cache_files {
+        let folder = cache_file.parent().unwrap_or(root);
+        let file_cache = FileCacheLatest::from_str(&fs::read_to_string(&cache_file)?)?;
+        rows.extend(utils::get_report_rows(root, folder, &file_cache));
+    }
+
+    let stats = utils::get_archive_stats(&rows, top);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Table => print!("{}", utils::render_archive_stats(&stats)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::{
+    cli::CliUserCommentsCommand,
+    clients::{
+        self, api_types::reddit::user_comments::RedditUserCommentsResponse, RedditProviderError,
+    },
+    utils::{self, state::SharedState, OutputMode, UserComment},
+};
+use owo_colors::OwoColorize;
+use std::{collections::HashSet, error::Error, fs, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::info;
+
+pub async fn handle_user_comments_command(
+    cmd: CliUserCommentsCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let user = &cmd.resource;
+    let mut spinner = utils::CrawlSpinner::new(
+        format!("Fetching comments from {}{}", "/u/".bold(), user.bold()),
+        OutputMode::Text,
+    );
+
+    // Saved next to the submissions of the user, so an account is archived in one folder
+    let output_folder = utils::get_output_folder(&cmd.output, &format!("user/{}", user));
+    utils::prepare_output_folder(&output_folder)?;
+    let ndjson_path = format!("{}/comments.ndjson", output_folder);
+
+    let saved = utils::read_user_comments(&ndjson_path)
+        .map_err(|e| format!("Failed to read {}: {}", ndjson_path, e))?;
+    let known = saved.iter().map(|c| c.id.clone()).collect::<HashSet<_>>();
+
+    let fetched = match &cmd.mock {
+        Some(mock_file) => {
+            info!("[FLAG] Mock mode enabled");
+
+            let file = fs::read_to_string(mock_file)
+                .map_err(|e| format!("Failed to read mock file: {}", e))?;
+
+            serde_json::from_str::<Vec<RedditUserCommentsResponse>>(&file)
+                .map_err(|e| format!("Failed to parse mock file: {}", e))?
+                .into_iter()
+                .flat_map(|r| r.data.children.into_iter().map(|c| c.data))
+                .collect()
+        }
+        None => {
+            match clients::RedditClient::default()
+                .get_user_comments(client, shared_state, &cmd, &known)
+                .await
+            {
+                Ok(comments) => comments,
+                Err(e @ (RedditProviderError::NotFound | RedditProviderError::Suspended)) => {
+                    spinner.fail(&format!("The user, {} is not available - {}", user, e));
+                    return Ok(());
+                }
+                Err(e) => return Err(Box::new(e)),
+            }
+        }
+    };
+
+    let fetched = fetched
+        .into_iter()
+        .map(UserComment::from)
+        .collect::<Vec<_>>();
+    let new_comments = fetched.iter().filter(|c| !known.contains(&c.id)).count();
+    let comments = utils::merge_user_comments(saved, fetched);
+
+    fs::write(&ndjson_path, utils::render_user_comments_ndjson(&comments)?)?;
+    if cmd.markdown {
+        fs::write(
+            format!("{}/comments.md", output_folder),
+            utils::render_user_comments_markdown(user, &comments),
+        )?;
+    }
+
+    spinner.success(&format!(
+        "Saved {} new comments, {} in total",
+        new_comments.bold(),
+        comments.len()
+    ));
+
+    Ok(())
+}
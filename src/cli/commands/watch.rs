@@ -0,0 +1,68 @@
+use super::batch::{read_batch_targets, run_batch_targets};
+use crate::{
+    cli::{CliWatchCommand, RedditCategoryFilter, RedditTimeframeFilter},
+    utils::{self, state::SharedState},
+};
+use chrono::Local;
+use std::{error::Error, sync::Arc};
+use tokio::{sync::Mutex, time::sleep};
+use tracing::{info, warn};
+
+pub async fn handle_watch_command(
+    cmd: CliWatchCommand,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+) -> Result<(), Box<dyn Error>> {
+    let CliWatchCommand {
+        ref path,
+        interval,
+        status_port,
+        parallel_targets,
+        ref options,
+    } = cmd;
+
+    if let Some(port) = status_port {
+        let ss_clone = Arc::clone(shared_state);
+        tokio::spawn(async move {
+            if let Err(e) = utils::serve_transfer_status(port, ss_clone).await {
+                warn!("[STATUS_SERVER_FAILED] {}", e);
+            }
+        });
+        info!(
+            "Serving the transfer status on http://127.0.0.1:{}/status",
+            port
+        );
+    }
+
+    let shutdown = shared_state.lock().await.shutdown.clone();
+
+    while !shutdown.is_requested() {
+        // Re-read on every run so targets can be added without restarting
+        let targets = read_batch_targets(path)?;
+        let failed = run_batch_targets(
+            &targets,
+            &RedditCategoryFilter::New,
+            &RedditTimeframeFilter::All,
+            options,
+            parallel_targets,
+            client,
+            shared_state,
+        )
+        .await;
+
+        let next_run = Local::now() + interval;
+        info!(
+            "Synced {} targets - {} failed, next run at {}",
+            targets.len(),
+            failed,
+            next_run.format("%H:%M:%S")
+        );
+
+        tokio::select! {
+            _ = sleep(interval) => {}
+            _ = shutdown.wait() => {}
+        }
+    }
+
+    Ok(())
+}
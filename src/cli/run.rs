@@ -1,15 +1,94 @@
+use crate::{
+    clients::{
+        normalize_subreddit, Backend, RedditAppCredentials, RedditCredentials, RedgifsQuality,
+    },
+    config::{resolve_flag, Config},
+    error::ExitStatus,
+    reddit_parser::PreviewSize,
+    utils::{
+        check_file_scheme, expand_path, parse_filter_date, parse_interval, parse_proxy_url,
+        parse_size, resolve_output_path, ArchiveFormat, AudioFormat, AuthorListFormat, CacheAction,
+        CheckpointSettings, CookieJar, Downloader, FailureInjection, GlobalCache, ImageConversion,
+        ImageFormat, ImportedArchive, LinkMode, MediaType, Notifier, NsfwFilter, Organize,
+        OutputFormat, OutputMode, PostFilter, ProgressMode, ProviderProxy, RemoteStorage,
+        RetryPolicy, SizeLimits, StickiedFilter, TransferTimeouts, UserAgentPool, VideoCodec,
+        VideoContainer, VideoProcessing, YtdlpCookies, DEFAULT_CONVERT_QUALITY,
+        DEFAULT_FILE_SCHEME,
+    },
+};
+use chrono::{DateTime, Utc};
 use clap::{builder::EnumValueParser, Arg, ArgAction, Command, ValueEnum};
 use owo_colors::OwoColorize;
-use std::fmt;
+use regex::Regex;
+use reqwest::Url;
+use std::{fmt, time::Duration};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct CliSharedOptions {
     pub concurrency: u16,
     pub mock: Option<String>,
     pub output: String,
     pub skip: bool,
-    pub verbose: bool,
+    pub force: bool,
+    pub dry_run: bool,
+    // Set by `--export-urls`, implies `dry_run`
+    pub export_urls: Option<String>,
+    pub format: OutputFormat,
+    pub verbose: u8,
+    pub quiet: bool,
+    pub log_file: Option<String>,
     pub limit: Option<u32>,
+    pub stop_after_cached_pages: Option<u32>,
+    pub inject_failure: Option<FailureInjection>,
+    pub filter: PostFilter,
+    pub export_captions: bool,
+    pub with_comments: bool,
+    pub write_metadata: bool,
+    pub file_scheme: String,
+    pub quality: RedgifsQuality,
+    pub progress: ProgressMode,
+    pub output_mode: OutputMode,
+    pub app_credentials: Option<RedditAppCredentials>,
+    pub proxy: Option<Url>,
+    pub provider_proxies: Vec<ProviderProxy>,
+    pub user_agents: UserAgentPool,
+    pub notifier: Notifier,
+    pub backend: Backend,
+    pub pushshift_token: Option<String>,
+    pub archive: Option<ArchiveFormat>,
+    pub archive_delete: bool,
+    pub size_limits: SizeLimits,
+    // Bytes per second all downloads share
+    pub rate_limit: Option<u64>,
+    pub embed_metadata: bool,
+    pub checksum: bool,
+    pub skip_existing: bool,
+    pub imported_archive: ImportedArchive,
+    pub global_cache: Option<GlobalCache>,
+    pub link_duplicates: Option<LinkMode>,
+    // Set for `s3://` and `webdav://` outputs, `output` is then its staging folder
+    pub remote: Option<RemoteStorage>,
+    pub downloader: Downloader,
+    pub convert_images: Option<ImageConversion>,
+    pub video_processing: VideoProcessing,
+    // ffmpeg processes remuxing or transcoding at once
+    pub video_tasks: u16,
+    // Set by `--audio-only`, video posts are stored as their audio track
+    pub audio_only: Option<AudioFormat>,
+    // Set by `--cookies`, sent along with the requests to their domains
+    pub cookies: Option<CookieJar>,
+    // Set by `--cookies` and `--cookies-from-browser`
+    pub ytdlp_cookies: YtdlpCookies,
+    // Connections per file of aria2c
+    pub aria2c_connections: u16,
+    pub preview_size: Option<PreviewSize>,
+    pub fallback_ytdlp: bool,
+    // yt-dlp processes of the fallback running at once
+    pub fallback_ytdlp_tasks: u16,
+    pub ytdlp_timeout: Duration,
+    pub transfer_timeouts: TransferTimeouts,
+    pub retry_policy: RetryPolicy,
+    pub checkpoint: CheckpointSettings,
 }
 
 #[derive(Debug)]
@@ -17,14 +96,178 @@ pub struct CliRedditCommand {
     pub resource: String,
     pub category: RedditCategoryFilter,
     pub timeframe: RedditTimeframeFilter,
+    /// Searches subreddits window by window instead of paging through the listing
+    pub deep: bool,
+    /// Crawls the authors of the posts of a subreddit afterwards
+    pub crawl_authors: bool,
+    pub max_authors: Option<usize>,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliPostCommand {
+    pub resource: String,
+    pub include_duplicates: bool,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliRedgifsUserCommand {
+    pub resource: String,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliBatchCommand {
+    pub path: String,
+    pub category: RedditCategoryFilter,
+    pub timeframe: RedditTimeframeFilter,
+    // Targets crawled at once
+    pub parallel_targets: usize,
     pub options: CliSharedOptions,
 }
 
+#[derive(Debug)]
+pub struct CliWatchCommand {
+    pub path: String,
+    pub interval: Duration,
+    pub status_port: Option<u16>,
+    pub parallel_targets: usize,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliScheduleCommand {
+    pub path: String,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliAccountCommand {
+    pub listing: RedditAccountListing,
+    pub credentials: RedditCredentials,
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliRebuildViewsCommand {
+    pub path: String,
+}
+
+#[derive(Debug)]
+pub struct CliRetryCommand {
+    pub options: CliSharedOptions,
+}
+
+#[derive(Debug)]
+pub struct CliCacheCommand {
+    pub action: CacheAction,
+    pub path: String,
+    // Hash files again during verify
+    pub deep: bool,
+}
+
+#[derive(Debug)]
+pub struct CliReportCommand {
+    pub path: String,
+    pub file: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CliStatsCommand {
+    pub path: String,
+    // Authors and subreddits listed
+    pub top: usize,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug)]
+pub struct CliDiffCommand {
+    pub path: String,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug)]
+pub struct CliUserCommentsCommand {
+    pub resource: String,
+    pub category: RedditCategoryFilter,
+    pub timeframe: RedditTimeframeFilter,
+    pub limit: Option<u32>,
+    pub output: String,
+    pub markdown: bool,
+    pub mock: Option<String>,
+}
+
+/// Listing `authors` ranks the posters of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthorSource {
+    Subreddit,
+    Search,
+}
+
+#[derive(Debug)]
+pub struct CliAuthorsCommand {
+    pub source: AuthorSource,
+    pub listing: CliRedditCommand,
+    pub format: AuthorListFormat,
+    pub file: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CliExplainCommand {
+    pub resource: String,
+    pub mock: Option<String>,
+    pub file_scheme: String,
+}
+
 #[derive(Debug)]
 pub enum CliCommand {
     User(CliRedditCommand),
     Search(CliRedditCommand),
     Subreddit(CliRedditCommand),
+    Domain(CliRedditCommand),
+    Post(CliPostCommand),
+    RedgifsUser(CliRedgifsUserCommand),
+    Batch(CliBatchCommand),
+    Watch(CliWatchCommand),
+    Schedule(CliScheduleCommand),
+    Saved(CliAccountCommand),
+    Upvoted(CliAccountCommand),
+    RebuildViews(CliRebuildViewsCommand),
+    Report(CliReportCommand),
+    Stats(CliStatsCommand),
+    Diff(CliDiffCommand),
+    Retry(CliRetryCommand),
+    Cache(CliCacheCommand),
+    Explain(CliExplainCommand),
+    Authors(CliAuthorsCommand),
+    UserComments(CliUserCommentsCommand),
+}
+
+impl CliCommand {
+    pub fn options(&self) -> Option<&CliSharedOptions> {
+        match self {
+            CliCommand::User(cmd)
+            | CliCommand::Search(cmd)
+            | CliCommand::Subreddit(cmd)
+            | CliCommand::Domain(cmd) => Some(&cmd.options),
+            CliCommand::Post(cmd) => Some(&cmd.options),
+            CliCommand::RedgifsUser(cmd) => Some(&cmd.options),
+            CliCommand::Retry(cmd) => Some(&cmd.options),
+            CliCommand::Batch(cmd) => Some(&cmd.options),
+            CliCommand::Watch(cmd) => Some(&cmd.options),
+            CliCommand::Schedule(cmd) => Some(&cmd.options),
+            CliCommand::Saved(cmd) | CliCommand::Upvoted(cmd) => Some(&cmd.options),
+            CliCommand::Authors(cmd) => Some(&cmd.listing.options),
+            CliCommand::RebuildViews(_)
+            | CliCommand::Report(_)
+            | CliCommand::Stats(_)
+            | CliCommand::Diff(_)
+            | CliCommand::Cache(_)
+            | CliCommand::Explain(_)
+            | CliCommand::UserComments(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
@@ -49,6 +292,23 @@ impl fmt::Display for RedditCategoryFilter {
     }
 }
 
+/// Listings of the authenticated user that require an OAuth session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedditAccountListing {
+    Saved,
+    Upvoted,
+}
+
+impl fmt::Display for RedditAccountListing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let listing_str = match self {
+            RedditAccountListing::Saved => "saved",
+            RedditAccountListing::Upvoted => "upvoted",
+        };
+        write!(f, "{}", listing_str)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
 pub enum RedditTimeframeFilter {
     Hour,
@@ -73,28 +333,79 @@ impl fmt::Display for RedditTimeframeFilter {
     }
 }
 
-pub fn run() -> CliCommand {
+pub fn run(config: &Config) -> CliCommand {
     let shared_args = &[
         Arg::new("verbose")
             .short('v')
             .long("verbose")
-            .long_help("Print verbose output")
+            .env("REDDIT_CLAWLER_VERBOSE")
+            .long_help("Print debug logs, -vv also prints trace logs. RUST_LOG takes precedence")
+            .action(ArgAction::Count),
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .env("REDDIT_CLAWLER_QUIET")
+            .long_help("Only print warnings and errors")
+            .conflicts_with("verbose")
             .action(ArgAction::SetTrue),
+        Arg::new("log-file")
+            .long("log-file")
+            .env("REDDIT_CLAWLER_LOG_FILE")
+            .long_help("Also appends the logs with timestamps to a file, e.g. for unattended runs")
+            .value_name("FILE")
+            .action(clap::ArgAction::Set),
         Arg::new("skip")
             .long("skip")
+            .env("REDDIT_CLAWLER_SKIP")
             .long_help("Skips download tasks for development purposes")
             .action(clap::ArgAction::SetTrue)
             .required(false)
             .hide(true),
+        Arg::new("force")
+            .long("force")
+            .env("REDDIT_CLAWLER_FORCE")
+            .long_help("Crawls resources marked as deleted, suspended, banned, private or quarantined in cache")
+            .action(ArgAction::SetTrue),
+        Arg::new("dry-run")
+            .long("dry-run")
+            .env("REDDIT_CLAWLER_DRY_RUN")
+            .long_help("Lists the files that would be downloaded without writing anything")
+            .action(ArgAction::SetTrue),
+        Arg::new("export-urls")
+            .long("export-urls")
+            .env("REDDIT_CLAWLER_EXPORT_URLS")
+            .long_help("Appends the resolved media URLs to a file, one per line, instead of downloading them, e.g. for aria2c -i")
+            .value_name("FILE")
+            .conflicts_with("dry-run")
+            .action(clap::ArgAction::Set),
+        Arg::new("format")
+            .long("format")
+            .env("REDDIT_CLAWLER_DRY_RUN_FORMAT")
+            .long_help("Output format of --dry-run")
+            .value_name("table|json")
+            .value_parser(EnumValueParser::<OutputFormat>::new())
+            .default_value("table")
+            .action(clap::ArgAction::Set),
         Arg::new("mock")
             .long("mock")
+            .env("REDDIT_CLAWLER_MOCK")
             .long_help("Pass a mock of a Reddit API response for development purposes")
             .action(clap::ArgAction::Set)
             .required(false)
             .hide(true),
+        Arg::new("inject-failure")
+            .long("inject-failure")
+            .env("REDDIT_CLAWLER_INJECT_FAILURE")
+            .long_help("Randomly fails downloads and requests for resilience testing e.g. rate=0.1,kind=timeout")
+            .value_name("rate=RATE,kind=timeout|error|notfound|ratelimit")
+            .value_parser(clap::value_parser!(FailureInjection))
+            .action(clap::ArgAction::Set)
+            .required(false)
+            .hide(true),
         Arg::new("tasks")
             .short('t')
             .long("tasks")
+            .env("REDDIT_CLAWLER_TASKS")
             .long_help("Amount of tasks spawned for download [1-100]")
             .value_name("tasks")
             .value_parser(clap::value_parser!(u16).range(1..=100))
@@ -103,19 +414,499 @@ pub fn run() -> CliCommand {
         Arg::new("limit")
             .short('l')
             .long("limit")
+            .env("REDDIT_CLAWLER_LIMIT")
             .long_help("Limit of fetch requests")
             .value_name("limit")
             .value_parser(clap::value_parser!(u32))
             .action(clap::ArgAction::Set),
+        Arg::new("stop-after-cached-pages")
+            .long("stop-after-cached-pages")
+            .env("REDDIT_CLAWLER_STOP_AFTER_CACHED_PAGES")
+            .long_help("Stops fetching category new, saved or upvoted posts after this many consecutive pages of cached posts")
+            .value_name("pages")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .action(clap::ArgAction::Set),
+        Arg::new("until-cached")
+            .long("until-cached")
+            .env("REDDIT_CLAWLER_UNTIL_CACHED")
+            .long_help("Stops fetching category new, saved or upvoted posts at the first page of cached posts, same as --stop-after-cached-pages 1")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("stop-after-cached-pages"),
+        Arg::new("exclude-stickied")
+            .long("exclude-stickied")
+            .env("REDDIT_CLAWLER_EXCLUDE_STICKIED")
+            .long_help("Skips posts pinned by the moderators")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("only-stickied"),
+        Arg::new("only-stickied")
+            .long("only-stickied")
+            .env("REDDIT_CLAWLER_ONLY_STICKIED")
+            .long_help("Only downloads posts pinned by the moderators")
+            .action(ArgAction::SetTrue),
+        Arg::new("exclude-spoilers")
+            .long("exclude-spoilers")
+            .env("REDDIT_CLAWLER_EXCLUDE_SPOILERS")
+            .long_help("Skips posts tagged as spoiler")
+            .action(ArgAction::SetTrue),
+        Arg::new("sfw-only")
+            .long("sfw-only")
+            .env("REDDIT_CLAWLER_SFW_ONLY")
+            .long_help("Skips posts marked as NSFW")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("nsfw-only"),
+        Arg::new("nsfw-only")
+            .long("nsfw-only")
+            .env("REDDIT_CLAWLER_NSFW_ONLY")
+            .long_help("Only downloads posts marked as NSFW")
+            .action(ArgAction::SetTrue),
+        Arg::new("min-upvotes")
+            .long("min-upvotes")
+            .env("REDDIT_CLAWLER_MIN_UPVOTES")
+            .long_help("Skips posts with fewer upvotes")
+            .value_name("upvotes")
+            .value_parser(clap::value_parser!(i64))
+            .action(clap::ArgAction::Set),
+        Arg::new("max-upvotes")
+            .long("max-upvotes")
+            .env("REDDIT_CLAWLER_MAX_UPVOTES")
+            .long_help("Skips posts with more upvotes")
+            .value_name("upvotes")
+            .value_parser(clap::value_parser!(i64))
+            .action(clap::ArgAction::Set),
+        Arg::new("after-date")
+            .long("after-date")
+            .env("REDDIT_CLAWLER_AFTER_DATE")
+            .long_help("Skips posts created before this ISO 8601 date, stops fetching category new once it is reached")
+            .value_name("date")
+            .value_parser(parse_filter_date)
+            .action(clap::ArgAction::Set),
+        Arg::new("before-date")
+            .long("before-date")
+            .env("REDDIT_CLAWLER_BEFORE_DATE")
+            .long_help("Skips posts created at or after this ISO 8601 date")
+            .value_name("date")
+            .value_parser(parse_filter_date)
+            .action(clap::ArgAction::Set),
+        Arg::new("only")
+            .long("only")
+            .env("REDDIT_CLAWLER_ONLY")
+            .long_help("Only downloads these media types, can be repeated or comma separated")
+            .value_name("images|videos|gifs|galleries")
+            .value_parser(EnumValueParser::<MediaType>::new())
+            .value_delimiter(',')
+            .action(ArgAction::Append),
+        Arg::new("title-match")
+            .long("title-match")
+            .env("REDDIT_CLAWLER_TITLE_MATCH")
+            .long_help("Only downloads posts whose title matches this regex, e.g. \\[OC\\]")
+            .value_name("regex")
+            .value_parser(|s: &str| Regex::new(s).map_err(|e| e.to_string()))
+            .action(clap::ArgAction::Set),
+        Arg::new("title-exclude")
+            .long("title-exclude")
+            .env("REDDIT_CLAWLER_TITLE_EXCLUDE")
+            .long_help("Skips posts whose title matches this regex")
+            .value_name("regex")
+            .value_parser(|s: &str| Regex::new(s).map_err(|e| e.to_string()))
+            .action(clap::ArgAction::Set),
+        Arg::new("thumbnails-only")
+            .long("thumbnails-only")
+            .env("REDDIT_CLAWLER_THUMBNAILS_ONLY")
+            .long_help("Downloads the smallest preview of images and galleries instead of the source, e.g. for index galleries")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("max-resolution"),
+        Arg::new("max-resolution")
+            .long("max-resolution")
+            .env("REDDIT_CLAWLER_MAX_RESOLUTION")
+            .long_help("Downloads the largest preview of images and galleries at most this many pixels wide, e.g. 1080. Smaller images keep their source")
+            .value_name("width")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .action(clap::ArgAction::Set),
+        Arg::new("export-captions")
+            .long("export-captions")
+            .env("REDDIT_CLAWLER_EXPORT_CAPTIONS")
+            .long_help("Writes a markdown file with title, text and image captions for captioned galleries")
+            .action(ArgAction::SetTrue),
+        Arg::new("with-comments")
+            .long("with-comments")
+            .env("REDDIT_CLAWLER_WITH_COMMENTS")
+            .long_help("Saves the comments of every downloaded post as JSON next to its files")
+            .action(ArgAction::SetTrue),
+        Arg::new("write-metadata")
+            .long("write-metadata")
+            .env("REDDIT_CLAWLER_WRITE_METADATA")
+            .long_help("Writes a <post id>.json with the crawled post data and its files next to the downloads")
+            .action(ArgAction::SetTrue),
+        Arg::new("file-scheme")
+            .long("file-scheme")
+            .env("REDDIT_CLAWLER_FILE_SCHEME")
+            .long_help("Naming scheme of downloaded files, placeholders: {UPVOTES} {AUTHOR} {POSTID} {DATE} {SPOILER} {SUBREDDIT} {TITLE} {PROVIDER} {INDEX} {EXT}")
+            .value_name("SCHEME")
+            .default_value(DEFAULT_FILE_SCHEME)
+            .action(clap::ArgAction::Set),
+        Arg::new("organize")
+            .long("organize")
+            .env("REDDIT_CLAWLER_ORGANIZE")
+            .long_help("Sorts the downloaded files of a resource into subfolders")
+            .value_name("flat|by-subreddit|by-date|by-provider")
+            .value_parser(EnumValueParser::<Organize>::new())
+            .default_value("flat")
+            .action(clap::ArgAction::Set),
+        Arg::new("quality")
+            .long("quality")
+            .env("REDDIT_CLAWLER_QUALITY")
+            .long_help("Quality of Redgifs downloads")
+            .value_name("hd|sd")
+            .value_parser(EnumValueParser::<RedgifsQuality>::new())
+            .default_value("hd")
+            .action(clap::ArgAction::Set),
+        Arg::new("progress")
+            .long("progress")
+            .env("REDDIT_CLAWLER_PROGRESS")
+            .long_help("Progress display, detailed adds a byte bar for every running download")
+            .value_name("simple|detailed")
+            .value_parser(EnumValueParser::<ProgressMode>::new())
+            .default_value("simple")
+            .action(clap::ArgAction::Set),
+        Arg::new("proxy")
+            .long("proxy")
+            .env("REDDIT_CLAWLER_PROXY")
+            .long_help("Proxy for every request e.g. http://127.0.0.1:8080 or socks5h://127.0.0.1:9050 for Tor")
+            .value_name("URL")
+            .action(clap::ArgAction::Set),
+        Arg::new("provider-proxy")
+            .long("provider-proxy")
+            .env("REDDIT_CLAWLER_PROVIDER_PROXY")
+            .long_help("Proxy for the requests of one provider, takes precedence over --proxy and can be repeated")
+            .value_name("reddit|redgifs|imgur|streamable|youtube=URL")
+            .value_parser(clap::value_parser!(ProviderProxy))
+            .value_delimiter(',')
+            .action(ArgAction::Append),
+        Arg::new("user-agent")
+            .long("user-agent")
+            .env("REDDIT_CLAWLER_USER_AGENT")
+            .long_help("User agent of the requests to Reddit and Redgifs")
+            .value_name("USER_AGENT")
+            .action(clap::ArgAction::Set),
+        Arg::new("user-agent-file")
+            .long("user-agent-file")
+            .env("REDDIT_CLAWLER_USER_AGENT_FILE")
+            .long_help("File with a user agent per line, the next one is used whenever Reddit rate limits a request")
+            .value_name("FILE")
+            .conflicts_with("user-agent")
+            .action(clap::ArgAction::Set),
+        Arg::new("output-format")
+            .long("output-format")
+            .env("REDDIT_CLAWLER_OUTPUT_FORMAT")
+            .long_help("Output of a crawl, json prints NDJSON events instead of spinners and progress bars")
+            .value_name("text|json")
+            .value_parser(EnumValueParser::<OutputMode>::new())
+            .default_value("text")
+            .action(clap::ArgAction::Set),
+        Arg::new("notify-webhook")
+            .long("notify-webhook")
+            .env("REDDIT_CLAWLER_NOTIFY_WEBHOOK")
+            .long_help("Discord or Slack compatible webhook receiving a summary whenever a crawl finishes")
+            .value_name("URL")
+            .value_parser(clap::value_parser!(Url))
+            .action(clap::ArgAction::Set),
+        Arg::new("notify-command")
+            .long("notify-command")
+            .env("REDDIT_CLAWLER_NOTIFY_COMMAND")
+            .long_help("Shell command run whenever a crawl finishes, the summary is passed in REDDIT_CLAWLER_RESOURCE, REDDIT_CLAWLER_DOWNLOADED, REDDIT_CLAWLER_FAILED, REDDIT_CLAWLER_BYTES and REDDIT_CLAWLER_MESSAGE")
+            .value_name("COMMAND")
+            .action(clap::ArgAction::Set),
+        Arg::new("archive")
+            .long("archive")
+            .env("REDDIT_CLAWLER_ARCHIVE")
+            .long_help("Bundles the files of a resource into a single archive next to its folder after the crawl")
+            .value_name("zip|tar.zst")
+            .value_parser(EnumValueParser::<ArchiveFormat>::new())
+            .action(clap::ArgAction::Set),
+        Arg::new("archive-delete")
+            .long("archive-delete")
+            .env("REDDIT_CLAWLER_ARCHIVE_DELETE")
+            .long_help("Deletes the archived files, the cache is kept so later crawls still skip them")
+            .requires("archive")
+            .action(ArgAction::SetTrue),
+        Arg::new("max-file-size")
+            .long("max-file-size")
+            .env("REDDIT_CLAWLER_MAX_FILE_SIZE")
+            .long_help("Skips files larger than this size e.g. 500M, checked with the Content-Length or by probing with yt-dlp")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .action(clap::ArgAction::Set),
+        Arg::new("max-total-size")
+            .long("max-total-size")
+            .env("REDDIT_CLAWLER_MAX_TOTAL_SIZE")
+            .long_help("Stops downloading once this run downloaded this much e.g. 50G, skipped files are recorded in the cache for the retry command")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .action(clap::ArgAction::Set),
+        Arg::new("rate-limit")
+            .long("rate-limit")
+            .env("REDDIT_CLAWLER_RATE_LIMIT")
+            .long_help("Caps the combined download speed of all tasks in bytes per second e.g. 5M")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .action(clap::ArgAction::Set),
+        Arg::new("embed-metadata")
+            .long("embed-metadata")
+            .env("REDDIT_CLAWLER_EMBED_METADATA")
+            .long_help("Writes the post title, author, permalink and date into the EXIF/XMP of downloaded images and the tags of mp4 videos (requires ffmpeg)")
+            .action(ArgAction::SetTrue),
+        Arg::new("fallback-ytdlp")
+            .long("fallback-ytdlp")
+            .env("REDDIT_CLAWLER_FALLBACK_YTDLP")
+            .long_help("Downloads links no provider supports with yt-dlp instead of skipping them")
+            .action(ArgAction::SetTrue),
+        Arg::new("fallback-ytdlp-tasks")
+            .long("fallback-ytdlp-tasks")
+            .env("REDDIT_CLAWLER_FALLBACK_YTDLP_TASKS")
+            .long_help("Amount of yt-dlp processes the fallback runs at once [1-100]")
+            .value_name("tasks")
+            .value_parser(clap::value_parser!(u16).range(1..=100))
+            .default_value("2")
+            .requires("fallback-ytdlp")
+            .action(clap::ArgAction::Set),
+        Arg::new("download-timeout")
+            .long("download-timeout")
+            .env("REDDIT_CLAWLER_DOWNLOAD_TIMEOUT")
+            .long_help("Fails a download that takes longer than this, e.g. 300s. Its .part file is resumed by the next run")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .action(clap::ArgAction::Set),
+        Arg::new("stall-timeout")
+            .long("stall-timeout")
+            .env("REDDIT_CLAWLER_STALL_TIMEOUT")
+            .long_help("Requests a download again when it received no data for this long, e.g. 30s")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .default_value("60s")
+            .action(clap::ArgAction::Set),
+        Arg::new("retries")
+            .long("retries")
+            .env("REDDIT_CLAWLER_RETRIES")
+            .long_help("Sends a request again this many times after connection errors, server errors or rate limits")
+            .value_name("count")
+            .value_parser(clap::value_parser!(u32))
+            .default_value("3")
+            .action(clap::ArgAction::Set),
+        Arg::new("retry-base-delay")
+            .long("retry-base-delay")
+            .env("REDDIT_CLAWLER_RETRY_BASE_DELAY")
+            .long_help("Delay before the first retry, doubled with every further retry e.g. 2s")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .default_value("1s")
+            .action(clap::ArgAction::Set),
+        Arg::new("retry-max-delay")
+            .long("retry-max-delay")
+            .env("REDDIT_CLAWLER_RETRY_MAX_DELAY")
+            .long_help("Longest delay between retries. Rate limited requests whose Retry-After is further away are not retried")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .default_value("5m")
+            .action(clap::ArgAction::Set),
+        Arg::new("ytdlp-timeout")
+            .long("ytdlp-timeout")
+            .env("REDDIT_CLAWLER_YTDLP_TIMEOUT")
+            .long_help("Stops a yt-dlp download that takes longer than this, e.g. 10m. It is recorded as failed for the retry command")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .default_value("30m")
+            .action(clap::ArgAction::Set),
+        Arg::new("checksum")
+            .long("checksum")
+            .env("REDDIT_CLAWLER_CHECKSUM")
+            .long_help("Records a blake3 hash of every downloaded file in the cache, checked by cache verify --deep")
+            .action(ArgAction::SetTrue),
+        Arg::new("skip-existing")
+            .long("skip-existing")
+            .env("REDDIT_CLAWLER_SKIP_EXISTING")
+            .long_help("Skips posts whose file already exists on disk and records them in the cache as downloaded, e.g. after the cache was lost")
+            .action(ArgAction::SetTrue),
+        Arg::new("downloader")
+            .long("downloader")
+            .env("REDDIT_CLAWLER_DOWNLOADER")
+            .long_help("Writes the files served over HTTP itself or with aria2c, which downloads every file over several connections")
+            .value_name("builtin|aria2c")
+            .value_parser(EnumValueParser::<Downloader>::new())
+            .default_value("builtin")
+            .action(clap::ArgAction::Set),
+        Arg::new("aria2c-connections")
+            .long("aria2c-connections")
+            .env("REDDIT_CLAWLER_ARIA2C_CONNECTIONS")
+            .long_help("Connections aria2c opens per file")
+            .value_name("connections")
+            .value_parser(clap::value_parser!(u16).range(1..=16))
+            .default_value("8")
+            .action(clap::ArgAction::Set),
+        Arg::new("convert-images")
+            .long("convert-images")
+            .env("REDDIT_CLAWLER_CONVERT_IMAGES")
+            .long_help("Converts downloaded jpg, png and webp images with avifenc or ffmpeg, the cache records the converted files")
+            .value_name("avif|webp")
+            .value_parser(EnumValueParser::<ImageFormat>::new())
+            .action(clap::ArgAction::Set),
+        Arg::new("convert-quality")
+            .long("convert-quality")
+            .env("REDDIT_CLAWLER_CONVERT_QUALITY")
+            .long_help("Quality of converted images from 0 to 100, defaults to 80")
+            .value_name("quality")
+            .value_parser(clap::value_parser!(u8).range(0..=100))
+            .requires("convert-images")
+            .action(clap::ArgAction::Set),
+        Arg::new("remux")
+            .long("remux")
+            .env("REDDIT_CLAWLER_REMUX")
+            .long_help("Remuxes downloaded videos into another container with ffmpeg, without encoding them again")
+            .value_name("mp4|mkv")
+            .value_parser(EnumValueParser::<VideoContainer>::new())
+            .action(clap::ArgAction::Set),
+        Arg::new("transcode")
+            .long("transcode")
+            .env("REDDIT_CLAWLER_TRANSCODE")
+            .long_help("Encodes downloaded videos again with ffmpeg, written as mp4 unless --remux is given")
+            .value_name("h264|h265")
+            .value_parser(EnumValueParser::<VideoCodec>::new())
+            .action(clap::ArgAction::Set),
+        Arg::new("video-tasks")
+            .long("video-tasks")
+            .env("REDDIT_CLAWLER_VIDEO_TASKS")
+            .long_help("Amount of ffmpeg processes remuxing or transcoding videos at once [1-100]")
+            .value_name("tasks")
+            .value_parser(clap::value_parser!(u16).range(1..=100))
+            .default_value("1")
+            .action(clap::ArgAction::Set),
+        Arg::new("audio-only")
+            .long("audio-only")
+            .env("REDDIT_CLAWLER_AUDIO_ONLY")
+            .long_help("Stores only the audio of Reddit videos, YouTube embeds and the yt-dlp fallback, extracted with yt-dlp and ffmpeg. Images and gifs are downloaded as usual")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("audio-format")
+            .long("audio-format")
+            .env("REDDIT_CLAWLER_AUDIO_FORMAT")
+            .long_help("Format of the audio stored with --audio-only, defaults to m4a")
+            .value_name("m4a|mp3")
+            .value_parser(EnumValueParser::<AudioFormat>::new())
+            .requires("audio-only")
+            .action(clap::ArgAction::Set),
+        Arg::new("cookies")
+            .long("cookies")
+            .env("REDDIT_CLAWLER_COOKIES")
+            .long_help("Netscape cookies.txt sent along with the requests to its domains and handed to yt-dlp, e.g. to download age-restricted YouTube videos or NSFW-gated content")
+            .value_name("PATH")
+            .action(clap::ArgAction::Set),
+        Arg::new("cookies-from-browser")
+            .long("cookies-from-browser")
+            .env("REDDIT_CLAWLER_COOKIES_FROM_BROWSER")
+            .long_help("Lets yt-dlp read the cookies of a browser e.g. firefox or chrome:Profile 1, takes BROWSER[+KEYRING][:PROFILE][::CONTAINER] like yt-dlp")
+            .value_name("BROWSER")
+            .action(clap::ArgAction::Set),
+        Arg::new("import-archive")
+            .long("import-archive")
+            .env("REDDIT_CLAWLER_IMPORT_ARCHIVE")
+            .long_help("Skips the posts downloaded by other tools, read from a gallery-dl SQLite archive, a BDFR archiver folder or a file with a post ID per line")
+            .value_name("PATH")
+            .action(clap::ArgAction::Set),
+        Arg::new("global-cache")
+            .long("global-cache")
+            .env("REDDIT_CLAWLER_GLOBAL_CACHE")
+            .long_help("SQLite database of the posts downloaded by every crawl, a post downloaded for a user is not downloaded again for its subreddit e.g. ~/.cache/reddit-clawler/global.db")
+            .value_name("PATH")
+            .action(clap::ArgAction::Set),
+        Arg::new("link-duplicates")
+            .long("link-duplicates")
+            .env("REDDIT_CLAWLER_LINK_DUPLICATES")
+            .long_help("Links files of the global cache into the crawled resource instead of skipping them, e.g. the posts of a user downloaded by a subreddit crawl. Needs --global-cache")
+            .value_name("hardlink|symlink")
+            .value_parser(EnumValueParser::<LinkMode>::new())
+            .action(clap::ArgAction::Set),
+        Arg::new("checkpoint-every")
+            .long("checkpoint-every")
+            .env("REDDIT_CLAWLER_CHECKPOINT_EVERY")
+            .long_help("Writes the cache during a crawl after this many downloads, so a crash does not lose them")
+            .value_name("downloads")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .default_value("100")
+            .action(clap::ArgAction::Set),
+        Arg::new("checkpoint-interval")
+            .long("checkpoint-interval")
+            .env("REDDIT_CLAWLER_CHECKPOINT_INTERVAL")
+            .long_help("Writes the cache during a crawl at least this often when there are new downloads, e.g. 30s or 5m")
+            .value_name("interval")
+            .value_parser(parse_interval)
+            .default_value("30s")
+            .action(clap::ArgAction::Set),
+        Arg::new("backend")
+            .long("backend")
+            .env("REDDIT_CLAWLER_BACKEND")
+            .long_help("Fetches user and subreddit posts from an archive instead, which reaches past the ~1000 posts of Reddit listings")
+            .value_name("reddit|pushshift|arctic-shift")
+            .value_parser(EnumValueParser::<Backend>::new())
+            .default_value("reddit")
+            .action(clap::ArgAction::Set),
+        Arg::new("pushshift-token")
+            .long("pushshift-token")
+            .env("REDDIT_CLAWLER_PUSHSHIFT_TOKEN")
+            .long_help("Access token of the Pushshift API")
+            .value_name("TOKEN")
+            .hide_env_values(true)
+            .action(clap::ArgAction::Set),
         Arg::new("output")
             .short('o')
             .long("output")
-            .long_help("File download output directory")
+            .env("REDDIT_CLAWLER_OUTPUT")
+            .long_help("File download output directory, or an s3://bucket/prefix or webdav://host/path URL the files are uploaded to")
             .value_name("PATH")
             .default_value("output")
             .action(clap::ArgAction::Set),
+        Arg::new("output-absolute")
+            .long("output-absolute")
+            .env("REDDIT_CLAWLER_OUTPUT_ABSOLUTE")
+            .long_help("Resolves the output directory to an absolute path")
+            .action(ArgAction::SetTrue),
+        Arg::new("client-id")
+            .long("client-id")
+            .env("REDDIT_CLAWLER_CLIENT_ID")
+            .long_help("Client ID of a Reddit app, requests go through the OAuth API with its higher rate limit")
+            .value_name("ID")
+            .requires("client-secret"),
+        Arg::new("client-secret")
+            .long("client-secret")
+            .env("REDDIT_CLAWLER_CLIENT_SECRET")
+            .long_help("Client secret of a Reddit app")
+            .value_name("SECRET")
+            .hide_env_values(true)
+            .requires("client-id"),
+    ];
+
+    let credential_args = &[
+        Arg::new("username")
+            .long("username")
+            .env("REDDIT_CLAWLER_USERNAME")
+            .long_help("Reddit username of the app developer")
+            .value_name("USERNAME")
+            .required(true),
+        Arg::new("password")
+            .long("password")
+            .env("REDDIT_CLAWLER_PASSWORD")
+            .long_help("Reddit password of the app developer")
+            .value_name("PASSWORD")
+            .hide_env_values(true)
+            .required(true),
     ];
 
+    let parallel_targets_arg = Arg::new("parallel-targets")
+        .long("parallel-targets")
+        .env("REDDIT_CLAWLER_PARALLEL_TARGETS")
+        .long_help("Crawls this many targets at once, their downloads share --tasks and the Reddit rate limit")
+        .value_name("count")
+        .value_parser(clap::value_parser!(u16).range(1..=16))
+        .default_value("1")
+        .action(clap::ArgAction::Set);
+
     let cmd = Command::new("reddit-clawler")
         .version("0.1.0")
         .about("Crawler for Reddit posts")
@@ -127,6 +918,7 @@ pub fn run() -> CliCommand {
                 .arg(
                     Arg::new("category")
                         .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
                         .long_help("Category for posts")
                         .value_name("hot|new|rising|top|controversial")
                         .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
@@ -135,6 +927,7 @@ pub fn run() -> CliCommand {
                 .arg(
                     Arg::new("timeframe")
                         .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
                         .long_help(
                             "Timeframe for posts - needed when using category top|controversial",
                         )
@@ -145,6 +938,64 @@ pub fn run() -> CliCommand {
                 )
                 .args(shared_args.clone()),
         )
+        .subcommand(
+            Command::new("user-comments")
+                .about("Save the comments of a specific user as NDJSON")
+                .arg(Arg::new("resource").required(true).index(1))
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
+                        .long_help("Category for comments")
+                        .value_name("hot|new|top|controversial")
+                        .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
+                        .default_value("new"),
+                )
+                .arg(
+                    Arg::new("timeframe")
+                        .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
+                        .long_help(
+                            "Timeframe for comments - needed when using category top|controversial",
+                        )
+                        .value_name("hour|day|week|month|year|all")
+                        .value_parser(EnumValueParser::<RedditTimeframeFilter>::new())
+                        .required_if_eq("category", "top")
+                        .required_if_eq("category", "controversial"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .short('l')
+                        .long("limit")
+                        .env("REDDIT_CLAWLER_LIMIT")
+                        .long_help("Limit of fetch requests")
+                        .value_name("limit")
+                        .value_parser(clap::value_parser!(u32)),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .env("REDDIT_CLAWLER_OUTPUT")
+                        .long_help("Output directory, the comments are saved next to the posts of the user")
+                        .value_name("PATH")
+                        .default_value("output"),
+                )
+                .arg(
+                    Arg::new("markdown")
+                        .long("markdown")
+                        .env("REDDIT_CLAWLER_MARKDOWN")
+                        .long_help("Also writes the comments as a readable comments.md")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("mock")
+                        .long("mock")
+                        .env("REDDIT_CLAWLER_MOCK")
+                        .long_help("Pass a mock of a Reddit API response for development purposes")
+                        .hide(true),
+                ),
+        )
         .subcommand(
             Command::new("search")
                 .about("Download posts from a specific search term")
@@ -152,6 +1003,7 @@ pub fn run() -> CliCommand {
                 .arg(
                     Arg::new("category")
                         .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
                         .long_help("Category for posts")
                         .value_name("hot|new|rising|top|controversial")
                         .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
@@ -160,6 +1012,7 @@ pub fn run() -> CliCommand {
                 .arg(
                     Arg::new("timeframe")
                         .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
                         .long_help(
                             "Timeframe for posts - needed when using category top|controversial",
                         )
@@ -173,10 +1026,347 @@ pub fn run() -> CliCommand {
         .subcommand(
             Command::new("subreddit")
                 .about("Download posts from a specific subreddit")
+                .arg(
+                    Arg::new("resource")
+                        .required(true)
+                        .index(1)
+                        .long_help("Subreddit to crawl, all, popular or several combined e.g. pics+aww+earthporn"),
+                )
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
+                        .long_help("Category for posts")
+                        .value_name("hot|new|rising|top|controversial")
+                        .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("timeframe")
+                        .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
+                        .long_help(
+                            "Timeframe for posts - needed when using category top|controversial",
+                        )
+                        .value_name("hour|day|week|month|year|all")
+                        .value_parser(EnumValueParser::<RedditTimeframeFilter>::new())
+                        .required_if_eq("category", "top")
+                        .required_if_eq("category", "controversial"),
+                )
+                .arg(
+                    Arg::new("deep")
+                        .long("deep")
+                        .env("REDDIT_CLAWLER_DEEP")
+                        .long_help("Searches the subreddit in time windows back to its creation, reaching past the ~1000 posts of a listing. Posts are fetched newest first regardless of the category")
+                        .conflicts_with("backend")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("crawl-authors")
+                        .long("crawl-authors")
+                        .env("REDDIT_CLAWLER_CRAWL_AUTHORS")
+                        .long_help("Crawls every author of the matched posts as a user afterwards, with the same options")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("max-authors")
+                        .long("max-authors")
+                        .env("REDDIT_CLAWLER_MAX_AUTHORS")
+                        .long_help("Only crawls the authors with the most matched posts")
+                        .value_name("count")
+                        .value_parser(clap::value_parser!(usize))
+                        .requires("crawl-authors")
+                        .action(clap::ArgAction::Set),
+                )
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("domain")
+                .about("Download posts linking to a domain, e.g. imgur.com")
                 .arg(Arg::new("resource").required(true).index(1))
                 .arg(
                     Arg::new("category")
                         .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
+                        .long_help("Category for posts")
+                        .value_name("hot|new|rising|top|controversial")
+                        .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("timeframe")
+                        .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
+                        .long_help(
+                            "Timeframe for posts - needed when using category top|controversial",
+                        )
+                        .value_name("hour|day|week|month|year|all")
+                        .value_parser(EnumValueParser::<RedditTimeframeFilter>::new())
+                        .required_if_eq("category", "top")
+                        .required_if_eq("category", "controversial"),
+                )
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("post")
+                .about("Download a single post")
+                .arg(
+                    Arg::new("resource")
+                        .long_help("URL or ID of the post")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("include-duplicates")
+                        .long("include-duplicates")
+                        .env("REDDIT_CLAWLER_INCLUDE_DUPLICATES")
+                        .long_help("Records other submissions of the same media in the cache")
+                        .action(ArgAction::SetTrue),
+                )
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("redgifs-user")
+                .about("Download the gifs of a Redgifs user")
+                .arg(
+                    Arg::new("resource")
+                        .long_help("Name of the Redgifs user")
+                        .value_name("USER")
+                        .required(true)
+                        .index(1),
+                )
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Download posts from every target listed in a file, one after another")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Text file with one user:NAME, subreddit:NAME, search:TERM or domain:NAME per line, or a TOML file with a targets list")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
+                        .long_help("Category for posts")
+                        .value_name("hot|new|rising|top|controversial")
+                        .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("timeframe")
+                        .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
+                        .long_help(
+                            "Timeframe for posts - needed when using category top|controversial",
+                        )
+                        .value_name("hour|day|week|month|year|all")
+                        .value_parser(EnumValueParser::<RedditTimeframeFilter>::new())
+                        .required_if_eq("category", "top")
+                        .required_if_eq("category", "controversial"),
+                )
+                .arg(parallel_targets_arg.clone())
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Keep running and crawl the new posts of every target in a file periodically")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Batch file with the targets, re-read before every run")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .env("REDDIT_CLAWLER_INTERVAL")
+                        .long_help("Pause between two runs, e.g. 90s, 15m, 6h or 1d")
+                        .value_name("interval")
+                        .value_parser(parse_interval)
+                        .default_value("15m")
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("status-port")
+                        .long("status-port")
+                        .env("REDDIT_CLAWLER_STATUS_PORT")
+                        .long_help("Serves the transfer status as JSON on http://127.0.0.1:PORT/status")
+                        .value_name("port")
+                        .value_parser(clap::value_parser!(u16))
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(parallel_targets_arg)
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("schedule")
+                .about("Keep running and crawl the new posts of every target on its own cron schedule")
+                .arg(
+                    Arg::new("path")
+                        .long_help("TOML file with a [[targets]] table per target, each with a target and a cron schedule")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("saved")
+                .about("Download the saved posts of the authenticated user")
+                .args(credential_args.clone())
+                .args(shared_args.clone())
+                // The account commands need a script app for the password grant
+                .mut_arg("client-id", |a| a.required(true))
+                .mut_arg("client-secret", |a| a.required(true)),
+        )
+        .subcommand(
+            Command::new("upvoted")
+                .about("Download the upvoted posts of the authenticated user")
+                .args(credential_args.clone())
+                .args(shared_args.clone())
+                // The account commands need a script app for the password grant
+                .mut_arg("client-id", |a| a.required(true))
+                .mut_arg("client-secret", |a| a.required(true)),
+        )
+        .subcommand(
+            Command::new("rebuild-views")
+                .about("Regenerate the views folders of symlinks grouped by subreddit and date")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Output directory containing the crawled resources")
+                        .value_name("PATH")
+                        .default_value("output")
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Export the cached files of the crawled resources as a CSV report")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Output directory containing the crawled resources")
+                        .value_name("PATH")
+                        .default_value("output")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("file")
+                        .long("file")
+                        .env("REDDIT_CLAWLER_REPORT_FILE")
+                        .long_help("Writes the report to a file instead of stdout")
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Summarize the caches of the crawled resources: totals, top posters, size by subreddit and growth per month")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Output directory containing the crawled resources")
+                        .value_name("PATH")
+                        .default_value("output")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .env("REDDIT_CLAWLER_STATS_TOP")
+                        .long_help("Amount of authors and subreddits listed")
+                        .value_name("count")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10")
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .env("REDDIT_CLAWLER_FORMAT")
+                        .long_help("Prints the stats as tables or as JSON")
+                        .value_name("table|json")
+                        .value_parser(EnumValueParser::<OutputFormat>::new())
+                        .default_value("table")
+                        .action(clap::ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Fetch the archived posts again and list the ones deleted or removed from Reddit since the last check")
+                .arg(
+                    Arg::new("path")
+                        .long_help("Output directory containing the crawled resources, or the folder of a single resource")
+                        .value_name("PATH")
+                        .default_value("output")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .env("REDDIT_CLAWLER_FORMAT")
+                        .long_help("Prints the taken down posts as a table or as JSON")
+                        .value_name("table|json")
+                        .value_parser(EnumValueParser::<OutputFormat>::new())
+                        .default_value("table")
+                        .action(clap::ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("retry")
+                .about("Download the failed files of the crawled resources in the output directory again")
+                .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("cache")
+                .about("Inspect and maintain the caches of the crawled resources")
+                .arg(
+                    Arg::new("action")
+                        .long_help("stats counts the cached files, verify marks files missing on disk or differing from the cache as failed, prune removes failed entries, migrate upgrades old caches and rebuild reconstructs the cache of a folder from the names of its files")
+                        .value_name("stats|verify|prune|migrate|rebuild")
+                        .value_parser(EnumValueParser::<CacheAction>::new())
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("path")
+                        .long_help("Output directory containing the crawled resources")
+                        .value_name("PATH")
+                        .default_value("output")
+                        .index(2),
+                )
+                .arg(
+                    Arg::new("deep")
+                        .long("deep")
+                        // `REDDIT_CLAWLER_DEEP` already turns on the deep search of subreddits
+                        .env("REDDIT_CLAWLER_CACHE_DEEP")
+                        .long_help("Hashes the files again during verify and compares them with the hash recorded by --checksum")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("authors")
+                .about("List the authors of a subreddit or search ranked by their posts, without downloading anything")
+                .arg(
+                    Arg::new("source")
+                        .long_help("Listing to rank the authors of")
+                        .value_name("subreddit|search")
+                        .value_parser(EnumValueParser::<AuthorSource>::new())
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("resource")
+                        .long_help("Subreddit or search term to crawl")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .env("REDDIT_CLAWLER_CATEGORY")
                         .long_help("Category for posts")
                         .value_name("hot|new|rising|top|controversial")
                         .value_parser(EnumValueParser::<RedditCategoryFilter>::new())
@@ -185,6 +1375,7 @@ pub fn run() -> CliCommand {
                 .arg(
                     Arg::new("timeframe")
                         .long("timeframe")
+                        .env("REDDIT_CLAWLER_TIMEFRAME")
                         .long_help(
                             "Timeframe for posts - needed when using category top|controversial",
                         )
@@ -193,36 +1384,321 @@ pub fn run() -> CliCommand {
                         .required_if_eq("category", "top")
                         .required_if_eq("category", "controversial"),
                 )
+                .arg(
+                    Arg::new("list-format")
+                        .long("list-format")
+                        .env("REDDIT_CLAWLER_LIST_FORMAT")
+                        .long_help("Writes the authors as CSV, as JSON or as user:NAME lines for batch files")
+                        .value_name("csv|json|batch")
+                        .value_parser(EnumValueParser::<AuthorListFormat>::new())
+                        .default_value("csv")
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("file")
+                        .long("file")
+                        .env("REDDIT_CLAWLER_AUTHORS_FILE")
+                        .long_help("Writes the authors to a file instead of stdout")
+                        .value_name("FILE"),
+                )
                 .args(shared_args.clone()),
+        )
+        .subcommand(
+            Command::new("explain")
+                .about("Print which parser branch matches a post and the files it would download")
+                .arg(
+                    Arg::new("resource")
+                        .long_help("Post URL or ID to explain")
+                        .value_name("POST")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("mock")
+                        .long("mock")
+                        .env("REDDIT_CLAWLER_MOCK")
+                        .long_help("Pass a mock of a Reddit API response for development purposes")
+                        .hide(true),
+                )
+                .arg(
+                    Arg::new("file-scheme")
+                        .long("file-scheme")
+                        .env("REDDIT_CLAWLER_FILE_SCHEME")
+                        .long_help("Naming scheme of downloaded files")
+                        .value_name("SCHEME")
+                        .default_value(DEFAULT_FILE_SCHEME),
+                ),
         );
 
-    let matches = cmd.get_matches();
+    // clap exits with 2 on invalid flags, which is the exit code of partial failures here
+    let matches = cmd.try_get_matches().unwrap_or_else(|e| {
+        let _ = e.print();
+        match e.use_stderr() {
+            true => ExitStatus::InvalidInput.exit(),
+            // --help and --version
+            false => ExitStatus::Success.exit(),
+        }
+    });
+
+    let get_file_scheme = |m: &clap::ArgMatches| {
+        let file_scheme = resolve_flag(m, "file-scheme", config.file_scheme.as_ref()).unwrap();
+        check_file_scheme(&file_scheme);
+        file_scheme
+    };
 
     let get_shared_options = |m: &clap::ArgMatches| {
-        let concurrency = m.get_one::<u16>("tasks").unwrap().to_owned();
+        let concurrency = resolve_flag(m, "tasks", config.tasks.as_ref()).unwrap();
         let mock = m.get_one::<String>("mock").cloned();
-        let output = m.get_one::<String>("output").unwrap().to_owned();
+        let output = resolve_flag(m, "output", config.output.as_ref()).unwrap();
+        let remote = RemoteStorage::parse(&output).unwrap_or_else(|e| {
+            println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+            ExitStatus::InvalidInput.exit()
+        });
+        // Archives bundle the downloaded files, which are removed once uploaded
+        if remote.is_some() && m.contains_id("archive") {
+            println!(
+                "{} --archive can only be used with a local output directory",
+                "[INVALID_OUTPUT]".bold().red()
+            );
+            ExitStatus::InvalidInput.exit()
+        }
+        let output = match &remote {
+            Some(remote) => remote.staging_dir().to_string_lossy().to_string(),
+            None => output,
+        };
+        let output =
+            resolve_output_path(&output, m.get_flag("output-absolute")).unwrap_or_else(|e| {
+                println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                ExitStatus::InvalidInput.exit()
+            });
         let skip = m.get_one::<bool>("skip").unwrap().to_owned();
-        let verbose = m.get_one::<bool>("verbose").unwrap().to_owned();
+        let verbose = m.get_count("verbose");
         let limit = m.get_one::<u32>("limit").copied();
+        let stop_after_cached_pages = match m.get_flag("until-cached") {
+            true => Some(1),
+            false => m.get_one::<u32>("stop-after-cached-pages").copied(),
+        };
+        let inject_failure = m.get_one::<FailureInjection>("inject-failure").copied();
+        let imported_archive = match m.get_one::<String>("import-archive") {
+            Some(path) => ImportedArchive::load(path).unwrap_or_else(|e| {
+                println!("{} {}: {}", "[INVALID_ARCHIVE]".bold().red(), path, e);
+                ExitStatus::InvalidInput.exit()
+            }),
+            None => ImportedArchive::default(),
+        };
+        let global_cache =
+            resolve_flag(m, "global-cache", config.global_cache.as_ref()).map(|path| {
+                GlobalCache::open(&path).unwrap_or_else(|e| {
+                    println!("{} {}: {}", "[INVALID_GLOBAL_CACHE]".bold().red(), path, e);
+                    ExitStatus::InvalidInput.exit()
+                })
+            });
+        let retry_policy = RetryPolicy {
+            retries: resolve_flag(m, "retries", config.retries.as_ref()).unwrap(),
+            base_delay: resolve_flag(
+                m,
+                "retry-base-delay",
+                config
+                    .retry_base_delay
+                    .as_deref()
+                    .and_then(|d| parse_interval(d).ok())
+                    .as_ref(),
+            )
+            .unwrap(),
+            max_delay: resolve_flag(
+                m,
+                "retry-max-delay",
+                config
+                    .retry_max_delay
+                    .as_deref()
+                    .and_then(|d| parse_interval(d).ok())
+                    .as_ref(),
+            )
+            .unwrap(),
+        };
+        if retry_policy.base_delay > retry_policy.max_delay {
+            println!(
+                "{} --retry-base-delay must not be longer than --retry-max-delay",
+                "[INVALID_RETRY_DELAY]".bold().red()
+            );
+            ExitStatus::InvalidInput.exit()
+        }
+        let cookie_file = m.get_one::<String>("cookies").map(|path| {
+            let jar = CookieJar::load(path).unwrap_or_else(|e| {
+                println!("{} {}", "[INVALID_COOKIES]".bold().red(), e);
+                ExitStatus::InvalidInput.exit()
+            });
+            // yt-dlp does not expand `~` on its own
+            (jar, expand_path(path).unwrap_or_else(|_| path.to_owned()))
+        });
+        let link_duplicates = m.get_one::<LinkMode>("link-duplicates").copied();
+        if link_duplicates.is_some() && global_cache.is_none() {
+            println!(
+                "{} --link-duplicates needs --global-cache to find the files to link",
+                "[INVALID_LINK_DUPLICATES]".bold().red()
+            );
+            ExitStatus::InvalidInput.exit()
+        }
+        let output_mode = m.get_one::<OutputMode>("output-format").unwrap().to_owned();
+        let user_agents = match m.get_one::<String>("user-agent-file") {
+            Some(path) => UserAgentPool::from_file(path),
+            None => UserAgentPool::new(
+                resolve_flag(m, "user-agent", config.user_agent.as_ref())
+                    .into_iter()
+                    .collect(),
+            ),
+        }
+        .unwrap_or_else(|e| {
+            println!("{} {}", "[INVALID_USER_AGENT]".bold().red(), e);
+            ExitStatus::InvalidInput.exit()
+        });
+        // Also checks the proxy of the config file, which is not parsed by clap
+        let proxy = resolve_flag(m, "proxy", config.proxy.as_ref()).map(|proxy| {
+            parse_proxy_url(&proxy).unwrap_or_else(|e| {
+                println!("{} {}", "[INVALID_PROXY]".bold().red(), e);
+                ExitStatus::InvalidInput.exit()
+            })
+        });
+
+        let stickied = match (m.get_flag("exclude-stickied"), m.get_flag("only-stickied")) {
+            (true, _) => StickiedFilter::Exclude,
+            (_, true) => StickiedFilter::Only,
+            _ => StickiedFilter::Include,
+        };
+        let nsfw = match (m.get_flag("sfw-only"), m.get_flag("nsfw-only")) {
+            (true, _) => NsfwFilter::Exclude,
+            (_, true) => NsfwFilter::Only,
+            _ => NsfwFilter::Include,
+        };
 
         CliSharedOptions {
             concurrency,
             mock,
             output,
             skip,
+            force: m.get_flag("force"),
+            // Nothing is downloaded or cached while exporting either
+            dry_run: m.get_flag("dry-run") || m.contains_id("export-urls"),
+            export_urls: m.get_one::<String>("export-urls").cloned(),
+            format: m.get_one::<OutputFormat>("format").unwrap().to_owned(),
             verbose,
+            quiet: m.get_flag("quiet"),
+            log_file: m.get_one::<String>("log-file").cloned(),
             limit,
+            stop_after_cached_pages,
+            inject_failure,
+            filter: PostFilter {
+                stickied,
+                exclude_spoilers: m.get_flag("exclude-spoilers"),
+                nsfw,
+                min_upvotes: m.get_one::<i64>("min-upvotes").copied(),
+                max_upvotes: m.get_one::<i64>("max-upvotes").copied(),
+                after_date: m.get_one::<DateTime<Utc>>("after-date").copied(),
+                before_date: m.get_one::<DateTime<Utc>>("before-date").copied(),
+                only: m
+                    .get_many::<MediaType>("only")
+                    .map(|types| types.copied().collect())
+                    .unwrap_or_default(),
+                title_match: m.get_one::<Regex>("title-match").cloned(),
+                title_exclude: m.get_one::<Regex>("title-exclude").cloned(),
+            },
+            export_captions: m.get_flag("export-captions"),
+            with_comments: m.get_flag("with-comments"),
+            write_metadata: m.get_flag("write-metadata"),
+            file_scheme: m
+                .get_one::<Organize>("organize")
+                .unwrap()
+                .apply(&get_file_scheme(m)),
+            quality: resolve_flag(m, "quality", config.quality.as_ref()).unwrap(),
+            progress: match output_mode {
+                OutputMode::Text => m.get_one::<ProgressMode>("progress").unwrap().to_owned(),
+                OutputMode::Json => ProgressMode::Hidden,
+            },
+            output_mode,
+            proxy,
+            user_agents,
+            notifier: Notifier {
+                webhook: m.get_one::<Url>("notify-webhook").cloned(),
+                command: m.get_one::<String>("notify-command").cloned(),
+            },
+            backend: m.get_one::<Backend>("backend").unwrap().to_owned(),
+            pushshift_token: m.get_one::<String>("pushshift-token").cloned(),
+            archive: m.get_one::<ArchiveFormat>("archive").copied(),
+            archive_delete: m.get_flag("archive-delete"),
+            size_limits: SizeLimits {
+                max_file_size: m.get_one::<u64>("max-file-size").copied(),
+                max_total_size: m.get_one::<u64>("max-total-size").copied(),
+            },
+            rate_limit: m.get_one::<u64>("rate-limit").copied(),
+            embed_metadata: m.get_flag("embed-metadata"),
+            checksum: m.get_flag("checksum"),
+            skip_existing: m.get_flag("skip-existing"),
+            imported_archive,
+            global_cache,
+            link_duplicates,
+            remote,
+            downloader: m.get_one::<Downloader>("downloader").unwrap().to_owned(),
+            convert_images: m.get_one::<ImageFormat>("convert-images").map(|format| {
+                ImageConversion {
+                    format: *format,
+                    quality: m
+                        .get_one::<u8>("convert-quality")
+                        .copied()
+                        .unwrap_or(DEFAULT_CONVERT_QUALITY),
+                }
+            }),
+            aria2c_connections: m.get_one::<u16>("aria2c-connections").copied().unwrap(),
+            preview_size: match m.get_flag("thumbnails-only") {
+                true => Some(PreviewSize::Thumbnail),
+                false => m
+                    .get_one::<u32>("max-resolution")
+                    .map(|max| PreviewSize::MaxResolution(*max)),
+            },
+            fallback_ytdlp: m.get_flag("fallback-ytdlp"),
+            fallback_ytdlp_tasks: m.get_one::<u16>("fallback-ytdlp-tasks").unwrap().to_owned(),
+            video_processing: VideoProcessing {
+                remux: m.get_one::<VideoContainer>("remux").copied(),
+                transcode: m.get_one::<VideoCodec>("transcode").copied(),
+            },
+            video_tasks: m.get_one::<u16>("video-tasks").unwrap().to_owned(),
+            ytdlp_cookies: YtdlpCookies {
+                file: cookie_file.as_ref().map(|(_, path)| path.to_owned()),
+                from_browser: m.get_one::<String>("cookies-from-browser").cloned(),
+            },
+            cookies: cookie_file.map(|(jar, _)| jar),
+            audio_only: m.get_flag("audio-only").then(|| {
+                m.get_one::<AudioFormat>("audio-format")
+                    .copied()
+                    .unwrap_or_default()
+            }),
+            transfer_timeouts: TransferTimeouts {
+                download: m.get_one::<Duration>("download-timeout").copied(),
+                stall: m.get_one::<Duration>("stall-timeout").unwrap().to_owned(),
+            },
+            ytdlp_timeout: m.get_one::<Duration>("ytdlp-timeout").unwrap().to_owned(),
+            retry_policy,
+            checkpoint: CheckpointSettings {
+                every: m.get_one::<u32>("checkpoint-every").unwrap().to_owned(),
+                interval: m
+                    .get_one::<Duration>("checkpoint-interval")
+                    .unwrap()
+                    .to_owned(),
+            },
+            provider_proxies: m
+                .get_many::<ProviderProxy>("provider-proxy")
+                .map(|proxies| proxies.cloned().collect())
+                .unwrap_or_default(),
+            app_credentials: m
+                .get_one::<String>("client-id")
+                .zip(m.get_one::<String>("client-secret"))
+                .map(|(client_id, client_secret)| RedditAppCredentials {
+                    client_id: client_id.to_string(),
+                    client_secret: client_secret.to_string(),
+                }),
         }
     };
 
-    let get_inputs = |m: &clap::ArgMatches| -> (
-        String,
-        RedditCategoryFilter,
-        RedditTimeframeFilter,
-        CliSharedOptions,
-    ) {
-        let resource = m.get_one::<String>("resource").unwrap().to_string();
+    let get_listing = |m: &clap::ArgMatches| -> (RedditCategoryFilter, RedditTimeframeFilter) {
         let category = m
             .get_one::<RedditCategoryFilter>("category")
             .unwrap()
@@ -248,10 +1724,36 @@ pub fn run() -> CliCommand {
                 .to_owned(),
         };
 
+        (category, timeframe)
+    };
+
+    let get_inputs = |m: &clap::ArgMatches| -> (
+        String,
+        RedditCategoryFilter,
+        RedditTimeframeFilter,
+        CliSharedOptions,
+    ) {
+        let resource = m.get_one::<String>("resource").unwrap().to_string();
+        let (category, timeframe) = get_listing(m);
         let shared_options = get_shared_options(m);
         (resource, category, timeframe, shared_options)
     };
 
+    let get_parallel_targets =
+        |m: &clap::ArgMatches| *m.get_one::<u16>("parallel-targets").unwrap() as usize;
+
+    let get_account_command =
+        |listing: RedditAccountListing, m: &clap::ArgMatches| CliAccountCommand {
+            listing,
+            credentials: RedditCredentials {
+                client_id: m.get_one::<String>("client-id").unwrap().to_string(),
+                client_secret: m.get_one::<String>("client-secret").unwrap().to_string(),
+                username: m.get_one::<String>("username").unwrap().to_string(),
+                password: m.get_one::<String>("password").unwrap().to_string(),
+            },
+            options: get_shared_options(m),
+        };
+
     match matches.subcommand() {
         Some(("user", m)) => {
             let (resource, category, timeframe, options)= get_inputs(m);
@@ -259,15 +1761,21 @@ pub fn run() -> CliCommand {
                 resource,
                 category,
                 timeframe,
+                deep: false,
+                crawl_authors: false,
+                max_authors: None,
                 options
             })
         }
         Some(("subreddit", m)) => {
             let (resource, category, timeframe, options)= get_inputs(m);
             CliCommand::Subreddit(CliRedditCommand {
-                resource,
+                resource: normalize_subreddit(&resource),
                 category,
                 timeframe,
+                deep: m.get_flag("deep"),
+                crawl_authors: m.get_flag("crawl-authors"),
+                max_authors: m.get_one::<usize>("max-authors").copied(),
                 options
             })
         }
@@ -277,9 +1785,165 @@ pub fn run() -> CliCommand {
                 resource,
                 category,
                 timeframe,
+                deep: false,
+                crawl_authors: false,
+                max_authors: None,
+                options
+            })
+        }
+        Some(("domain", m)) => {
+            let (resource, category, timeframe, options)= get_inputs(m);
+            CliCommand::Domain(CliRedditCommand {
+                resource,
+                category,
+                timeframe,
+                deep: false,
+                crawl_authors: false,
+                max_authors: None,
                 options
             })
         }
+        Some(("post", m)) => CliCommand::Post(CliPostCommand {
+            resource: m.get_one::<String>("resource").unwrap().to_string(),
+            include_duplicates: m.get_flag("include-duplicates"),
+            options: get_shared_options(m),
+        }),
+        Some(("redgifs-user", m)) => CliCommand::RedgifsUser(CliRedgifsUserCommand {
+            resource: m.get_one::<String>("resource").unwrap().to_string(),
+            options: get_shared_options(m),
+        }),
+        Some(("batch", m)) => {
+            let (category, timeframe) = get_listing(m);
+            CliCommand::Batch(CliBatchCommand {
+                path: m.get_one::<String>("path").unwrap().to_string(),
+                category,
+                timeframe,
+                parallel_targets: get_parallel_targets(m),
+                options: get_shared_options(m),
+            })
+        }
+        Some(("watch", m)) => {
+            let mut options = get_shared_options(m);
+            // Without it every run would page through the whole listing again
+            options.stop_after_cached_pages = options.stop_after_cached_pages.or(Some(1));
+
+            CliCommand::Watch(CliWatchCommand {
+                path: m.get_one::<String>("path").unwrap().to_string(),
+                interval: m.get_one::<Duration>("interval").unwrap().to_owned(),
+                status_port: m.get_one::<u16>("status-port").copied(),
+                parallel_targets: get_parallel_targets(m),
+                options,
+            })
+        }
+        Some(("schedule", m)) => {
+            let mut options = get_shared_options(m);
+            options.stop_after_cached_pages = options.stop_after_cached_pages.or(Some(1));
+
+            CliCommand::Schedule(CliScheduleCommand {
+                path: m.get_one::<String>("path").unwrap().to_string(),
+                options,
+            })
+        }
+        Some(("saved", m)) => CliCommand::Saved(get_account_command(RedditAccountListing::Saved, m)),
+        Some(("upvoted", m)) => {
+            CliCommand::Upvoted(get_account_command(RedditAccountListing::Upvoted, m))
+        }
+        Some(("rebuild-views", m)) => {
+            let path = resolve_flag(m, "path", config.output.as_ref()).unwrap();
+            CliCommand::RebuildViews(CliRebuildViewsCommand {
+                path: resolve_output_path(&path, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+            })
+        }
+        Some(("retry", m)) => CliCommand::Retry(CliRetryCommand {
+            options: get_shared_options(m),
+        }),
+        Some(("cache", m)) => {
+            let path = resolve_flag(m, "path", config.output.as_ref()).unwrap();
+            CliCommand::Cache(CliCacheCommand {
+                action: m.get_one::<CacheAction>("action").unwrap().to_owned(),
+                deep: m.get_flag("deep"),
+                path: resolve_output_path(&path, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+            })
+        }
+        Some(("report", m)) => {
+            let path = resolve_flag(m, "path", config.output.as_ref()).unwrap();
+            CliCommand::Report(CliReportCommand {
+                path: resolve_output_path(&path, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+                file: m.get_one::<String>("file").cloned(),
+            })
+        }
+        Some(("stats", m)) => {
+            let path = resolve_flag(m, "path", config.output.as_ref()).unwrap();
+            CliCommand::Stats(CliStatsCommand {
+                path: resolve_output_path(&path, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+                top: m.get_one::<usize>("top").copied().unwrap(),
+                format: m.get_one::<OutputFormat>("format").unwrap().to_owned(),
+            })
+        }
+        Some(("diff", m)) => {
+            let path = resolve_flag(m, "path", config.output.as_ref()).unwrap();
+            CliCommand::Diff(CliDiffCommand {
+                path: resolve_output_path(&path, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+                format: m.get_one::<OutputFormat>("format").unwrap().to_owned(),
+            })
+        }
+        Some(("user-comments", m)) => {
+            let (category, timeframe) = get_listing(m);
+            let output = resolve_flag(m, "output", config.output.as_ref()).unwrap();
+            CliCommand::UserComments(CliUserCommentsCommand {
+                resource: m.get_one::<String>("resource").unwrap().to_string(),
+                category,
+                timeframe,
+                limit: m.get_one::<u32>("limit").copied(),
+                output: resolve_output_path(&output, false).unwrap_or_else(|e| {
+                    println!("{} {}", "[INVALID_OUTPUT]".bold().red(), e);
+                    ExitStatus::InvalidInput.exit()
+                }),
+                markdown: m.get_flag("markdown"),
+                mock: m.get_one::<String>("mock").cloned(),
+            })
+        }
+        Some(("authors", m)) => {
+            let (resource, category, timeframe, options) = get_inputs(m);
+            let source = m.get_one::<AuthorSource>("source").unwrap().to_owned();
+            CliCommand::Authors(CliAuthorsCommand {
+                source,
+                listing: CliRedditCommand {
+                    resource: match source {
+                        AuthorSource::Subreddit => normalize_subreddit(&resource),
+                        AuthorSource::Search => resource,
+                    },
+                    category,
+                    timeframe,
+                    deep: false,
+                    crawl_authors: false,
+                    max_authors: None,
+                    options,
+                },
+                format: m.get_one::<AuthorListFormat>("list-format").unwrap().to_owned(),
+                file: m.get_one::<String>("file").cloned(),
+            })
+        }
+        Some(("explain", m)) => CliCommand::Explain(CliExplainCommand {
+            resource: m.get_one::<String>("resource").unwrap().to_string(),
+            mock: m.get_one::<String>("mock").cloned(),
+            file_scheme: get_file_scheme(m),
+        }),
         _ => unreachable!(
             "Subcommand not found. Please file an issue: https://github.com/ecklf/reddit-clawler/issues/new"
         ),
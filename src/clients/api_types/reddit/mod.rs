@@ -1,2 +1,5 @@
+pub mod access_token;
 pub mod submitted_response;
+pub mod subreddit_about;
 pub mod user_about;
+pub mod user_comments;
@@ -46,8 +46,9 @@ pub struct RedditSubmittedChildData {
     // pub thumbnail: String,
     // pub created: f64,
     // pub url_overridden_by_dest: Option<String>,
-    // #[serde(rename = "over_18")]
-    // pub over_18: bool,
+    #[serde(rename = "over_18")]
+    #[serde(default)]
+    pub over_18: bool,
     pub preview: Option<Preview>,
     #[serde(rename = "media_only")]
     pub media_only: bool,
@@ -69,19 +70,45 @@ pub struct RedditSubmittedChildData {
     pub media_metadata: Option<HashMap<String, MediaMetadataValue>>,
     #[serde(rename = "gallery_data")]
     pub gallery_data: Option<GalleryData>,
+    #[serde(default)]
+    pub stickied: bool,
+    #[serde(default)]
+    pub spoiler: bool,
+    #[serde(rename = "contest_mode")]
+    #[serde(default)]
+    pub contest_mode: bool,
+    #[serde(default)]
+    pub permalink: String,
+    #[serde(default)]
+    pub selftext: String,
+    #[serde(rename = "link_flair_text")]
+    #[serde(default)]
+    pub link_flair_text: Option<String>,
+    #[serde(default)]
+    pub domain: String,
+    #[serde(rename = "is_self")]
+    #[serde(default)]
+    pub is_self: bool,
+    #[serde(rename = "post_hint")]
+    #[serde(default)]
+    pub post_hint: Option<String>,
+    #[serde(rename = "poll_data")]
+    #[serde(default)]
+    pub poll_data: Option<Value>,
+    // Set once a post is deleted by its author or removed by moderators or Reddit
+    #[serde(rename = "removed_by_category")]
+    #[serde(default)]
+    pub removed_by_category: Option<String>,
 }
 
-fn shitty_reddit_datetime_utc<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+pub fn shitty_reddit_datetime_utc<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: Deserializer<'de>,
 {
     let timestamp: f64 = Deserialize::deserialize(deserializer)?;
     // Convert the floating-point timestamp to i64 and then to DateTime<Utc>
     let utc_timestamp_seconds = (timestamp * 1000.0).round() as i64;
-    Ok(DateTime::<Utc>::from_naive_utc_and_offset(
-        chrono::NaiveDateTime::from_timestamp_opt(utc_timestamp_seconds / 1000, 0).unwrap(),
-        Utc,
-    ))
+    Ok(DateTime::<Utc>::from_timestamp(utc_timestamp_seconds / 1000, 0).unwrap())
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -293,10 +320,14 @@ pub struct Media {
 #[serde(rename_all = "camelCase")]
 pub struct MediaMetadataValue {
     pub status: String,
-    // pub e: String,
-    // pub m: String,
+    // Kind of media, e.g. Image or AnimatedImage
+    pub e: Option<String>,
+    // Mime type, e.g. image/jpg
+    pub m: Option<String>,
     // pub o: Option<Vec<O>>,
-    // pub p: Vec<P>,
+    // Previews of the image in ascending width
+    #[serde(default)]
+    pub p: Vec<P>,
     pub s: Option<S>,
     pub id: Option<String>,
 }
@@ -339,4 +370,8 @@ pub struct Item {
     #[serde(rename = "media_id")]
     pub media_id: String,
     pub id: i64,
+    pub caption: Option<String>,
+    // Link the gallery item points to, set by the poster
+    #[serde(rename = "outbound_url")]
+    pub outbound_url: Option<String>,
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditSubredditAbout {
+    pub kind: String,
+    pub data: RedditSubredditAboutData,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditSubredditAboutData {
+    #[serde(rename = "display_name")]
+    pub display_name: String,
+    // public, restricted, private, archived, gold_restricted, employees_only or user
+    #[serde(rename = "subreddit_type")]
+    pub subreddit_type: String,
+    #[serde(default)]
+    pub quarantine: bool,
+    #[serde(rename = "created_utc")]
+    pub created_utc: Option<f64>,
+}
+
+/// Body of a 403/404 response, `reason` tells why a subreddit can not be accessed
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedditErrorResponse {
+    pub reason: Option<String>,
+    pub message: Option<String>,
+    pub error: Option<u16>,
+}
@@ -0,0 +1,41 @@
+use super::submitted_response::shitty_reddit_datetime_utc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditUserCommentsResponse {
+    pub kind: Option<String>,
+    pub data: RedditUserCommentsData,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditUserCommentsData {
+    pub after: Option<String>,
+    pub children: Vec<RedditUserCommentChild>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditUserCommentChild {
+    pub kind: String,
+    pub data: RedditUserCommentData,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedditUserCommentData {
+    pub id: String,
+    pub author: String,
+    pub body: String,
+    pub subreddit: String,
+    pub score: i64,
+    pub permalink: String,
+    #[serde(rename = "created_utc")]
+    #[serde(deserialize_with = "shitty_reddit_datetime_utc")]
+    pub created_utc: DateTime<Utc>,
+    // Title of the post the comment was written under
+    #[serde(rename = "link_title")]
+    pub link_title: Option<String>,
+}
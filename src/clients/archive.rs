@@ -0,0 +1,244 @@
+use crate::{
+    cli::CliSharedOptions,
+    clients::api_types::reddit::submitted_response::{
+        Data, RedditSubmittedChild, RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    utils::{
+        emit_event, get_rate_limit_wait, sleep_with_countdown, state::SharedState,
+        CachedPageStreak, CrawlEvent, OutputMode,
+    },
+};
+use clap::ValueEnum;
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fmt, sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tracing::debug;
+
+// Used when a 429 response of an archive does not tell when the limit resets
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+// Attempts of a page that keeps getting rate limited before the crawl is aborted
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+const MAX_SUBMISSIONS_PER_REQUEST: u32 = 100;
+
+/// Where the posts of a listing are fetched from
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Reddit listings, capped at roughly 1000 posts
+    #[default]
+    Reddit,
+    /// Pushshift archive, needs a token from a moderator account
+    Pushshift,
+    /// Arctic Shift archive
+    ArcticShift,
+}
+
+impl Backend {
+    pub fn archive(&self) -> Option<Archive> {
+        match self {
+            Backend::Reddit => None,
+            Backend::Pushshift => Some(Archive::Pushshift),
+            Backend::ArcticShift => Some(Archive::ArcticShift),
+        }
+    }
+}
+
+/// Archive of Reddit posts paged by timestamp, reaching further back than Reddit listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archive {
+    Pushshift,
+    ArcticShift,
+}
+
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Archive::Pushshift => write!(f, "Pushshift"),
+            Archive::ArcticShift => write!(f, "Arctic Shift"),
+        }
+    }
+}
+
+/// Archives only index posts by author and subreddit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveQuery<'a> {
+    Author(&'a str),
+    Subreddit(&'a str),
+}
+
+#[derive(Error, Debug)]
+pub enum ArchiveClientError {
+    #[error("ReqwestMiddleware error: {0}")]
+    ReqwestMiddleware(#[from] reqwest_middleware::Error),
+    #[error("Reqwest error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("{0} returned a 429 Too Many Requests error")]
+    TooManyRequests(Archive),
+    #[error("{0} returned a {1} status")]
+    Status(Archive, reqwest::StatusCode),
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct ArchiveResponse {
+    #[serde(default)]
+    pub data: Vec<Value>,
+}
+
+/// Newest posts first, `before` and `after` are unix timestamps
+pub fn gen_archive_url(
+    archive: Archive,
+    query: ArchiveQuery,
+    before: Option<i64>,
+    after: Option<i64>,
+) -> String {
+    let (field, value) = match query {
+        ArchiveQuery::Author(author) => ("author", author),
+        ArchiveQuery::Subreddit(subreddit) => ("subreddit", subreddit),
+    };
+
+    let mut url = match archive {
+        Archive::Pushshift => format!(
+            "https://api.pushshift.io/reddit/search/submission?{}={}&size={}&sort=desc&sort_type=created_utc",
+            field, value, MAX_SUBMISSIONS_PER_REQUEST
+        ),
+        Archive::ArcticShift => format!(
+            "https://arctic-shift.photon-reddit.com/api/posts/search?{}={}&limit={}&sort=desc",
+            field, value, MAX_SUBMISSIONS_PER_REQUEST
+        ),
+    };
+    if let Some(before) = before {
+        url.push_str(&format!("&before={}", before));
+    }
+    if let Some(after) = after {
+        url.push_str(&format!("&after={}", after));
+    }
+
+    url
+}
+
+/// Wraps archived posts in a Reddit listing so they go through the same parser
+pub fn parse_archive_response(response: ArchiveResponse) -> RedditSubmittedResponse {
+    let children = response
+        .data
+        .into_iter()
+        .filter_map(
+            |post| match serde_json::from_value::<RedditSubmittedChildData>(post) {
+                Ok(data) => Some(RedditSubmittedChild {
+                    kind: Some(String::from("t3")),
+                    data,
+                }),
+                Err(e) => {
+                    debug!("Skipping an archived post missing Reddit fields: {}", e);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    RedditSubmittedResponse {
+        kind: Some(String::from("Listing")),
+        data: Data {
+            after: None,
+            children,
+            before: None,
+        },
+    }
+}
+
+/// Pages through an archive by timestamp, every page is sent to `pages` as soon as it arrives
+pub async fn get_archive_submissions(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    archive: Archive,
+    query: ArchiveQuery<'_>,
+    options: &CliSharedOptions,
+    pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+) -> Result<(), ArchiveClientError> {
+    let CliSharedOptions {
+        limit,
+        stop_after_cached_pages,
+        filter,
+        ..
+    } = options;
+
+    // The date filters are passed on, so the archive does not return posts that are skipped anyway
+    let mut before = filter.before_date.map(|d| d.timestamp());
+    let after = filter.after_date.map(|d| d.timestamp());
+    let mut request_count: u32 = 0;
+    let mut cached_pages = CachedPageStreak::new(*stop_after_cached_pages);
+    let mut rate_limit_retries: u32 = 0;
+
+    loop {
+        let url = gen_archive_url(archive, query, before, after);
+
+        let user_agent = shared_state.lock().await.user_agents.current().to_owned();
+        let request = client.get(&url).header(USER_AGENT, user_agent);
+        let request = match (archive, &options.pushshift_token) {
+            (Archive::Pushshift, Some(token)) => request.bearer_auth(token),
+            _ => request,
+        };
+        debug!("Fetching {}", url);
+        let res = request.send().await?;
+
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            rate_limit_retries += 1;
+            if rate_limit_retries > MAX_RATE_LIMIT_RETRIES {
+                return Err(ArchiveClientError::TooManyRequests(archive));
+            }
+            let wait = get_rate_limit_wait(res.headers()).unwrap_or(DEFAULT_RATE_LIMIT_WAIT);
+            sleep_with_countdown(wait, &format!("Rate limited by {}, resuming in", archive)).await;
+            continue;
+        }
+        rate_limit_retries = 0;
+
+        if !res.status().is_success() {
+            return Err(ArchiveClientError::Status(archive, res.status()));
+        }
+
+        let res: ArchiveResponse = res.json().await?;
+        // Read before parsing, a page whose posts are all skipped still moves the cursor
+        let oldest = res
+            .data
+            .iter()
+            .filter_map(|post| post.get("created_utc")?.as_f64())
+            .map(|created| created as i64)
+            .min();
+        let Some(oldest) = oldest else {
+            break;
+        };
+
+        let mut res = parse_archive_response(res);
+        let children_count = res.data.children.len();
+
+        let ss = shared_state.lock().await;
+        res.data.children.retain(|rc| !ss.is_cached(&rc.data.id));
+        drop(ss);
+
+        // The receiver only hangs up when the crawl is aborted
+        if !res.data.children.is_empty() && pages.send(res.clone().into()).await.is_err() {
+            break;
+        }
+
+        request_count += 1;
+        if options.output_mode == OutputMode::Json {
+            emit_event(&CrawlEvent::PageFetched {
+                url: &url,
+                page: request_count,
+                posts: children_count,
+            });
+        }
+        // Archives are sorted by date, so a streak of fully cached pages means we caught up
+        if cached_pages.observe(children_count, res.data.children.len()) {
+            break;
+        }
+        if limit.is_some_and(|l| request_count >= l) {
+            break;
+        }
+
+        before = Some(oldest);
+    }
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
 pub mod api_types;
+mod archive;
 mod reddit;
 mod redgifs;
+mod streamable;
+pub use archive::*;
 pub use reddit::*;
 pub use redgifs::*;
+pub use streamable::*;
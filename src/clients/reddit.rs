@@ -1,16 +1,55 @@
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    cli::{CliRedditCommand, CliSharedOptions, RedditCategoryFilter, RedditTimeframeFilter},
-    clients::api_types::reddit::{
-        submitted_response::RedditSubmittedResponse, user_about::RedditUserAbout,
+    cli::{
+        CliRedditCommand, CliSharedOptions, CliUserCommentsCommand, RedditAccountListing,
+        RedditCategoryFilter, RedditTimeframeFilter,
+    },
+    clients::{
+        api_types::reddit::{
+            access_token::RedditAccessTokenResponse,
+            submitted_response::RedditSubmittedResponse,
+            subreddit_about::{RedditErrorResponse, RedditSubredditAbout},
+            user_about::RedditUserAbout,
+            user_comments::{RedditUserCommentData, RedditUserCommentsResponse},
+        },
+        ArchiveClientError, RedgifsClientError,
+    },
+    utils::{
+        emit_event, get_rate_limit_wait, sleep_with_countdown,
+        state::{RedditToken, SharedState},
+        CachedPageStreak, CrawlEvent, InjectedFailureKind, OutputMode, ABOUT_REQUEST_INTERVAL,
     },
-    utils::state::SharedState,
 };
-use reqwest::header::HeaderMap;
+use reqwest::header::{HeaderMap, USER_AGENT};
+use serde::de::DeserializeOwned;
 use thiserror::Error;
-use tokio::sync::Mutex;
+use tokio::{
+    sync::{mpsc, Mutex},
+    time::sleep,
+};
+use tracing::debug;
 const MAX_SUBMISSIONS_PER_REQUEST: u32 = 100;
+// How long about.json lookups are reused from the about cache
+const ABOUT_CACHE_TTL_HOURS: i64 = 6;
+// Attempts of a page that keeps getting rate limited before the crawl is aborted
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+// Span of the first --deep search window
+const DEEP_WINDOW_SPAN: i64 = 30 * 24 * 60 * 60;
+const DEEP_WINDOW_MIN_SPAN: i64 = 60 * 60;
+const DEEP_WINDOW_MAX_SPAN: i64 = 365 * 24 * 60 * 60;
+// Search listings end after about this many results, a window reaching it may be truncated
+const DEEP_WINDOW_CAP: usize = 250;
+// June 2005, used when about.json does not tell when a subreddit was created
+const REDDIT_LAUNCH_TIMESTAMP: i64 = 1119484800;
+// Used when a 429 response does not tell when the limit resets
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+// Access tokens are refreshed this long before Reddit expires them
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
 
 #[derive(Error, Debug)]
 pub enum RedditProviderError {
@@ -24,10 +63,106 @@ pub enum RedditProviderError {
     NotFound,
     #[error("Reddit returned a Suspended status")]
     Suspended,
+    #[error("The subreddit has been banned")]
+    Banned,
+    #[error("The subreddit is private")]
+    Private,
+    #[error("The subreddit is quarantined")]
+    Quarantined,
     #[error("Reddit returned a 429 Too Many Requests error")]
     TooManyRequests,
     #[error("Reddit returned a 403 Forbidden error")]
     Forbidden,
+    #[error("Reddit rejected the OAuth credentials")]
+    Unauthorized,
+    #[error("Injected {0:?} failure")]
+    InjectedFailure(InjectedFailureKind),
+    #[error(transparent)]
+    Archive(#[from] ArchiveClientError),
+    #[error(transparent)]
+    Redgifs(RedgifsClientError),
+}
+
+// Crawls of Redgifs profiles run on the same engine as Reddit listings
+impl From<RedgifsClientError> for RedditProviderError {
+    fn from(e: RedgifsClientError) -> Self {
+        match e {
+            RedgifsClientError::NotFound => RedditProviderError::NotFound,
+            e => RedditProviderError::Redgifs(e),
+        }
+    }
+}
+
+impl From<InjectedFailureKind> for RedditProviderError {
+    fn from(kind: InjectedFailureKind) -> Self {
+        match kind {
+            InjectedFailureKind::NotFound => RedditProviderError::NotFound,
+            InjectedFailureKind::RateLimit => RedditProviderError::TooManyRequests,
+            _ => RedditProviderError::InjectedFailure(kind),
+        }
+    }
+}
+
+/// Credentials of a Reddit app, used for the application only OAuth2 grant
+#[derive(Clone)]
+pub struct RedditAppCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+// Keeps the secret out of debug output
+impl std::fmt::Debug for RedditAppCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedditAppCredentials")
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Credentials of a Reddit "script" app, used for the OAuth2 password grant
+#[derive(Clone)]
+pub struct RedditCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub username: String,
+    pub password: String,
+}
+
+// Keeps the secrets out of debug output
+impl std::fmt::Debug for RedditCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedditCredentials")
+            .field("client_id", &self.client_id)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Order of a listing, decides whether paging through it can stop early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListingOrder {
+    /// Newest posts first
+    Created,
+    /// Most recently saved or upvoted posts first
+    Added,
+    /// Ranked by score or activity, new posts may follow cached pages
+    Ranked,
+}
+
+impl From<&RedditCategoryFilter> for ListingOrder {
+    fn from(category: &RedditCategoryFilter) -> Self {
+        match category {
+            RedditCategoryFilter::New => ListingOrder::Created,
+            _ => ListingOrder::Ranked,
+        }
+    }
+}
+
+/// Retries of the page of a listing that is currently requested
+#[derive(Debug, Default)]
+struct PageRetries {
+    rate_limit: u32,
+    token_renewed: bool,
 }
 
 pub struct RedditClient {
@@ -36,16 +171,112 @@ pub struct RedditClient {
 
 impl Default for RedditClient {
     fn default() -> Self {
-        let mut map: HeaderMap = reqwest::header::HeaderMap::new();
-        map.insert(
-            reqwest::header::USER_AGENT,
-            reqwest::header::HeaderValue::from_static("Reddit-User-Analysis"),
-        );
+        // The user agent is added per request from the pool in the shared state
+        Self {
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+/// Time range of a `--deep` search, `start` and `end` are inclusive unix timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeepWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl DeepWindow {
+    pub fn new(end: i64, floor: i64) -> Self {
+        Self {
+            start: (end - DEEP_WINDOW_SPAN).max(floor),
+            end,
+        }
+    }
+
+    /// Window searched after this one, `None` once `floor` is reached
+    ///
+    /// A window with as many posts as a listing returns was likely truncated, so the rest of it is
+    /// searched again in a narrower window. Sparse windows are widened to save requests.
+    pub fn next(&self, floor: i64, posts: usize, oldest: Option<i64>) -> Option<Self> {
+        let span = self.end - self.start;
+
+        let (end, span) = match oldest {
+            Some(oldest) if posts >= DEEP_WINDOW_CAP && oldest > self.start => {
+                (oldest, (span / 2).max(DEEP_WINDOW_MIN_SPAN))
+            }
+            _ if posts < DEEP_WINDOW_CAP / 4 => (self.start, (span * 2).min(DEEP_WINDOW_MAX_SPAN)),
+            _ => (self.start, span),
+        };
+
+        match end > floor {
+            true => Some(Self {
+                start: (end - span).max(floor),
+                end,
+            }),
+            false => None,
+        }
+    }
+}
+
+/// Extracts the post ID from a post URL, a short link or a plain ID
+pub fn extract_post_id(resource: &str) -> Option<&str> {
+    let resource = resource.trim().trim_end_matches('/');
+
+    let id = match resource {
+        _ if resource.contains("/comments/") => resource
+            .split("/comments/")
+            .last()?
+            .split(['/', '?', '#'])
+            .next()?,
+        _ if resource.contains("redd.it/") => resource
+            .split("redd.it/")
+            .last()?
+            .split(['/', '?', '#'])
+            .next()?,
+        _ => resource.trim_start_matches("t3_"),
+    };
+
+    match !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        true => Some(id),
+        false => None,
+    }
+}
+
+/// Normalizes a subreddit like `r/pics`, `All` or `pics+aww`, combined subreddits are sorted
+/// so the same combination always crawls into the same folder
+pub fn normalize_subreddit(resource: &str) -> String {
+    let resource = resource.trim().trim_start_matches('/');
+    let resource = resource
+        .strip_prefix("r/")
+        .unwrap_or(resource)
+        .trim_end_matches('/');
 
-        Self { headers: map }
+    let mut subreddits: Vec<&str> = Vec::new();
+    for subreddit in resource.split('+').map(str::trim) {
+        if !subreddit.is_empty() && !subreddits.iter().any(|s| s.eq_ignore_ascii_case(subreddit)) {
+            subreddits.push(subreddit);
+        }
+    }
+
+    match subreddits.as_slice() {
+        [subreddit] if is_combined_subreddit(subreddit) => subreddit.to_ascii_lowercase(),
+        _ => {
+            subreddits.sort_by_key(|s| s.to_ascii_lowercase());
+            subreddits.join("+")
+        }
     }
 }
 
+/// Front page listings like `all`, `popular` or `all-pics` and multi-subreddits like `pics+aww`
+/// combine several subreddits, so they have no about.json
+pub fn is_combined_subreddit(subreddit: &str) -> bool {
+    let subreddit = subreddit.to_ascii_lowercase();
+    subreddit.contains('+')
+        || subreddit == "all"
+        || subreddit == "popular"
+        || subreddit.starts_with("all-")
+}
+
 impl RedditClient {
     fn gen_user_submitted_url(
         &self,
@@ -73,6 +304,7 @@ impl RedditClient {
         &self,
         client: &reqwest_middleware::ClientWithMiddleware,
         user: &str,
+        user_agent: &str,
     ) -> Result<RedditUserAbout, RedditProviderError> {
         let res = client
             .get(format!(
@@ -80,6 +312,7 @@ impl RedditClient {
                 user
             ))
             .headers(self.headers.to_owned())
+            .header(USER_AGENT, user_agent)
             .send()
             .await
             .map_err(RedditProviderError::ReqwestMiddleware)?;
@@ -97,17 +330,99 @@ impl RedditClient {
             .map_err(RedditProviderError::Reqwest)
     }
 
+    /// Looks up a user's about.json, preferring the persistent about cache
+    pub async fn get_user_about(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        user: &str,
+    ) -> Result<RedditUserAbout, RedditProviderError> {
+        let key = format!("user/{}", user);
+        let mut ss = shared_state.lock().await;
+
+        if let Some(about) = ss
+            .about_cache
+            .get::<RedditUserAbout>(&key, chrono::Duration::hours(ABOUT_CACHE_TTL_HOURS))
+        {
+            return Ok(about);
+        }
+
+        // Throttle lookups that miss the cache, the lock keeps concurrent lookups in line
+        if let Some(last_request) = ss.last_about_request {
+            let elapsed = last_request.elapsed();
+            if elapsed < ABOUT_REQUEST_INTERVAL {
+                tokio::time::sleep(ABOUT_REQUEST_INTERVAL - elapsed).await;
+            }
+        }
+        ss.last_about_request = Some(std::time::Instant::now());
+
+        let about = self
+            .gen_user_about_url(client, user, ss.user_agents.current())
+            .await?;
+        // Failing to persist the cache should not fail the lookup
+        let _ = ss.about_cache.insert(&key, &about);
+
+        Ok(about)
+    }
+
+    /// Tells why a subreddit listing responded with 403 or 404 by looking up its about.json
+    pub async fn get_subreddit_unavailable_reason(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        subreddit: &str,
+        listing_error: RedditProviderError,
+    ) -> RedditProviderError {
+        if is_combined_subreddit(subreddit) {
+            return listing_error;
+        }
+
+        let url = format!(
+            "https://www.reddit.com/r/{}/about.json?raw_json=1",
+            subreddit
+        );
+        let res = match self.send_throttled(client, shared_state, &url).await {
+            Ok(res) => res,
+            Err(_) => return listing_error,
+        };
+
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return RedditProviderError::TooManyRequests;
+        }
+
+        // Banned subreddits answer with 404 and private or quarantined ones with 403
+        if !res.status().is_success() {
+            let reason = res
+                .json::<RedditErrorResponse>()
+                .await
+                .ok()
+                .and_then(|body| body.reason);
+
+            return match reason.as_deref() {
+                Some("banned") => RedditProviderError::Banned,
+                Some("private") => RedditProviderError::Private,
+                Some("quarantined") => RedditProviderError::Quarantined,
+                _ => listing_error,
+            };
+        }
+
+        // Readable with the current credentials, e.g. a private subreddit the account joined
+        match res.json::<RedditSubredditAbout>().await {
+            Ok(about) if about.data.quarantine => RedditProviderError::Quarantined,
+            Ok(about) if about.data.subreddit_type == "private" => RedditProviderError::Private,
+            _ => listing_error,
+        }
+    }
+
+    /// Fetches the submissions of a user, every page is sent to `pages` as soon as it arrives
     pub async fn get_user_submissions(
         &self,
         client: &reqwest_middleware::ClientWithMiddleware,
         shared_state: &Arc<Mutex<SharedState>>,
         cmd: &CliRedditCommand,
         options: &CliSharedOptions,
-    ) -> Result<Vec<RedditSubmittedResponse>, RedditProviderError> {
-        let mut responses: Vec<RedditSubmittedResponse> = Vec::new();
-        let mut after: Option<String> = None;
-        let mut request_count: u32 = 0;
-
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
         let CliRedditCommand {
             resource: user,
             category,
@@ -115,76 +430,111 @@ impl RedditClient {
             ..
         } = cmd;
 
-        let CliSharedOptions { limit, .. } = options;
+        let listed = self
+            .stream_listing(
+                client,
+                shared_state,
+                options,
+                ListingOrder::from(category),
+                |after| self.gen_user_submitted_url(user, after, category, timeframe),
+                pages,
+            )
+            .await;
 
-        loop {
-            let url = match after {
-                Some(after) => self.gen_user_submitted_url(user, Some(&after), category, timeframe),
-                None => self.gen_user_submitted_url(user, None, category, timeframe),
-            };
+        match listed {
+            Err(RedditProviderError::Forbidden) => Err(self
+                .get_user_forbidden_reason(client, shared_state, user)
+                .await),
+            listed => listed,
+        }
+    }
 
-            let res = client
-                .get(&url)
-                .headers(self.headers.to_owned())
-                .send()
-                .await
-                .map_err(RedditProviderError::ReqwestMiddleware)?;
+    /// Why the listings of a user answered 403, suspended accounts are told apart
+    async fn get_user_forbidden_reason(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        user: &str,
+    ) -> RedditProviderError {
+        match self.get_user_about(client, shared_state, user).await {
+            Ok(about) if about.data.is_suspended => RedditProviderError::Suspended,
+            _ => RedditProviderError::Forbidden,
+        }
+    }
 
-            if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(RedditProviderError::TooManyRequests);
-            }
+    fn gen_user_comments_url(
+        &self,
+        user: &str,
+        after: Option<&str>,
+        category: &RedditCategoryFilter,
+        timeframe: &RedditTimeframeFilter,
+    ) -> String {
+        match after {
+            Some(after) => format!(
+                "https://www.reddit.com/user/{}/comments.json?limit={}&sort={}&t={}&after={}&raw_json=1",
+                user, MAX_SUBMISSIONS_PER_REQUEST, category, timeframe, after
+            ),
+            None => format!(
+                "https://www.reddit.com/user/{}/comments.json?limit={}&sort={}&t={}&raw_json=1",
+                user, MAX_SUBMISSIONS_PER_REQUEST, category, timeframe
+            ),
+        }
+    }
 
-            if res.status() == reqwest::StatusCode::NOT_FOUND {
-                return Err(RedditProviderError::NotFound);
-            }
+    /// Fetches the comments of a user, category new stops at the first page with a `known` comment
+    pub async fn get_user_comments(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        cmd: &CliUserCommentsCommand,
+        known: &HashSet<String>,
+    ) -> Result<Vec<RedditUserCommentData>, RedditProviderError> {
+        let CliUserCommentsCommand {
+            resource: user,
+            category,
+            timeframe,
+            limit,
+            ..
+        } = cmd;
 
-            if res.status() == reqwest::StatusCode::FORBIDDEN {
-                let about = self
-                    .gen_user_about_url(client, user)
-                    .await
-                    .map_err(|_| RedditProviderError::Forbidden)?;
+        let mut comments = Vec::new();
+        let mut after: Option<String> = None;
+        let mut request_count: u32 = 0;
+        let mut retries = PageRetries::default();
 
-                match about.data.is_suspended {
-                    true => return Err(RedditProviderError::Suspended),
-                    false => return Err(RedditProviderError::Forbidden),
+        loop {
+            let url = self.gen_user_comments_url(user, after.as_deref(), category, timeframe);
+            let res: RedditUserCommentsResponse = match self
+                .fetch_listing_page(client, shared_state, &url, &mut retries)
+                .await
+            {
+                Ok(Some(res)) => res,
+                // `after` is unchanged, so the same page is requested again
+                Ok(None) => continue,
+                Err(RedditProviderError::Forbidden) => {
+                    return Err(self
+                        .get_user_forbidden_reason(client, shared_state, user)
+                        .await)
                 }
-            }
-
-            let mut res: RedditSubmittedResponse =
-                res.json().await.map_err(RedditProviderError::Reqwest)?;
-
-            let file_cache = &shared_state.lock().await.file_cache;
+                Err(e) => return Err(e),
+            };
+            request_count += 1;
 
-            let non_downloaded = res
-                .data
-                .children
-                .into_iter()
-                .filter(|rc| !file_cache.files.iter().any(|f| f.id == rc.data.id))
-                .collect::<Vec<_>>();
-            res.data.children = non_downloaded;
+            // Newer comments come first, so a known one means the rest was saved before
+            let caught_up = *category == RedditCategoryFilter::New
+                && res.data.children.iter().any(|c| known.contains(&c.data.id));
+            comments.extend(res.data.children.into_iter().map(|c| c.data));
 
-            if !res.data.children.is_empty() {
-                responses.push(res.to_owned());
+            if caught_up || limit.is_some_and(|limit| request_count >= limit) {
+                break;
             }
-
-            request_count += 1;
             match res.data.after {
-                Some(a) => {
-                    // Skip downloading if limit is reached
-                    if let Some(l) = limit {
-                        if request_count >= *l {
-                            break;
-                        }
-                    }
-                    after = Some(a);
-                }
-                None => {
-                    break;
-                }
+                Some(a) => after = Some(a),
+                None => break,
             }
         }
 
-        Ok(responses)
+        Ok(comments)
     }
 
     fn gen_subreddit_submitted_url(
@@ -209,17 +559,15 @@ impl RedditClient {
         }
     }
 
+    /// Fetches the submissions of a subreddit, every page is sent to `pages` as soon as it arrives
     pub async fn get_subreddit_submissions(
         &self,
         client: &reqwest_middleware::ClientWithMiddleware,
         shared_state: &Arc<Mutex<SharedState>>,
         cmd: &CliRedditCommand,
         options: &CliSharedOptions,
-    ) -> Result<Vec<RedditSubmittedResponse>, RedditProviderError> {
-        let mut responses: Vec<RedditSubmittedResponse> = Vec::new();
-        let mut after: Option<String> = None;
-        let mut request_count: u32 = 0;
-
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
         let CliRedditCommand {
             resource: subreddit,
             category,
@@ -227,75 +575,253 @@ impl RedditClient {
             ..
         } = cmd;
 
-        let CliSharedOptions { limit, .. } = options;
+        let listed = self
+            .stream_listing(
+                client,
+                shared_state,
+                options,
+                ListingOrder::from(category),
+                |after| self.gen_subreddit_submitted_url(subreddit, after, category, timeframe),
+                pages,
+            )
+            .await;
 
-        loop {
-            let url = match after {
-                Some(after) => {
-                    self.gen_subreddit_submitted_url(subreddit, Some(&after), category, timeframe)
-                }
-                None => self.gen_subreddit_submitted_url(subreddit, None, category, timeframe),
+        match listed {
+            Err(
+                listing_error @ (RedditProviderError::NotFound | RedditProviderError::Forbidden),
+            ) => Err(self
+                .get_subreddit_unavailable_reason(client, shared_state, subreddit, listing_error)
+                .await),
+            listed => listed,
+        }
+    }
+
+    fn gen_search_url(
+        &self,
+        term: &str,
+        after: Option<&str>,
+        category: &RedditCategoryFilter,
+        timeframe: &RedditTimeframeFilter,
+    ) -> String {
+        let category = category.to_string();
+        let timeframe = timeframe.to_string();
+
+        match after {
+            Some(after) => format!(
+                "https://www.reddit.com/search.json?q={}&include_over_18=on&count=100&sort={}&t={}&after={}&raw_json=1",
+                term, category, timeframe, after
+            ),
+            None => format!(
+                "https://www.reddit.com/search.json?q={}&include_over_18=on&count=100&sort={}&t={}&raw_json=1",
+                term, category, timeframe
+            ),
+        }
+    }
+
+    fn gen_subreddit_window_url(
+        &self,
+        subreddit: &str,
+        window: &DeepWindow,
+        after: Option<&str>,
+    ) -> String {
+        match after {
+            Some(after) => format!(
+                "https://www.reddit.com/r/{}/search.json?q=timestamp:{}..{}&restrict_sr=on&syntax=cloudsearch&sort=new&include_over_18=on&limit=100&after={}&raw_json=1",
+                subreddit, window.start, window.end, after
+            ),
+            None => format!(
+                "https://www.reddit.com/r/{}/search.json?q=timestamp:{}..{}&restrict_sr=on&syntax=cloudsearch&sort=new&include_over_18=on&limit=100&raw_json=1",
+                subreddit, window.start, window.end
+            ),
+        }
+    }
+
+    /// Looks up when a subreddit was created, the oldest post a `--deep` crawl can find
+    async fn get_subreddit_created(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        subreddit: &str,
+    ) -> Result<i64, RedditProviderError> {
+        if is_combined_subreddit(subreddit) {
+            return Ok(REDDIT_LAUNCH_TIMESTAMP);
+        }
+
+        let url = format!(
+            "https://www.reddit.com/r/{}/about.json?raw_json=1",
+            subreddit
+        );
+        let res = self.send_throttled(client, shared_state, &url).await?;
+
+        if res.status() == reqwest::StatusCode::NOT_FOUND
+            || res.status() == reqwest::StatusCode::FORBIDDEN
+        {
+            let listing_error = match res.status() {
+                reqwest::StatusCode::NOT_FOUND => RedditProviderError::NotFound,
+                _ => RedditProviderError::Forbidden,
             };
+            return Err(self
+                .get_subreddit_unavailable_reason(client, shared_state, subreddit, listing_error)
+                .await);
+        }
 
-            let res = client
-                .get(&url)
-                .headers(self.headers.to_owned())
-                .send()
-                .await
-                .map_err(RedditProviderError::ReqwestMiddleware)?;
+        Ok(res
+            .json::<RedditSubredditAbout>()
+            .await
+            .ok()
+            .and_then(|about| about.data.created_utc)
+            .map(|created| created as i64)
+            .unwrap_or(REDDIT_LAUNCH_TIMESTAMP))
+    }
 
-            if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(RedditProviderError::TooManyRequests);
-            }
+    /// Searches a subreddit window by window back to its creation, reaching past the listing cap
+    pub async fn get_subreddit_deep_submissions(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        cmd: &CliRedditCommand,
+        options: &CliSharedOptions,
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
+        let CliRedditCommand {
+            resource: subreddit,
+            ..
+        } = cmd;
 
-            if res.status() == reqwest::StatusCode::NOT_FOUND {
-                return Err(RedditProviderError::NotFound);
-            }
+        let CliSharedOptions {
+            limit,
+            stop_after_cached_pages,
+            filter,
+            ..
+        } = options;
 
-            if res.status() == reqwest::StatusCode::FORBIDDEN {
-                return Err(RedditProviderError::Forbidden);
-            }
+        let created = self
+            .get_subreddit_created(client, shared_state, subreddit)
+            .await?;
+        let floor = filter
+            .after_date
+            .map_or(created, |d| d.timestamp().max(created));
+        let end = filter
+            .before_date
+            .map_or_else(|| chrono::Utc::now().timestamp(), |d| d.timestamp());
 
-            let mut res: RedditSubmittedResponse =
-                res.json().await.map_err(RedditProviderError::Reqwest)?;
+        let mut window = DeepWindow::new(end, floor);
+        let mut after: Option<String> = None;
+        // Neighbouring windows share their bounds, so a post can be found twice
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut window_posts: usize = 0;
+        let mut window_oldest: Option<i64> = None;
+        let mut request_count: u32 = 0;
+        let mut cached_pages = CachedPageStreak::new(*stop_after_cached_pages);
+        let mut retries = PageRetries::default();
 
-            let file_cache = &shared_state.lock().await.file_cache;
+        debug!("Searching {} back to {}", subreddit, floor);
 
-            let non_downloaded = res
+        loop {
+            let url = self.gen_subreddit_window_url(subreddit, &window, after.as_deref());
+            let Some(mut res) = self
+                .fetch_listing_page::<RedditSubmittedResponse>(
+                    client,
+                    shared_state,
+                    &url,
+                    &mut retries,
+                )
+                .await?
+            else {
+                // `after` is unchanged, so the same page is requested again
+                continue;
+            };
+
+            let children_count = res.data.children.len();
+            window_posts += children_count;
+            window_oldest = res
                 .data
                 .children
-                .into_iter()
-                .filter(|rc| !file_cache.files.iter().any(|f| f.id == rc.data.id))
-                .collect::<Vec<_>>();
-            res.data.children = non_downloaded;
+                .iter()
+                .map(|c| c.data.created_utc.timestamp())
+                .chain(window_oldest)
+                .min();
 
-            if !res.data.children.is_empty() {
-                responses.push(res.to_owned());
+            let ss = shared_state.lock().await;
+            res.data
+                .children
+                .retain(|rc| seen.insert(rc.data.id.clone()) && !ss.is_cached(&rc.data.id));
+            drop(ss);
+
+            // The receiver only hangs up when the crawl is aborted
+            if !res.data.children.is_empty() && pages.send(res.clone().into()).await.is_err() {
+                break;
             }
 
             request_count += 1;
-            match res.data.after {
-                Some(a) => {
-                    // Skip downloading if limit is reached
-                    if let Some(l) = limit {
-                        if request_count >= *l {
-                            break;
-                        }
-                    }
-                    after = Some(a);
-                }
-                None => {
-                    break;
+            if options.output_mode == OutputMode::Json {
+                emit_event(&CrawlEvent::PageFetched {
+                    url: &url,
+                    page: request_count,
+                    posts: children_count,
+                });
+            }
+            // Windows are searched newest first, so a streak of fully cached pages means we caught up
+            if cached_pages.observe(children_count, res.data.children.len()) {
+                break;
+            }
+            if limit.is_some_and(|l| request_count >= l) {
+                break;
+            }
+
+            if let Some(a) = res.data.after {
+                after = Some(a);
+                continue;
+            }
+
+            match window.next(floor, window_posts, window_oldest) {
+                Some(next) => {
+                    debug!(
+                        "Searched {}..{} with {} posts, continuing with {}..{}",
+                        window.start, window.end, window_posts, next.start, next.end
+                    );
+                    window = next;
+                    after = None;
+                    window_posts = 0;
+                    window_oldest = None;
                 }
+                None => break,
             }
         }
 
-        Ok(responses)
+        Ok(())
     }
 
-    fn gen_search_url(
+    /// Fetches the results of a search, every page is sent to `pages` as soon as it arrives
+    pub async fn get_search_submissions(
         &self,
-        term: &str,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        cmd: &CliRedditCommand,
+        options: &CliSharedOptions,
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
+        let CliRedditCommand {
+            resource: term,
+            category,
+            timeframe,
+            ..
+        } = cmd;
+
+        self.stream_listing(
+            client,
+            shared_state,
+            options,
+            ListingOrder::from(category),
+            |after| self.gen_search_url(term, after, category, timeframe),
+            pages,
+        )
+        .await
+    }
+
+    fn gen_domain_url(
+        &self,
+        domain: &str,
         after: Option<&str>,
         category: &RedditCategoryFilter,
         timeframe: &RedditTimeframeFilter,
@@ -305,95 +831,503 @@ impl RedditClient {
 
         match after {
             Some(after) => format!(
-                "https://www.reddit.com/search.json?q={}&include_over_18=on&count=100&sort={}&t={}&after={}&raw_json=1",
-                term, category, timeframe, after
+                "https://www.reddit.com/domain/{}/{}.json?include_over_18=on&limit=100&t={}&after={}&raw_json=1",
+                domain, category, timeframe, after
             ),
             None => format!(
-                "https://www.reddit.com/search.json?q={}&include_over_18=on&count=100&sort={}&t={}&raw_json=1",
-                term, category, timeframe
+                "https://www.reddit.com/domain/{}/{}.json?include_over_18=on&limit=100&t={}&raw_json=1",
+                domain, category, timeframe
             ),
         }
     }
 
-    pub async fn get_search_submissions(
+    /// Fetches the posts linking to a domain, every page is sent to `pages` as soon as it arrives
+    pub async fn get_domain_submissions(
         &self,
         client: &reqwest_middleware::ClientWithMiddleware,
         shared_state: &Arc<Mutex<SharedState>>,
         cmd: &CliRedditCommand,
         options: &CliSharedOptions,
-    ) -> Result<Vec<RedditSubmittedResponse>, RedditProviderError> {
-        let mut responses: Vec<RedditSubmittedResponse> = Vec::new();
-        let mut after: Option<String> = None;
-        let mut request_count: u32 = 0;
-
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
         let CliRedditCommand {
-            resource: term,
+            resource: domain,
             category,
             timeframe,
             ..
         } = cmd;
 
-        let CliSharedOptions { limit, .. } = options;
+        self.stream_listing(
+            client,
+            shared_state,
+            options,
+            ListingOrder::from(category),
+            |after| self.gen_domain_url(domain, after, category, timeframe),
+            pages,
+        )
+        .await
+    }
+
+    /// Pages through a listing, every page is sent to `pages` as soon as it arrives
+    async fn stream_listing(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        options: &CliSharedOptions,
+        order: ListingOrder,
+        gen_url: impl Fn(Option<&str>) -> String,
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
+        let CliSharedOptions {
+            limit,
+            stop_after_cached_pages,
+            filter,
+            ..
+        } = options;
+        let mut after: Option<String> = None;
+        let mut request_count: u32 = 0;
+        let mut cached_pages = CachedPageStreak::new(match order {
+            ListingOrder::Ranked => None,
+            ListingOrder::Created | ListingOrder::Added => *stop_after_cached_pages,
+        });
+        let mut retries = PageRetries::default();
 
         loop {
-            let url = match after {
-                Some(after) => self.gen_search_url(term, Some(&after), category, timeframe),
-                None => self.gen_search_url(term, None, category, timeframe),
+            let url = gen_url(after.as_deref());
+            let Some(mut res) = self
+                .fetch_listing_page::<RedditSubmittedResponse>(
+                    client,
+                    shared_state,
+                    &url,
+                    &mut retries,
+                )
+                .await?
+            else {
+                // `after` is unchanged, so the same page is requested again
+                continue;
             };
+            let next = res.data.after.clone();
 
-            let res = client
-                .get(&url)
-                .headers(self.headers.to_owned())
-                .send()
-                .await
-                .map_err(RedditProviderError::ReqwestMiddleware)?;
+            let children_count = res.data.children.len();
+            let reached_cutoff = res
+                .data
+                .children
+                .last()
+                .is_some_and(|c| filter.is_before_cutoff(c.data.created_utc));
 
-            if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(RedditProviderError::TooManyRequests);
-            }
+            let ss = shared_state.lock().await;
+            res.data.children.retain(|rc| !ss.is_cached(&rc.data.id));
+            // The downloader needs the state to make room for the next page
+            drop(ss);
+            let uncached_count = res.data.children.len();
 
-            if res.status() == reqwest::StatusCode::NOT_FOUND {
-                return Err(RedditProviderError::NotFound);
+            // The receiver only hangs up when the crawl is aborted
+            if uncached_count > 0 && pages.send(res.into()).await.is_err() {
+                break;
             }
 
-            if res.status() == reqwest::StatusCode::FORBIDDEN {
-                return Err(RedditProviderError::Forbidden);
+            request_count += 1;
+            if options.output_mode == OutputMode::Json {
+                emit_event(&CrawlEvent::PageFetched {
+                    url: &url,
+                    page: request_count,
+                    posts: children_count,
+                });
+            }
+            // The latest posts come first, so a streak of fully cached pages means we caught up
+            if cached_pages.observe(children_count, uncached_count) {
+                break;
+            }
+            // Only listings sorted by date can not continue with posts newer than the cutoff
+            if order == ListingOrder::Created && reached_cutoff {
+                break;
+            }
+            match next {
+                // Skip downloading if limit is reached
+                Some(_) if limit.is_some_and(|l| request_count >= l) => break,
+                Some(next) => after = Some(next),
+                None => break,
             }
+        }
 
-            let mut res: RedditSubmittedResponse =
-                res.json().await.map_err(RedditProviderError::Reqwest)?;
+        Ok(())
+    }
 
-            let file_cache = &shared_state.lock().await.file_cache;
+    /// Requests a page of a listing, `None` when the same page has to be requested again
+    ///
+    /// A listing answering 401, 403 or 404 ends with the matching error, callers look up why.
+    async fn fetch_listing_page<T: DeserializeOwned>(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        url: &str,
+        retries: &mut PageRetries,
+    ) -> Result<Option<T>, RedditProviderError> {
+        if let Some(kind) = shared_state
+            .lock()
+            .await
+            .failure_injection
+            .and_then(|f| f.roll())
+        {
+            return Err(RedditProviderError::from(kind));
+        }
 
-            let non_downloaded = res
-                .data
-                .children
-                .into_iter()
-                .filter(|rc| !file_cache.files.iter().any(|f| f.id == rc.data.id))
-                .collect::<Vec<_>>();
-            res.data.children = non_downloaded;
+        let res = self.send_throttled(client, shared_state, url).await?;
 
-            if !res.data.children.is_empty() {
-                responses.push(res.to_owned());
-            }
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.wait_for_rate_limit(res.headers(), &mut retries.rate_limit)
+                .await?;
+            return Ok(None);
+        }
+        // The revoked token was cleared, the page is requested once more with a new one
+        if res.status() == reqwest::StatusCode::UNAUTHORIZED && !retries.token_renewed {
+            retries.token_renewed = true;
+            return Ok(None);
+        }
+        *retries = PageRetries::default();
 
-            request_count += 1;
-            match res.data.after {
-                Some(a) => {
-                    // Skip downloading if limit is reached
-                    if let Some(l) = limit {
-                        if request_count >= *l {
-                            break;
-                        }
-                    }
-                    after = Some(a);
-                }
-                None => {
-                    break;
-                }
+        match res.status() {
+            reqwest::StatusCode::UNAUTHORIZED => Err(RedditProviderError::Unauthorized),
+            reqwest::StatusCode::NOT_FOUND => Err(RedditProviderError::NotFound),
+            reqwest::StatusCode::FORBIDDEN => Err(RedditProviderError::Forbidden),
+            _ => Ok(Some(
+                res.json().await.map_err(RedditProviderError::Reqwest)?,
+            )),
+        }
+    }
+
+    /// Waits for the rate limit of a 429 response to reset, gives up after a few attempts
+    async fn wait_for_rate_limit(
+        &self,
+        headers: &HeaderMap,
+        retries: &mut u32,
+    ) -> Result<(), RedditProviderError> {
+        *retries += 1;
+        if *retries > MAX_RATE_LIMIT_RETRIES {
+            return Err(RedditProviderError::TooManyRequests);
+        }
+
+        let wait = get_rate_limit_wait(headers).unwrap_or(DEFAULT_RATE_LIMIT_WAIT);
+        sleep_with_countdown(wait, "Rate limited by Reddit, resuming in").await;
+
+        Ok(())
+    }
+
+    /// Returns the cached access token, requesting a new one once it expired
+    async fn get_access_token(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+    ) -> Result<Option<String>, RedditProviderError> {
+        let (account, app, user_agent) = {
+            let ss = shared_state.lock().await;
+            if let Some(token) = ss
+                .reddit_token
+                .as_ref()
+                .filter(|t| t.expires_at > Instant::now())
+            {
+                return Ok(Some(token.access_token.clone()));
             }
+            (
+                ss.reddit_account.clone(),
+                ss.reddit_app.clone(),
+                ss.user_agents.current().to_owned(),
+            )
+        };
+
+        // Downloads keep using the state while the token is requested
+        let response = match (&account, &app) {
+            (Some(credentials), _) => self.authenticate(client, credentials, &user_agent).await?,
+            (None, Some(app)) => self.authenticate_app(client, app, &user_agent).await?,
+            (None, None) => return Ok(None),
+        };
+
+        let lifetime = Duration::from_secs(response.expires_in.max(0) as u64)
+            .saturating_sub(TOKEN_EXPIRY_MARGIN);
+        shared_state.lock().await.reddit_token = Some(RedditToken {
+            access_token: response.access_token.clone(),
+            expires_at: Instant::now() + lifetime,
+        });
+
+        Ok(Some(response.access_token))
+    }
+
+    /// Sends a request once the rate limit allows it and records the limit of its response
+    #[tracing::instrument(name = "request", level = "debug", skip_all, fields(url = %url))]
+    async fn send_throttled(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        url: &str,
+    ) -> Result<reqwest::Response, RedditProviderError> {
+        let request = match self.get_access_token(client, shared_state).await? {
+            // Authenticated requests only get the higher rate limit on the OAuth host
+            Some(token) => client
+                .get(url.replace("https://www.reddit.com/", "https://oauth.reddit.com/"))
+                .bearer_auth(token),
+            None => client.get(url),
+        }
+        .headers(self.headers.to_owned())
+        .header(USER_AGENT, shared_state.lock().await.user_agents.current());
+
+        let wait = shared_state
+            .lock()
+            .await
+            .rate_limiter
+            .lock()
+            .unwrap()
+            .acquire(Instant::now());
+        if !wait.is_zero() {
+            debug!("Waiting {:?} for the rate limit", wait);
+            sleep(wait).await;
+        }
+
+        let res = request
+            .send()
+            .await
+            .map_err(RedditProviderError::ReqwestMiddleware)?;
+        debug!("Received {}", res.status());
+
+        let mut ss = shared_state.lock().await;
+        ss.rate_limiter
+            .lock()
+            .unwrap()
+            .update(res.headers(), Instant::now());
+        // A revoked token is requested again with the next request, listings retry the page
+        if res.status() == reqwest::StatusCode::UNAUTHORIZED {
+            ss.reddit_token = None;
+        }
+        // Redgifs tokens are bound to the user agent they were requested with
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && ss.user_agents.rotate() {
+            ss.redgifs_token = None;
+        }
+
+        Ok(res)
+    }
+
+    async fn get_listing_pair<T: DeserializeOwned, U: DeserializeOwned>(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        url: &str,
+    ) -> Result<(T, U), RedditProviderError> {
+        self.get_json(client, shared_state, url).await
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        url: &str,
+    ) -> Result<T, RedditProviderError> {
+        if let Some(kind) = shared_state
+            .lock()
+            .await
+            .failure_injection
+            .and_then(|f| f.roll())
+        {
+            return Err(RedditProviderError::from(kind));
+        }
+
+        let res = self.send_throttled(client, shared_state, url).await?;
+
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(RedditProviderError::TooManyRequests);
+        }
+
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RedditProviderError::NotFound);
+        }
+
+        if res.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(RedditProviderError::Forbidden);
+        }
+
+        res.json().await.map_err(RedditProviderError::Reqwest)
+    }
+
+    /// Fetches a single post, the comments listed alongside it are discarded
+    pub async fn get_post(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        id: &str,
+    ) -> Result<RedditSubmittedResponse, RedditProviderError> {
+        let url = format!("https://www.reddit.com/comments/{}.json?raw_json=1", id);
+        let (post, _) = self
+            .get_listing_pair::<_, serde_json::Value>(client, shared_state, &url)
+            .await?;
+        Ok(post)
+    }
+
+    /// Fetches posts by their IDs, 100 per request. Deleted posts are missing from the listings
+    pub async fn get_posts_by_id(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        ids: &[String],
+    ) -> Result<Vec<RedditSubmittedResponse>, RedditProviderError> {
+        let mut responses = Vec::new();
+
+        for chunk in ids.chunks(MAX_SUBMISSIONS_PER_REQUEST as usize) {
+            let names = chunk
+                .iter()
+                .map(|id| format!("t3_{}", id))
+                .collect::<Vec<_>>()
+                .join(",");
+            let url = format!(
+                "https://www.reddit.com/by_id/{}.json?limit={}&raw_json=1",
+                names, MAX_SUBMISSIONS_PER_REQUEST
+            );
+            responses.push(self.get_json(client, shared_state, &url).await?);
         }
 
         Ok(responses)
     }
+
+    /// Fetches the comment tree of a post as returned by Reddit
+    pub async fn get_post_comments(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        id: &str,
+    ) -> Result<serde_json::Value, RedditProviderError> {
+        let url = format!(
+            "https://www.reddit.com/comments/{}.json?limit=500&raw_json=1",
+            id
+        );
+        let (_, comments) = self
+            .get_listing_pair::<serde_json::Value, _>(client, shared_state, &url)
+            .await?;
+        Ok(comments)
+    }
+
+    /// Fetches the other submissions of the same link, listed as "other discussions" on Reddit
+    pub async fn get_post_duplicates(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        id: &str,
+    ) -> Result<RedditSubmittedResponse, RedditProviderError> {
+        let url = format!(
+            "https://www.reddit.com/duplicates/{}.json?limit={}&raw_json=1",
+            id, MAX_SUBMISSIONS_PER_REQUEST
+        );
+        let (_, duplicates) = self
+            .get_listing_pair::<serde_json::Value, _>(client, shared_state, &url)
+            .await?;
+        Ok(duplicates)
+    }
+
+    /// Requests an OAuth2 access token for the given script app credentials
+    pub async fn authenticate(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        credentials: &RedditCredentials,
+        user_agent: &str,
+    ) -> Result<RedditAccessTokenResponse, RedditProviderError> {
+        self.request_token(
+            client,
+            user_agent,
+            &credentials.client_id,
+            &credentials.client_secret,
+            &[
+                ("grant_type", "password"),
+                ("username", &credentials.username),
+                ("password", &credentials.password),
+            ],
+        )
+        .await
+    }
+
+    /// Requests an application only OAuth2 access token, which is not tied to an account
+    pub async fn authenticate_app(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        app: &RedditAppCredentials,
+        user_agent: &str,
+    ) -> Result<RedditAccessTokenResponse, RedditProviderError> {
+        self.request_token(
+            client,
+            user_agent,
+            &app.client_id,
+            &app.client_secret,
+            &[("grant_type", "client_credentials")],
+        )
+        .await
+    }
+
+    async fn request_token(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        user_agent: &str,
+        client_id: &str,
+        client_secret: &str,
+        form: &[(&str, &str)],
+    ) -> Result<RedditAccessTokenResponse, RedditProviderError> {
+        let res = client
+            .post("https://www.reddit.com/api/v1/access_token")
+            .headers(self.headers.to_owned())
+            .header(USER_AGENT, user_agent)
+            .basic_auth(client_id, Some(client_secret))
+            .form(form)
+            .send()
+            .await
+            .map_err(RedditProviderError::ReqwestMiddleware)?;
+
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(RedditProviderError::TooManyRequests);
+        }
+
+        if !res.status().is_success() {
+            return Err(RedditProviderError::Unauthorized);
+        }
+
+        // Wrong user credentials are reported with a 200 and an error body
+        let body = res.text().await.map_err(RedditProviderError::Reqwest)?;
+        serde_json::from_str::<RedditAccessTokenResponse>(&body)
+            .map_err(|_| RedditProviderError::Unauthorized)
+    }
+
+    fn gen_account_url(
+        &self,
+        user: &str,
+        after: Option<&str>,
+        listing: &RedditAccountListing,
+    ) -> String {
+        match after {
+            Some(after) => format!(
+                "https://oauth.reddit.com/user/{}/{}?type=links&limit={}&after={}&raw_json=1",
+                user, listing, MAX_SUBMISSIONS_PER_REQUEST, after
+            ),
+            None => format!(
+                "https://oauth.reddit.com/user/{}/{}?type=links&limit={}&raw_json=1",
+                user, listing, MAX_SUBMISSIONS_PER_REQUEST
+            ),
+        }
+    }
+
+    /// Fetches the posts the authenticated user `cmd.resource` saved or upvoted, comments are left
+    /// out. Every page is sent to `pages` as soon as it arrives
+    pub async fn get_account_submissions(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        cmd: &CliRedditCommand,
+        listing: &RedditAccountListing,
+        options: &CliSharedOptions,
+        pages: &mpsc::Sender<impl From<RedditSubmittedResponse> + Send>,
+    ) -> Result<(), RedditProviderError> {
+        let CliRedditCommand { resource: user, .. } = cmd;
+
+        self.stream_listing(
+            client,
+            shared_state,
+            options,
+            ListingOrder::Added,
+            |after| self.gen_account_url(user, after, listing),
+            pages,
+        )
+        .await
+    }
 }
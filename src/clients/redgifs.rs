@@ -1,9 +1,10 @@
-use crate::utils::state::SharedState;
-use reqwest::{header::HeaderMap, Response};
-use serde::{Deserialize, Serialize};
+use crate::utils::{emit_event, state::SharedState, CrawlEvent, OutputMode};
+use reqwest::{header::HeaderMap, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::Mutex;
+use tracing::debug;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RedgifsTemporaryTokenResponse {
@@ -33,25 +34,51 @@ pub struct RedgifsGif {
     // pub width: i64,
     // pub height: i64,
     // pub hls: bool,
-    // pub likes: i64,
+    #[serde(default)]
+    pub likes: i64,
     // pub niches: Vec<Value>,
     // pub tags: Vec<String>,
     // pub verified: bool,
     // pub views: Value,
-    // pub description: String,
+    #[serde(default)]
+    pub description: Option<String>,
     // pub duration: f64,
     // pub published: bool,
     pub urls: RedgifsUrls,
-    // pub user_name: String,
-    // #[serde(rename = "type")]
-    // pub type_field: i64,
+    #[serde(default)]
+    pub user_name: String,
+    // 1 for videos and 2 for images
+    #[serde(rename = "type", default = "default_gif_type")]
+    pub type_field: i64,
     // pub avg_color: String,
-    // pub gallery: Value,
+    // ID of the gallery the gif belongs to
+    #[serde(default)]
+    pub gallery: Option<String>,
     // pub hide_home: bool,
     // pub hide_trending: bool,
     // pub sexuality: Vec<String>,
 }
 
+fn default_gif_type() -> i64 {
+    1
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedgifsGalleryResponse {
+    pub gifs: Vec<RedgifsGif>,
+}
+
+/// A page of the gifs of a user, newest first
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedgifsUserResponse {
+    pub page: u32,
+    pub pages: u32,
+    pub total: u32,
+    pub gifs: Vec<RedgifsGif>,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RedgifsUrls {
@@ -62,8 +89,11 @@ pub struct RedgifsUrls {
     pub sd: String,
 }
 
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum RedgifsQuality {
     SD,
+    #[default]
     HD,
 }
 
@@ -77,6 +107,8 @@ pub enum RedgifsClientError {
     SerdeJson(#[from] serde_json::Error),
     #[error("ID extraction failed")]
     ExtractionFailed,
+    #[error("Not found")]
+    NotFound,
 }
 
 // lazy_static! {
@@ -91,12 +123,12 @@ pub enum RedgifsClientError {
 //     };
 // }
 
-fn get_header_map() -> HeaderMap {
+fn get_header_map(user_agent: &str) -> HeaderMap {
     let mut map: HeaderMap = reqwest::header::HeaderMap::new();
-    map.insert(
-        reqwest::header::USER_AGENT,
-        reqwest::header::HeaderValue::from_static("Reddit-User-Analysis"),
-    );
+    // Validated when the user agent pool is loaded
+    if let Ok(value) = reqwest::header::HeaderValue::from_str(user_agent) {
+        map.insert(reqwest::header::USER_AGENT, value);
+    }
 
     map
 }
@@ -104,10 +136,11 @@ fn get_header_map() -> HeaderMap {
 /// https://github.com/Redgifs/api/wiki/Temporary-tokens
 async fn get_temporary_token(
     client: &reqwest_middleware::ClientWithMiddleware,
+    user_agent: &str,
 ) -> Result<RedgifsTemporaryTokenResponse, RedgifsClientError> {
     client
         .get("https://api.redgifs.com/v2/auth/temporary")
-        .headers(get_header_map())
+        .headers(get_header_map(user_agent))
         .send()
         .await
         .map_err(RedgifsClientError::ReqwestMiddleware)?
@@ -116,61 +149,186 @@ async fn get_temporary_token(
         .map_err(RedgifsClientError::Reqwest)
 }
 
-pub async fn download_redgifs_media(
+/// Returns the cached token with the user agent it is bound to, requesting a new one if needed
+async fn get_token(
     client: &reqwest_middleware::ClientWithMiddleware,
     shared_state: &Arc<Mutex<SharedState>>,
-    url: &str,
-    gif_quality: RedgifsQuality,
-) -> Result<Response, RedgifsClientError> {
+) -> Result<(String, String), RedgifsClientError> {
+    // Held while requesting so concurrent downloads share a single new token
     let mut state = shared_state.lock().await;
+    let user_agent = state.user_agents.current().to_owned();
 
     let token = match &state.redgifs_token {
         Some(t) => t.clone(),
         None => {
-            let res = get_temporary_token(client).await?;
+            let res = get_temporary_token(client, &user_agent).await?;
             state.redgifs_token = Some(res.token.clone());
             res.token
         }
     };
 
-    let post_id = match url {
+    Ok((token, user_agent))
+}
+
+async fn send_authenticated(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    token: &str,
+    user_agent: &str,
+    url: &str,
+) -> Result<Response, RedgifsClientError> {
+    client
+        .get(url)
+        .headers(get_header_map(user_agent))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .map_err(RedgifsClientError::ReqwestMiddleware)
+}
+
+/// Requests the API with the temporary token
+async fn get_api<T: DeserializeOwned>(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    url: &str,
+) -> Result<T, RedgifsClientError> {
+    let (token, user_agent) = get_token(client, shared_state).await?;
+    let mut res = send_authenticated(client, &token, &user_agent, url).await?;
+
+    // Temporary tokens expire during long crawls, an expired token is replaced once
+    if res.status() == StatusCode::UNAUTHORIZED {
+        {
+            let mut state = shared_state.lock().await;
+            // Another download may have replaced the token already
+            if state.redgifs_token.as_ref() == Some(&token) {
+                state.redgifs_token = None;
+            }
+        }
+        debug!("Redgifs token expired, requesting a new one");
+
+        let (token, user_agent) = get_token(client, shared_state).await?;
+        res = send_authenticated(client, &token, &user_agent, url).await?;
+    }
+
+    if res.status() == StatusCode::NOT_FOUND || res.status() == StatusCode::GONE {
+        return Err(RedgifsClientError::NotFound);
+    }
+
+    res.error_for_status()
+        .map_err(RedgifsClientError::Reqwest)?
+        .json()
+        .await
+        .map_err(RedgifsClientError::Reqwest)
+}
+
+/// Extracts the ID of a gif from `redgifs.com/watch/id`, `redgifs.com/ifr/id` or `i.redgifs.com/i/id.jpg`
+pub fn extract_redgifs_id(url: &str) -> Result<&str, RedgifsClientError> {
+    match url {
         _ if url.contains("redgifs.com/i/") => url
             .split("/i/")
             .last()
             .ok_or(RedgifsClientError::ExtractionFailed)?
             .split('.')
             .next()
-            .ok_or(RedgifsClientError::ExtractionFailed)?,
+            .ok_or(RedgifsClientError::ExtractionFailed),
         _ if url.contains("redgifs.com/watch/") => url
             .split("/watch/")
             .last()
-            .ok_or(RedgifsClientError::ExtractionFailed)?,
+            .ok_or(RedgifsClientError::ExtractionFailed),
         _ if url.contains("redgifs.com/ifr/") => url
             .split("/ifr/")
             .last()
-            .ok_or(RedgifsClientError::ExtractionFailed)?,
-        _ => return Err(RedgifsClientError::ExtractionFailed),
-    };
+            .ok_or(RedgifsClientError::ExtractionFailed),
+        _ => Err(RedgifsClientError::ExtractionFailed),
+    }
+}
 
-    let res: RedgifsGifResponse = client
-        .get(format!("https://api.redgifs.com/v2/gifs/{}", post_id))
-        .headers(get_header_map())
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await
-        .map_err(RedgifsClientError::ReqwestMiddleware)?
-        .json()
-        .await
-        .map_err(RedgifsClientError::Reqwest)?;
+/// Resolves a linked gif, a gif that is part of a gallery expands into every gif of the gallery
+pub async fn get_redgifs_media(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    url: &str,
+) -> Result<Vec<RedgifsGif>, RedgifsClientError> {
+    let post_id = extract_redgifs_id(url)?;
+    let res: RedgifsGifResponse = get_api(
+        client,
+        shared_state,
+        &format!("https://api.redgifs.com/v2/gifs/{}", post_id),
+    )
+    .await?;
 
-    let dl_url = match gif_quality {
-        RedgifsQuality::SD => res.gif.urls.sd,
-        RedgifsQuality::HD => res.gif.urls.hd,
-    };
+    match &res.gif.gallery {
+        Some(gallery) => {
+            let gallery: RedgifsGalleryResponse = get_api(
+                client,
+                shared_state,
+                &format!("https://api.redgifs.com/v2/gallery/{}", gallery),
+            )
+            .await?;
+            Ok(gallery.gifs)
+        }
+        None => Ok(vec![res.gif]),
+    }
+}
+
+/// Fetches the pages of a user's gifs, `limit` caps the number of requests
+pub async fn get_redgifs_user_gifs(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    user: &str,
+    limit: Option<u32>,
+) -> Result<Vec<RedgifsUserResponse>, RedgifsClientError> {
+    let mut responses = Vec::new();
+    let mut page: u32 = 1;
+
+    loop {
+        let url = format!(
+            "https://api.redgifs.com/v2/users/{}/search?order=new&count=80&page={}",
+            user.to_lowercase(),
+            page
+        );
+        let res: RedgifsUserResponse = get_api(client, shared_state, &url).await?;
+
+        if shared_state.lock().await.output_mode == OutputMode::Json {
+            emit_event(&CrawlEvent::PageFetched {
+                url: &url,
+                page,
+                posts: res.gifs.len(),
+            });
+        }
+
+        let last_page = res.gifs.is_empty()
+            || res.page >= res.pages
+            || limit.is_some_and(|limit| page >= limit);
+        responses.push(res);
+
+        if last_page {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(responses)
+}
+
+/// URL of the file of a gif in the requested quality
+pub fn get_redgifs_media_url(gif: &RedgifsGif, gif_quality: RedgifsQuality) -> &str {
+    match gif_quality {
+        RedgifsQuality::SD => &gif.urls.sd,
+        RedgifsQuality::HD => &gif.urls.hd,
+    }
+}
+
+pub async fn download_redgifs_media(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    gif: &RedgifsGif,
+    gif_quality: RedgifsQuality,
+) -> Result<Response, RedgifsClientError> {
+    let user_agent = shared_state.lock().await.user_agents.current().to_owned();
 
     client
-        .get(dl_url)
-        .headers(get_header_map())
+        .get(get_redgifs_media_url(gif, gif_quality))
+        .headers(get_header_map(&user_agent))
         .send()
         .await
         .map_err(RedgifsClientError::ReqwestMiddleware)
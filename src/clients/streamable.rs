@@ -0,0 +1,90 @@
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamableVideoResponse {
+    pub files: StreamableFiles,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamableFiles {
+    pub mp4: Option<StreamableFile>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamableFile {
+    pub url: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum StreamableClientError {
+    #[error("ReqwestMiddleware error: {0}")]
+    ReqwestMiddleware(#[from] reqwest_middleware::Error),
+    #[error("Reqwest error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("ID extraction failed")]
+    ExtractionFailed,
+    #[error("Streamable returned a Not Found status")]
+    NotFound,
+}
+
+/// Extracts the video ID of `streamable.com/ID` and the `/e/ID` and `/o/ID` embeds
+pub fn extract_streamable_id(url: &str) -> Option<&str> {
+    let path = url.split("streamable.com/").nth(1)?;
+    let path = path
+        .strip_prefix("e/")
+        .or_else(|| path.strip_prefix("o/"))
+        .unwrap_or(path);
+    let id = path.split(['/', '?', '#']).next()?;
+
+    match !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        true => Some(id),
+        false => None,
+    }
+}
+
+/// Direct URL of the mp4 file of a video, see https://support.streamable.com/api-documentation
+pub async fn get_streamable_media_url(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+) -> Result<String, StreamableClientError> {
+    let video_id = extract_streamable_id(url).ok_or(StreamableClientError::ExtractionFailed)?;
+
+    let res = client
+        .get(format!("https://api.streamable.com/videos/{}", video_id))
+        .send()
+        .await
+        .map_err(StreamableClientError::ReqwestMiddleware)?;
+
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(StreamableClientError::NotFound);
+    }
+
+    let res: StreamableVideoResponse = res.json().await.map_err(StreamableClientError::Reqwest)?;
+
+    // Videos that are still processing or were removed have no file
+    let dl_url = res
+        .files
+        .mp4
+        .and_then(|f| f.url)
+        .ok_or(StreamableClientError::NotFound)?;
+    // Older responses use protocol relative URLs
+    match dl_url.starts_with("//") {
+        true => Ok(format!("https:{}", dl_url)),
+        false => Ok(dl_url),
+    }
+}
+
+pub async fn download_streamable_media(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+) -> Result<Response, StreamableClientError> {
+    let dl_url = get_streamable_media_url(client, url).await?;
+
+    client
+        .get(dl_url)
+        .send()
+        .await
+        .map_err(StreamableClientError::ReqwestMiddleware)
+}
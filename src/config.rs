@@ -0,0 +1,91 @@
+use crate::{
+    clients::RedgifsQuality,
+    utils::{get_config_dir, parse_interval},
+};
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Deserialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read {0}: {1}")]
+    Read(String, io::Error),
+    #[error("Invalid config file - {0}")]
+    Toml(String),
+    #[error("Invalid tasks {0} - expected a number between 1 and 100")]
+    Tasks(u16),
+    #[error("Invalid {0} - {1}")]
+    Interval(&'static str, String),
+}
+
+/// Defaults loaded from `config.toml`, command line flags and environment variables take precedence
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub output: Option<String>,
+    pub tasks: Option<u16>,
+    pub file_scheme: Option<String>,
+    pub quality: Option<RedgifsQuality>,
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    pub global_cache: Option<String>,
+    pub retries: Option<u32>,
+    // Intervals like `--retry-base-delay`, e.g. `5s`
+    pub retry_base_delay: Option<String>,
+    pub retry_max_delay: Option<String>,
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config =
+            toml::from_str::<Config>(s).map_err(|e| ConfigError::Toml(e.message().to_owned()))?;
+
+        for (key, interval) in [
+            ("retry-base-delay", &config.retry_base_delay),
+            ("retry-max-delay", &config.retry_max_delay),
+        ] {
+            if let Some(Err(e)) = interval.as_deref().map(parse_interval) {
+                return Err(ConfigError::Interval(key, e));
+            }
+        }
+
+        match config.tasks {
+            Some(tasks) if !(1..=100).contains(&tasks) => Err(ConfigError::Tasks(tasks)),
+            _ => Ok(config),
+        }
+    }
+}
+
+impl Config {
+    /// `~/.config/reddit-clawler/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        get_config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Loads the config file, a missing file leaves every default untouched
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Config::from_str(&content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Read(path.display().to_string(), e)),
+        }
+    }
+}
+
+/// Reads a flag, the config value only replaces the built-in default of the flag
+pub fn resolve_flag<T: Clone + Send + Sync + 'static>(
+    m: &ArgMatches,
+    id: &str,
+    config_value: Option<&T>,
+) -> Option<T> {
+    match (m.value_source(id), config_value) {
+        (None | Some(ValueSource::DefaultValue), Some(value)) => Some(value.clone()),
+        _ => m.get_one::<T>(id).cloned(),
+    }
+}
@@ -0,0 +1,109 @@
+use crate::clients::{ArchiveClientError, RedditProviderError, RedgifsClientError};
+use std::{error::Error, process::ExitCode};
+use thiserror::Error;
+
+/// Exit codes of a run, so wrappers like cron or systemd can react to how it ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success = 0,
+    // Errors without a more specific code
+    Error = 1,
+    // Some downloads failed, they are recorded in the cache for `retry`
+    PartialFailure = 2,
+    RateLimited = 3,
+    // Deleted, suspended, banned, private or quarantined
+    ResourceGone = 4,
+    AuthFailed = 5,
+    // Flags, config or input files that can not be used
+    InvalidInput = 6,
+    MissingDependency = 7,
+    Interrupted = 130,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Quits right away, used for errors found before anything is crawled
+    pub fn exit(self) -> ! {
+        std::process::exit(self.code() as i32)
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status.code())
+    }
+}
+
+/// How a run failed, returned by `main` as its exit code
+#[derive(Error, Debug)]
+pub enum ClawlerError {
+    #[error("{0} downloads failed, `retry` downloads them again")]
+    PartialFailure(u64),
+    #[error("{0}")]
+    RateLimited(String),
+    #[error("{0}")]
+    ResourceGone(String),
+    #[error("{0}")]
+    AuthFailed(String),
+    #[error("Stopped by Ctrl-C")]
+    Interrupted,
+    #[error("{0}")]
+    Other(Box<dyn Error>),
+}
+
+impl ClawlerError {
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            ClawlerError::PartialFailure(_) => ExitStatus::PartialFailure,
+            ClawlerError::RateLimited(_) => ExitStatus::RateLimited,
+            ClawlerError::ResourceGone(_) => ExitStatus::ResourceGone,
+            ClawlerError::AuthFailed(_) => ExitStatus::AuthFailed,
+            ClawlerError::Interrupted => ExitStatus::Interrupted,
+            ClawlerError::Other(_) => ExitStatus::Error,
+        }
+    }
+
+    /// Sorts an error of a command into the variant of its exit code
+    pub fn classify(e: impl Into<Box<dyn Error>>) -> Self {
+        let e = e.into();
+
+        if let Some(reddit) = e.downcast_ref::<RedditProviderError>() {
+            match reddit {
+                RedditProviderError::TooManyRequests
+                | RedditProviderError::Archive(ArchiveClientError::TooManyRequests(_)) => {
+                    return ClawlerError::RateLimited(reddit.to_string())
+                }
+                RedditProviderError::NotFound
+                | RedditProviderError::Suspended
+                | RedditProviderError::Banned
+                | RedditProviderError::Private
+                | RedditProviderError::Quarantined => {
+                    return ClawlerError::ResourceGone(reddit.to_string())
+                }
+                RedditProviderError::Unauthorized | RedditProviderError::Forbidden => {
+                    return ClawlerError::AuthFailed(reddit.to_string())
+                }
+                _ => {}
+            }
+        }
+        if let Some(archive @ ArchiveClientError::TooManyRequests(_)) =
+            e.downcast_ref::<ArchiveClientError>()
+        {
+            return ClawlerError::RateLimited(archive.to_string());
+        }
+        if let Some(RedgifsClientError::NotFound) = e.downcast_ref::<RedgifsClientError>() {
+            return ClawlerError::ResourceGone(String::from("Redgifs returned a Not Found status"));
+        }
+
+        ClawlerError::Other(e)
+    }
+}
+
+impl From<Box<dyn Error>> for ClawlerError {
+    fn from(e: Box<dyn Error>) -> Self {
+        ClawlerError::classify(e)
+    }
+}
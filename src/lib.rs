@@ -1,4 +1,7 @@
 pub mod cli;
 pub mod clients;
+pub mod config;
+pub mod error;
+pub mod providers;
 pub mod reddit_parser;
 pub mod utils;
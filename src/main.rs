@@ -1,50 +1,313 @@
+use owo_colors::OwoColorize;
 use reddit_clawler::{
     cli,
-    utils::{self, state::SharedState},
+    config::Config,
+    error::{ClawlerError, ExitStatus},
+    utils::{
+        self,
+        state::{SharedState, DEFAULT_FALLBACK_YTDLP_TASKS, DEFAULT_YTDLP_TIMEOUT},
+        AboutCache,
+    },
 };
 use reqwest_middleware::ClientBuilder;
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
-use std::{error::Error, sync::Arc};
-use tokio::sync::Mutex;
+use std::{fs, path::PathBuf, process::ExitCode, sync::Arc};
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{error, info, warn};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitStatus::Success.into(),
+        Err(e) => {
+            match e {
+                ClawlerError::PartialFailure(_)
+                | ClawlerError::ResourceGone(_)
+                | ClawlerError::Interrupted => warn!("{}", e),
+                _ => error!("{}", e),
+            }
+            e.exit_status().into()
+        }
+    }
+}
+
+async fn run() -> Result<(), ClawlerError> {
+    let config = match Config::default_path() {
+        Some(path) => Config::load(&path).unwrap_or_else(|e| {
+            println!("{} {}", "[INVALID_CONFIG]".bold().red(), e);
+            ExitStatus::InvalidInput.exit()
+        }),
+        None => Config::default(),
+    };
+    let cli_request = cli::run(&config);
+
+    let (verbose, quiet, log_file) = match cli_request.options() {
+        Some(options) => (options.verbose, options.quiet, options.log_file.as_deref()),
+        None => (0, false, None),
+    };
+    utils::init_logging(verbose, quiet, log_file).unwrap_or_else(|e| {
+        println!("{} {}", "[INVALID_LOG_FILE]".bold().red(), e);
+        ExitStatus::InvalidInput.exit()
+    });
+
     // Checks for dependencies that will be used in future versions
     utils::check_deps()?;
-    // Checks for file_scheme that will be used in future version
-    // let file_scheme = String::from("{UPVOTES}__ID}_{AUTHOR}_{POSTID}_{DATE}");
-    // check_file_scheme(&file_scheme);
-    let cli_request = cli::run();
+    if cli_request
+        .options()
+        .is_some_and(|o| o.downloader == utils::Downloader::Aria2c)
+        && !utils::check_aria2c()
+    {
+        error!("Missing CLI dependencies: aria2c");
+        ExitStatus::MissingDependency.exit()
+    }
+    if let Some(conversion) = cli_request.options().and_then(|o| o.convert_images) {
+        if !conversion.format.is_installed() {
+            error!("Missing CLI dependencies: {}", conversion.format.program());
+            ExitStatus::MissingDependency.exit()
+        }
+    }
+    if cli_request
+        .options()
+        .is_some_and(|o| o.video_processing.is_enabled() || o.audio_only.is_some())
+        && std::process::Command::new("ffmpeg")
+            .arg("-version")
+            .output()
+            .is_err()
+    {
+        error!("Missing CLI dependencies: ffmpeg");
+        ExitStatus::MissingDependency.exit()
+    }
 
     // Create client and state that is shared between tokio tasks
-    // Retries up to 3 times with increasing intervals between attempts
-    let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
-
-    let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/96.0.4664.110 Safari/537.36";
-    let client = ClientBuilder::new(
-        reqwest::Client::builder()
-            .user_agent(user_agent)
-            .build()
-            .unwrap(),
-    )
-    .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-    .build();
+    // Retries with increasing intervals between attempts, rate limits are waited out
+    let retry_policy = cli_request
+        .options()
+        .map(|o| o.retry_policy)
+        .unwrap_or_default();
+
+    let user_agents = match cli_request.options() {
+        Some(options) => options.user_agents.clone(),
+        None => utils::UserAgentPool::new(config.user_agent.clone().into_iter().collect())
+            .map_err(ClawlerError::classify)?,
+    };
+    // Media downloads keep the first user agent, Reddit and Redgifs requests follow the rotation
+    let mut client_builder = reqwest::Client::builder().user_agent(user_agents.current());
+    let (proxy, provider_proxies) = match cli_request.options() {
+        Some(options) => (options.proxy.clone(), options.provider_proxies.clone()),
+        None => (
+            config
+                .proxy
+                .as_deref()
+                .map(utils::parse_proxy_url)
+                .transpose()
+                .map_err(ClawlerError::classify)?,
+            Vec::new(),
+        ),
+    };
+    if proxy.is_some() || !provider_proxies.is_empty() {
+        let (proxy, provider_proxies) = (proxy.clone(), provider_proxies.clone());
+        client_builder = client_builder.proxy(reqwest::Proxy::custom(move |url| {
+            utils::select_proxy(url, proxy.as_ref(), &provider_proxies).cloned()
+        }));
+    }
+    let mut client = ClientBuilder::new(client_builder.build().unwrap())
+        .with(retry_policy.transient_middleware())
+        .with(retry_policy.rate_limit_middleware());
+    // The cookies of `--cookies` are sent to their domains, e.g. to see NSFW-gated posts
+    if let Some(cookies) = cli_request.options().and_then(|o| o.cookies.clone()) {
+        client = client.with(cookies.middleware());
+    }
+    let client = client.build();
 
     // Shared state between tokio tasks e.g. caching an authorization token
-    let shared_state: Arc<Mutex<SharedState>> = Arc::new(Mutex::new(SharedState::default()));
+    let shared_state: Arc<Mutex<SharedState>> = Arc::new(Mutex::new(SharedState {
+        failure_injection: cli_request.options().and_then(|o| o.inject_failure),
+        redgifs_quality: cli_request.options().map(|o| o.quality).unwrap_or_default(),
+        dry_run: cli_request.options().is_some_and(|o| o.dry_run),
+        embed_metadata: cli_request.options().is_some_and(|o| o.embed_metadata),
+        checksum: cli_request.options().is_some_and(|o| o.checksum),
+        skip_existing: cli_request.options().is_some_and(|o| o.skip_existing),
+        downloader: cli_request
+            .options()
+            .map(|o| o.downloader)
+            .unwrap_or_default(),
+        convert_images: cli_request.options().and_then(|o| o.convert_images),
+        video_processing: cli_request
+            .options()
+            .map(|o| o.video_processing)
+            .unwrap_or_default(),
+        video_slots: Arc::new(Semaphore::new(
+            cli_request
+                .options()
+                .map(|o| o.video_tasks as usize)
+                .unwrap_or(utils::DEFAULT_VIDEO_TASKS),
+        )),
+        audio_only: cli_request.options().and_then(|o| o.audio_only),
+        ytdlp_cookies: cli_request
+            .options()
+            .map(|o| o.ytdlp_cookies.clone())
+            .unwrap_or_default(),
+        aria2c_connections: cli_request
+            .options()
+            .map(|o| o.aria2c_connections)
+            .unwrap_or(utils::DEFAULT_ARIA2C_CONNECTIONS),
+        imported_archive: cli_request
+            .options()
+            .map(|o| o.imported_archive.clone())
+            .unwrap_or_default(),
+        global_cache: cli_request.options().and_then(|o| o.global_cache.clone()),
+        link_duplicates: cli_request.options().and_then(|o| o.link_duplicates),
+        ytdlp_slots: Arc::new(Semaphore::new(
+            cli_request
+                .options()
+                .map(|o| o.fallback_ytdlp_tasks as usize)
+                .unwrap_or(DEFAULT_FALLBACK_YTDLP_TASKS),
+        )),
+        transfer_timeouts: cli_request
+            .options()
+            .map(|o| o.transfer_timeouts)
+            .unwrap_or_default(),
+        ytdlp_timeout: cli_request
+            .options()
+            .map(|o| o.ytdlp_timeout)
+            .unwrap_or(DEFAULT_YTDLP_TIMEOUT),
+        bandwidth: cli_request
+            .options()
+            .and_then(|o| o.rate_limit)
+            .map(utils::BandwidthLimiter::new),
+        size_limits: cli_request
+            .options()
+            .map(|o| o.size_limits)
+            .unwrap_or_default(),
+        output_mode: cli_request
+            .options()
+            .map(|o| o.output_mode)
+            .unwrap_or_default(),
+        proxy,
+        provider_proxies,
+        user_agents,
+        reddit_app: cli_request
+            .options()
+            .and_then(|o| o.app_credentials.clone()),
+        about_cache: AboutCache::default_path()
+            .map(AboutCache::load)
+            .unwrap_or_default(),
+        ..SharedState::default()
+    }));
 
-    match cli_request {
-        cli::CliCommand::User(cmd) => {
-            cli::handle_user_command(cmd, &client, &shared_state).await?;
-        }
+    // Only commands that download are stopped gracefully, the others quit on Ctrl-C as usual
+    if cli_request.options().is_some() {
+        shared_state.lock().await.shutdown.listen();
+    }
+
+    let remote = match cli_request.options() {
+        Some(options) => match &options.remote {
+            Some(remote) => {
+                let staging_dir = PathBuf::from(&options.output);
+                fs::create_dir_all(&staging_dir).map_err(ClawlerError::classify)?;
+                let pulled = remote
+                    .pull_caches(&staging_dir)
+                    .await
+                    .map_err(ClawlerError::classify)?;
+                info!("Fetched {} caches from {}", pulled, remote.url());
+
+                // A dry run writes nothing to upload
+                match options.dry_run {
+                    true => None,
+                    false => {
+                        let (uploads, handle) =
+                            utils::RemoteUploads::spawn(remote.clone(), staging_dir.clone());
+                        shared_state.lock().await.remote_uploads = Some(uploads);
+                        Some((remote.clone(), staging_dir, handle))
+                    }
+                }
+            }
+            None => None,
+        },
+        None => None,
+    };
+
+    let result = match cli_request {
+        cli::CliCommand::User(cmd) => cli::handle_user_command(cmd, &client, &shared_state).await,
 
         cli::CliCommand::Subreddit(cmd) => {
-            cli::handle_subreddit_command(cmd, &client, &shared_state).await?;
+            cli::handle_subreddit_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::Domain(cmd) => {
+            cli::handle_domain_command(cmd, &client, &shared_state).await
         }
 
         cli::CliCommand::Search(cmd) => {
-            cli::handle_search_command(cmd, &client, &shared_state).await?;
+            cli::handle_search_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::Post(cmd) => cli::handle_post_command(cmd, &client, &shared_state).await,
+
+        cli::CliCommand::RedgifsUser(cmd) => {
+            cli::handle_redgifs_user_command(cmd, &client, &shared_state).await
         }
+
+        cli::CliCommand::Batch(cmd) => cli::handle_batch_command(cmd, &client, &shared_state).await,
+
+        cli::CliCommand::Watch(cmd) => cli::handle_watch_command(cmd, &client, &shared_state).await,
+
+        cli::CliCommand::Schedule(cmd) => {
+            cli::handle_schedule_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::Saved(cmd) | cli::CliCommand::Upvoted(cmd) => {
+            cli::handle_account_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::RebuildViews(cmd) => cli::handle_rebuild_views_command(cmd),
+
+        cli::CliCommand::Retry(cmd) => cli::handle_retry_command(cmd, &client, &shared_state).await,
+
+        cli::CliCommand::Cache(cmd) => cli::handle_cache_command(cmd),
+
+        cli::CliCommand::Report(cmd) => cli::handle_report_command(cmd),
+
+        cli::CliCommand::Stats(cmd) => cli::handle_stats_command(cmd),
+
+        cli::CliCommand::Diff(cmd) => cli::handle_diff_command(cmd, &client, &shared_state).await,
+
+        cli::CliCommand::Authors(cmd) => {
+            cli::handle_authors_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::Explain(cmd) => {
+            cli::handle_explain_command(cmd, &client, &shared_state).await
+        }
+
+        cli::CliCommand::UserComments(cmd) => {
+            cli::handle_user_comments_command(cmd, &client, &shared_state).await
+        }
+    };
+
+    // Uploads what is still queued and the files written besides downloads, e.g. metadata
+    if let Some((remote, staging_dir, handle)) = remote {
+        shared_state.lock().await.remote_uploads = None;
+        handle.await.map_err(ClawlerError::classify)?;
+        let uploaded = utils::flush_staging_dir(&remote, &staging_dir)
+            .await
+            .map_err(ClawlerError::classify)?;
+        info!("Uploaded {} remaining files to {}", uploaded, remote.url());
+    }
+    result?;
+
+    // Finished runs still report resources that are gone and downloads that failed
+    let ss = shared_state.lock().await;
+    if ss.shutdown.is_requested() {
+        return Err(ClawlerError::Interrupted);
+    }
+    if ss.resources_gone > 0 {
+        return Err(ClawlerError::ResourceGone(format!(
+            "{} resources are no longer available",
+            ss.resources_gone
+        )));
+    }
+    if ss.downloads_failed > 0 {
+        return Err(ClawlerError::PartialFailure(ss.downloads_failed));
     }
 
     Ok(())
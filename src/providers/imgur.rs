@@ -0,0 +1,77 @@
+use super::{MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+};
+use async_trait::async_trait;
+use reqwest::header;
+
+/// Reads the extension and download URL of an Imgur link, `.gifv` pages are rewritten to the mp4
+/// they play
+fn get_imgur_media(url: &str) -> (String, String) {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "gifv" => (
+            String::from("mp4"),
+            format!("{}.mp4", &path[..path.len() - ".gifv".len()]),
+        ),
+        _ => (extension, url.to_owned()),
+    }
+}
+
+pub struct ImgurProvider;
+
+#[async_trait]
+impl MediaProvider for ImgurProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[RedditMediaProviderType::ImgurImage]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        data.url.contains("imgur")
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        _parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        trace.push(String::from("Matched Imgur embed"));
+        let (extension, url) = get_imgur_media(&data.url);
+        if url != data.url {
+            trace.push(format!("Rewrote gifv to {}", url));
+        }
+        vec![RedditCrawlerPost::new(
+            data,
+            RedditMediaProviderType::ImgurImage,
+            &extension,
+            &url,
+        )]
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        let response = ctx.client.get(&media.url).send().await?;
+
+        // Imgur returns "text/html" when the post has been deleted
+        match response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|v| v == "text/html")
+        {
+            true => Ok(ProviderDownload::NotFound),
+            false => Ok(ProviderDownload::HttpResponse(response)),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+mod imgur;
+mod reddit;
+mod redgifs;
+mod streamable;
+mod youtube;
+mod ytdlp;
+pub use imgur::*;
+pub use reddit::*;
+pub use redgifs::*;
+pub use streamable::*;
+pub use youtube::*;
+pub use ytdlp::*;
+
+use crate::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::{save_http_response, state::SharedState, SavedResponse, TransferSettings},
+};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use reqwest::Response;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+lazy_static! {
+    /// Providers the parser and the downloader use
+    pub static ref PROVIDERS: ProviderRegistry = ProviderRegistry::default();
+}
+
+/// What a provider fetched for a post
+pub enum ProviderDownload {
+    /// Saved by the downloader, named after the served type
+    HttpResponse(Response),
+    /// Files the provider saved itself, e.g. the gifs of a Redgifs gallery
+    Saved(Vec<SavedResponse>),
+    /// Written to `ProviderContext::file_path` by an external tool
+    File(String),
+    NotFound,
+    /// Message of a failed download, e.g. the error an external tool printed
+    Failed(String),
+}
+
+/// The running download a provider fetches a post for
+pub struct ProviderContext<'a> {
+    pub client: &'a reqwest_middleware::ClientWithMiddleware,
+    pub shared_state: &'a Arc<Mutex<SharedState>>,
+    pub folder_path: &'a str,
+    pub file_scheme: &'a str,
+    // Path of the post for providers writing the file themselves
+    pub file_path: &'a str,
+    pub transfer: &'a TransferSettings,
+}
+
+impl ProviderContext<'_> {
+    /// Writes a media response like the downloader does, for providers saving several files
+    pub async fn save_http_response(
+        &self,
+        response: Response,
+        media: &RedditCrawlerPost,
+    ) -> Result<SavedResponse, anyhow::Error> {
+        save_http_response(
+            self.client,
+            response,
+            self.folder_path,
+            media,
+            self.file_scheme,
+            self.transfer,
+        )
+        .await
+    }
+}
+
+/// A host posts link to, detected from the crawled post and downloaded by provider type
+#[async_trait]
+pub trait MediaProvider: Send + Sync {
+    /// Provider types of the posts `resolve` returns
+    fn provider_types(&self) -> &'static [RedditMediaProviderType];
+
+    /// Whether the post is resolved by this provider
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool;
+
+    /// Turns a post into the files to download and records the branches taken in `trace`
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost>;
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error>;
+
+    /// URLs of the files `download` would fetch, written by `--export-urls`
+    ///
+    /// Defaults to the URL of the post, pages like YouTube videos are left to the downloader
+    /// reading the list.
+    async fn resolve_urls(
+        &self,
+        _client: &reqwest_middleware::ClientWithMiddleware,
+        _shared_state: &Arc<Mutex<SharedState>>,
+        media: &RedditCrawlerPost,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        Ok(vec![media.url.clone()])
+    }
+}
+
+/// Providers in the order they are detected, the first match resolves a post
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn MediaProvider>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(RedditProvider));
+        registry.register(Box::new(YoutubeProvider));
+        registry.register(Box::new(RedgifsProvider));
+        registry.register(Box::new(StreamableProvider));
+        registry.register(Box::new(ImgurProvider));
+        registry.register(Box::new(YtdlpProvider));
+        registry
+    }
+}
+
+impl ProviderRegistry {
+    /// A registry without providers, `default()` has the built-in ones
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Adds a provider, which is detected after the ones registered before
+    pub fn register(&mut self, provider: Box<dyn MediaProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn detect(&self, data: &RedditSubmittedChildData) -> Option<&dyn MediaProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.detect(data))
+            .map(|p| p.as_ref())
+    }
+
+    /// The provider that downloads posts of a provider type
+    pub fn get(&self, provider: &RedditMediaProviderType) -> Option<&dyn MediaProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.provider_types().contains(provider))
+            .map(|p| p.as_ref())
+    }
+
+    /// Resolves a post with the first provider that detects it
+    pub fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        let posts = self
+            .detect(data)
+            .map(|provider| provider.resolve(data, parser, trace))
+            .unwrap_or_default();
+
+        if posts.is_empty() {
+            trace.push(String::from("No parser branch matched"));
+        }
+        posts
+    }
+}
@@ -0,0 +1,305 @@
+use super::{MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::api_types::reddit::submitted_response::{
+        MediaMetadataValue, RedditSubmittedChildData,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::{download_reddit_audio, download_reddit_video, RedditVideoDownload},
+};
+use async_trait::async_trait;
+
+/// Reads the image extension of a URL like `i.redd.it/abc.jpg`
+fn get_image_extension(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next()?;
+    let (_, extension) = path.rsplit('/').next()?.rsplit_once('.')?;
+
+    match extension.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => Some("jpg"),
+        "png" => Some("png"),
+        "webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Picks the extension and URL of a gallery item from its media metadata
+///
+/// Animated items only have gif and mp4 renditions, the mp4 is preferred. Images are fetched in
+/// their original format from i.redd.it, the source preview is converted to webp
+fn get_gallery_media(media_id: &str, media: &MediaMetadataValue) -> Option<(&'static str, String)> {
+    let s_media = media.s.as_ref()?;
+
+    if media.e.as_deref() == Some("AnimatedImage") {
+        return match (&s_media.mp4, &s_media.gif) {
+            (Some(mp4), _) => Some(("mp4", mp4.to_owned())),
+            (None, Some(gif)) => Some(("gif", gif.to_owned())),
+            (None, None) => None,
+        };
+    }
+
+    let extension = match media.m.as_deref() {
+        Some("image/jpg" | "image/jpeg") => Some("jpg"),
+        Some("image/png") => Some("png"),
+        Some("image/gif") => Some("gif"),
+        Some("image/webp") => Some("webp"),
+        _ => None,
+    };
+
+    match extension {
+        Some(extension) => Some((
+            extension,
+            format!("https://i.redd.it/{}.{}", media_id, extension),
+        )),
+        None => s_media.u.as_ref().map(|u| ("webp", u.to_owned())),
+    }
+}
+
+/// Media hosted by Reddit: images, gifs, videos and galleries
+pub struct RedditProvider;
+
+impl RedditProvider {
+    /// Single images and videos on Reddit's own media domains
+    fn resolve_media_domain(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        match data.is_video {
+            Some(true) => {
+                trace.push(String::from("is_video = true"));
+                if let Some(u) = data.media.as_ref().and_then(|m| m.reddit_video.as_ref()) {
+                    trace.push(String::from("Matched hosted Reddit video"));
+                    return vec![RedditCrawlerPost::new(
+                        data,
+                        RedditMediaProviderType::RedditVideo,
+                        "mp4",
+                        &u.fallback_url,
+                    )];
+                }
+            }
+            Some(false) => {
+                trace.push(String::from("is_video = false"));
+                let images = data
+                    .preview
+                    .as_ref()
+                    .map(|preview| preview.images.as_slice())
+                    .unwrap_or_default();
+
+                let videos = images
+                    .iter()
+                    .filter_map(|image| image.variants.mp4.as_ref())
+                    .map(|mp4_src| {
+                        RedditCrawlerPost::new(
+                            data,
+                            RedditMediaProviderType::RedditImage,
+                            "mp4",
+                            &mp4_src.source.url,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if !videos.is_empty() {
+                    trace.push(String::from("Matched mp4 variant of the preview"));
+                    return videos;
+                }
+
+                let gifs = images
+                    .iter()
+                    .filter_map(|image| image.variants.gif.as_ref())
+                    .map(|gif_src| {
+                        RedditCrawlerPost::new(
+                            data,
+                            RedditMediaProviderType::RedditGifVideo,
+                            "gif",
+                            &gif_src.source.url,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if !gifs.is_empty() {
+                    trace.push(String::from("Matched gif variant of the preview"));
+                    return gifs;
+                }
+
+                let extension: String = data.url.split('.').rev().take(1).collect();
+                if extension == "gif" {
+                    trace.push(String::from("Matched gif by URL extension"));
+                    return vec![RedditCrawlerPost::new(
+                        data,
+                        RedditMediaProviderType::RedditImage,
+                        "gif",
+                        &data.url,
+                    )];
+                }
+
+                trace.push(String::from("Matched single image"));
+                let preview_url = parser.preview_size.and_then(|preview_size| {
+                    let image = images.first()?;
+                    let resolutions = image
+                        .resolutions
+                        .iter()
+                        .map(|r| (r.width, r.url.as_str()))
+                        .collect::<Vec<_>>();
+                    preview_size.select(image.source.width, &resolutions)
+                });
+                if preview_url.is_some() {
+                    trace.push(String::from("Matched preview resolution of the image"));
+                }
+
+                return vec![RedditCrawlerPost::new(
+                    data,
+                    RedditMediaProviderType::RedditImage,
+                    get_image_extension(&data.url).unwrap_or("webp"),
+                    preview_url.unwrap_or(&data.url),
+                )];
+            }
+            None => {
+                // No-op, there may be more cases to handle
+                trace.push(String::from("is_video is missing"));
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Galleries and the animated media of posts linking elsewhere
+    fn resolve_media_metadata(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        let Some(media_metadata) = &data.media_metadata else {
+            return Vec::new();
+        };
+
+        if data.is_gallery == Some(true) {
+            trace.push(String::from("Matched gallery"));
+        }
+        if let (Some(true), Some(gallery_data)) = (data.is_gallery, &data.gallery_data) {
+            return gallery_data
+                .items
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| {
+                    let media = media_metadata.get(&item.media_id)?;
+                    let s_media = media.s.as_ref()?;
+                    let (extension, u) = get_gallery_media(&item.media_id, media)?;
+
+                    let resolutions = media
+                        .p
+                        .iter()
+                        .filter_map(|p| Some((p.x, p.u.as_deref()?)))
+                        .collect::<Vec<_>>();
+                    // Previews are still images, animated items keep their video
+                    let preview = parser
+                        .preview_size
+                        .filter(|_| extension != "mp4" && extension != "gif")
+                        .and_then(|preview_size| preview_size.select(s_media.x, &resolutions));
+                    let u = preview.map(str::to_owned).unwrap_or(u);
+
+                    Some(RedditCrawlerPost {
+                        index: Some(i),
+                        caption: item.caption.to_owned(),
+                        title: format!("{}-{}", data.title, i),
+                        ..RedditCrawlerPost::new(
+                            data,
+                            RedditMediaProviderType::RedditGalleryImage,
+                            extension,
+                            &u,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+        }
+
+        // Handle Reddit posts with mp4
+        trace.push(String::from("Matched media metadata"));
+        media_metadata
+            .values()
+            .enumerate()
+            .filter_map(|(i, media)| {
+                let mp4 = media.s.as_ref()?.mp4.as_ref()?;
+                Some(RedditCrawlerPost {
+                    index: Some(i),
+                    title: format!("{}-{}", data.title, i),
+                    ..RedditCrawlerPost::new(
+                        data,
+                        RedditMediaProviderType::RedditGifVideo,
+                        "mp4",
+                        mp4,
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[async_trait]
+impl MediaProvider for RedditProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[
+            RedditMediaProviderType::RedditImage,
+            RedditMediaProviderType::RedditGifVideo,
+            RedditMediaProviderType::RedditVideo,
+            RedditMediaProviderType::RedditGalleryImage,
+        ]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        // Galleries are not on the media domain, but always come with their media metadata
+        data.is_reddit_media_domain || data.media_metadata.is_some()
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        match data.is_reddit_media_domain {
+            true => self.resolve_media_domain(data, parser, trace),
+            false => self.resolve_media_metadata(data, parser, trace),
+        }
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        if media.provider != RedditMediaProviderType::RedditVideo {
+            return Ok(ProviderDownload::HttpResponse(
+                ctx.client.get(&media.url).send().await?,
+            ));
+        }
+
+        // Only the audio track is fetched, the video is never downloaded
+        if let Some(format) = ctx.transfer.audio_only {
+            download_reddit_audio(
+                ctx.client,
+                &media.url,
+                ctx.file_path,
+                format,
+                ctx.transfer.progress.as_ref(),
+                ctx.transfer.bandwidth.as_ref(),
+                ctx.transfer.timeouts,
+            )
+            .await?;
+            return Ok(ProviderDownload::File(ctx.file_path.to_owned()));
+        }
+
+        // Video and audio are separate DASH tracks, muxed into the file of the post
+        match download_reddit_video(
+            ctx.client,
+            &media.url,
+            ctx.file_path,
+            ctx.transfer.progress.as_ref(),
+            ctx.transfer.bandwidth.as_ref(),
+            ctx.transfer.timeouts,
+        )
+        .await?
+        {
+            RedditVideoDownload::NotFound => Ok(ProviderDownload::NotFound),
+            _ => Ok(ProviderDownload::File(ctx.file_path.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::{MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::{
+        api_types::reddit::submitted_response::RedditSubmittedChildData, download_redgifs_media,
+        get_redgifs_media, get_redgifs_media_url, RedgifsClientError,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::state::SharedState,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Extracts the name of a Gfycat link, e.g. `gfycat.com/ifr/Name` or `gfycat.com/Name-tags`
+fn get_gfycat_id(url: &str) -> Option<String> {
+    let name = url
+        .split("gfycat.com/")
+        .nth(1)?
+        .split(['?', '#'])
+        .next()?
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()?
+        .split(['-', '.'])
+        .next()?;
+
+    match !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        true => Some(name.to_lowercase()),
+        false => None,
+    }
+}
+
+fn is_redgifs_image(url: &str) -> bool {
+    url.contains("redgifs.com/i/")
+}
+
+fn is_redgifs_video(url: &str) -> bool {
+    url.contains("redgifs.com/watch/") || url.contains("redgifs.com/ifr/")
+}
+
+/// Redgifs images, videos and galleries, including the Gfycat videos mirrored there
+pub struct RedgifsProvider;
+
+#[async_trait]
+impl MediaProvider for RedgifsProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[
+            RedditMediaProviderType::RedgifsImage,
+            RedditMediaProviderType::RedgifsVideo,
+        ]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        is_redgifs_image(&data.url)
+            || is_redgifs_video(&data.url)
+            || get_gfycat_id(&data.url).is_some()
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        _parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        if is_redgifs_image(&data.url) {
+            trace.push(String::from("Matched Redgifs image"));
+            return vec![RedditCrawlerPost::new(
+                data,
+                RedditMediaProviderType::RedgifsImage,
+                "webp",
+                &data.url,
+            )];
+        }
+        if is_redgifs_video(&data.url) {
+            trace.push(String::from("Matched Redgifs video"));
+            return vec![RedditCrawlerPost::new(
+                data,
+                RedditMediaProviderType::RedgifsVideo,
+                "mp4",
+                &data.url,
+            )];
+        }
+        // Gfycat shut down, its videos are mirrored on Redgifs under the lowercase name
+        if let Some(gfycat_id) = get_gfycat_id(&data.url) {
+            trace.push(String::from("Matched Gfycat video mirrored on Redgifs"));
+            return vec![RedditCrawlerPost::new(
+                data,
+                RedditMediaProviderType::RedgifsVideo,
+                "mp4",
+                &format!("https://www.redgifs.com/watch/{}", gfycat_id),
+            )];
+        }
+
+        Vec::new()
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        let quality = ctx.shared_state.lock().await.redgifs_quality;
+
+        let gifs = match get_redgifs_media(ctx.client, ctx.shared_state, &media.url).await {
+            Ok(gifs) => gifs,
+            Err(RedgifsClientError::NotFound) => return Ok(ProviderDownload::NotFound),
+            Err(e) => return Err(e.into()),
+        };
+
+        if let [gif] = gifs.as_slice() {
+            return Ok(ProviderDownload::HttpResponse(
+                download_redgifs_media(ctx.client, ctx.shared_state, gif, quality).await?,
+            ));
+        }
+
+        // Every gif of the gallery is saved like an item of a Reddit gallery
+        let mut saved = Vec::new();
+        for (i, gif) in gifs.iter().enumerate() {
+            let item = RedditCrawlerPost {
+                extension: match gif.type_field {
+                    2 => String::from("jpg"),
+                    _ => String::from("mp4"),
+                },
+                index: Some(i),
+                ..media.clone()
+            };
+            let response =
+                download_redgifs_media(ctx.client, ctx.shared_state, gif, quality).await?;
+            saved.push(ctx.save_http_response(response, &item).await?);
+        }
+
+        Ok(ProviderDownload::Saved(saved))
+    }
+
+    async fn resolve_urls(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        shared_state: &Arc<Mutex<SharedState>>,
+        media: &RedditCrawlerPost,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let quality = shared_state.lock().await.redgifs_quality;
+
+        match get_redgifs_media(client, shared_state, &media.url).await {
+            Ok(gifs) => Ok(gifs
+                .iter()
+                .map(|gif| get_redgifs_media_url(gif, quality).to_owned())
+                .collect()),
+            Err(RedgifsClientError::NotFound) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
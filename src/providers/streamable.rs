@@ -0,0 +1,65 @@
+use super::{MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::{
+        api_types::reddit::submitted_response::RedditSubmittedChildData, download_streamable_media,
+        get_streamable_media_url, StreamableClientError,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::state::SharedState,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub struct StreamableProvider;
+
+#[async_trait]
+impl MediaProvider for StreamableProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[RedditMediaProviderType::StreamableVideo]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        data.url.contains("streamable.com/")
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        _parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        trace.push(String::from("Matched Streamable video"));
+        vec![RedditCrawlerPost::new(
+            data,
+            RedditMediaProviderType::StreamableVideo,
+            "mp4",
+            &data.url,
+        )]
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        match download_streamable_media(ctx.client, &media.url).await {
+            Ok(response) => Ok(ProviderDownload::HttpResponse(response)),
+            Err(StreamableClientError::NotFound) => Ok(ProviderDownload::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn resolve_urls(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        _shared_state: &Arc<Mutex<SharedState>>,
+        media: &RedditCrawlerPost,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        match get_streamable_media_url(client, &media.url).await {
+            Ok(url) => Ok(vec![url]),
+            Err(StreamableClientError::NotFound) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::{ytdlp::download_with_ytdlp, MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+};
+use async_trait::async_trait;
+
+/// YouTube embeds, downloaded with yt-dlp
+pub struct YoutubeProvider;
+
+#[async_trait]
+impl MediaProvider for YoutubeProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[RedditMediaProviderType::YoutubeVideo]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        data.media
+            .as_ref()
+            .is_some_and(|m| m.type_field.as_deref() == Some("youtube.com"))
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        _parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        trace.push(String::from("Matched YouTube embed"));
+        vec![RedditCrawlerPost::new(
+            data,
+            RedditMediaProviderType::YoutubeVideo,
+            "mp4",
+            &data.url,
+        )]
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        download_with_ytdlp(ctx, &media.url).await
+    }
+}
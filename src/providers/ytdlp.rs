@@ -0,0 +1,259 @@
+use super::{MediaProvider, ProviderContext, ProviderDownload};
+use crate::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::{file_too_large, select_proxy, YtdlpCookies},
+};
+use async_trait::async_trait;
+use std::{path::Path, process::Stdio, time::Duration};
+use tokio::{process::Command, time};
+use tracing::{trace, warn};
+
+const YTDLP_FORMAT: &str = "bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best";
+// Probing only fetches the page of the video, the size is treated as unknown after this
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+// Errors of videos YouTube only serves to signed in accounts, e.g. age-restricted ones
+const SIGN_IN_ERRORS: [&str; 3] = [
+    "Sign in to confirm",
+    "age-restricted",
+    "inappropriate for some users",
+];
+
+/// Asks yt-dlp for the size of the format it would download, `None` when it is unknown
+async fn probe_ytdlp_file_size(
+    url: &str,
+    format: &str,
+    proxy: Option<&reqwest::Url>,
+    cookies: &YtdlpCookies,
+) -> Option<u64> {
+    let mut command = Command::new("yt-dlp");
+    if let Some(proxy) = proxy {
+        command.arg("--proxy").arg(proxy.as_str());
+    }
+    command.args(cookies.get_args());
+
+    command
+        .arg(url)
+        .arg("-f")
+        .arg(format)
+        .arg("--skip-download")
+        .arg("--print")
+        .arg("%(filesize,filesize_approx)s")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    let output = time::timeout(PROBE_TIMEOUT, command.output())
+        .await
+        .ok()?
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|bytes| bytes as u64)
+}
+
+/// Message of a failed yt-dlp run, its `ERROR:` lines or else the last line it printed
+pub fn get_ytdlp_error(code: Option<i32>, stderr: &str) -> String {
+    let lines = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>();
+    let errors = lines
+        .iter()
+        .filter_map(|l| l.strip_prefix("ERROR:"))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    let message = match (errors.is_empty(), lines.last()) {
+        (false, _) => errors.join("; "),
+        (true, Some(line)) => line.to_string(),
+        (true, None) => String::from("no output"),
+    };
+
+    match code {
+        Some(code) => format!("yt-dlp exited with code {}: {}", code, message),
+        // Killed by a signal
+        None => format!("yt-dlp was terminated: {}", message),
+    }
+}
+
+/// Whether yt-dlp failed because the video needs a signed in account, which cookies provide
+pub fn requires_sign_in(stderr: &str) -> bool {
+    SIGN_IN_ERRORS.iter().any(|error| stderr.contains(error))
+}
+
+/// Downloads a URL with yt-dlp to the file path of the post
+pub(super) async fn download_with_ytdlp(
+    ctx: &ProviderContext<'_>,
+    url: &str,
+) -> Result<ProviderDownload, anyhow::Error> {
+    let transfer = ctx.transfer;
+    let (proxy, timeout, shutdown, cookies) = {
+        let ss = ctx.shared_state.lock().await;
+        let proxy = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| select_proxy(&url, ss.proxy.as_ref(), &ss.provider_proxies).cloned());
+        (
+            proxy,
+            ss.ytdlp_timeout,
+            ss.shutdown.clone(),
+            ss.ytdlp_cookies.clone(),
+        )
+    };
+    // Downloads waiting for a slot of the fallback are not started anymore
+    if shutdown.is_requested() {
+        return Ok(ProviderDownload::Failed(String::from(
+            "yt-dlp was stopped by Ctrl-C",
+        )));
+    }
+
+    let mut command = Command::new("yt-dlp");
+    // yt-dlp names the extracted audio after its format, the post already is
+    let (format_args, output) = match transfer.audio_only {
+        Some(format) => (
+            format.get_ytdlp_args().to_vec(),
+            Path::new(ctx.file_path)
+                .with_extension("%(ext)s")
+                .to_string_lossy()
+                .to_string(),
+        ),
+        None => (vec!["-f", YTDLP_FORMAT], ctx.file_path.to_owned()),
+    };
+
+    if let Some(max_file_size) = transfer.size_limits.max_file_size {
+        if let Some(bytes) = probe_ytdlp_file_size(url, format_args[1], proxy.as_ref(), &cookies)
+            .await
+            .filter(|bytes| transfer.size_limits.exceeds_file_size(*bytes))
+        {
+            return Err(file_too_large(
+                ctx.file_path.to_owned(),
+                bytes,
+                transfer.size_limits,
+            ));
+        }
+        // Probed sizes are missing for some formats, yt-dlp checks them while downloading
+        command.arg("--max-filesize").arg(max_file_size.to_string());
+    }
+
+    if let Some(proxy) = proxy {
+        command.arg("--proxy").arg(proxy.as_str());
+    }
+    // yt-dlp throttles on its own, so it only stays below the limit by itself
+    if let Some(bandwidth) = &transfer.bandwidth {
+        command
+            .arg("--limit-rate")
+            .arg(bandwidth.bytes_per_second().to_string());
+    }
+
+    command.args(cookies.get_args());
+
+    let child = command
+        .arg(url)
+        .args(&format_args)
+        .arg("-o")
+        .arg(&output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        // Stops yt-dlp when the download is abandoned below
+        .kill_on_drop(true)
+        .spawn();
+
+    let child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            return Ok(ProviderDownload::Failed(format!(
+                "Spawning yt-dlp failed: {}",
+                e
+            )))
+        }
+    };
+
+    // yt-dlp keeps its .part file, so a retry resumes where it stopped
+    let output = tokio::select! {
+        output = child.wait_with_output() => output?,
+        _ = time::sleep(timeout) => {
+            return Ok(ProviderDownload::Failed(format!(
+                "yt-dlp timed out after {}s",
+                timeout.as_secs()
+            )))
+        }
+        _ = shutdown.wait() => {
+            return Ok(ProviderDownload::Failed(String::from(
+                "yt-dlp was stopped by Ctrl-C",
+            )))
+        }
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+        trace!("yt-dlp: {}", line);
+    }
+
+    if output.status.success() {
+        return Ok(ProviderDownload::File(ctx.file_path.to_owned()));
+    }
+
+    let mut error = get_ytdlp_error(output.status.code(), &stderr);
+    if !cookies.is_set() && requires_sign_in(&stderr) {
+        warn!(
+            "[SIGN_IN_REQUIRED] {} - pass --cookies or --cookies-from-browser",
+            url
+        );
+        error.push_str(", pass --cookies or --cookies-from-browser");
+    }
+    Ok(ProviderDownload::Failed(error))
+}
+
+/// Links no other provider handles, handed to yt-dlp with `--fallback-ytdlp`
+///
+/// Registered last, so it only sees the posts that would otherwise be skipped.
+pub struct YtdlpProvider;
+
+#[async_trait]
+impl MediaProvider for YtdlpProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[RedditMediaProviderType::YtdlpVideo]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        // Text posts and polls link to their own comments
+        !data.is_self && data.poll_data.is_none() && data.url.starts_with("http")
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        if !parser.fallback_ytdlp {
+            trace.push(String::from("yt-dlp fallback is disabled"));
+            return Vec::new();
+        }
+
+        trace.push(String::from("Matched yt-dlp fallback"));
+        vec![RedditCrawlerPost::new(
+            data,
+            RedditMediaProviderType::YtdlpVideo,
+            "mp4",
+            &data.url,
+        )]
+    }
+
+    async fn download(
+        &self,
+        ctx: &ProviderContext<'_>,
+        media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        // Waits for a slot of `--fallback-ytdlp-tasks` instead of spawning a process per task
+        let slots = ctx.shared_state.lock().await.ytdlp_slots.clone();
+        let _permit = slots.acquire_owned().await?;
+
+        download_with_ytdlp(ctx, &media.url).await
+    }
+}
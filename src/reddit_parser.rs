@@ -1,9 +1,17 @@
-use crate::clients::api_types::reddit::submitted_response::{
-    RedditSubmittedChild, RedditSubmittedChildData, RedditSubmittedResponse,
+use crate::{
+    clients::{
+        api_types::reddit::submitted_response::{
+            RedditSubmittedChild, RedditSubmittedChildData, RedditSubmittedResponse,
+        },
+        RedgifsUserResponse,
+    },
+    providers::PROVIDERS,
+    utils::PostRemoval,
 };
 use chrono::{DateTime, Utc};
+use std::{collections::HashSet, fmt};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub enum RedditMediaProviderType {
     RedditImage,
     RedditGifVideo,
@@ -13,10 +21,32 @@ pub enum RedditMediaProviderType {
     YoutubeVideo,
     RedgifsImage,
     RedgifsVideo,
+    StreamableVideo,
+    YtdlpVideo,
+    #[default]
     None,
 }
 
-#[derive(Debug, Clone)]
+impl fmt::Display for RedditMediaProviderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let provider_str = match self {
+            RedditMediaProviderType::RedditImage => "reddit_image",
+            RedditMediaProviderType::RedditGifVideo => "reddit_gif_video",
+            RedditMediaProviderType::RedditVideo => "reddit_video",
+            RedditMediaProviderType::RedditGalleryImage => "reddit_gallery_image",
+            RedditMediaProviderType::ImgurImage => "imgur_image",
+            RedditMediaProviderType::YoutubeVideo => "youtube_video",
+            RedditMediaProviderType::RedgifsImage => "redgifs_image",
+            RedditMediaProviderType::RedgifsVideo => "redgifs_video",
+            RedditMediaProviderType::StreamableVideo => "streamable_video",
+            RedditMediaProviderType::YtdlpVideo => "ytdlp_video",
+            RedditMediaProviderType::None => "none",
+        };
+        write!(f, "{}", provider_str)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
 pub struct RedditCrawlerPost {
     pub author: String,
     pub created_utc: DateTime<Utc>,
@@ -27,12 +57,83 @@ pub struct RedditCrawlerPost {
     pub title: String,
     pub upvotes: i64,
     pub url: String,
+    // Pinned by the moderators e.g. daily threads
+    pub stickied: bool,
+    pub spoiler: bool,
+    // Marked as NSFW
+    pub over_18: bool,
+    // Comments are randomly sorted and scores hidden
+    pub contest_mode: bool,
     // This is the index of the image in the gallery
     pub index: Option<usize>,
+    // Caption of the gallery item
+    pub caption: Option<String>,
+    // Set for deleted and removed posts
+    pub removal: Option<PostRemoval>,
+}
+
+impl RedditCrawlerPost {
+    /// A file of a crawled post, gallery items set their index and caption on top
+    pub fn new(
+        data: &RedditSubmittedChildData,
+        provider: RedditMediaProviderType,
+        extension: &str,
+        url: &str,
+    ) -> Self {
+        Self {
+            author: data.author.to_owned(),
+            created_utc: data.created_utc,
+            extension: extension.to_owned(),
+            id: data.id.to_owned(),
+            index: None,
+            caption: None,
+            provider,
+            subreddit: data.subreddit.to_owned(),
+            title: data.title.to_owned(),
+            upvotes: data.ups,
+            stickied: data.stickied,
+            spoiler: data.spoiler,
+            over_18: data.over_18,
+            contest_mode: data.contest_mode,
+            url: url.to_owned(),
+            removal: PostRemoval::of(data),
+        }
+    }
+}
+
+/// Set by `--thumbnails-only` and `--max-resolution`, images are downloaded as a smaller preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewSize {
+    Thumbnail,
+    MaxResolution(u32),
+}
+
+impl PreviewSize {
+    /// Picks the preview among `(width, url)` resolutions, `None` keeps the source image
+    pub fn select<'a>(&self, source_width: i64, resolutions: &[(i64, &'a str)]) -> Option<&'a str> {
+        let smallest = resolutions.iter().min_by_key(|(width, _)| *width);
+
+        let resolution = match self {
+            PreviewSize::Thumbnail => smallest,
+            PreviewSize::MaxResolution(max) if source_width <= i64::from(*max) => None,
+            // Falls back to the smallest preview when all are wider than the limit
+            PreviewSize::MaxResolution(max) => resolutions
+                .iter()
+                .filter(|(width, _)| *width <= i64::from(*max))
+                .max_by_key(|(width, _)| *width)
+                .or(smallest),
+        };
+
+        resolution.map(|(_, url)| *url)
+    }
 }
 
 #[derive(Default, Debug, Clone)]
-pub struct RedditPostParser {}
+pub struct RedditPostParser {
+    pub preview_size: Option<PreviewSize>,
+    // Set by `--fallback-ytdlp`, links no provider handles are downloaded with yt-dlp
+    pub fallback_ytdlp: bool,
+}
 
 impl RedditPostParser {
     pub fn parse(&self, response: &RedditSubmittedResponse) -> Vec<RedditCrawlerPost> {
@@ -40,300 +141,68 @@ impl RedditPostParser {
             .data
             .children
             .iter()
-            .flat_map(|child| self.parse_user_submitted(child))
+            .flat_map(|child| self.parse_user_submitted(child, &mut Vec::new()))
             .collect::<Vec<_>>()
     }
 
-    fn parse_user_submitted(&self, child: &RedditSubmittedChild) -> Vec<RedditCrawlerPost> {
-        let data = &child.data;
-        let RedditSubmittedChildData {
-            author,
-            created_utc,
-            is_gallery,
-            is_reddit_media_domain,
-            media,
-            media_metadata,
-            subreddit,
-            title,
-            ups: upvotes,
-            is_video,
-            ..
-        } = data;
-
-        // Set to `true` if the post is hosted on Reddit's own media domai.
-        // This excludes gallery posts, which is also hosted there?
-        match is_reddit_media_domain {
-            // Handle Reddit posts with single images or videos
-            true => {
-                match is_video {
-                    Some(true) => {
-                        if let Some(m) = media {
-                            if let Some(u) = &m.reddit_video {
-                                return vec![
-                                    (RedditCrawlerPost {
-                                        author: author.to_owned(),
-                                        created_utc: created_utc.to_owned(),
-                                        extension: "mp4".to_owned(),
-                                        id: data.id.to_owned(),
-                                        index: None,
-                                        provider: RedditMediaProviderType::RedditVideo,
-                                        subreddit: subreddit.to_owned(),
-                                        title: title.to_owned(),
-                                        upvotes: upvotes.to_owned(),
-                                        url: u.hls_url.to_owned(),
-                                    }),
-                                ];
-                            }
-                        }
-                    }
-                    Some(false) => {
-                        let videos =
-                            data.preview.as_ref().map(|preview| {
-                                preview
-                                    .images
-                                    .iter()
-                                    .filter_map(|image| {
-                                        image.variants.mp4.as_ref().map(|mp4_src| {
-                                            RedditCrawlerPost {
-                                                author: author.to_owned(),
-                                                created_utc: created_utc.to_owned(),
-                                                extension: "mp4".to_owned(),
-                                                id: data.id.to_owned(),
-                                                index: None,
-                                                provider: RedditMediaProviderType::RedditImage,
-                                                subreddit: subreddit.to_owned(),
-                                                title: title.to_owned(),
-                                                upvotes: upvotes.to_owned(),
-                                                url: mp4_src.source.url.to_owned(),
-                                            }
-                                        })
-                                    })
-                                    .collect::<Vec<_>>()
-                            });
+    /// Parses the gifs of a Redgifs user, a gallery is kept once and expanded by the downloader
+    pub fn parse_redgifs_user(&self, responses: &[RedgifsUserResponse]) -> Vec<RedditCrawlerPost> {
+        let mut galleries = HashSet::new();
 
-                        if let Some(videos) = videos {
-                            if !videos.is_empty() {
-                                return videos;
-                            }
-                        }
-
-                        let gifs =
-                            data.preview.as_ref().map(|preview| {
-                                preview
-                                    .images
-                                    .iter()
-                                    .filter_map(|image| {
-                                        image.variants.gif.as_ref().map(|gif_src| {
-                                            RedditCrawlerPost {
-                                                author: author.to_owned(),
-                                                created_utc: created_utc.to_owned(),
-                                                extension: "gif".to_owned(),
-                                                id: data.id.to_owned(),
-                                                index: None,
-                                                provider: RedditMediaProviderType::RedditGifVideo,
-                                                subreddit: subreddit.to_owned(),
-                                                title: title.to_owned(),
-                                                upvotes: upvotes.to_owned(),
-                                                url: gif_src.source.url.to_owned(),
-                                            }
-                                        })
-                                    })
-                                    .collect::<Vec<_>>()
-                            });
-
-                        if let Some(gifs) = gifs {
-                            if !gifs.is_empty() {
-                                return gifs;
-                            }
-                        }
-
-                        let extension: String = data.url.split('.').rev().take(1).collect();
-
-                        if extension == "gif" {
-                            return vec![
-                                (RedditCrawlerPost {
-                                    author: author.to_owned(),
-                                    created_utc: created_utc.to_owned(),
-                                    extension: "gif".to_owned(),
-                                    id: data.id.to_owned(),
-                                    index: None,
-                                    provider: RedditMediaProviderType::RedditImage,
-                                    subreddit: subreddit.to_owned(),
-                                    title: title.to_owned(),
-                                    upvotes: upvotes.to_owned(),
-                                    url: data.url.to_owned(),
-                                }),
-                            ];
-                        }
+        responses
+            .iter()
+            .flat_map(|r| r.gifs.iter())
+            .filter(|gif| match &gif.gallery {
+                Some(gallery) => galleries.insert(gallery.to_owned()),
+                None => true,
+            })
+            .map(|gif| {
+                let (provider, extension) = match gif.type_field {
+                    2 => (RedditMediaProviderType::RedgifsImage, "jpg"),
+                    _ => (RedditMediaProviderType::RedgifsVideo, "mp4"),
+                };
 
-                        return vec![
-                            (RedditCrawlerPost {
-                                author: author.to_owned(),
-                                created_utc: created_utc.to_owned(),
-                                extension: "webp".to_owned(),
-                                id: data.id.to_owned(),
-                                index: None,
-                                provider: RedditMediaProviderType::RedditImage,
-                                subreddit: subreddit.to_owned(),
-                                title: title.to_owned(),
-                                upvotes: upvotes.to_owned(),
-                                url: data.url.to_owned(),
-                            }),
-                        ];
-                    }
-                    _ => {
-                        // No-op, there may be more cases to handle
-                    }
+                RedditCrawlerPost {
+                    author: gif.user_name.to_owned(),
+                    created_utc: DateTime::from_timestamp(gif.create_date, 0).unwrap_or_default(),
+                    extension: extension.to_owned(),
+                    id: gif.id.to_owned(),
+                    index: None,
+                    caption: None,
+                    provider,
+                    subreddit: String::new(),
+                    title: gif.description.to_owned().unwrap_or_default(),
+                    upvotes: gif.likes,
+                    stickied: false,
+                    spoiler: false,
+                    // Redgifs only hosts adult content
+                    over_18: true,
+                    contest_mode: false,
+                    url: format!("https://www.redgifs.com/watch/{}", gif.id),
+                    removal: None,
                 }
-            }
-            // Handle all other media
-            false => {
-                // Handle Reddit posts with galleries
-                if let (Some(media_metadata), Some(true)) = (media_metadata, is_gallery) {
-                    if let Some(gallery_data) = &data.gallery_data {
-                        let media_ids = gallery_data
-                            .items
-                            .iter()
-                            .map(|item| item.media_id.to_owned())
-                            .collect::<Vec<String>>();
-
-                        return media_ids
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(i, media_id)| {
-                                media_metadata.get(media_id).and_then(|media| {
-                                    media.s.as_ref().and_then(|s_media| {
-                                        if let Some(u) = &s_media.u {
-                                            return Some(RedditCrawlerPost {
-                                                author: author.to_owned(),
-                                                created_utc: created_utc.to_owned(),
-                                                extension: "webp".to_owned(),
-                                                id: data.id.to_owned(),
-                                                index: Some(i),
-                                                provider:
-                                                    RedditMediaProviderType::RedditGalleryImage,
-                                                subreddit: subreddit.to_owned(),
-                                                title: format!("{}-{}", title, i),
-                                                upvotes: upvotes.to_owned(),
-                                                url: u.to_owned(),
-                                            });
-                                        }
+            })
+            .collect::<Vec<_>>()
+    }
 
-                                        None
-                                    })
-                                })
-                            })
-                            .collect::<Vec<_>>();
-                    }
-                }
-                // Handle Reddit posts with mp4
-                if let Some(media_metadata) = media_metadata {
-                    let media_ids = media_metadata.keys().collect::<Vec<&String>>();
+    /// Parses a single post and records which branches were taken along the way
+    pub fn explain(&self, child: &RedditSubmittedChild) -> (Vec<RedditCrawlerPost>, Vec<String>) {
+        let mut trace = Vec::new();
+        let posts = self.parse_user_submitted(child, &mut trace);
+        (posts, trace)
+    }
 
-                    return media_ids
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, media_id)| {
-                            media_metadata.get(*media_id).and_then(|media| {
-                                media.s.as_ref().and_then(|s_media| {
-                                    if let Some(mp4) = &s_media.mp4 {
-                                        return Some(RedditCrawlerPost {
-                                            author: author.to_owned(),
-                                            created_utc: created_utc.to_owned(),
-                                            extension: "mp4".to_owned(),
-                                            id: data.id.to_owned(),
-                                            index: Some(i),
-                                            provider: RedditMediaProviderType::RedditGifVideo,
-                                            subreddit: subreddit.to_owned(),
-                                            title: format!("{}-{}", title, i),
-                                            upvotes: upvotes.to_owned(),
-                                            url: mp4.to_owned(),
-                                        });
-                                    }
-                                    None
-                                })
-                            })
-                        })
-                        .collect::<Vec<_>>();
-                }
-                // Handle YouTube embeds
-                if let Some(m) = media {
-                    match &m.type_field {
-                        Some(tf) if tf.eq("youtube.com") => {
-                            return vec![
-                                (RedditCrawlerPost {
-                                    author: author.to_owned(),
-                                    created_utc: created_utc.to_owned(),
-                                    extension: "mp4".to_owned(),
-                                    id: data.id.to_owned(),
-                                    index: None,
-                                    provider: RedditMediaProviderType::YoutubeVideo,
-                                    subreddit: subreddit.to_owned(),
-                                    title: title.to_owned(),
-                                    upvotes: upvotes.to_owned(),
-                                    url: data.url.to_owned(),
-                                }),
-                            ];
-                        }
-                        _ => {}
-                    }
-                }
-                // Handle Redgifs images
-                if data.url.contains("redgifs.com/i/") {
-                    return vec![
-                        (RedditCrawlerPost {
-                            author: author.to_owned(),
-                            created_utc: created_utc.to_owned(),
-                            extension: "webp".to_owned(),
-                            id: data.id.to_owned(),
-                            index: None,
-                            provider: RedditMediaProviderType::RedgifsImage,
-                            subreddit: subreddit.to_owned(),
-                            title: title.to_owned(),
-                            upvotes: upvotes.to_owned(),
-                            url: data.url.to_owned(),
-                        }),
-                    ];
-                }
-                // Handle Redgifs video embeds
-                if data.url.contains("redgifs.com/watch/") || data.url.contains("redgifs.com/ifr/")
-                {
-                    return vec![
-                        (RedditCrawlerPost {
-                            author: author.to_owned(),
-                            created_utc: created_utc.to_owned(),
-                            extension: "mp4".to_owned(),
-                            id: data.id.to_owned(),
-                            index: None,
-                            provider: RedditMediaProviderType::RedgifsVideo,
-                            subreddit: subreddit.to_owned(),
-                            title: title.to_owned(),
-                            upvotes: upvotes.to_owned(),
-                            url: data.url.to_owned(),
-                        }),
-                    ];
-                }
-                // Handle Imgur embeds
-                if data.url.contains("imgur") {
-                    let extension: String = data.url.split('.').rev().take(1).collect();
-                    return vec![
-                        (RedditCrawlerPost {
-                            author: author.to_owned(),
-                            created_utc: created_utc.to_owned(),
-                            extension,
-                            id: data.id.to_owned(),
-                            index: None,
-                            provider: RedditMediaProviderType::ImgurImage,
-                            subreddit: subreddit.to_owned(),
-                            title: title.to_owned(),
-                            upvotes: upvotes.to_owned(),
-                            url: data.url.to_owned(),
-                        }),
-                    ];
-                }
-            }
-        }
-        // All cases fell through, return empty vector
-        Vec::with_capacity(0)
+    fn parse_user_submitted(
+        &self,
+        child: &RedditSubmittedChild,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        // Set to `true` if the post is hosted on Reddit's own media domai.
+        // This excludes gallery posts, which is also hosted there?
+        trace.push(format!(
+            "is_reddit_media_domain = {}",
+            child.data.is_reddit_media_domain
+        ));
+        PROVIDERS.resolve(&child.data, self, trace)
     }
 }
@@ -0,0 +1,71 @@
+use super::get_config_dir;
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::PathBuf};
+
+// Minimum time between two about.json requests that miss the cache
+pub const ABOUT_REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AboutCacheEntry {
+    pub fetched_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+/// Persistent lookup cache for user and subreddit about.json data, shared across runs
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AboutCache {
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    pub entries: HashMap<String, AboutCacheEntry>,
+}
+
+impl AboutCache {
+    pub fn default_path() -> Option<PathBuf> {
+        get_config_dir().map(|dir| dir.join("about_cache.json"))
+    }
+
+    /// Loads the cache from disk, starting over if it is missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let cache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<AboutCache>(&c).ok())
+            .unwrap_or_default();
+
+        AboutCache {
+            path: Some(path),
+            ..cache
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str, ttl: Duration) -> Option<T> {
+        self.entries
+            .get(key)
+            .filter(|e| Utc::now() - e.fetched_at < ttl)
+            .and_then(|e| serde_json::from_value(e.data.clone()).ok())
+    }
+
+    pub fn insert<T: Serialize>(&mut self, key: &str, data: &T) -> Result<(), anyhow::Error> {
+        self.entries.insert(
+            key.to_owned(),
+            AboutCacheEntry {
+                fetched_at: Utc::now(),
+                data: serde_json::to_value(data)?,
+            },
+        );
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::{info, warn};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+// Stays next to the archived files, later crawls still need it to skip downloaded posts
+const CACHE_FILE_NAME: &str = "cache.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArchiveFormat {
+    Zip,
+    #[value(name = "tar.zst")]
+    TarZst,
+}
+
+impl ArchiveFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+/// Archive written by `write_archive`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveSummary {
+    pub path: String,
+    pub files: usize,
+}
+
+/// Bundles the files of a resource folder into `{folder}-{timestamp}.{ext}` next to it
+///
+/// Files are streamed into the archive one by one. With `delete_files` the archived files are
+/// removed afterwards, except for the cache. Returns `None` when there is nothing to archive.
+pub fn write_archive(
+    folder: &str,
+    format: ArchiveFormat,
+    delete_files: bool,
+) -> Result<Option<ArchiveSummary>, ArchiveError> {
+    let root = Path::new(folder);
+    let mut files = Vec::new();
+    list_files(root, &mut files)?;
+    files.sort();
+
+    if files.iter().all(|f| f == Path::new(CACHE_FILE_NAME)) {
+        return Ok(None);
+    }
+
+    let path = format!(
+        "{}-{}.{}",
+        folder.trim_end_matches('/'),
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    // Written under a temporary name, so an interrupted crawl does not leave a broken archive
+    let partial_path = format!("{}.part", path);
+    let writer = BufWriter::new(File::create(&partial_path)?);
+
+    match format {
+        ArchiveFormat::Zip => write_zip(writer, root, &files)?,
+        ArchiveFormat::TarZst => write_tar_zst(writer, root, &files)?,
+    }
+    fs::rename(&partial_path, &path)?;
+
+    if delete_files {
+        for file in files.iter().filter(|f| *f != Path::new(CACHE_FILE_NAME)) {
+            fs::remove_file(root.join(file))?;
+        }
+        remove_empty_folders(root)?;
+    }
+
+    Ok(Some(ArchiveSummary {
+        path,
+        files: files.len(),
+    }))
+}
+
+/// Archives a crawled folder, a failed archive is only logged since the files are still there
+pub fn archive_output_folder(folder: &str, format: ArchiveFormat, delete_files: bool) {
+    match write_archive(folder, format, delete_files) {
+        Ok(Some(summary)) => info!(
+            "[ARCHIVE] Bundled {} files into {}",
+            summary.files, summary.path
+        ),
+        Ok(None) => info!("[ARCHIVE] No files to archive in {}", folder),
+        Err(e) => warn!("[ARCHIVE_FAILED] {}", e),
+    }
+}
+
+/// Collects the regular files below `root` as relative paths, symlinks are skipped
+fn list_files(root: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut folders = vec![root.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                folders.push(entry.path());
+            } else if file_type.is_file() {
+                if let Ok(relative) = entry.path().strip_prefix(root) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn remove_empty_folders(folder: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            remove_empty_folders(&entry.path())?;
+            // Fails for folders that still contain files, which are kept
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
+// Archive entries always use `/`, regardless of the platform
+fn entry_name(file: &Path) -> String {
+    file.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn write_zip<W: Write + io::Seek>(
+    writer: W,
+    root: &Path,
+    files: &[PathBuf],
+) -> Result<(), ArchiveError> {
+    let mut zip = ZipWriter::new(writer);
+
+    for file in files {
+        let mut source = File::open(root.join(file))?;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(source.metadata()?.len() >= u32::MAX as u64);
+
+        zip.start_file(entry_name(file), options)?;
+        io::copy(&mut source, &mut zip)?;
+    }
+
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+fn write_tar_zst<W: Write>(writer: W, root: &Path, files: &[PathBuf]) -> Result<(), ArchiveError> {
+    let mut tar = tar::Builder::new(zstd::Encoder::new(writer, 0)?);
+
+    for file in files {
+        // Keeps the modification time, which downloads set to the post date
+        tar.append_path_with_name(root.join(file), entry_name(file))?;
+    }
+
+    tar.into_inner()?.finish()?.flush()?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::Read,
+    path::Path,
+    sync::Arc,
+};
+
+lazy_static! {
+    static ref POST_ID_RE: Regex = Regex::new(r"^[0-9a-z]{5,10}$").unwrap();
+    // Entries of the reddit extractor of gallery-dl start with the post ID
+    static ref GALLERY_DL_ENTRY_RE: Regex = Regex::new(r"^reddit([0-9a-z]{5,10})").unwrap();
+}
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Post IDs downloaded by other tools, skipped like cached posts with `--import-archive`
+#[derive(Default, Debug, Clone)]
+pub struct ImportedArchive {
+    ids: Arc<HashSet<String>>,
+}
+
+impl ImportedArchive {
+    /// Reads a gallery-dl SQLite archive, a BDFR archiver folder or a BDFR ID file
+    pub fn load(path: &str) -> Result<Self, anyhow::Error> {
+        let path = Path::new(path);
+
+        let ids = match path.is_dir() {
+            true => read_bdfr_folder(path)?,
+            false if is_sqlite_file(path)? => read_gallery_dl_archive(path)?,
+            false => read_id_file(&fs::read_to_string(path)?),
+        };
+
+        Ok(Self { ids: Arc::new(ids) })
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+fn is_sqlite_file(path: &Path) -> Result<bool, std::io::Error> {
+    let mut header = [0; SQLITE_HEADER.len()];
+    match File::open(path)?.read_exact(&mut header) {
+        Ok(()) => Ok(header == SQLITE_HEADER),
+        // Shorter than the header, e.g. an ID file with a single post
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Post ID of an entry in the archive table of gallery-dl, `None` for other extractors
+pub fn get_gallery_dl_post_id(entry: &str) -> Option<String> {
+    GALLERY_DL_ENTRY_RE
+        .captures(entry)
+        .map(|captures| captures[1].to_string())
+}
+
+fn read_gallery_dl_archive(path: &Path) -> Result<HashSet<String>, anyhow::Error> {
+    let connection =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement = connection.prepare("SELECT entry FROM archive")?;
+    let entries = statement.query_map([], |row| row.get::<_, String>(0))?;
+
+    let mut ids = HashSet::new();
+    for entry in entries {
+        if let Some(id) = get_gallery_dl_post_id(&entry?) {
+            ids.insert(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// One post ID per line, as passed to `--exclude-id-file` of BDFR
+pub fn read_id_file(data: &str) -> HashSet<String> {
+    data.lines()
+        .map(|l| l.trim().trim_start_matches("t3_"))
+        .filter(|l| POST_ID_RE.is_match(l))
+        .map(String::from)
+        .collect()
+}
+
+/// JSON files written by the archiver of BDFR, one per post
+fn read_bdfr_folder(folder: &Path) -> Result<HashSet<String>, anyhow::Error> {
+    let mut ids = HashSet::new();
+    let mut folders = vec![folder.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                folders.push(path);
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let id = serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&path)?)
+                .ok()
+                .and_then(|post| post.get("id")?.as_str().map(String::from));
+            if let Some(id) = id.filter(|id| POST_ID_RE.is_match(id)) {
+                ids.insert(id);
+            }
+        }
+    }
+    Ok(ids)
+}
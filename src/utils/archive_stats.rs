@@ -0,0 +1,157 @@
+use super::ReportRow;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+};
+
+/// Downloaded files and their size grouped by an author, subreddit or month
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsGroup {
+    pub name: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Totals of every cache below an output directory, printed by `stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStats {
+    pub resources: usize,
+    // Posts are counted once, even when several resources downloaded them
+    pub posts: usize,
+    pub files: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub bytes: u64,
+    pub top_authors: Vec<StatsGroup>,
+    pub top_subreddits: Vec<StatsGroup>,
+    // Files per month the posts were created in, oldest first
+    pub growth: Vec<StatsGroup>,
+}
+
+fn add_to_group(groups: &mut HashMap<String, (usize, u64)>, name: &str, bytes: u64) {
+    let group = groups.entry(name.to_owned()).or_default();
+    group.0 += 1;
+    group.1 += bytes;
+}
+
+/// Largest groups by `key` first, `top` limits how many are kept
+fn get_top_groups<K: Ord>(
+    groups: HashMap<String, (usize, u64)>,
+    top: usize,
+    key: impl Fn(&StatsGroup) -> K,
+) -> Vec<StatsGroup> {
+    let mut groups = groups
+        .into_iter()
+        .map(|(name, (files, bytes))| StatsGroup { name, files, bytes })
+        .collect::<Vec<_>>();
+    // Sorted by name first, so groups of the same size keep a stable order
+    groups.sort_by(|a, b| a.name.cmp(&b.name));
+    groups.sort_by_key(|g| Reverse(key(g)));
+    groups.truncate(top);
+    groups
+}
+
+/// Aggregates the report rows of every cached file, only downloaded files count towards groups
+pub fn get_archive_stats(rows: &[ReportRow], top: usize) -> ArchiveStats {
+    let mut stats = ArchiveStats {
+        resources: rows
+            .iter()
+            .map(|r| r.resource.as_str())
+            .collect::<HashSet<_>>()
+            .len(),
+        posts: rows
+            .iter()
+            .map(|r| r.id.as_str())
+            .collect::<HashSet<_>>()
+            .len(),
+        ..ArchiveStats::default()
+    };
+    let mut authors = HashMap::new();
+    let mut subreddits = HashMap::new();
+    let mut months = BTreeMap::<String, (usize, u64)>::new();
+
+    for row in rows {
+        match row.status {
+            "downloaded" => {}
+            "skipped" => {
+                stats.skipped += 1;
+                continue;
+            }
+            _ => {
+                stats.failed += 1;
+                continue;
+            }
+        }
+
+        let bytes = row.bytes.unwrap_or_default();
+        stats.files += 1;
+        stats.bytes += bytes;
+
+        if let Some(author) = &row.author {
+            add_to_group(&mut authors, author, bytes);
+        }
+        add_to_group(&mut subreddits, &row.subreddit, bytes);
+        // RFC 3339 dates start with the month e.g. `2023-11`
+        let month = months
+            .entry(row.created_utc.chars().take(7).collect())
+            .or_default();
+        month.0 += 1;
+        month.1 += bytes;
+    }
+
+    // Posters by the files they posted, subreddits by the space they take up
+    stats.top_authors = get_top_groups(authors, top, |g| g.files);
+    stats.top_subreddits = get_top_groups(subreddits, top, |g| g.bytes);
+    stats.growth = months
+        .into_iter()
+        .map(|(name, (files, bytes))| StatsGroup { name, files, bytes })
+        .collect();
+
+    stats
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.2} MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+fn render_groups(out: &mut String, title: &str, groups: &[StatsGroup]) {
+    let width = groups
+        .iter()
+        .map(|g| g.name.len())
+        .chain([title.len()])
+        .max()
+        .unwrap_or_default();
+
+    let _ = writeln!(out, "\n{:<width$}  {:>6}  {:>12}", title, "FILES", "SIZE");
+    for group in groups {
+        let _ = writeln!(
+            out,
+            "{:<width$}  {:>6}  {:>12}",
+            group.name,
+            group.files,
+            format_size(group.bytes)
+        );
+    }
+}
+
+pub fn render_archive_stats(stats: &ArchiveStats) -> String {
+    let mut out = format!(
+        "{} resources, {} posts - {} files downloaded ({}), {} failed, {} skipped\n",
+        stats.resources,
+        stats.posts,
+        stats.files,
+        format_size(stats.bytes),
+        stats.failed,
+        stats.skipped
+    );
+
+    render_groups(&mut out, "AUTHOR", &stats.top_authors);
+    render_groups(&mut out, "SUBREDDIT", &stats.top_subreddits);
+    render_groups(&mut out, "MONTH", &stats.growth);
+
+    out
+}
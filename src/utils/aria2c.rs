@@ -0,0 +1,159 @@
+use super::{
+    get_long_path, get_part_file_path, select_proxy, BandwidthLimiter, ProviderProxy,
+    TransferTimeouts,
+};
+use std::{fs, path::Path, process::Stdio};
+use thiserror::Error;
+use tokio::{process::Command, time};
+use tracing::trace;
+
+pub const DEFAULT_ARIA2C_CONNECTIONS: u16 = 8;
+
+/// What writes the files served over HTTP, set by `--downloader`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Downloader {
+    /// Streams every file over a single connection
+    #[default]
+    Builtin,
+    /// Hands files to aria2c, which splits them into segments downloaded in parallel
+    Aria2c,
+}
+
+/// Settings of aria2c processes started with `--downloader aria2c`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aria2cSettings {
+    // Connections per file, `--aria2c-connections`
+    pub connections: u16,
+    pub proxy: Option<reqwest::Url>,
+    pub provider_proxies: Vec<ProviderProxy>,
+}
+
+#[derive(Error, Debug)]
+pub enum Aria2cError {
+    #[error("Spawning aria2c failed: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("aria2c exited with code {code:?}: {message}")]
+    Failed { code: Option<i32>, message: String },
+    #[error("aria2c did not finish {file_path} within {}s", .timeout.as_secs())]
+    TimedOut {
+        file_path: String,
+        timeout: std::time::Duration,
+    },
+}
+
+/// Whether aria2c can be started, checked once before downloading with it
+pub fn check_aria2c() -> bool {
+    std::process::Command::new("aria2c")
+        .arg("--version")
+        .output()
+        .is_ok()
+}
+
+/// Arguments downloading `url` to `file_path` through its .part file
+pub fn get_aria2c_args(
+    url: &str,
+    file_path: &str,
+    settings: &Aria2cSettings,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Vec<String> {
+    let part_path = get_part_file_path(file_path);
+    let part_path = Path::new(&part_path);
+    let folder = part_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = part_path.file_name().unwrap_or_default();
+
+    let mut args = vec![
+        format!("--dir={}", folder.display()),
+        format!("--out={}", name.to_string_lossy()),
+        format!("--max-connection-per-server={}", settings.connections),
+        format!("--split={}", settings.connections),
+        // Resumes the .part file of an earlier run instead of renaming the new one
+        String::from("--continue=true"),
+        String::from("--auto-file-renaming=false"),
+        String::from("--allow-overwrite=true"),
+        format!("--timeout={}", timeouts.stall.as_secs().max(1)),
+        String::from("--max-tries=3"),
+        String::from("--summary-interval=0"),
+        String::from("--console-log-level=error"),
+        String::from("--download-result=hide"),
+    ];
+
+    let proxy = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| select_proxy(&url, settings.proxy.as_ref(), &settings.provider_proxies));
+    if let Some(proxy) = proxy {
+        args.push(format!("--all-proxy={}", proxy));
+    }
+    // Every process gets the whole limit, so it is only kept while one runs at a time
+    if let Some(bandwidth) = bandwidth {
+        args.push(format!(
+            "--max-download-limit={}",
+            bandwidth.bytes_per_second()
+        ));
+    }
+
+    args.push(url.to_owned());
+    args
+}
+
+/// Downloads `url` to `file_path` with aria2c, returns the bytes written
+pub async fn download_with_aria2c(
+    url: &str,
+    file_path: &str,
+    settings: &Aria2cSettings,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Result<u64, anyhow::Error> {
+    let child = Command::new("aria2c")
+        .args(get_aria2c_args(
+            url, file_path, settings, bandwidth, timeouts,
+        ))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(Aria2cError::Spawn)?;
+
+    let output = match timeouts.download {
+        Some(timeout) => time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| Aria2cError::TimedOut {
+                file_path: file_path.to_owned(),
+                timeout,
+            })??,
+        None => child.wait_with_output().await?,
+    };
+
+    // aria2c prints its errors to stdout
+    let printed = [output.stdout, output.stderr]
+        .iter()
+        .map(|o| String::from_utf8_lossy(o).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    for line in printed.lines().filter(|l| !l.trim().is_empty()) {
+        trace!("aria2c: {}", line);
+    }
+
+    if !output.status.success() {
+        return Err(Aria2cError::Failed {
+            code: output.status.code(),
+            message: printed
+                .lines()
+                .map(str::trim)
+                .rfind(|l| !l.is_empty())
+                .unwrap_or("no output")
+                .to_owned(),
+        }
+        .into());
+    }
+
+    let part_path = get_long_path(&get_part_file_path(file_path));
+    let bytes = fs::metadata(&part_path)?.len();
+    fs::rename(&part_path, get_long_path(file_path))?;
+
+    Ok(bytes)
+}
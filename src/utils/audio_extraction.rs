@@ -0,0 +1,68 @@
+use crate::reddit_parser::RedditMediaProviderType;
+use thiserror::Error;
+
+/// Format the audio of video posts is stored as with `--audio-only`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum AudioFormat {
+    #[default]
+    M4a,
+    Mp3,
+}
+
+/// Skips a video with `--audio-only` that has no audio track to extract
+#[derive(Error, Debug)]
+#[error("{url} has no audio track")]
+pub struct NoAudioTrackError {
+    pub url: String,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Mp3 => "mp3",
+        }
+    }
+
+    /// Arguments of yt-dlp downloading only the audio of a video, converted with ffmpeg
+    pub fn get_ytdlp_args(&self) -> [&'static str; 5] {
+        // m4a sources are kept as they are, anything else is converted
+        let format = match self {
+            AudioFormat::M4a => "bestaudio[ext=m4a]/bestaudio",
+            AudioFormat::Mp3 => "bestaudio",
+        };
+        ["-f", format, "-x", "--audio-format", self.extension()]
+    }
+
+    /// Arguments of ffmpeg extracting the first audio track of `input` to `output`
+    pub fn get_ffmpeg_args(&self, input: &str, output: &str) -> Vec<String> {
+        let mut args = vec![
+            "-y",
+            "-loglevel",
+            "error",
+            "-i",
+            input,
+            "-map",
+            "0:a:0",
+            "-vn",
+        ];
+        match self {
+            // Reddit and YouTube serve AAC, which fits into m4a as it is
+            AudioFormat::M4a => args.extend(["-c:a", "copy"]),
+            AudioFormat::Mp3 => args.extend(["-c:a", "libmp3lame", "-q:a", "2"]),
+        }
+        args.push(output);
+
+        args.into_iter().map(String::from).collect()
+    }
+}
+
+/// Posts whose audio is extracted with `--audio-only`, images and gifs are downloaded as usual
+pub fn extracts_audio(provider: &RedditMediaProviderType) -> bool {
+    matches!(
+        provider,
+        RedditMediaProviderType::RedditVideo
+            | RedditMediaProviderType::YoutubeVideo
+            | RedditMediaProviderType::YtdlpVideo
+    )
+}
@@ -0,0 +1,26 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+// Author of posts whose account was deleted
+pub(super) const DELETED_AUTHOR: &str = "[deleted]";
+
+/// Unique authors of the posts matched by a subreddit crawl, the most active ones first
+///
+/// `max_authors` keeps only that many of them, like `--max-authors`.
+pub fn get_expanded_authors(authors: &[String], max_authors: Option<usize>) -> Vec<String> {
+    let mut counts = HashMap::<&str, usize>::new();
+    for author in authors
+        .iter()
+        .filter(|a| !a.is_empty() && a.as_str() != DELETED_AUTHOR)
+    {
+        *counts.entry(author).or_default() += 1;
+    }
+
+    let mut authors = counts.into_iter().collect::<Vec<_>>();
+    authors.sort_by_key(|(author, count)| (Reverse(*count), *author));
+
+    authors
+        .into_iter()
+        .take(max_authors.unwrap_or(usize::MAX))
+        .map(|(author, _)| author.to_owned())
+        .collect()
+}
@@ -0,0 +1,96 @@
+use super::{author_expansion::DELETED_AUTHOR, escape_csv_field};
+use crate::reddit_parser::RedditCrawlerPost;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AuthorListFormat {
+    #[default]
+    Csv,
+    Json,
+    // `user:NAME` lines, read by `batch`
+    Batch,
+}
+
+/// An author of the posts listed by `authors`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorRank {
+    pub author: String,
+    pub posts: usize,
+    pub upvotes: i64,
+}
+
+/// Authors of the crawled posts ranked by their post count, then by their upvotes
+///
+/// Gallery items share the id of their post, so a gallery counts as one post.
+pub fn get_author_ranks(posts: &[RedditCrawlerPost]) -> Vec<AuthorRank> {
+    let mut seen = HashSet::new();
+    let mut ranks = HashMap::<&str, (usize, i64)>::new();
+
+    for post in posts
+        .iter()
+        .filter(|p| !p.author.is_empty() && p.author != DELETED_AUTHOR)
+    {
+        if !seen.insert(post.id.as_str()) {
+            continue;
+        }
+        let rank = ranks.entry(&post.author).or_default();
+        rank.0 += 1;
+        rank.1 += post.upvotes;
+    }
+
+    let mut ranks = ranks
+        .into_iter()
+        .map(|(author, (posts, upvotes))| AuthorRank {
+            author: author.to_owned(),
+            posts,
+            upvotes,
+        })
+        .collect::<Vec<_>>();
+    ranks.sort_by(|a, b| {
+        (Reverse(a.posts), Reverse(a.upvotes), &a.author).cmp(&(
+            Reverse(b.posts),
+            Reverse(b.upvotes),
+            &b.author,
+        ))
+    });
+
+    ranks
+}
+
+pub fn write_author_list<W: Write>(
+    mut writer: W,
+    ranks: &[AuthorRank],
+    format: AuthorListFormat,
+) -> io::Result<()> {
+    match format {
+        AuthorListFormat::Csv => {
+            writeln!(writer, "author,posts,upvotes")?;
+            for rank in ranks {
+                writeln!(
+                    writer,
+                    "{},{},{}",
+                    escape_csv_field(&rank.author),
+                    rank.posts,
+                    rank.upvotes
+                )?;
+            }
+        }
+        AuthorListFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, ranks)?;
+            writeln!(writer)?;
+        }
+        AuthorListFormat::Batch => {
+            for rank in ranks {
+                writeln!(writer, "user:{}", rank.author)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+/// Token bucket refilled with `--rate-limit` bytes per second
+#[derive(Debug)]
+pub struct BandwidthBucket {
+    bytes_per_second: f64,
+    // Goes negative when a chunk takes more than is left, later chunks wait for the debt
+    tokens: f64,
+    updated_at: Option<Instant>,
+}
+
+impl BandwidthBucket {
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second: bytes_per_second as f64,
+            tokens: bytes_per_second as f64,
+            updated_at: None,
+        }
+    }
+
+    /// Takes tokens for `bytes` and returns how long to wait before passing them on
+    pub fn reserve(&mut self, bytes: u64, now: Instant) -> Duration {
+        let elapsed = self
+            .updated_at
+            .map(|updated_at| now.saturating_duration_since(updated_at).as_secs_f64())
+            .unwrap_or_default();
+        // Bursts are capped at a second worth of bytes, idle time does not add up beyond it
+        self.tokens = (self.tokens + elapsed * self.bytes_per_second).min(self.bytes_per_second);
+        self.updated_at = Some(now);
+        self.tokens -= bytes as f64;
+
+        match self.tokens < 0.0 {
+            true => Duration::from_secs_f64(-self.tokens / self.bytes_per_second),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+/// Caps the combined throughput of all downloads, clones share the same bucket
+#[derive(Debug, Clone)]
+pub struct BandwidthLimiter {
+    bytes_per_second: u64,
+    bucket: Arc<Mutex<BandwidthBucket>>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            bucket: Arc::new(Mutex::new(BandwidthBucket::new(bytes_per_second))),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// Sleeps until a received chunk of `bytes` fits into the limit
+    pub async fn throttle(&self, bytes: u64) {
+        let wait = self.bucket.lock().unwrap().reserve(bytes, Instant::now());
+
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::clients::normalize_subreddit;
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchTargetKind {
+    User,
+    Subreddit,
+    Search,
+    Domain,
+}
+
+/// A single resource of a batch file, e.g. `user:spez`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchTarget {
+    pub kind: BatchTargetKind,
+    pub resource: String,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BatchTargetError {
+    #[error("Invalid target {0} - expected user:NAME, subreddit:NAME, search:TERM or domain:NAME")]
+    Target(String),
+    #[error("Invalid TOML batch file - {0}")]
+    Toml(String),
+}
+
+impl FromStr for BatchTarget {
+    type Err = BatchTargetError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, resource) = s
+            .split_once(':')
+            .map(|(kind, resource)| (kind.trim(), resource.trim()))
+            .filter(|(_, resource)| !resource.is_empty())
+            .ok_or_else(|| BatchTargetError::Target(s.to_owned()))?;
+
+        let kind = match kind {
+            "user" | "u" => BatchTargetKind::User,
+            "subreddit" | "r" => BatchTargetKind::Subreddit,
+            "search" => BatchTargetKind::Search,
+            "domain" => BatchTargetKind::Domain,
+            _ => return Err(BatchTargetError::Target(s.to_owned())),
+        };
+
+        let resource = match kind {
+            BatchTargetKind::Subreddit => normalize_subreddit(resource),
+            _ => resource.to_owned(),
+        };
+
+        Ok(BatchTarget { kind, resource })
+    }
+}
+
+impl fmt::Display for BatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            BatchTargetKind::User => "user",
+            BatchTargetKind::Subreddit => "subreddit",
+            BatchTargetKind::Search => "search",
+            BatchTargetKind::Domain => "domain",
+        };
+        write!(f, "{}:{}", kind, self.resource)
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchFile {
+    targets: Vec<String>,
+}
+
+/// Parses one target per line, blank lines and `#` comments are skipped
+pub fn parse_batch_targets(content: &str) -> Result<Vec<BatchTarget>, BatchTargetError> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(BatchTarget::from_str)
+        .collect()
+}
+
+/// Parses a TOML batch file of the form `targets = ["user:spez", "subreddit:aww"]`
+pub fn parse_batch_targets_toml(content: &str) -> Result<Vec<BatchTarget>, BatchTargetError> {
+    toml::from_str::<BatchFile>(content)
+        .map_err(|e| BatchTargetError::Toml(e.message().to_owned()))?
+        .targets
+        .iter()
+        .map(|target| BatchTarget::from_str(target))
+        .collect()
+}
@@ -0,0 +1,124 @@
+use super::{
+    hash_file,
+    state::{FileCacheItemLatest, FileCacheLatest},
+};
+use std::{fs, ops::AddAssign, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CacheAction {
+    /// Count the cached, downloaded, failed and missing files
+    Stats,
+    /// Mark downloaded files that are missing on disk or differ from their checksum as failed
+    Verify,
+    /// Remove failed entries, so the next crawl retries them like new posts
+    Prune,
+    /// Upgrade every cache to the latest version
+    Migrate,
+    /// Reconstruct the cache of a folder from the names of its files
+    Rebuild,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub downloaded: usize,
+    pub failed: usize,
+    // Downloaded according to the cache, but no longer on disk
+    pub missing: usize,
+    pub bytes: u64,
+}
+
+impl AddAssign for CacheStats {
+    fn add_assign(&mut self, other: Self) {
+        self.entries += other.entries;
+        self.downloaded += other.downloaded;
+        self.failed += other.failed;
+        self.missing += other.missing;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Size of a downloaded file inside `folder`, `None` when it is not on disk
+fn get_file_size(folder: &Path, item: &FileCacheItemLatest) -> Option<u64> {
+    let file_name = item.file_name.as_ref()?;
+    fs::metadata(folder.join(file_name))
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+}
+
+pub fn get_cache_stats(folder: &Path, cache: &FileCacheLatest) -> CacheStats {
+    cache
+        .files
+        .iter()
+        .fold(CacheStats::default(), |mut stats, item| {
+            stats.entries += 1;
+            match (item.success, get_file_size(folder, item)) {
+                (true, Some(bytes)) => {
+                    stats.downloaded += 1;
+                    stats.bytes += bytes;
+                }
+                (true, None) => {
+                    stats.downloaded += 1;
+                    stats.missing += 1;
+                }
+                (false, _) => stats.failed += 1,
+            }
+            stats
+        })
+}
+
+/// Why a downloaded file no longer matches the cache, `None` when it is intact
+fn get_corruption(folder: &Path, item: &FileCacheItemLatest, deep: bool) -> Option<String> {
+    let size = match get_file_size(folder, item) {
+        Some(size) => size,
+        None => return Some(String::from("File is missing on disk")),
+    };
+    let checksum = item.checksum.as_ref()?;
+
+    // Catches files truncated by earlier versions, which were still recorded as downloaded
+    if size != checksum.size {
+        return Some(format!(
+            "File has {} bytes, {} were downloaded",
+            size, checksum.size
+        ));
+    }
+
+    match (deep, &checksum.blake3, &item.file_name) {
+        (true, Some(expected), Some(file_name)) => match hash_file(&folder.join(file_name)) {
+            Ok(hash) if &hash == expected => None,
+            Ok(_) => Some(String::from("File does not match its blake3 hash")),
+            Err(e) => Some(format!("Hashing the file failed: {}", e)),
+        },
+        _ => None,
+    }
+}
+
+/// Marks downloaded files missing on disk or differing from their recorded size as failed,
+/// returns how many were marked
+///
+/// With `deep` files downloaded with `--checksum` are hashed again as well.
+pub fn verify_cache(folder: &Path, cache: &mut FileCacheLatest, deep: bool) -> usize {
+    let mut marked = 0;
+
+    for item in cache.files.iter_mut().filter(|item| item.success) {
+        // Older caches do not record file names, their files can not be checked
+        if item.file_name.is_none() {
+            continue;
+        }
+        if let Some(error) = get_corruption(folder, item, deep) {
+            item.success = false;
+            item.error = Some(error);
+            marked += 1;
+        }
+    }
+
+    marked
+}
+
+/// Removes the failed entries, returns how many were removed
+pub fn prune_cache(cache: &mut FileCacheLatest) -> usize {
+    let entries = cache.files.len();
+    cache.files.retain(|item| item.success);
+    entries - cache.files.len()
+}
@@ -0,0 +1,87 @@
+use super::{
+    state::{FileCacheItemLatest, FileCacheLatest, FileChecksum},
+    VIEWS_FOLDER,
+};
+use chrono::{NaiveDate, TimeZone, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{fs, path::Path};
+
+lazy_static! {
+    // `{UPVOTES}_{AUTHOR}_{POSTID}_{DATE}` followed by the index of gallery items, authors may
+    // contain underscores themselves
+    static ref DEFAULT_SCHEME_RE: Regex = Regex::new(
+        r"^(-?\d+)_(.+)_([0-9a-z]+)_(\d{4}-\d{2}-\d{2})(?:_(\d+))?\.([0-9A-Za-z]+)$"
+    )
+    .unwrap();
+}
+
+/// Cache entry of a file named after the default file scheme, `None` for other files
+pub fn get_cache_item_from_file_name(file_name: &str) -> Option<FileCacheItemLatest> {
+    let name = Path::new(file_name).file_name()?.to_str()?;
+    let captures = DEFAULT_SCHEME_RE.captures(name)?;
+
+    let date = NaiveDate::parse_from_str(&captures[4], "%Y-%m-%d").ok()?;
+    Some(FileCacheItemLatest {
+        id: captures[3].to_string(),
+        // Only the day is part of the name
+        created_utc: Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?),
+        success: true,
+        index: captures.get(5).and_then(|i| i.as_str().parse().ok()),
+        author: Some(captures[2].to_string()),
+        upvotes: captures[1].parse().ok(),
+        file_name: Some(file_name.to_owned()),
+        ..FileCacheItemLatest::default()
+    })
+}
+
+/// Adds the files below `folder` named after the default file scheme to its cache, returns how
+/// many were added
+///
+/// Subfolders with a cache of their own belong to other resources and are left alone.
+pub fn rebuild_cache(folder: &Path, cache: &mut FileCacheLatest) -> Result<usize, std::io::Error> {
+    let mut added = 0;
+    let mut folders = vec![folder.to_path_buf()];
+
+    while let Some(current) = folders.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+
+            if file_type.is_dir() {
+                if entry.file_name() != VIEWS_FOLDER && !path.join("cache.json").is_file() {
+                    folders.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(folder) else {
+                continue;
+            };
+            let file_name = relative.to_string_lossy().replace('\\', "/");
+            let Some(mut item) = get_cache_item_from_file_name(&file_name) else {
+                continue;
+            };
+            let cached = cache
+                .files
+                .iter()
+                .any(|f| f.id == item.id && f.index == item.index);
+            if cached {
+                continue;
+            }
+
+            item.checksum = Some(FileChecksum {
+                size: entry.metadata()?.len(),
+                blake3: None,
+            });
+            cache.files.push(item);
+            added += 1;
+        }
+    }
+
+    Ok(added)
+}
@@ -0,0 +1,33 @@
+/// Counts consecutive fully cached pages of a listing, set by `--stop-after-cached-pages`
+///
+/// Only listings with the latest posts first may stop early, any other order can still hold
+/// uncached posts after a cached page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedPageStreak {
+    max_pages: Option<u32>,
+    pages: u32,
+}
+
+impl CachedPageStreak {
+    pub fn new(max_pages: Option<u32>) -> Self {
+        Self {
+            max_pages,
+            pages: 0,
+        }
+    }
+
+    /// Records a page of `fetched` posts of which `uncached` were new, returns whether to stop
+    ///
+    /// An empty page neither extends nor keeps the streak.
+    pub fn observe(&mut self, fetched: usize, uncached: usize) -> bool {
+        let Some(max_pages) = self.max_pages else {
+            return false;
+        };
+
+        match fetched > 0 && uncached == 0 {
+            true => self.pages += 1,
+            false => self.pages = 0,
+        }
+        self.pages >= max_pages
+    }
+}
@@ -0,0 +1,79 @@
+use super::{get_file_name, get_file_path, prepare_file_folder};
+use crate::{
+    clients::api_types::reddit::submitted_response::{
+        RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    reddit_parser::RedditCrawlerPost,
+};
+use std::fs;
+
+/// Renders the title, selftext and image captions of a gallery with links to its files
+pub fn render_caption_markdown(
+    data: &RedditSubmittedChildData,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> Option<String> {
+    let items = &data.gallery_data.as_ref()?.items;
+    if items.iter().all(|item| item.caption.is_none()) {
+        return None;
+    }
+
+    let mut markdown = format!("# {}\n\n", data.title);
+    if !data.selftext.is_empty() {
+        markdown.push_str(&format!("{}\n\n", data.selftext));
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        let file_name = posts
+            .iter()
+            .find(|p| p.id == data.id && p.index == Some(i))
+            .map(|p| get_file_name(p, file_scheme))
+            // The markdown is written next to the files, even when they are organized into folders
+            .map(|f| f.rsplit('/').next().unwrap_or_default().to_owned());
+
+        let caption = item.caption.as_deref().unwrap_or_default();
+        match file_name {
+            Some(file_name) => markdown.push_str(&format!("![{}]({})\n\n", caption, file_name)),
+            None => markdown.push_str(&format!("*Image {} was not downloaded*\n\n", i + 1)),
+        }
+        if !caption.is_empty() {
+            markdown.push_str(&format!("{}\n\n", caption));
+        }
+    }
+
+    Some(markdown)
+}
+
+/// Writes a `<post>.md` next to the files of every gallery that has captions
+pub fn write_caption_markdown(
+    folder_path: &str,
+    responses: &[RedditSubmittedResponse],
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> Result<usize, anyhow::Error> {
+    let mut written = 0;
+
+    for child in responses.iter().flat_map(|r| r.data.children.iter()) {
+        let Some(markdown) = render_caption_markdown(&child.data, posts, file_scheme) else {
+            continue;
+        };
+        let Some(first) = posts.iter().find(|p| p.id == child.data.id) else {
+            continue;
+        };
+
+        let markdown_path = get_file_path(
+            folder_path,
+            &RedditCrawlerPost {
+                extension: String::from("md"),
+                index: None,
+                ..first.clone()
+            },
+            file_scheme,
+        );
+        prepare_file_folder(&markdown_path)?;
+        fs::write(markdown_path, markdown)?;
+        written += 1;
+    }
+
+    Ok(written)
+}
@@ -1,5 +1,6 @@
-use owo_colors::OwoColorize;
+use crate::error::ExitStatus;
 use std::process::Command;
+use tracing::error;
 
 const DEPENDENCIES: [(&str, &str, &str); 1] = [
     ("yt-dlp", "yt-dlp", "--version"),
@@ -11,7 +12,7 @@ pub fn check_deps() -> Result<(), Box<dyn std::error::Error>> {
     let mut collect: Vec<String> = Vec::with_capacity(DEPENDENCIES.len());
     for (name, cmd, arg) in DEPENDENCIES.iter() {
         if Command::new(cmd).arg(arg).output().is_err() {
-            collect.push(name.red().bold().to_string());
+            collect.push(name.to_string());
         };
     }
 
@@ -20,6 +21,6 @@ pub fn check_deps() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let missing_deps = collect.join(", ");
-    println!("Missing CLI dependencies: {}", missing_deps);
-    std::process::exit(0)
+    error!("Missing CLI dependencies: {}", missing_deps);
+    ExitStatus::MissingDependency.exit()
 }
@@ -1,3 +1,4 @@
+use crate::error::ExitStatus;
 use lazy_static::lazy_static;
 use owo_colors::OwoColorize;
 use regex::Regex;
@@ -6,7 +7,50 @@ lazy_static! {
     static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{[^{]+\}").unwrap();
 }
 
-const VALID_PLACEHOLDERS: [&str; 4] = ["{UPVOTES}", "{AUTHOR}", "{POSTID}", "{DATE}"];
+pub const DEFAULT_FILE_SCHEME: &str = "{UPVOTES}_{AUTHOR}_{POSTID}_{DATE}";
+
+const VALID_PLACEHOLDERS: [&str; 13] = [
+    "{UPVOTES}",
+    "{AUTHOR}",
+    "{POSTID}",
+    "{DATE}",
+    "{YEAR}",
+    "{MONTH}",
+    "{SPOILER}",
+    "{SUBREDDIT}",
+    "{TITLE}",
+    "{PROVIDER}",
+    "{INDEX}",
+    "{CAPTION}",
+    "{EXT}",
+];
+
+/// Subfolders files are sorted into below the folder of the crawled resource
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Organize {
+    /// Every file directly in the folder of the resource
+    #[default]
+    Flat,
+    /// A folder per subreddit
+    BySubreddit,
+    /// A folder per year with a folder per month
+    ByDate,
+    /// A folder per media provider
+    ByProvider,
+}
+
+impl Organize {
+    /// Prefixes a file scheme with the subfolders of the layout
+    pub fn apply(&self, file_scheme: &str) -> String {
+        let folder = match self {
+            Organize::Flat => return file_scheme.to_owned(),
+            Organize::BySubreddit => "{SUBREDDIT}",
+            Organize::ByDate => "{YEAR}/{MONTH}",
+            Organize::ByProvider => "{PROVIDER}",
+        };
+        format!("{}/{}", folder, file_scheme)
+    }
+}
 
 pub fn check_file_scheme(placeholder: &str) {
     let res = PLACEHOLDER_RE
@@ -28,7 +72,7 @@ pub fn check_file_scheme(placeholder: &str) {
                 "Valid placeholders: {}",
                 VALID_PLACEHOLDERS.join(" ").bold()
             );
-            std::process::exit(0)
+            ExitStatus::InvalidInput.exit()
         }
     }
 }
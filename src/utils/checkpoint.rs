@@ -0,0 +1,80 @@
+use super::state::SharedState;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::Mutex, task::JoinHandle, time::sleep};
+use tracing::{debug, warn};
+
+// How often the checkpoint task looks for new cache entries
+const CHECKPOINT_TICK: Duration = Duration::from_secs(1);
+
+/// Set by `--checkpoint-every` and `--checkpoint-interval`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointSettings {
+    pub every: u32,
+    pub interval: Duration,
+}
+
+// Same as the defaults of the flags
+impl Default for CheckpointSettings {
+    fn default() -> Self {
+        Self {
+            every: 100,
+            interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CheckpointSettings {
+    /// Whether `recorded` new cache entries are written, `elapsed` since the last checkpoint
+    pub fn is_due(&self, recorded: usize, elapsed: Duration) -> bool {
+        recorded > 0 && (recorded >= self.every as usize || elapsed >= self.interval)
+    }
+}
+
+/// Writes the cache of a running crawl in the background, stopped once it is dropped
+pub struct CacheCheckpoints {
+    task: JoinHandle<()>,
+}
+
+impl CacheCheckpoints {
+    pub fn spawn(
+        shared_state: &Arc<Mutex<SharedState>>,
+        file_cache_path: &str,
+        settings: CheckpointSettings,
+    ) -> Self {
+        let shared_state = Arc::clone(shared_state);
+        let file_cache_path = file_cache_path.to_owned();
+
+        let task = tokio::spawn(async move {
+            let mut written = shared_state.lock().await.file_cache.files.len();
+            let mut last_checkpoint = Instant::now();
+
+            loop {
+                sleep(CHECKPOINT_TICK).await;
+
+                let ss = shared_state.lock().await;
+                let files = ss.file_cache.files.len();
+                if !settings.is_due(files.saturating_sub(written), last_checkpoint.elapsed()) {
+                    continue;
+                }
+
+                match ss.write_file_cache(&file_cache_path) {
+                    Ok(()) => debug!("Checkpointed {} cached files", files),
+                    Err(e) => warn!("[CHECKPOINT_FAILED] {} - {}", file_cache_path, e),
+                }
+                written = files;
+                last_checkpoint = Instant::now();
+            }
+        });
+
+        Self { task }
+    }
+}
+
+impl Drop for CacheCheckpoints {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,22 @@
+use super::{get_long_path, state::FileChecksum};
+use std::{fs::File, io, path::Path};
+
+/// Hex encoded blake3 hash of a file, read in chunks so large videos are not held in memory
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Size of a downloaded file, hashed as well with `--checksum`
+pub async fn get_file_checksum(file_path: &str, hash: bool) -> Result<FileChecksum, anyhow::Error> {
+    let path = get_long_path(file_path);
+    let size = std::fs::metadata(&path)?.len();
+
+    let blake3 = match hash {
+        true => Some(tokio::task::spawn_blocking(move || hash_file(&path)).await??),
+        false => None,
+    };
+
+    Ok(FileChecksum { size, blake3 })
+}
@@ -0,0 +1,187 @@
+use super::expand_path;
+use async_trait::async_trait;
+use chrono::Utc;
+use http::Extensions;
+use reqwest::{
+    header::{HeaderValue, COOKIE},
+    Request, Response, Url,
+};
+use reqwest_middleware::{Middleware, Next};
+use std::fs;
+use thiserror::Error;
+
+// Prefix of the cookies a browser only exposes to HTTP, the line is not a comment
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+#[derive(Error, Debug)]
+pub enum CookieFileError {
+    #[error("Failed to read {0}: {1}")]
+    Read(String, String),
+    #[error("Line {0} is not a Netscape cookie with 7 tab separated fields")]
+    Invalid(usize),
+    #[error("{0} does not contain a cookie")]
+    Empty(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    pub domain: String,
+    pub include_subdomains: bool,
+    pub path: String,
+    pub secure: bool,
+    // Unix timestamp, `None` for cookies of the browser session
+    pub expires: Option<i64>,
+    pub name: String,
+    pub value: String,
+}
+
+impl Cookie {
+    fn matches(&self, url: &Url, now: i64) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let domain = self.domain.trim_start_matches('.');
+        let domain_matches = host.eq_ignore_ascii_case(domain)
+            || (self.include_subdomains
+                && host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", domain.to_ascii_lowercase())));
+
+        domain_matches
+            && url.path().starts_with(&self.path)
+            && (!self.secure || url.scheme() == "https")
+            && self.expires.is_none_or(|expires| expires > now)
+    }
+}
+
+/// Cookies of a Netscape `cookies.txt` given with `--cookies`, as exported by browser add-ons
+/// and yt-dlp
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn parse(content: &str) -> Result<Self, CookieFileError> {
+        let mut cookies = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let line = line.strip_prefix(HTTP_ONLY_PREFIX).unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split('\t').collect::<Vec<_>>();
+            let [domain, include_subdomains, path, secure, expires, name, value] = fields[..]
+            else {
+                return Err(CookieFileError::Invalid(index + 1));
+            };
+            let expires = expires
+                .parse::<i64>()
+                .map_err(|_| CookieFileError::Invalid(index + 1))?;
+
+            cookies.push(Cookie {
+                domain: domain.to_owned(),
+                include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE"),
+                path: path.to_owned(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: (expires > 0).then_some(expires),
+                name: name.to_owned(),
+                value: value.to_owned(),
+            });
+        }
+
+        Ok(Self { cookies })
+    }
+
+    pub fn load(path: &str) -> Result<Self, CookieFileError> {
+        let content = expand_path(path)
+            .map_err(|e| e.to_string())
+            .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+            .map_err(|e| CookieFileError::Read(path.to_owned(), e))?;
+
+        let jar = Self::parse(&content)?;
+        match jar.cookies.is_empty() {
+            true => Err(CookieFileError::Empty(path.to_owned())),
+            false => Ok(jar),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    /// Value of the `Cookie` header sent to `url`, expired cookies are left out
+    pub fn get_header(&self, url: &Url, now: i64) -> Option<String> {
+        let header = self
+            .cookies
+            .iter()
+            .filter(|c| c.matches(url, now))
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        (!header.is_empty()).then_some(header)
+    }
+
+    pub fn middleware(self) -> CookieMiddleware {
+        CookieMiddleware { jar: self }
+    }
+}
+
+/// Set by `--cookies` and `--cookies-from-browser`, handed to yt-dlp
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct YtdlpCookies {
+    pub file: Option<String>,
+    // `BROWSER[+KEYRING][:PROFILE][::CONTAINER]` as yt-dlp takes it
+    pub from_browser: Option<String>,
+}
+
+impl YtdlpCookies {
+    pub fn is_set(&self) -> bool {
+        self.file.is_some() || self.from_browser.is_some()
+    }
+
+    pub fn get_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(file) = &self.file {
+            args.extend([String::from("--cookies"), file.to_owned()]);
+        }
+        if let Some(browser) = &self.from_browser {
+            args.extend([String::from("--cookies-from-browser"), browser.to_owned()]);
+        }
+        args
+    }
+}
+
+/// Sends the cookies of `--cookies` along with every request to their domain
+pub struct CookieMiddleware {
+    jar: CookieJar,
+}
+
+#[async_trait]
+impl Middleware for CookieMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if !req.headers().contains_key(COOKIE) {
+            if let Some(value) = self
+                .jar
+                .get_header(req.url(), Utc::now().timestamp())
+                .and_then(|header| HeaderValue::from_str(&header).ok())
+            {
+                req.headers_mut().insert(COOKIE, value);
+            }
+        }
+
+        next.run(req, extensions).await
+    }
+}
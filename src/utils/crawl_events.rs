@@ -0,0 +1,133 @@
+use super::SyncProgress;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use spinoff::{spinners, Color, Spinner};
+use std::sync::Arc;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputMode {
+    /// Spinners, progress bars and human readable messages
+    #[default]
+    Text,
+    /// NDJSON events on stdout, logs stay on stderr
+    Json,
+}
+
+/// Emitted as a line of JSON by `--output-format json`
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CrawlEvent<'a> {
+    CrawlStarted {
+        resource: &'a str,
+    },
+    PageFetched {
+        url: &'a str,
+        page: u32,
+        posts: usize,
+    },
+    FileDownloaded {
+        id: &'a str,
+        index: Option<usize>,
+        url: &'a str,
+        file_name: &'a str,
+        bytes: f64,
+    },
+    DownloadFailed {
+        id: &'a str,
+        index: Option<usize>,
+        url: &'a str,
+        reason: &'a str,
+    },
+    DownloadSkipped {
+        id: &'a str,
+        index: Option<usize>,
+        url: &'a str,
+        reason: &'a str,
+    },
+    CrawlFinished {
+        resource: &'a str,
+        downloaded: u64,
+        failed: u64,
+        bytes: f64,
+    },
+    Failed {
+        message: &'a str,
+    },
+}
+
+pub fn emit_event(event: &CrawlEvent) {
+    if let Ok(line) = serde_json::to_string(event) {
+        println!("{}", line);
+    }
+}
+
+/// Spinner shown while a resource is fetched, JSON mode reports failures as events instead
+pub struct CrawlSpinner {
+    spinner: Option<Spinner>,
+    // Set in multi-resource runs, drawn below their aggregate bar instead
+    nested: Option<(Arc<SyncProgress>, ProgressBar)>,
+}
+
+impl CrawlSpinner {
+    pub fn new(text: String, mode: OutputMode) -> Self {
+        let spinner = match mode {
+            OutputMode::Text => Some(Spinner::new(
+                spinners::Dots,
+                text,
+                Color::TrueColor {
+                    r: 237,
+                    g: 106,
+                    b: 44,
+                },
+            )),
+            OutputMode::Json => None,
+        };
+
+        Self {
+            spinner,
+            nested: None,
+        }
+    }
+
+    /// Spinner of a resource crawled by a multi-resource run
+    pub fn new_in(text: String, sync_progress: &Arc<SyncProgress>) -> Self {
+        let bar = sync_progress.add_spinner(text);
+
+        Self {
+            spinner: None,
+            nested: Some((Arc::clone(sync_progress), bar)),
+        }
+    }
+
+    pub fn update_text(&mut self, text: String) {
+        match (&mut self.spinner, &self.nested) {
+            (Some(spinner), _) => spinner.update_text(text),
+            (None, Some((_, bar))) => bar.set_message(text),
+            (None, None) => {}
+        }
+    }
+
+    pub fn success(&mut self, message: &str) {
+        match (&mut self.spinner, &self.nested) {
+            (Some(spinner), _) => spinner.success(message),
+            (None, Some((sync_progress, bar))) => {
+                sync_progress.finish_spinner(bar, format!("✔ {}", message))
+            }
+            (None, None) => {}
+        }
+    }
+
+    pub fn fail(&mut self, message: &str) {
+        match (&mut self.spinner, &self.nested) {
+            (Some(spinner), _) => spinner.fail(message),
+            (None, Some((sync_progress, bar))) => {
+                sync_progress.finish_spinner(bar, format!("✖ {}", message))
+            }
+            (None, None) => emit_event(&CrawlEvent::Failed { message }),
+        }
+    }
+}
@@ -1,42 +1,238 @@
-use indicatif::{ProgressBar, ProgressState, ProgressStyle};
-use std::cmp::min;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle};
+use std::{
+    cmp::min,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProgressMode {
+    /// A single bar counting files
+    #[default]
+    Simple,
+    /// An aggregate bar plus a byte bar for every running download
+    Detailed,
+    /// No bars at all, set by `--output-format json`
+    #[value(skip)]
+    Hidden,
+}
 
 pub struct DownloadProgress {
     pub control: ProgressBar,
     pub total_count: u64,
+    // Includes the bytes of downloads that are still running
+    received_bytes: Arc<AtomicU64>,
+    // Only set in detailed mode, per-file bars are added to it
+    multi: Option<MultiProgress>,
+    // Set in multi-resource runs, the bar joins their bars once it is shown
+    sync: Option<MultiProgress>,
+    mode: ProgressMode,
+}
+
+/// Byte level progress handed to the streaming downloader
+#[derive(Clone)]
+pub struct ByteProgress {
+    control: ProgressBar,
+    total_count: u64,
+    received_bytes: Arc<AtomicU64>,
+    multi: Option<MultiProgress>,
+}
+
+/// Progress of a single download, its bar is removed once it is dropped
+pub struct FileProgress {
+    parent: ByteProgress,
+    bar: Option<ProgressBar>,
+}
+
+fn bytes_to_mb(bytes: f64) -> String {
+    let mb = bytes / 1024.0 / 1024.0;
+    format!("{:.2} MB", mb)
+}
+
+fn download_progress_style() -> ProgressStyle {
+    ProgressStyle::with_template(
+        "{spinner:.202} — [{elapsed_precise}] — [{wide_bar:.202}] — {msg} ({eta})",
+    )
+    .unwrap()
+    .with_key(
+        "eta",
+        |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+            write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap()
+        },
+    )
+    .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+    .progress_chars("█▉▊▋▌▍▎▏  ")
+}
+
+fn file_progress_style(has_length: bool) -> ProgressStyle {
+    let template = match has_length {
+        true => "  {msg:40!} [{bar:30.202}] {bytes}/{total_bytes} ({bytes_per_sec})",
+        // Servers without a Content-Length only allow counting the received bytes
+        false => "  {msg:40!} {spinner:.202} {bytes} ({bytes_per_sec})",
+    };
+
+    ProgressStyle::with_template(template)
+        .unwrap()
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+        .progress_chars("█▉▊▋▌▍▎▏  ")
+}
+
+impl ByteProgress {
+    /// Starts tracking a download, `resumed` bytes were already written by an earlier run
+    pub fn start_file(&self, name: &str, length: Option<u64>, resumed: u64) -> FileProgress {
+        let bar = self.multi.as_ref().map(|multi| {
+            let bar = multi.add(ProgressBar::new(length.unwrap_or(0)));
+            bar.set_style(file_progress_style(length.is_some()));
+            bar.set_message(name.to_owned());
+            bar.set_position(resumed);
+            bar
+        });
+
+        FileProgress {
+            parent: self.clone(),
+            bar,
+        }
+    }
+}
+
+impl FileProgress {
+    pub fn inc(&self, len: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(len);
+        }
+
+        let ByteProgress {
+            control,
+            total_count,
+            received_bytes,
+            ..
+        } = &self.parent;
+
+        let received = received_bytes.fetch_add(len, Ordering::Relaxed) + len;
+        control.set_message(format!(
+            "{}/{} - {}",
+            control.position(),
+            // The length grows while pages are still being fetched
+            control.length().unwrap_or(*total_count),
+            bytes_to_mb(received as f64)
+        ));
+    }
+}
+
+impl Drop for FileProgress {
+    fn drop(&mut self) {
+        if let (Some(multi), Some(bar)) = (&self.parent.multi, &self.bar) {
+            bar.finish_and_clear();
+            multi.remove(bar);
+        }
+    }
 }
 
 impl DownloadProgress {
     pub fn new(len: u64) -> Self {
         let stats = ProgressBar::new(len);
-        stats.set_style(
-            ProgressStyle::with_template(
-                "{spinner:.202} — [{elapsed_precise}] — [{wide_bar:.202}] — {msg} ({eta})",
-            )
-            .unwrap()
-            .with_key(
-                "eta",
-                |state: &ProgressState, w: &mut dyn std::fmt::Write| {
-                    write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap()
-                },
-            )
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .progress_chars("█▉▊▋▌▍▎▏  "),
-        );
+        stats.set_style(download_progress_style());
 
         DownloadProgress {
             control: stats,
             total_count: len,
+            received_bytes: Arc::new(AtomicU64::new(0)),
+            multi: None,
+            sync: None,
+            mode: ProgressMode::Simple,
+        }
+    }
+
+    /// Creates a progress bar that is rendered below the aggregate bar of a multi-resource run
+    ///
+    /// Concurrent resources share the bars, so it stays detached until it is shown.
+    pub fn new_in(sync_progress: &SyncProgress, len: u64, mode: ProgressMode) -> Self {
+        let mut progress = DownloadProgress::new(len);
+        progress
+            .control
+            .set_draw_target(ProgressDrawTarget::hidden());
+
+        if mode == ProgressMode::Detailed {
+            progress.multi = Some(sync_progress.multi.clone());
         }
+        progress.sync = Some(sync_progress.multi.clone());
+        progress.mode = mode;
+
+        progress
     }
 
     pub fn bytes_to_mb(&self, bytes: f64) -> String {
-        let mb = bytes / 1024.0 / 1024.0;
-        format!("{:.2} MB", mb)
+        bytes_to_mb(bytes)
+    }
+
+    pub fn with_mode(len: u64, mode: ProgressMode) -> Self {
+        let mut progress = DownloadProgress::new(len);
+
+        match mode {
+            ProgressMode::Detailed => {
+                let multi = MultiProgress::new();
+                progress.control = multi.add(progress.control);
+                progress.multi = Some(multi);
+            }
+            ProgressMode::Hidden => progress.hide(),
+            ProgressMode::Simple => {}
+        }
+        progress.mode = mode;
+
+        progress
+    }
+
+    /// Shares the bars with the downloader so it can report bytes while files are downloading
+    pub fn byte_progress(&self) -> ByteProgress {
+        ByteProgress {
+            control: self.control.clone(),
+            total_count: self.total_count,
+            received_bytes: Arc::clone(&self.received_bytes),
+            multi: self.multi.clone(),
+        }
+    }
+
+    /// Adds posts found on a newly fetched page to the bar
+    pub fn inc_length(&mut self, len: u64) {
+        self.total_count += len;
+        self.control.inc_length(len);
+    }
+
+    /// Keeps the bars from drawing while a spinner is still running
+    pub fn hide(&self) {
+        // Detached until shown, the shared bars keep drawing
+        if self.sync.is_some() {
+            return;
+        }
+
+        match &self.multi {
+            Some(multi) => multi.set_draw_target(ProgressDrawTarget::hidden()),
+            None => self.control.set_draw_target(ProgressDrawTarget::hidden()),
+        }
+    }
+
+    pub fn show(&self) {
+        if self.mode == ProgressMode::Hidden {
+            return;
+        }
+        if let Some(sync) = &self.sync {
+            sync.add(self.control.clone());
+            return;
+        }
+
+        match &self.multi {
+            Some(multi) => multi.set_draw_target(ProgressDrawTarget::stderr()),
+            None => self.control.set_draw_target(ProgressDrawTarget::stderr()),
+        }
     }
 
     pub fn update_progress(&self, current_count: u64, total_count: u64, bytes_downloaded: f64) {
         let progress_pos = min(current_count, total_count);
+        let received = self.received_bytes.load(Ordering::Relaxed) as f64;
+        let bytes_downloaded = bytes_downloaded.max(received);
 
         let msg = format!(
             "{}/{} - {}",
@@ -50,6 +246,13 @@ impl DownloadProgress {
     }
 
     pub fn post_report(&self, current_count: u64, total_count: u64, bytes_downloaded: f64) {
+        // Multi-resource runs print a summary of the resource instead
+        if let Some(sync) = &self.sync {
+            self.control.finish_and_clear();
+            sync.remove(&self.control);
+            return;
+        }
+
         let msg = format!(
             "Downloaded {}/{} - {}",
             current_count,
@@ -60,3 +263,70 @@ impl DownloadProgress {
         self.control.finish_with_message(msg);
     }
 }
+
+/// Outer progress bar for runs spanning multiple resources (e.g. syncing many subscriptions)
+#[derive(Debug)]
+pub struct SyncProgress {
+    pub multi: MultiProgress,
+    pub control: ProgressBar,
+    pub total_resources: u64,
+}
+
+impl SyncProgress {
+    pub fn new(total_resources: u64) -> Self {
+        let multi = MultiProgress::new();
+        let control = multi.add(ProgressBar::new(total_resources));
+        control.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.202} — [{elapsed_precise}] — Resources {pos}/{len} — {msg}",
+            )
+            .unwrap()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+        );
+
+        SyncProgress {
+            multi,
+            control,
+            total_resources,
+        }
+    }
+
+    pub fn start_resource(&self, resource: &str) {
+        self.control.set_message(resource.to_owned());
+    }
+
+    /// Spinner of a resource that is still being fetched, drawn below the aggregate bar
+    pub fn add_spinner(&self, text: String) -> ProgressBar {
+        let spinner = self.multi.add(ProgressBar::new_spinner());
+        spinner.set_style(
+            ProgressStyle::with_template("{spinner:.202} {msg}")
+                .unwrap()
+                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+        );
+        spinner.set_message(text);
+        spinner.enable_steady_tick(Duration::from_millis(80));
+        spinner
+    }
+
+    /// Replaces a spinner with the line it finished with
+    pub fn finish_spinner(&self, spinner: &ProgressBar, line: String) {
+        spinner.finish_and_clear();
+        self.multi.remove(spinner);
+        let _ = self.multi.println(line);
+    }
+
+    /// Marks a resource as completed and appends its summary above the progress bars
+    pub fn finish_resource(&self, resource: &str, summary: &str) {
+        // Printing through `MultiProgress` keeps the bars at the bottom of the terminal
+        let _ = self.multi.println(format!("{} — {}", resource, summary));
+        self.control.inc(1);
+    }
+
+    pub fn post_report(&self) {
+        self.control.finish_with_message(format!(
+            "Synced {}/{} resources",
+            self.control.position(),
+            self.total_resources
+        ));
+    }
+}
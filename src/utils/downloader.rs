@@ -1,35 +1,52 @@
-use super::state::SharedState;
+use super::{
+    download_with_aria2c, embed_file_metadata, emit_event, extracts_audio, get_file_checksum,
+    get_link_file_name,
+    state::{FileCacheItemLatest, FileChecksum, FileProvenance, SharedState},
+    Aria2cSettings, AudioFormat, BandwidthLimiter, ByteProgress, CrawlEvent, Downloader,
+    EmbeddedMetadata, FileTooLargeError, ImageConversion, InjectedFailureKind, NoAudioTrackError,
+    OutputMode, RemoteUploads, SizeLimits, SkipReason, TransferTimeout, TransferTimeoutError,
+    TransferTimeouts, VideoProcessing, STALL_RETRIES,
+};
 use crate::{
-    clients::{download_redgifs_media, RedgifsQuality},
-    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType},
+    providers::{ProviderContext, ProviderDownload, PROVIDERS},
+    reddit_parser::RedditCrawlerPost,
 };
+use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use filetime::FileTime;
-use reqwest::Response;
+use futures_util::StreamExt;
+use reqwest::{header, Response, StatusCode};
 use std::{
     fs::{self, File},
-    io::Write,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
     sync::Arc,
 };
-use tokio::sync::Mutex;
+use tokio::{
+    fs::OpenOptions,
+    io::AsyncWriteExt,
+    sync::{Mutex, Semaphore},
+    time::{self, Instant},
+};
+use tracing::{debug, info, warn};
 
 pub fn prepare_output_folder(folder_path: &str) -> Result<(), anyhow::Error> {
-    if fs::metadata(folder_path).is_err() {
-        fs::create_dir_all(folder_path)?;
+    let folder_path = get_long_path(folder_path);
+    if fs::metadata(&folder_path).is_err() {
+        fs::create_dir_all(&folder_path)?;
     }
     Ok(())
 }
 
-pub fn get_output_folder(path: &str, stem: &str) -> String {
-    format!("{}/{}", path, stem)
+/// Creates the folder of a file, file schemes with a `/` place files in subfolders
+pub fn prepare_file_folder(file_path: &str) -> Result<(), anyhow::Error> {
+    match Path::new(file_path).parent() {
+        Some(parent) => prepare_output_folder(&parent.to_string_lossy()),
+        None => Ok(()),
+    }
 }
 
-enum ProviderHandlerReturned {
-    HttpResponse(Response),
-    ThirdPartyResponse(String),
-    NotFound,
-    Unhandled,
+pub fn get_output_folder(path: &str, stem: &str) -> String {
+    format!("{}/{}", path, stem)
 }
 
 pub async fn set_file_timestamp(
@@ -46,133 +63,1051 @@ pub async fn set_file_timestamp(
     Ok(())
 }
 
+fn get_file_provenance(response: &Response) -> FileProvenance {
+    let get_header = |name: header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_owned())
+    };
+
+    FileProvenance {
+        etag: get_header(header::ETAG),
+        last_modified: get_header(header::LAST_MODIFIED),
+        content_length: response.content_length(),
+        final_url: response.url().to_string(),
+    }
+}
+
+/// Path of the temporary file a download is written to until it is complete
+pub fn get_part_file_path(file_path: &str) -> String {
+    format!("{}.part", file_path)
+}
+
+/// Parses the complete length out of a `Content-Range` header like `bytes 100-199/200`
+pub fn get_content_range_total(content_range: &str) -> Option<u64> {
+    content_range
+        .strip_prefix("bytes ")?
+        .split_once('/')?
+        .1
+        .parse()
+        .ok()
+}
+
+/// Streams a response body to a `.part` file and moves it into place once it is complete.
+/// A `.part` file left behind by an interrupted run is resumed with a range request.
+/// Stalled transfers are requested again and resume their `.part` file the same way.
+pub async fn write_http_response(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    mut response: Response,
+    file_path: &str,
+    progress: Option<&ByteProgress>,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Result<u64, anyhow::Error> {
+    let url = response.url().to_string();
+    let started = Instant::now();
+    let mut retries = 0;
+
+    loop {
+        let written = write_http_response_attempt(
+            client, response, file_path, progress, bandwidth, timeouts, started,
+        )
+        .await;
+
+        match written {
+            Err(e) if retries < STALL_RETRIES && is_stalled(&e) => {
+                retries += 1;
+                warn!(
+                    "[STALLED] {} - requesting it again ({}/{})",
+                    e, retries, STALL_RETRIES
+                );
+                let (deadline, _) = timeouts.chunk_deadline(started, Instant::now());
+                response = time::timeout_at(deadline, client.get(&url).send())
+                    .await
+                    .map_err(|_| stalled(file_path, timeouts))??;
+            }
+            written => return written,
+        }
+    }
+}
+
+fn is_stalled(e: &anyhow::Error) -> bool {
+    matches!(e.downcast_ref(), Some(TransferTimeoutError::Stalled { .. }))
+}
+
+fn stalled(file_path: &str, timeouts: TransferTimeouts) -> TransferTimeoutError {
+    TransferTimeoutError::Stalled {
+        file_path: file_path.to_owned(),
+        timeout: timeouts.stall,
+    }
+}
+
+async fn write_http_response_attempt(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    mut response: Response,
+    file_path: &str,
+    progress: Option<&ByteProgress>,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+    started: Instant,
+) -> Result<u64, anyhow::Error> {
+    let part_path = get_long_path(&get_part_file_path(file_path));
+    let resume_from = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let accepts_ranges = response
+        .headers()
+        .get(header::ACCEPT_RANGES)
+        .is_some_and(|v| v == "bytes");
+
+    let mut expected_length = response.content_length();
+    let mut append = false;
+
+    if resume_from > 0 && accepts_ranges {
+        let mut request = client
+            .get(response.url().as_str())
+            .header(header::RANGE, format!("bytes={}-", resume_from));
+        // Makes the server send the whole file again if it changed since the interruption
+        if let Some(etag) = response.headers().get(header::ETAG) {
+            request = request.header(header::IF_RANGE, etag);
+        }
+
+        let resumed = request.send().await?;
+        if resumed.status() == StatusCode::PARTIAL_CONTENT {
+            expected_length = resumed
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(get_content_range_total);
+            response = resumed;
+            append = true;
+        }
+    }
+
+    let mut out = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&part_path)
+        .await?;
+
+    let file_progress = progress.map(|p| {
+        let name = file_path.rsplit('/').next().unwrap_or(file_path);
+        let resumed = if append { resume_from } else { 0 };
+        p.start_file(name, expected_length, resumed)
+    });
+
+    // Only a single chunk is held in memory, even for multi-GB videos
+    let mut stream = response.bytes_stream();
+    loop {
+        let (deadline, timeout) = timeouts.chunk_deadline(started, Instant::now());
+        let chunk = match time::timeout_at(deadline, stream.next()).await {
+            Ok(Some(chunk)) => chunk?,
+            Ok(None) => break,
+            Err(_) => {
+                // Keeps the received bytes in the .part file to resume from
+                out.flush().await?;
+                return Err(match timeout {
+                    TransferTimeout::Stalled => stalled(file_path, timeouts),
+                    TransferTimeout::TimedOut => TransferTimeoutError::TimedOut {
+                        file_path: file_path.to_owned(),
+                        timeout: timeouts.download.unwrap_or_default(),
+                    },
+                }
+                .into());
+            }
+        };
+        if let Some(bandwidth) = bandwidth {
+            bandwidth.throttle(chunk.len() as u64).await;
+        }
+        out.write_all(&chunk).await?;
+        if let Some(file_progress) = &file_progress {
+            file_progress.inc(chunk.len() as u64);
+        }
+    }
+    out.flush().await?;
+
+    let written = out.metadata().await?.len();
+    drop(out);
+
+    if let Some(expected_length) = expected_length {
+        if written != expected_length {
+            // A longer file can not be resumed, it is started over on the next run
+            if written > expected_length {
+                fs::remove_file(&part_path)?;
+            }
+            return Err(anyhow!(
+                "Incomplete download of {}: received {} of {} bytes",
+                file_path,
+                written,
+                expected_length
+            ));
+        }
+    }
+
+    fs::rename(&part_path, get_long_path(file_path))?;
+
+    Ok(written)
+}
+
 pub enum DownloadPostResult {
-    ReceivedBytes(f64),
-    ReceivedFailed,
+    /// Bytes written, provenance of HTTP downloads, the name of the written file and its checksum
+    ReceivedBytes(f64, Option<FileProvenance>, String, FileChecksum),
+    /// Why the download failed, recorded in the cache
+    ReceivedFailed(String),
     ReceivedNotFound,
     ReceivedUnhandled,
+    ReceivedSkipped(SkipReason),
 }
 
-pub async fn download_crawler_post(
-    client: &reqwest_middleware::ClientWithMiddleware,
-    shared_state: &Arc<Mutex<SharedState>>,
-    folder_path: &str,
-    media: &RedditCrawlerPost,
-) -> Result<DownloadPostResult, anyhow::Error> {
+impl DownloadPostResult {
+    /// Cache entry recording how downloading `post` went, unhandled posts are not recorded
+    pub fn into_cache_item(self, post: &RedditCrawlerPost) -> Option<FileCacheItemLatest> {
+        let item = FileCacheItemLatest {
+            id: post.id.clone(),
+            created_utc: post.created_utc,
+            title: post.title.clone(),
+            subreddit: post.subreddit.clone(),
+            url: post.url.clone(),
+            success: false,
+            index: post.index,
+            spoiler: post.spoiler,
+            author: Some(post.author.clone()),
+            upvotes: Some(post.upvotes),
+            removal: post.removal,
+            ..FileCacheItemLatest::default()
+        };
+
+        match self {
+            DownloadPostResult::ReceivedBytes(_, provenance, file_name, checksum) => {
+                Some(FileCacheItemLatest {
+                    success: true,
+                    file_name: Some(file_name),
+                    provenance,
+                    checksum: Some(checksum),
+                    ..item
+                })
+            }
+            DownloadPostResult::ReceivedFailed(error) => Some(FileCacheItemLatest {
+                error: Some(error),
+                ..item
+            }),
+            DownloadPostResult::ReceivedNotFound => Some(item),
+            DownloadPostResult::ReceivedSkipped(reason) => Some(FileCacheItemLatest {
+                skipped: Some(reason),
+                ..item
+            }),
+            DownloadPostResult::ReceivedUnhandled => None,
+        }
+    }
+}
+
+// Longest title kept in file names, the rest of the scheme needs room too
+const MAX_TITLE_LENGTH: usize = 100;
+// Longest file name most file systems allow, in bytes (ext4) or UTF-16 units (NTFS)
+const MAX_FILE_NAME_LENGTH: usize = 255;
+// Device names Windows reserves regardless of the extension, e.g. `con.jpg`
+const RESERVED_FILE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Replaces characters that are not allowed in file names on any supported platform
+pub fn sanitize_file_name_part(part: &str) -> String {
+    let sanitized = part
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() || c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    // Collapses runs of separators, e.g. from consecutive spaces
+    let mut collapsed = String::with_capacity(sanitized.len());
+    for c in sanitized.chars() {
+        if !(c == '_' && collapsed.ends_with('_')) {
+            collapsed.push(c);
+        }
+    }
+
+    collapsed.trim_matches(['_', '.', ' ']).to_owned()
+}
+
+/// Makes every folder and file name of a relative path valid on Windows, Linux and macOS
+///
+/// Characters NTFS does not allow are replaced, trailing dots and spaces trimmed, reserved
+/// device names suffixed with `_` and overlong names shortened while keeping their extension.
+pub fn sanitize_file_path(file_path: &str) -> String {
+    file_path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(sanitize_path_segment)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sanitize_path_segment(segment: &str) -> String {
+    let mut segment = segment
+        .chars()
+        .map(|c| match c {
+            '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    if segment.len() > MAX_FILE_NAME_LENGTH {
+        let extension = match segment.rsplit_once('.') {
+            Some((_, extension)) if extension.len() <= 8 => format!(".{}", extension),
+            _ => String::new(),
+        };
+        let mut end = MAX_FILE_NAME_LENGTH - extension.len();
+        while !segment.is_char_boundary(end) {
+            end -= 1;
+        }
+        segment = format!("{}{}", &segment[..end], extension);
+    }
+
+    // Windows drops trailing dots and spaces, which also rules out `.` and `..`
+    let segment = match segment.trim_end_matches(['.', ' ']) {
+        "" => "_",
+        segment => segment,
+    };
+
+    let (stem, rest) = segment.split_at(segment.find('.').unwrap_or(segment.len()));
+    match RESERVED_FILE_NAMES.contains(&stem.to_ascii_uppercase().as_str()) {
+        true => format!("{}_{}", stem, rest),
+        false => segment.to_owned(),
+    }
+}
+
+/// Turns an absolute Windows path into a `\\?\` path, which is not limited to 260 characters
+pub fn get_verbatim_path(absolute_path: &str) -> String {
+    let path = absolute_path.replace('/', "\\");
+
+    if path.starts_with(r"\\?\") {
+        return path;
+    }
+
+    match path.strip_prefix(r"\\") {
+        Some(unc_path) => format!(r"\\?\UNC\{}", unc_path),
+        None => format!(r"\\?\{}", path),
+    }
+}
+
+/// Path for file system calls, long paths get the `\\?\` prefix on Windows
+pub fn get_long_path(path: &str) -> PathBuf {
+    #[cfg(windows)]
+    {
+        // Windows APIs reject longer paths without the prefix
+        const MAX_PATH_LENGTH: usize = 260;
+
+        if path.len() >= MAX_PATH_LENGTH {
+            if let Ok(absolute) = std::path::absolute(path) {
+                return PathBuf::from(get_verbatim_path(&absolute.to_string_lossy()));
+            }
+        }
+    }
+
+    PathBuf::from(path)
+}
+
+/// Maps the `Content-Type` of a download to a file extension
+pub fn get_extension_from_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+
+    match mime.as_str() {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/avif" => Some("avif"),
+        "video/mp4" => Some("mp4"),
+        "video/webm" => Some("webm"),
+        _ => None,
+    }
+}
+
+/// Computes the file name (including extension) a post is downloaded to
+pub fn get_file_name(media: &RedditCrawlerPost, file_scheme: &str) -> String {
     let RedditCrawlerPost {
         author,
         created_utc,
         extension,
         id,
         index,
+        caption,
         provider,
-        subreddit: _subreddit,
+        spoiler,
+        subreddit,
         title,
         upvotes,
-        url,
+        ..
     } = media;
 
-    let file_scheme = String::from("{UPVOTES}_{AUTHOR}_{POSTID}_{DATE}");
     let formatted_date = created_utc.format("%Y-%m-%d").to_string();
+    let title = sanitize_file_name_part(title)
+        .chars()
+        .take(MAX_TITLE_LENGTH)
+        .collect::<String>();
+    let formatted_index = index.map(|i| i.to_string()).unwrap_or_default();
+    // Only gallery items have captions, other posts leave the placeholder empty
+    let caption = sanitize_file_name_part(caption.as_deref().unwrap_or_default())
+        .chars()
+        .take(MAX_TITLE_LENGTH)
+        .collect::<String>();
 
     let mut file_name = file_scheme
         .replace("{UPVOTES}", &upvotes.to_string())
         .replace("{AUTHOR}", &author.to_string())
         .replace("{POSTID}", &id.to_string())
-        .replace("{DATE}", &formatted_date);
+        .replace("{DATE}", &formatted_date)
+        .replace("{YEAR}", &created_utc.format("%Y").to_string())
+        .replace("{MONTH}", &created_utc.format("%m").to_string())
+        .replace("{SPOILER}", if *spoiler { "spoiler" } else { "" })
+        .replace("{SUBREDDIT}", subreddit)
+        .replace("{TITLE}", &title)
+        .replace("{PROVIDER}", &provider.to_string())
+        .replace("{INDEX}", &formatted_index)
+        .replace("{CAPTION}", &caption)
+        .replace("{EXT}", extension);
 
-    if let Some(index) = index {
+    // Schemes without {INDEX} or {EXT} still need unique gallery items and an extension
+    if let (Some(index), false) = (index, file_scheme.contains("{INDEX}")) {
         file_name = format!("{}_{}", file_name, index);
     }
 
-    let file_path = format!(
-        "./{folder_path}/{file_name}.{extension}",
-        folder_path = folder_path,
-        file_name = file_name,
-        extension = extension
-    );
-
-    let response = match provider {
-        RedditMediaProviderType::RedditImage
-        | RedditMediaProviderType::RedditGalleryImage
-        | RedditMediaProviderType::RedditGifVideo => {
-            ProviderHandlerReturned::HttpResponse(client.get(url).send().await?)
-        }
-        RedditMediaProviderType::RedditVideo => {
-            let mut child = Command::new("yt-dlp")
-                .arg(url)
-                .arg("-o")
-                .arg(&file_path)
-                .stdin(Stdio::null())
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn()
-                .expect("Spawning yt-dlp process failed");
-
-            child.wait().expect("Download with yt-dlp process failed");
-            ProviderHandlerReturned::ThirdPartyResponse(file_path.clone())
-        }
-        RedditMediaProviderType::RedgifsImage | RedditMediaProviderType::RedgifsVideo => {
-            ProviderHandlerReturned::HttpResponse(
-                download_redgifs_media(client, shared_state, url, RedgifsQuality::HD).await?,
+    let file_name = match file_scheme.contains("{EXT}") {
+        true => file_name,
+        false => format!("{}.{}", file_name, extension),
+    };
+
+    // Also drops empty folders, so posts without a subreddit, e.g. from Redgifs, stay in the
+    // folder of the resource
+    sanitize_file_path(&file_name)
+}
+
+/// Computes the path a post is downloaded to
+pub fn get_file_path(folder_path: &str, media: &RedditCrawlerPost, file_scheme: &str) -> String {
+    format!("{}/{}", folder_path, get_file_name(media, file_scheme))
+}
+
+#[tracing::instrument(
+    name = "download",
+    level = "debug",
+    skip_all,
+    fields(id = %media.id, index = ?media.index, provider = ?media.provider)
+)]
+pub async fn download_crawler_post(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    folder_path: &str,
+    media: &RedditCrawlerPost,
+    file_scheme: &str,
+) -> Result<DownloadPostResult, anyhow::Error> {
+    debug!("Downloading {}", media.url);
+    let result =
+        match download_post_media(client, shared_state, folder_path, media, file_scheme).await {
+            Err(e) if e.is::<FileTooLargeError>() => {
+                debug!("{}", e);
+                Ok(DownloadPostResult::ReceivedSkipped(
+                    SkipReason::FileTooLarge,
+                ))
+            }
+            Err(e) if e.is::<NoAudioTrackError>() => {
+                debug!("{}", e);
+                Ok(DownloadPostResult::ReceivedSkipped(
+                    SkipReason::NoAudioTrack,
+                ))
+            }
+            result => result,
+        };
+
+    if let Ok(DownloadPostResult::ReceivedBytes(bytes, _, _, _)) = &result {
+        shared_state.lock().await.received_bytes += *bytes as u64;
+    }
+
+    let reason = match &result {
+        Ok(DownloadPostResult::ReceivedBytes(bytes, _, file_name, _)) => {
+            debug!("Saved {} ({} bytes)", file_name, bytes);
+            None
+        }
+        Ok(DownloadPostResult::ReceivedNotFound) => Some(String::from("not found")),
+        Ok(DownloadPostResult::ReceivedFailed(error)) => Some(error.to_owned()),
+        Ok(DownloadPostResult::ReceivedUnhandled) => None,
+        Ok(DownloadPostResult::ReceivedSkipped(_)) => None,
+        Err(e) => Some(e.to_string()),
+    };
+
+    if let Some(reason) = &reason {
+        debug!("Download failed: {}", reason);
+        shared_state.lock().await.downloads_failed += 1;
+    }
+    shared_state.lock().await.run_report.record(media, &result);
+
+    if let Ok(DownloadPostResult::ReceivedBytes(_, _, file_name, _)) = &result {
+        let mut ss = shared_state.lock().await;
+        let resource = ss.run_report.resource.clone();
+        if let Some(global_cache) = ss.global_cache.as_mut() {
+            let file_path = format!("{}/{}", folder_path, file_name);
+            if let Err(e) = global_cache.record(media, &resource, &file_path) {
+                warn!("[GLOBAL_CACHE_FAILED] {} - {}", media.id, e);
+            }
+        }
+    }
+
+    if shared_state.lock().await.output_mode == OutputMode::Json {
+        let RedditCrawlerPost { id, index, url, .. } = media;
+
+        if let Ok(DownloadPostResult::ReceivedBytes(bytes, _, file_name, _)) = &result {
+            emit_event(&CrawlEvent::FileDownloaded {
+                id,
+                index: *index,
+                url,
+                file_name,
+                bytes: *bytes,
+            });
+        }
+
+        if let Ok(DownloadPostResult::ReceivedSkipped(reason)) = &result {
+            emit_event(&CrawlEvent::DownloadSkipped {
+                id,
+                index: *index,
+                url,
+                reason: &reason.to_string(),
+            });
+        }
+
+        if let Some(reason) = &reason {
+            emit_event(&CrawlEvent::DownloadFailed {
+                id,
+                index: *index,
+                url,
+                reason,
+            });
+        }
+    }
+
+    result
+}
+
+/// Settings of the running crawl every download of a post follows
+pub struct TransferSettings {
+    pub progress: Option<ByteProgress>,
+    pub bandwidth: Option<BandwidthLimiter>,
+    pub size_limits: SizeLimits,
+    pub embed_metadata: bool,
+    pub timeouts: TransferTimeouts,
+    pub checksum: bool,
+    // Set with `--downloader aria2c`, which then writes the files instead
+    pub aria2c: Option<Aria2cSettings>,
+    pub convert_images: Option<ImageConversion>,
+    pub video_processing: VideoProcessing,
+    // Set by `--video-tasks`, shared with the other downloads of the crawl
+    pub video_slots: Arc<Semaphore>,
+    // Only set for the video posts `--audio-only` extracts the audio of
+    pub audio_only: Option<AudioFormat>,
+    // Set for remote `--output` URLs
+    pub remote: Option<RemoteUploads>,
+}
+
+impl TransferSettings {
+    /// Whether a file is streamed to the remote output, files changed after downloading are
+    /// staged instead
+    pub fn streams_to_remote(&self, file_name: &str) -> bool {
+        self.remote.is_some()
+            && self.aria2c.is_none()
+            && !self.embed_metadata
+            && !self
+                .convert_images
+                .is_some_and(|conversion| conversion.converts(file_name))
+            && !self.video_processing.processes(file_name)
+    }
+}
+
+/// A media response written by `save_http_response`
+pub struct SavedResponse {
+    pub bytes: u64,
+    pub provenance: FileProvenance,
+    pub file_name: String,
+    /// Checksum of a file streamed to the remote output, it was not written locally
+    pub uploaded: Option<FileChecksum>,
+}
+
+/// Writes a media response, named after the served type when it differs from the post
+pub async fn save_http_response(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    response: Response,
+    folder_path: &str,
+    media: &RedditCrawlerPost,
+    file_scheme: &str,
+    transfer: &TransferSettings,
+) -> Result<SavedResponse, anyhow::Error> {
+    let TransferSettings {
+        progress,
+        bandwidth,
+        size_limits,
+        embed_metadata,
+        timeouts,
+        aria2c,
+        ..
+    } = transfer;
+    let provenance = get_file_provenance(&response);
+    // The served type wins over the extension guessed from the post
+    let file_name = match response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(get_extension_from_content_type)
+    {
+        Some(extension) if extension != media.extension => get_file_name(
+            &RedditCrawlerPost {
+                extension: extension.to_owned(),
+                ..media.clone()
+            },
+            file_scheme,
+        ),
+        _ => get_file_name(media, file_scheme),
+    };
+    let file_path = format!("{}/{}", folder_path, file_name);
+
+    if let Some(bytes) = response
+        .content_length()
+        .filter(|bytes| size_limits.exceeds_file_size(*bytes))
+    {
+        return Err(file_too_large(file_path, bytes, *size_limits));
+    }
+
+    if let Some(remote) = transfer
+        .remote
+        .as_ref()
+        .filter(|_| transfer.streams_to_remote(&file_name))
+    {
+        let checksum = upload_http_response(response, remote, &file_path, transfer).await?;
+        return Ok(SavedResponse {
+            bytes: checksum.size,
+            provenance,
+            file_name,
+            uploaded: Some(checksum),
+        });
+    }
+
+    let bytes = match aria2c {
+        // The response only told the name, aria2c requests the file again in segments
+        Some(settings) => {
+            let url = response.url().to_string();
+            drop(response);
+            download_with_aria2c(&url, &file_path, settings, bandwidth.as_ref(), *timeouts).await?
+        }
+        None => {
+            write_http_response(
+                client,
+                response,
+                &file_path,
+                progress.as_ref(),
+                bandwidth.as_ref(),
+                *timeouts,
             )
+            .await?
         }
-        RedditMediaProviderType::YoutubeVideo => {
-            let mut child = Command::new("yt-dlp")
-                .arg(url)
-                .arg("-f")
-                .arg("bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best")
-                .arg("-o")
-                .arg(&file_path)
-                .stdin(Stdio::null())
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn()
-                .expect("Spawning yt-dlp process failed");
-
-            child.wait().expect("Download with yt-dlp process failed");
-            ProviderHandlerReturned::ThirdPartyResponse(file_path.clone())
-        }
-        RedditMediaProviderType::ImgurImage => {
-            let response = client.get(url).send().await?;
-            let content_type = response.headers().get("content-type");
-            match content_type {
-                Some(value) => match value.to_str() {
-                    Ok(s) => {
-                        // Imgur returns "text/html" when the post has been deleted
-                        if s == "text/html" {
-                            ProviderHandlerReturned::NotFound
-                        } else {
-                            ProviderHandlerReturned::HttpResponse(response)
-                        }
+    };
+    // Servers without a Content-Length are only caught once the file is written
+    if size_limits.exceeds_file_size(bytes) {
+        fs::remove_file(get_long_path(&file_path))?;
+        return Err(file_too_large(file_path, bytes, *size_limits));
+    }
+    if *embed_metadata {
+        embed_post_metadata(&file_path, media);
+    }
+    set_file_timestamp(File::open(get_long_path(&file_path))?, media.created_utc).await?;
+
+    Ok(SavedResponse {
+        bytes,
+        provenance,
+        file_name,
+        uploaded: None,
+    })
+}
+
+/// Streams a response body to the remote output, hashing it on the way with `--checksum`
+///
+/// Only a single chunk is held in memory on S3. An interrupted upload is dropped and the post
+/// downloaded again by the next run, there is no `.part` file to resume.
+async fn upload_http_response(
+    response: Response,
+    remote: &RemoteUploads,
+    file_path: &str,
+    transfer: &TransferSettings,
+) -> Result<FileChecksum, anyhow::Error> {
+    let TransferSettings {
+        progress,
+        bandwidth,
+        size_limits,
+        timeouts,
+        checksum,
+        ..
+    } = transfer;
+    let expected_length = response.content_length();
+    let file_progress = progress.as_ref().map(|p| {
+        let name = file_path.rsplit('/').next().unwrap_or(file_path);
+        p.start_file(name, expected_length, 0)
+    });
+
+    let mut writer = remote.start_upload(file_path)?;
+    let mut hasher = checksum.then(blake3::Hasher::new);
+    let mut size: u64 = 0;
+    let started = Instant::now();
+    let mut stream = response.bytes_stream();
+
+    let streamed: Result<(), anyhow::Error> = async {
+        loop {
+            let (deadline, timeout) = timeouts.chunk_deadline(started, Instant::now());
+            let chunk = match time::timeout_at(deadline, stream.next()).await {
+                Ok(Some(chunk)) => chunk?,
+                Ok(None) => break,
+                Err(_) => {
+                    return Err(match timeout {
+                        TransferTimeout::Stalled => stalled(file_path, *timeouts),
+                        TransferTimeout::TimedOut => TransferTimeoutError::TimedOut {
+                            file_path: file_path.to_owned(),
+                            timeout: timeouts.download.unwrap_or_default(),
+                        },
                     }
-                    Err(_) => ProviderHandlerReturned::HttpResponse(response),
-                },
-                _ => ProviderHandlerReturned::HttpResponse(response),
+                    .into());
+                }
+            };
+            size += chunk.len() as u64;
+            // Servers without a Content-Length are caught before the whole file is uploaded
+            if size_limits.exceeds_file_size(size) {
+                return Err(file_too_large(file_path.to_owned(), size, *size_limits));
+            }
+            if let Some(bandwidth) = bandwidth {
+                bandwidth.throttle(chunk.len() as u64).await;
+            }
+            if let Some(hasher) = &mut hasher {
+                hasher.update(&chunk);
             }
+            if let Some(file_progress) = &file_progress {
+                file_progress.inc(chunk.len() as u64);
+            }
+            writer.write(chunk).await?;
+        }
+
+        match expected_length {
+            Some(expected_length) if size != expected_length => Err(anyhow!(
+                "Incomplete download of {}: received {} of {} bytes",
+                file_path,
+                size,
+                expected_length
+            )),
+            _ => Ok(()),
+        }
+    }
+    .await;
+
+    if let Err(e) = streamed {
+        writer.abort().await;
+        return Err(e);
+    }
+    writer.finish().await?;
+    debug!("Streamed {} to the remote output", file_path);
+
+    Ok(FileChecksum {
+        size,
+        blake3: hasher.map(|h| h.finalize().to_hex().to_string()),
+    })
+}
+
+/// Converts a downloaded image with `--convert-images`, a file that fails to convert is kept
+async fn convert_downloaded_image(
+    conversion: ImageConversion,
+    folder_path: &str,
+    file_name: String,
+    media: &RedditCrawlerPost,
+    transfer: &TransferSettings,
+) -> Result<String, anyhow::Error> {
+    let converted = match conversion.convert(folder_path, &file_name).await {
+        Ok(converted) => converted,
+        Err(e) => {
+            warn!("[CONVERT_FAILED] {} - {}", file_name, e);
+            return Ok(file_name);
+        }
+    };
+    restore_file_metadata(&format!("{}/{}", folder_path, converted), media, transfer).await?;
+
+    Ok(converted)
+}
+
+/// Remuxes or transcodes a downloaded video, a file that fails to process is kept
+async fn process_downloaded_video(
+    folder_path: &str,
+    file_name: String,
+    media: &RedditCrawlerPost,
+    transfer: &TransferSettings,
+) -> Result<String, anyhow::Error> {
+    // Waits for a slot of `--video-tasks`, so ffmpeg does not run once per download task
+    let _permit = transfer.video_slots.clone().acquire_owned().await?;
+
+    let processed = match transfer
+        .video_processing
+        .process(folder_path, &file_name)
+        .await
+    {
+        Ok(processed) => processed,
+        Err(e) => {
+            warn!("[VIDEO_PROCESSING_FAILED] {} - {}", file_name, e);
+            return Ok(file_name);
+        }
+    };
+    restore_file_metadata(&format!("{}/{}", folder_path, processed), media, transfer).await?;
+
+    Ok(processed)
+}
+
+/// Encoders drop the tags and timestamps of the original file
+async fn restore_file_metadata(
+    file_path: &str,
+    media: &RedditCrawlerPost,
+    transfer: &TransferSettings,
+) -> Result<(), anyhow::Error> {
+    if transfer.embed_metadata {
+        embed_post_metadata(file_path, media);
+    }
+    set_file_timestamp(File::open(get_long_path(file_path))?, media.created_utc).await?;
+
+    Ok(())
+}
+
+/// Tags a downloaded file with its post, a file that can not be tagged is still kept
+fn embed_post_metadata(file_path: &str, media: &RedditCrawlerPost) {
+    let metadata = EmbeddedMetadata::from_post(media);
+    if let Err(e) = embed_file_metadata(&get_long_path(file_path), &metadata) {
+        warn!("[EMBED_METADATA_FAILED] {} - {}", file_path, e);
+    }
+}
+
+pub fn file_too_large(file_path: String, bytes: u64, size_limits: SizeLimits) -> anyhow::Error {
+    FileTooLargeError {
+        file_path,
+        bytes,
+        limit: size_limits.max_file_size.unwrap_or_default(),
+    }
+    .into()
+}
+
+async fn download_post_media(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    folder_path: &str,
+    media: &RedditCrawlerPost,
+    file_scheme: &str,
+) -> Result<DownloadPostResult, anyhow::Error> {
+    let RedditCrawlerPost {
+        created_utc,
+        provider,
+        title,
+        ..
+    } = media;
+
+    // The media of taken down posts is gone or replaced by a placeholder
+    if media.removal.is_some_and(|removal| removal.is_removed()) {
+        return Ok(DownloadPostResult::ReceivedSkipped(SkipReason::Removed));
+    }
+
+    let (injected_failure, skip_existing, duplicate, transfer) = {
+        let ss = shared_state.lock().await;
+        if ss.size_limits.quota_reached(ss.received_bytes) {
+            return Ok(DownloadPostResult::ReceivedSkipped(
+                SkipReason::QuotaReached,
+            ));
         }
-        RedditMediaProviderType::None => {
-            println!("Skipping unsupported provider: {}", &title);
-            ProviderHandlerReturned::Unhandled
+        (
+            ss.failure_injection.and_then(|f| f.roll()),
+            ss.skip_existing,
+            // Staged files are removed once uploaded, there is nothing to link to
+            ss.link_duplicates
+                .filter(|_| ss.remote_uploads.is_none())
+                .and_then(|mode| {
+                    let original = ss.global_cache.as_ref()?.get_file_path(media)?;
+                    Some((mode, PathBuf::from(original)))
+                }),
+            TransferSettings {
+                progress: ss.byte_progress.clone(),
+                bandwidth: ss.bandwidth.clone(),
+                size_limits: ss.size_limits,
+                embed_metadata: ss.embed_metadata,
+                timeouts: ss.transfer_timeouts,
+                checksum: ss.checksum,
+                aria2c: (ss.downloader == Downloader::Aria2c).then(|| Aria2cSettings {
+                    connections: ss.aria2c_connections,
+                    proxy: ss.proxy.clone(),
+                    provider_proxies: ss.provider_proxies.clone(),
+                }),
+                convert_images: ss.convert_images,
+                video_processing: ss.video_processing,
+                video_slots: ss.video_slots.clone(),
+                audio_only: ss.audio_only.filter(|_| extracts_audio(provider)),
+                remote: ss.remote_uploads.clone(),
+            },
+        )
+    };
+
+    match injected_failure {
+        Some(InjectedFailureKind::NotFound) => return Ok(DownloadPostResult::ReceivedNotFound),
+        Some(kind) => return Err(anyhow!("Injected {:?} failure", kind)),
+        None => {}
+    }
+
+    // Extracted audio is named after its format instead of the video
+    let audio_media;
+    let media = match transfer.audio_only {
+        Some(format) => {
+            audio_media = RedditCrawlerPost {
+                extension: format.extension().to_owned(),
+                ..media.clone()
+            };
+            &audio_media
         }
+        None => media,
     };
 
-    match response {
-        ProviderHandlerReturned::HttpResponse(response) => {
-            let bytes = response.bytes().await?;
+    let file_path = get_file_path(folder_path, media, file_scheme);
+    prepare_file_folder(&file_path)?;
+
+    // A file left by a crawl whose cache was lost is recorded as downloaded again
+    if skip_existing && get_long_path(&file_path).is_file() {
+        debug!("Skipping existing file {}", file_path);
+        let checksum = get_file_checksum(&file_path, transfer.checksum).await?;
+        return Ok(DownloadPostResult::ReceivedBytes(
+            0.0,
+            None,
+            get_file_name(media, file_scheme),
+            checksum,
+        ));
+    }
+
+    // A file downloaded by another crawl is linked instead of downloaded again
+    if let Some((mode, original)) = duplicate.filter(|(_, original)| original.is_file()) {
+        let file_name = get_link_file_name(
+            &get_file_name(media, file_scheme),
+            &media.extension,
+            &original,
+        );
+        let link_path = format!("{}/{}", folder_path, file_name);
+
+        let linked = match Path::new(&link_path).exists() {
+            true => Ok(()),
+            false => mode.link(&original, &get_long_path(&link_path)),
+        };
+        match linked {
+            Ok(()) => {
+                debug!("Linked {} to {}", link_path, original.display());
+                let checksum = get_file_checksum(&link_path, transfer.checksum).await?;
+                return Ok(DownloadPostResult::ReceivedBytes(
+                    0.0, None, file_name, checksum,
+                ));
+            }
+            // e.g. hard links across file systems, the file is downloaded instead
+            Err(e) => warn!(
+                "[LINK_FAILED] {} to {} - {}",
+                link_path,
+                original.display(),
+                e
+            ),
+        }
+    }
 
-            let mut out = File::create(&file_path)?;
-            out.write_all(&bytes)?;
-            set_file_timestamp(out, *created_utc).await?;
+    let Some(provider) = PROVIDERS.get(provider) else {
+        info!("Skipping unsupported provider: {}", &title);
+        return Ok(DownloadPostResult::ReceivedUnhandled);
+    };
+    let ctx = ProviderContext {
+        client,
+        shared_state,
+        folder_path,
+        file_scheme,
+        file_path: &file_path,
+        transfer: &transfer,
+    };
 
-            Ok(DownloadPostResult::ReceivedBytes(bytes.len() as f64))
+    let (bytes, provenance, file_name) = match provider.download(&ctx, media).await? {
+        ProviderDownload::HttpResponse(response) => {
+            let saved =
+                save_http_response(client, response, folder_path, media, file_scheme, &transfer)
+                    .await?;
+            if let Some(checksum) = saved.uploaded {
+                return Ok(DownloadPostResult::ReceivedBytes(
+                    saved.bytes as f64,
+                    Some(saved.provenance),
+                    saved.file_name,
+                    checksum,
+                ));
+            }
+            (saved.bytes, Some(saved.provenance), saved.file_name)
+        }
+        ProviderDownload::Saved(received) => {
+            // The cache records a post once, with its first file
+            let bytes = received.iter().map(|saved| saved.bytes).sum::<u64>();
+            let mut received = received.into_iter();
+            let Some(first) = received.next() else {
+                return Ok(DownloadPostResult::ReceivedNotFound);
+            };
+            // The first file is uploaded below, once its checksum was taken
+            if let Some(uploads) = &transfer.remote {
+                for other in received.filter(|other| other.uploaded.is_none()) {
+                    uploads.queue(format!("{}/{}", folder_path, other.file_name), false);
+                }
+            }
+            if let Some(checksum) = first.uploaded {
+                return Ok(DownloadPostResult::ReceivedBytes(
+                    bytes as f64,
+                    Some(first.provenance),
+                    first.file_name,
+                    checksum,
+                ));
+            }
+            (bytes, Some(first.provenance), first.file_name)
         }
-        ProviderHandlerReturned::ThirdPartyResponse(fp) => {
-            let bytes = fs::metadata(fp)?.len() as f64;
-            set_file_timestamp(File::open(&file_path)?, *created_utc).await?;
-            Ok(DownloadPostResult::ReceivedBytes(bytes))
+        ProviderDownload::File(fp) => {
+            let bytes = fs::metadata(get_long_path(&fp))?.len();
+            if transfer.size_limits.exceeds_file_size(bytes) {
+                fs::remove_file(get_long_path(&fp))?;
+                return Err(file_too_large(fp, bytes, transfer.size_limits));
+            }
+            if transfer.embed_metadata {
+                embed_post_metadata(&fp, media);
+            }
+            set_file_timestamp(File::open(get_long_path(&fp))?, *created_utc).await?;
+            (bytes, None, get_file_name(media, file_scheme))
         }
-        ProviderHandlerReturned::NotFound => Ok(DownloadPostResult::ReceivedNotFound),
-        ProviderHandlerReturned::Unhandled => Ok(DownloadPostResult::ReceivedUnhandled),
+        ProviderDownload::NotFound => return Ok(DownloadPostResult::ReceivedNotFound),
+        ProviderDownload::Failed(error) => return Ok(DownloadPostResult::ReceivedFailed(error)),
+    };
+
+    let file_name = match transfer.convert_images {
+        Some(conversion) if conversion.converts(&file_name) => {
+            convert_downloaded_image(conversion, folder_path, file_name, media, &transfer).await?
+        }
+        _ => file_name,
+    };
+    let file_name = match transfer.video_processing.processes(&file_name) {
+        true => process_downloaded_video(folder_path, file_name, media, &transfer).await?,
+        false => file_name,
+    };
+
+    // Taken after embedding metadata, so it matches the file as it stays on disk
+    let checksum =
+        get_file_checksum(&format!("{}/{}", folder_path, file_name), transfer.checksum).await?;
+    if let Some(uploads) = &transfer.remote {
+        uploads.queue(format!("{}/{}", folder_path, file_name), false);
     }
+
+    Ok(DownloadPostResult::ReceivedBytes(
+        bytes as f64,
+        provenance,
+        file_name,
+        checksum,
+    ))
 }
@@ -0,0 +1,142 @@
+use super::{find_naming_conflicts, get_file_name, print_naming_conflicts};
+use crate::reddit_parser::{RedditCrawlerPost, RedditMediaProviderType};
+use futures_util::{stream, StreamExt};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// A file that would be downloaded without `--dry-run`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunItem {
+    pub id: String,
+    pub index: Option<usize>,
+    pub provider: String,
+    pub file_name: String,
+    pub url: String,
+    // Only known for files that are served directly, read from a HEAD request
+    pub size: Option<u64>,
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.2} MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+async fn estimate_size(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    post: &RedditCrawlerPost,
+) -> Option<u64> {
+    match post.provider {
+        RedditMediaProviderType::RedditImage
+        | RedditMediaProviderType::RedditGalleryImage
+        | RedditMediaProviderType::RedditGifVideo
+        | RedditMediaProviderType::ImgurImage => {}
+        // Resolving the media of the other providers needs additional API requests
+        _ => return None,
+    }
+
+    let response = client.head(&post.url).send().await.ok()?;
+    match response.status().is_success() {
+        true => response.content_length(),
+        false => None,
+    }
+}
+
+/// Lists the files of `posts` with a size estimate, without downloading them
+pub async fn get_dry_run_items(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+    concurrency: u16,
+) -> Vec<DryRunItem> {
+    stream::iter(posts)
+        .map(|post| async move {
+            DryRunItem {
+                id: post.id.clone(),
+                index: post.index,
+                provider: post.provider.to_string(),
+                file_name: get_file_name(post, file_scheme),
+                url: post.url.clone(),
+                size: estimate_size(client, post).await,
+            }
+        })
+        .buffered(concurrency.max(1) as usize)
+        .collect()
+        .await
+}
+
+pub fn render_dry_run_table(items: &[DryRunItem]) -> String {
+    let provider_width = items
+        .iter()
+        .map(|i| i.provider.len())
+        .chain(["PROVIDER".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut table = format!(
+        "{:<provider_width$}  {:>10}  URL\n",
+        "PROVIDER",
+        "SIZE",
+        provider_width = provider_width
+    );
+
+    for item in items {
+        let size = item
+            .size
+            .map(format_size)
+            .unwrap_or_else(|| String::from("?"));
+        table.push_str(&format!(
+            "{:<provider_width$}  {:>10}  {}\n",
+            item.provider,
+            size,
+            item.url,
+            provider_width = provider_width
+        ));
+    }
+
+    table
+}
+
+/// Prints what would be downloaded to `folder_path` and the naming conflicts it would cause
+pub async fn print_dry_run(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    folder_path: &str,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+    concurrency: u16,
+    format: OutputFormat,
+) -> Result<(), anyhow::Error> {
+    let items = get_dry_run_items(client, posts, file_scheme, concurrency).await;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&items)?),
+        OutputFormat::Table => {
+            println!(
+                "{}",
+                format_args!(
+                    "{} {}",
+                    "[FLAG]".red().bold(),
+                    "Dry run, nothing is written".bold()
+                ),
+            );
+            print!("{}", render_dry_run_table(&items));
+
+            let known_size = items.iter().filter_map(|i| i.size).sum::<u64>();
+            println!(
+                "{} files, at least {}",
+                items.len().to_string().bold(),
+                format_size(known_size)
+            );
+
+            print_naming_conflicts(&find_naming_conflicts(folder_path, posts, file_scheme));
+        }
+    }
+
+    Ok(())
+}
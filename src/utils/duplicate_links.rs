@@ -0,0 +1,36 @@
+use super::views::symlink_file;
+use std::{fs, path::Path};
+
+/// How `--link-duplicates` points a crawl at a file another crawl already downloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LinkMode {
+    // Both paths share the data, deleting one keeps the other intact
+    Hardlink,
+    // Points to the absolute path of the file, breaks when it is moved or deleted
+    Symlink,
+}
+
+impl LinkMode {
+    pub fn link(self, original: &Path, link: &Path) -> Result<(), std::io::Error> {
+        match self {
+            LinkMode::Hardlink => fs::hard_link(original, link),
+            LinkMode::Symlink => symlink_file(&fs::canonicalize(original)?, link),
+        }
+    }
+}
+
+/// Name of the link to `original`, whose extension may differ from the post after conversions
+/// or when the served type did not match
+pub fn get_link_file_name(file_name: &str, extension: &str, original: &Path) -> String {
+    let stem = file_name
+        .strip_suffix(extension)
+        .and_then(|stem| stem.strip_suffix('.'))
+        .unwrap_or(file_name);
+
+    match original.extension() {
+        Some(original_extension) => {
+            format!("{}.{}", stem, original_extension.to_string_lossy())
+        }
+        None => stem.to_owned(),
+    }
+}
@@ -0,0 +1,340 @@
+use crate::reddit_parser::RedditCrawlerPost;
+use chrono::{DateTime, Utc};
+use img_parts::{
+    jpeg::{markers, JpegSegment},
+    png::PngChunk,
+    Bytes, DynImage, ImageEXIF,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+    process::{Command, Stdio},
+};
+use thiserror::Error;
+
+// Identify the EXIF and XMP packets among the APP1 segments of a JPEG
+const JPEG_EXIF_PREFIX: &[u8] = b"Exif\0\0";
+const JPEG_XMP_PREFIX: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+// iTXt keyword of the XMP packet in a PNG, followed by an uncompressed empty language and tag
+const PNG_XMP_PREFIX: &[u8] = b"XML:com.adobe.xmp\0\0\0\0\0";
+const PNG_CHUNK_ITXT: [u8; 4] = *b"iTXt";
+
+const EXIF_TYPE_ASCII: u16 = 2;
+const EXIF_TYPE_LONG: u16 = 4;
+const EXIF_TAG_IMAGE_DESCRIPTION: u16 = 0x010e;
+const EXIF_TAG_DATE_TIME: u16 = 0x0132;
+const EXIF_TAG_ARTIST: u16 = 0x013b;
+const EXIF_TAG_EXIF_IFD: u16 = 0x8769;
+const EXIF_TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const EXIF_TAG_USER_COMMENT: u16 = 0x9286;
+
+#[derive(Error, Debug)]
+pub enum EmbedMetadataError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Image error: {0}")]
+    Image(#[from] img_parts::Error),
+    #[error("ffmpeg failed to tag {0}")]
+    Ffmpeg(String),
+}
+
+/// Provenance of a post written into its files by `--embed-metadata`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedMetadata {
+    pub title: String,
+    pub author: String,
+    pub permalink: String,
+    pub created_utc: DateTime<Utc>,
+}
+
+impl EmbeddedMetadata {
+    pub fn from_post(post: &RedditCrawlerPost) -> Self {
+        // Redgifs user posts have no Reddit post to link to
+        let permalink = match post.subreddit.is_empty() {
+            true => post.url.clone(),
+            false => format!(
+                "https://www.reddit.com/r/{}/comments/{}",
+                post.subreddit, post.id
+            ),
+        };
+
+        Self {
+            title: post.title.clone(),
+            author: post.author.clone(),
+            permalink,
+            created_utc: post.created_utc,
+        }
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Dublin Core title, creator and source plus the creation date as an XMP packet
+pub fn get_xmp_packet(metadata: &EmbeddedMetadata) -> String {
+    format!(
+        concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">",
+            "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+            "<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" ",
+            "xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">",
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+            "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+            "<dc:source>{}</dc:source>",
+            "<xmp:CreateDate>{}</xmp:CreateDate>",
+            "</rdf:Description>",
+            "</rdf:RDF>",
+            "</x:xmpmeta>",
+            "<?xpacket end=\"w\"?>"
+        ),
+        escape_xml(&metadata.title),
+        escape_xml(&metadata.author),
+        escape_xml(&metadata.permalink),
+        metadata
+            .created_utc
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    )
+}
+
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    value: Vec<u8>,
+}
+
+impl IfdEntry {
+    fn ascii(tag: u16, value: &str) -> Self {
+        let mut value = value.as_bytes().to_vec();
+        value.push(0);
+        Self {
+            tag,
+            kind: EXIF_TYPE_ASCII,
+            count: value.len() as u32,
+            value,
+        }
+    }
+
+    fn long(tag: u16, value: u32) -> Self {
+        Self {
+            tag,
+            kind: EXIF_TYPE_LONG,
+            count: 1,
+            value: value.to_le_bytes().to_vec(),
+        }
+    }
+
+    // Values of up to 4 bytes are stored in the entry, longer ones after the directory
+    fn data_len(&self) -> usize {
+        match self.value.len() {
+            0..=4 => 0,
+            len => len + len % 2,
+        }
+    }
+}
+
+fn get_ifd_len(entries: &[IfdEntry]) -> usize {
+    2 + entries.len() * 12 + 4 + entries.iter().map(IfdEntry::data_len).sum::<usize>()
+}
+
+/// Appends an image file directory, `out` starts with the TIFF header offsets are relative to
+fn write_ifd(out: &mut Vec<u8>, entries: &[IfdEntry]) {
+    let mut data_offset = out.len() + 2 + entries.len() * 12 + 4;
+    let mut data = Vec::new();
+
+    out.extend((entries.len() as u16).to_le_bytes());
+    for entry in entries {
+        out.extend(entry.tag.to_le_bytes());
+        out.extend(entry.kind.to_le_bytes());
+        out.extend(entry.count.to_le_bytes());
+
+        match entry.data_len() {
+            0 => {
+                let mut value = entry.value.clone();
+                value.resize(4, 0);
+                out.extend(value);
+            }
+            len => {
+                out.extend((data_offset as u32).to_le_bytes());
+                data.extend(&entry.value);
+                data.resize(data.len() + len - entry.value.len(), 0);
+                data_offset += len;
+            }
+        }
+    }
+    // No further directories follow
+    out.extend(0u32.to_le_bytes());
+    out.extend(data);
+}
+
+/// Little endian TIFF structure with the title, author, permalink and date of a post
+pub fn get_exif(metadata: &EmbeddedMetadata) -> Vec<u8> {
+    let date_time = metadata.created_utc.format("%Y:%m:%d %H:%M:%S").to_string();
+    // User comments start with their character code, the permalink is plain ASCII
+    let mut user_comment = b"ASCII\0\0\0".to_vec();
+    user_comment.extend(metadata.permalink.as_bytes());
+
+    let mut ifd0 = vec![
+        IfdEntry::ascii(EXIF_TAG_IMAGE_DESCRIPTION, &metadata.title),
+        IfdEntry::ascii(EXIF_TAG_DATE_TIME, &date_time),
+        IfdEntry::ascii(EXIF_TAG_ARTIST, &metadata.author),
+        IfdEntry::long(EXIF_TAG_EXIF_IFD, 0),
+    ];
+    let exif_ifd = vec![
+        IfdEntry::ascii(EXIF_TAG_DATE_TIME_ORIGINAL, &date_time),
+        IfdEntry {
+            tag: EXIF_TAG_USER_COMMENT,
+            // Undefined, the bytes are not null terminated
+            kind: 7,
+            count: user_comment.len() as u32,
+            value: user_comment,
+        },
+    ];
+
+    // The Exif directory follows the first one
+    let exif_ifd_offset = 8 + get_ifd_len(&ifd0);
+    ifd0[3] = IfdEntry::long(EXIF_TAG_EXIF_IFD, exif_ifd_offset as u32);
+
+    let mut out = b"II\x2a\x00\x08\x00\x00\x00".to_vec();
+    write_ifd(&mut out, &ifd0);
+    write_ifd(&mut out, &exif_ifd);
+    out
+}
+
+fn is_xmp_segment(segment: &JpegSegment) -> bool {
+    segment.marker() == markers::APP1 && segment.contents().starts_with(JPEG_XMP_PREFIX)
+}
+
+/// Writes EXIF and XMP into a JPEG, PNG or WebP, returns `false` for other files
+///
+/// EXIF the image already carries, e.g. from the camera, is kept. WebP only gets the EXIF.
+pub fn embed_image_metadata(
+    file_path: &Path,
+    metadata: &EmbeddedMetadata,
+) -> Result<bool, EmbedMetadataError> {
+    let Some(mut image) = DynImage::from_bytes(Bytes::from(fs::read(file_path)?))? else {
+        return Ok(false);
+    };
+
+    let has_exif = image.exif().is_some();
+    let xmp = get_xmp_packet(metadata);
+    match &mut image {
+        DynImage::Jpeg(jpeg) => {
+            let segments = jpeg.segments_mut();
+            segments.retain(|segment| !is_xmp_segment(segment));
+            // Metadata segments come before the tables and image data
+            let mut position = segments
+                .iter()
+                .position(|s| !(markers::APP0..=markers::APP15).contains(&s.marker()))
+                .unwrap_or(segments.len());
+            // img-parts inserts EXIF at a fixed index, which minimal files do not reach
+            if !has_exif {
+                let contents = [JPEG_EXIF_PREFIX, &get_exif(metadata)].concat();
+                segments.insert(
+                    position,
+                    JpegSegment::new_with_contents(markers::APP1, Bytes::from(contents)),
+                );
+                position += 1;
+            }
+            let contents = [JPEG_XMP_PREFIX, xmp.as_bytes()].concat();
+            segments.insert(
+                position,
+                JpegSegment::new_with_contents(markers::APP1, Bytes::from(contents)),
+            );
+        }
+        DynImage::Png(png) => {
+            if !has_exif {
+                png.set_exif(Some(Bytes::from(get_exif(metadata))));
+            }
+            let chunks = png.chunks_mut();
+            chunks.retain(|chunk| {
+                !(chunk.kind() == PNG_CHUNK_ITXT && chunk.contents().starts_with(PNG_XMP_PREFIX))
+            });
+            let contents = [PNG_XMP_PREFIX, xmp.as_bytes()].concat();
+            // Placed before the closing IEND chunk
+            chunks.insert(
+                chunks.len().saturating_sub(1),
+                PngChunk::new(PNG_CHUNK_ITXT, Bytes::from(contents)),
+            );
+        }
+        DynImage::WebP(webp) => {
+            if !has_exif {
+                webp.set_exif(Some(Bytes::from(get_exif(metadata))));
+            }
+        }
+    }
+
+    let partial_path = format!("{}.meta", file_path.display());
+    image
+        .encoder()
+        .write_to(BufWriter::new(File::create(&partial_path)?))?;
+    fs::rename(&partial_path, file_path)?;
+
+    Ok(true)
+}
+
+/// Tags an mp4 with the metadata of its post, the streams are copied without re-encoding
+pub fn embed_video_metadata(
+    file_path: &Path,
+    metadata: &EmbeddedMetadata,
+) -> Result<(), EmbedMetadataError> {
+    let partial_path = format!("{}.meta.mp4", file_path.display());
+    let tagged = Command::new("ffmpeg")
+        .arg("-y")
+        .args(["-loglevel", "error"])
+        .arg("-i")
+        .arg(file_path)
+        .args(["-map", "0", "-c", "copy"])
+        .arg("-metadata")
+        .arg(format!("title={}", metadata.title))
+        .arg("-metadata")
+        .arg(format!("artist={}", metadata.author))
+        .arg("-metadata")
+        .arg(format!("comment={}", metadata.permalink))
+        .arg("-metadata")
+        .arg(format!(
+            "creation_time={}",
+            metadata
+                .created_utc
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ))
+        .arg(&partial_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    if !tagged {
+        let _ = fs::remove_file(&partial_path);
+        return Err(EmbedMetadataError::Ffmpeg(file_path.display().to_string()));
+    }
+
+    fs::rename(&partial_path, file_path)?;
+    Ok(())
+}
+
+/// Embeds the metadata of a post into a downloaded file, formats without tags are left alone
+pub fn embed_file_metadata(
+    file_path: &Path,
+    metadata: &EmbeddedMetadata,
+) -> Result<(), EmbedMetadataError> {
+    let extension = file_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("jpg" | "jpeg" | "png" | "webp") => {
+            embed_image_metadata(file_path, metadata).map(|_| ())
+        }
+        Some("mp4") => embed_video_metadata(file_path, metadata),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,59 @@
+use super::state::SharedState;
+use crate::{providers::PROVIDERS, reddit_parser::RedditCrawlerPost};
+use futures_util::{stream, StreamExt};
+use std::{fs::OpenOptions, io::Write, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Direct URLs of the files of `posts`, posts that fail to resolve are logged and left out
+pub async fn get_media_urls(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    posts: &[RedditCrawlerPost],
+    concurrency: u16,
+) -> Vec<String> {
+    let resolved = stream::iter(posts)
+        .map(|post| async move {
+            match PROVIDERS.get(&post.provider) {
+                Some(provider) => provider.resolve_urls(client, shared_state, post).await,
+                None => Ok(Vec::new()),
+            }
+        })
+        .buffered(concurrency.max(1) as usize)
+        .collect::<Vec<_>>()
+        .await;
+
+    posts
+        .iter()
+        .zip(resolved)
+        .flat_map(|(post, urls)| {
+            urls.unwrap_or_else(|e| {
+                warn!("[EXPORT_URLS_FAILED] {} - {}", post.url, e);
+                Vec::new()
+            })
+        })
+        .collect()
+}
+
+/// Appends the direct URLs of `posts` to `file_path`, one per line, instead of downloading them
+pub async fn export_media_urls(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    posts: &[RedditCrawlerPost],
+    file_path: &str,
+    concurrency: u16,
+) -> Result<(), anyhow::Error> {
+    let urls = get_media_urls(client, shared_state, posts, concurrency).await;
+
+    // Appended, so a batch of targets ends up in a single list
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    for url in urls.iter() {
+        writeln!(file, "{}", url)?;
+    }
+
+    info!("Exported {} URLs to {}", urls.len(), file_path);
+    Ok(())
+}
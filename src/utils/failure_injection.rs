@@ -0,0 +1,79 @@
+use rand::Rng;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedFailureKind {
+    Timeout,
+    Error,
+    NotFound,
+    RateLimit,
+}
+
+impl FromStr for InjectedFailureKind {
+    type Err = FailureInjectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "timeout" => Ok(InjectedFailureKind::Timeout),
+            "error" => Ok(InjectedFailureKind::Error),
+            "notfound" => Ok(InjectedFailureKind::NotFound),
+            "ratelimit" => Ok(InjectedFailureKind::RateLimit),
+            _ => Err(FailureInjectionError::Kind(s.to_owned())),
+        }
+    }
+}
+
+/// Randomly fails downloads and requests for resilience testing, e.g. `rate=0.1,kind=timeout`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailureInjection {
+    pub rate: f64,
+    pub kind: InjectedFailureKind,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FailureInjectionError {
+    #[error("Invalid failure rate {0} - expected a number between 0.0 and 1.0")]
+    Rate(String),
+    #[error("Invalid failure kind {0} - expected timeout|error|notfound|ratelimit")]
+    Kind(String),
+    #[error("Invalid failure injection option {0}")]
+    Option(String),
+}
+
+impl FromStr for FailureInjection {
+    type Err = FailureInjectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut injection = FailureInjection {
+            rate: 0.0,
+            kind: InjectedFailureKind::Error,
+        };
+
+        for option in s.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            match option.split_once('=') {
+                Some(("rate", value)) => {
+                    injection.rate = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|r| (0.0..=1.0).contains(r))
+                        .ok_or_else(|| FailureInjectionError::Rate(value.to_owned()))?;
+                }
+                Some(("kind", value)) => {
+                    injection.kind = value.parse()?;
+                }
+                _ => return Err(FailureInjectionError::Option(option.to_owned())),
+            }
+        }
+
+        Ok(injection)
+    }
+}
+
+impl FailureInjection {
+    /// Rolls the dice and returns the kind of failure to simulate, if any
+    pub fn roll(&self) -> Option<InjectedFailureKind> {
+        if self.rate > 0.0 && rand::thread_rng().gen_bool(self.rate) {
+            return Some(self.kind);
+        }
+        None
+    }
+}
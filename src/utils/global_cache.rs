@@ -0,0 +1,109 @@
+use super::expand_path;
+use crate::reddit_parser::RedditCrawlerPost;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// Runs sharing the database wait this long for each other's writes
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts downloaded by any crawl, set by `--global-cache` so a post downloaded for a user is
+/// skipped when its subreddit is crawled and the other way around
+#[derive(Debug, Clone)]
+pub struct GlobalCache {
+    connection: Arc<Mutex<Connection>>,
+    ids: HashSet<String>,
+}
+
+impl GlobalCache {
+    /// Opens the SQLite database at `path`, creating it and its folder on first use
+    pub fn open(path: &str) -> Result<Self, anyhow::Error> {
+        let path = expand_path(path)?;
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(&path)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS downloads (
+                id TEXT NOT NULL,
+                item INTEGER NOT NULL,
+                resource TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                downloaded_at TEXT NOT NULL,
+                PRIMARY KEY (id, item)
+            )",
+        )?;
+
+        let ids = connection
+            .prepare("SELECT DISTINCT id FROM downloads")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            ids,
+        })
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Path a file of `post` was first downloaded to, `--link-duplicates` links it
+    pub fn get_file_path(&self, post: &RedditCrawlerPost) -> Option<String> {
+        if !self.contains(&post.id) {
+            return None;
+        }
+
+        self.connection
+            .lock()
+            .ok()?
+            .query_row(
+                "SELECT file_path FROM downloads WHERE id = ?1 AND item = ?2",
+                params![post.id, post.index.unwrap_or_default() as i64],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+    }
+
+    /// Records a downloaded file of `post`, gallery items are recorded one by one
+    pub fn record(
+        &mut self,
+        post: &RedditCrawlerPost,
+        resource: &str,
+        file_path: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Global cache connection poisoned"))?
+            .execute(
+                "INSERT OR IGNORE INTO downloads (id, item, resource, file_path, downloaded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    post.id,
+                    post.index.unwrap_or_default() as i64,
+                    resource,
+                    file_path,
+                    Utc::now().to_rfc3339()
+                ],
+            )?;
+        self.ids.insert(post.id.clone());
+        Ok(())
+    }
+}
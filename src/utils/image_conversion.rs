@@ -0,0 +1,162 @@
+use super::get_long_path;
+use std::{fs, path::Path, process::Stdio};
+use thiserror::Error;
+use tokio::process::Command;
+
+pub const DEFAULT_CONVERT_QUALITY: u8 = 80;
+
+/// Format downloaded images are converted to with `--convert-images`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImageFormat {
+    /// Encoded by avifenc
+    Avif,
+    /// Encoded by ffmpeg
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Avif => "avif",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    /// CLI tool encoding the format
+    pub fn program(&self) -> &'static str {
+        match self {
+            ImageFormat::Avif => "avifenc",
+            ImageFormat::Webp => "ffmpeg",
+        }
+    }
+
+    /// Whether the encoder can be started, checked once before crawling
+    pub fn is_installed(&self) -> bool {
+        let version = match self {
+            ImageFormat::Avif => "--version",
+            ImageFormat::Webp => "-version",
+        };
+        std::process::Command::new(self.program())
+            .arg(version)
+            .output()
+            .is_ok()
+    }
+}
+
+/// Set by `--convert-images` and `--convert-quality`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageConversion {
+    pub format: ImageFormat,
+    // 0 to 100, higher keeps more detail
+    pub quality: u8,
+}
+
+#[derive(Error, Debug)]
+pub enum ImageConversionError {
+    #[error("Spawning {program} failed: {source}")]
+    Spawn {
+        program: &'static str,
+        source: std::io::Error,
+    },
+    #[error("{program} exited with code {code:?}: {message}")]
+    Failed {
+        program: &'static str,
+        code: Option<i32>,
+        message: String,
+    },
+}
+
+impl ImageConversion {
+    /// Whether a downloaded file is converted, animated gifs and videos are kept as they are
+    pub fn converts(&self, file_name: &str) -> bool {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some(extension) if extension == self.format.extension() => false,
+            Some("jpg" | "jpeg" | "png" | "webp") => true,
+            _ => false,
+        }
+    }
+
+    /// Arguments encoding `input` to `output`
+    pub fn get_args(&self, input: &str, output: &str) -> Vec<String> {
+        let quality = self.quality.min(100).to_string();
+
+        match self.format {
+            ImageFormat::Avif => vec![
+                String::from("-q"),
+                quality,
+                input.to_owned(),
+                output.to_owned(),
+            ],
+            ImageFormat::Webp => vec![
+                String::from("-y"),
+                String::from("-loglevel"),
+                String::from("error"),
+                String::from("-i"),
+                input.to_owned(),
+                String::from("-quality"),
+                quality,
+                output.to_owned(),
+            ],
+        }
+    }
+
+    /// Converts the file `file_name` inside `folder_path` and removes the original, returns the
+    /// name of the converted file
+    pub async fn convert(
+        &self,
+        folder_path: &str,
+        file_name: &str,
+    ) -> Result<String, anyhow::Error> {
+        let program = self.format.program();
+        let converted_name = Path::new(file_name)
+            .with_extension(self.format.extension())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let input = format!("{}/{}", folder_path, file_name);
+        let output = format!("{}/{}", folder_path, converted_name);
+        // Named so encoders still pick the format from the extension
+        let partial = format!(
+            "{}/{}",
+            folder_path,
+            Path::new(file_name)
+                .with_extension(format!("part.{}", self.format.extension()))
+                .to_string_lossy()
+        );
+
+        let long_input = get_long_path(&input).to_string_lossy().to_string();
+        let long_partial = get_long_path(&partial).to_string_lossy().to_string();
+        let result = Command::new(program)
+            .args(self.get_args(&long_input, &long_partial))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|source| ImageConversionError::Spawn { program, source })?;
+
+        if !result.status.success() {
+            let _ = fs::remove_file(get_long_path(&partial));
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(ImageConversionError::Failed {
+                program,
+                code: result.status.code(),
+                message: stderr
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|l| !l.is_empty())
+                    .unwrap_or("no output")
+                    .to_owned(),
+            }
+            .into());
+        }
+
+        fs::rename(get_long_path(&partial), get_long_path(&output))?;
+        fs::remove_file(get_long_path(&input))?;
+        Ok(converted_name)
+    }
+}
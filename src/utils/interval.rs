@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+/// Parses an interval like `90s`, `15m`, `6h` or `1d`, plain numbers are seconds
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (amount, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| format!("{} is not a valid interval (e.g. 15m)", value))?;
+
+    let seconds = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 60 * 60,
+        "d" => amount * 60 * 60 * 24,
+        _ => return Err(format!("Unknown interval unit {}, use s|m|h|d", unit)),
+    };
+
+    match seconds {
+        0 => Err(String::from("The interval must be greater than 0")),
+        _ => Ok(Duration::from_secs(seconds)),
+    }
+}
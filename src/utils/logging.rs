@@ -0,0 +1,56 @@
+use std::{fs::OpenOptions, io, sync::Mutex};
+use tracing_subscriber::{
+    field::MakeExt, fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
+};
+
+/// Level of the crawler's own logs, `-q` only keeps warnings and every `-v` adds a level
+pub fn get_log_level(verbose: u8, quiet: bool) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "warn",
+        (false, 0) => "info",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    }
+}
+
+/// Logs to stderr and optionally appends to a log file, `RUST_LOG` replaces the level flags
+pub fn init_logging(verbose: u8, quiet: bool, log_file: Option<&str>) -> io::Result<()> {
+    // Dependencies stay at warn so -v does not flood the output with HTTP internals
+    let directives = format!("warn,reddit_clawler={}", get_log_level(verbose, quiet));
+    let filter =
+        || EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&directives));
+
+    let console = fmt::layer()
+        .with_writer(io::stderr)
+        .without_time()
+        .with_target(false)
+        .with_level(verbose > 0)
+        .with_filter(filter());
+
+    let file = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            // A separate field formatter keeps the colored span fields of stderr out of the file
+            let fields = fmt::format::debug_fn(|writer, field, value| match field.name() {
+                "message" => write!(writer, "{:?}", value),
+                name => write!(writer, "{}={:?}", name, value),
+            })
+            .delimited(" ");
+            Some(
+                fmt::layer()
+                    .with_writer(Mutex::new(file))
+                    .with_ansi(false)
+                    .fmt_fields(fields)
+                    .with_filter(filter()),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file)
+        .init();
+
+    Ok(())
+}
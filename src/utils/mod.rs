@@ -1,9 +1,115 @@
+mod about_cache;
+mod archive_export;
+mod archive_import;
+mod archive_stats;
+mod aria2c;
+mod audio_extraction;
+mod author_expansion;
+mod author_list;
+mod bandwidth;
+mod batch_targets;
+mod cache_maintenance;
+mod cache_rebuild;
+mod cached_pages;
+mod caption_markdown;
 mod check_deps;
 mod check_file_scheme;
+mod checkpoint;
+mod checksum;
+mod cookies;
+mod crawl_events;
 mod download_progress;
 mod downloader;
+mod dry_run;
+mod duplicate_links;
+mod embed_metadata;
+mod export_urls;
+mod failure_injection;
+mod global_cache;
+mod image_conversion;
+mod interval;
+mod logging;
+mod naming_conflicts;
+mod notify;
+mod output_path;
+mod post_comments;
+mod post_filter;
+mod post_metadata;
+mod post_removal;
+mod proxy;
+mod rate_limiter;
+mod reddit_video;
+mod remote_storage;
+mod report;
+mod retry_policy;
+mod run_report;
+mod schedule;
+mod shutdown;
+mod size_limits;
 pub mod state;
+mod status_server;
+mod takedown_diff;
+mod transfer_status;
+mod transfer_timeout;
+mod unsupported_posts;
+mod user_agent;
+mod user_comments;
+mod video_processing;
+mod views;
+pub use about_cache::*;
+pub use archive_export::*;
+pub use archive_import::*;
+pub use archive_stats::*;
+pub use aria2c::*;
+pub use audio_extraction::*;
+pub use author_expansion::*;
+pub use author_list::*;
+pub use bandwidth::*;
+pub use batch_targets::*;
+pub use cache_maintenance::*;
+pub use cache_rebuild::*;
+pub use cached_pages::*;
+pub use caption_markdown::*;
 pub use check_deps::*;
 pub use check_file_scheme::*;
+pub use checkpoint::*;
+pub use checksum::*;
+pub use cookies::*;
+pub use crawl_events::*;
 pub use download_progress::*;
 pub use downloader::*;
+pub use dry_run::*;
+pub use duplicate_links::*;
+pub use embed_metadata::*;
+pub use export_urls::*;
+pub use failure_injection::*;
+pub use global_cache::*;
+pub use image_conversion::*;
+pub use interval::*;
+pub use logging::*;
+pub use naming_conflicts::*;
+pub use notify::*;
+pub use output_path::*;
+pub use post_comments::*;
+pub use post_filter::*;
+pub use post_metadata::*;
+pub use post_removal::*;
+pub use proxy::*;
+pub use rate_limiter::*;
+pub use reddit_video::*;
+pub use remote_storage::*;
+pub use report::*;
+pub use retry_policy::*;
+pub use run_report::*;
+pub use schedule::*;
+pub use shutdown::*;
+pub use size_limits::*;
+pub use status_server::*;
+pub use takedown_diff::*;
+pub use transfer_status::*;
+pub use transfer_timeout::*;
+pub use unsupported_posts::*;
+pub use user_agent::*;
+pub use user_comments::*;
+pub use video_processing::*;
+pub use views::*;
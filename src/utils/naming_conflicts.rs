@@ -0,0 +1,100 @@
+use super::get_file_path;
+use crate::reddit_parser::RedditCrawlerPost;
+use owo_colors::OwoColorize;
+use std::{collections::BTreeMap, path::Path};
+
+// Windows `MAX_PATH`, the strictest limit of the supported platforms
+pub const MAX_PATH_LENGTH: usize = 260;
+// Most file systems limit a single path component to 255 bytes
+pub const MAX_FILE_NAME_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamingConflict {
+    /// Different posts resolve to the same file and would overwrite each other
+    Collision {
+        file_path: String,
+        post_ids: Vec<String>,
+    },
+    /// The resulting path exceeds the platform limits
+    PathTooLong {
+        file_path: String,
+        post_id: String,
+        length: usize,
+    },
+}
+
+/// Detects file name collisions and path length violations before anything is downloaded
+pub fn find_naming_conflicts(
+    folder_path: &str,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> Vec<NamingConflict> {
+    let mut paths: BTreeMap<String, Vec<(&str, Option<usize>)>> = BTreeMap::new();
+    let mut conflicts: Vec<NamingConflict> = Vec::new();
+
+    for post in posts {
+        let file_path = get_file_path(folder_path, post, file_scheme);
+        let file_name_length = Path::new(&file_path)
+            .file_name()
+            .map(|f| f.len())
+            .unwrap_or_default();
+
+        if file_path.len() > MAX_PATH_LENGTH || file_name_length > MAX_FILE_NAME_LENGTH {
+            conflicts.push(NamingConflict::PathTooLong {
+                file_path: file_path.clone(),
+                post_id: post.id.clone(),
+                length: file_path.len(),
+            });
+        }
+
+        let entries = paths.entry(file_path).or_default();
+        // The same gallery item listed twice is not a collision
+        if !entries.contains(&(post.id.as_str(), post.index)) {
+            entries.push((post.id.as_str(), post.index));
+        }
+    }
+
+    for (file_path, entries) in paths {
+        if entries.len() > 1 {
+            conflicts.push(NamingConflict::Collision {
+                file_path,
+                post_ids: entries.iter().map(|(id, _)| id.to_string()).collect(),
+            });
+        }
+    }
+
+    conflicts
+}
+
+pub fn print_naming_conflicts(conflicts: &[NamingConflict]) {
+    if conflicts.is_empty() {
+        println!("No file naming conflicts found");
+        return;
+    }
+
+    for conflict in conflicts {
+        match conflict {
+            NamingConflict::Collision {
+                file_path,
+                post_ids,
+            } => println!(
+                "{} {} is used by posts {}",
+                "[COLLISION]".bold().red(),
+                file_path.bold(),
+                post_ids.join(", ")
+            ),
+            NamingConflict::PathTooLong {
+                file_path,
+                post_id,
+                length,
+            } => println!(
+                "{} {} of post {} has {} characters (max. {})",
+                "[PATH_TOO_LONG]".bold().red(),
+                file_path.bold(),
+                post_id,
+                length,
+                MAX_PATH_LENGTH
+            ),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use reqwest::{header::CONTENT_TYPE, Url};
+use serde::Serialize;
+use serde_json::json;
+use std::process::Command;
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    #[error("Webhook request failed: {0}")]
+    Webhook(#[from] reqwest_middleware::Error),
+    #[error("Webhook responded with {0}")]
+    WebhookStatus(reqwest::StatusCode),
+    #[error("Failed to run {0}: {1}")]
+    Command(String, std::io::Error),
+    #[error("{0} exited with {1}")]
+    CommandStatus(String, std::process::ExitStatus),
+}
+
+/// Outcome of a finished crawl, sent by `--notify-webhook` and `--notify-command`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrawlSummary {
+    pub resource: String,
+    pub downloaded: u64,
+    pub failed: u64,
+    pub bytes: f64,
+}
+
+impl CrawlSummary {
+    pub fn message(&self) -> String {
+        format!(
+            "Finished crawling {} - downloaded {} files ({:.2} MB), {} failed",
+            self.resource,
+            self.downloaded,
+            self.bytes / 1024.0 / 1024.0,
+            self.failed
+        )
+    }
+}
+
+/// Discord reads `content` and Slack reads `text`, both ignore the other fields
+pub fn get_webhook_payload(summary: &CrawlSummary) -> serde_json::Value {
+    json!({
+        "content": summary.message(),
+        "text": summary.message(),
+        "resource": summary.resource,
+        "downloaded": summary.downloaded,
+        "failed": summary.failed,
+        "bytes": summary.bytes,
+    })
+}
+
+/// Notifications fired whenever a crawl finishes
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Notifier {
+    pub webhook: Option<Url>,
+    pub command: Option<String>,
+}
+
+impl Notifier {
+    /// A failed notification is only logged, the crawl itself already succeeded
+    pub async fn send(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+        summary: &CrawlSummary,
+    ) {
+        if let Some(url) = &self.webhook {
+            match post_webhook(client, url, summary).await {
+                Ok(()) => info!("[NOTIFY] Sent summary of {} to webhook", summary.resource),
+                Err(e) => warn!("[NOTIFY_FAILED] {}", e),
+            }
+        }
+
+        if let Some(command) = &self.command {
+            if let Err(e) = run_command(command, summary) {
+                warn!("[NOTIFY_FAILED] {}", e);
+            }
+        }
+    }
+}
+
+async fn post_webhook(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &Url,
+    summary: &CrawlSummary,
+) -> Result<(), NotifyError> {
+    let res = client
+        .post(url.to_owned())
+        .header(CONTENT_TYPE, "application/json")
+        .body(get_webhook_payload(summary).to_string())
+        .send()
+        .await?;
+
+    match res.status().is_success() {
+        true => Ok(()),
+        false => Err(NotifyError::WebhookStatus(res.status())),
+    }
+}
+
+/// Runs the command through the shell with the summary in `REDDIT_CLAWLER_*` variables
+fn run_command(command: &str, summary: &CrawlSummary) -> Result<(), NotifyError> {
+    let (shell, flag) = match cfg!(windows) {
+        true => ("cmd", "/C"),
+        false => ("sh", "-c"),
+    };
+
+    let status = Command::new(shell)
+        .args([flag, command])
+        .env("REDDIT_CLAWLER_RESOURCE", &summary.resource)
+        .env("REDDIT_CLAWLER_DOWNLOADED", summary.downloaded.to_string())
+        .env("REDDIT_CLAWLER_FAILED", summary.failed.to_string())
+        .env("REDDIT_CLAWLER_BYTES", summary.bytes.to_string())
+        .env("REDDIT_CLAWLER_MESSAGE", summary.message())
+        .status()
+        .map_err(|e| NotifyError::Command(command.to_owned(), e))?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(NotifyError::CommandStatus(command.to_owned(), status)),
+    }
+}
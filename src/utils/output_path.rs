@@ -0,0 +1,94 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum OutputPathError {
+    #[error("Could not determine the home directory")]
+    HomeDirectory,
+    #[error("Environment variable {0} is not set")]
+    EnvironmentVariable(String),
+    #[error("Could not resolve the absolute path of {0}")]
+    Absolute(String),
+}
+
+fn get_home_dir() -> Result<String, OutputPathError> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map_err(|_| OutputPathError::HomeDirectory)
+}
+
+/// Directory for files shared across runs, e.g. `~/.config/reddit-clawler`
+pub fn get_config_dir() -> Option<PathBuf> {
+    env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            get_home_dir()
+                .ok()
+                .map(|home| Path::new(&home).join(".config"))
+        })
+        .map(|dir| dir.join("reddit-clawler"))
+}
+
+/// Expands a leading `~` and `$VAR` / `${VAR}` environment variables
+pub fn expand_path(path: &str) -> Result<String, OutputPathError> {
+    let path = match path {
+        "~" => get_home_dir()?,
+        _ if path.starts_with("~/") || path.starts_with("~\\") => {
+            format!("{}{}", get_home_dir()?, &path[1..])
+        }
+        _ => path.to_owned(),
+    };
+
+    let mut expanded = String::with_capacity(path.len());
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        if braced && chars.next_if_eq(&'}').is_none() {
+            return Err(OutputPathError::EnvironmentVariable(name));
+        }
+
+        match name.is_empty() {
+            // A lone `$` is kept as is
+            true if !braced => expanded.push('$'),
+            _ => expanded.push_str(
+                &env::var(&name).map_err(|_| OutputPathError::EnvironmentVariable(name))?,
+            ),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Resolves the output directory once so every consumer sees the same path
+pub fn resolve_output_path(path: &str, absolute: bool) -> Result<String, OutputPathError> {
+    let expanded = expand_path(path)?;
+
+    let resolved = match absolute {
+        true => std::path::absolute(Path::new(&expanded))
+            .map_err(|_| OutputPathError::Absolute(expanded.clone()))?
+            .to_string_lossy()
+            .to_string(),
+        false => expanded,
+    };
+
+    // Trailing separators would otherwise produce `output//user/...`
+    match resolved.trim_end_matches(['/', '\\']) {
+        "" => Ok(resolved),
+        trimmed => Ok(trimmed.to_owned()),
+    }
+}
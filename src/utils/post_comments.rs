@@ -0,0 +1,68 @@
+use super::{get_file_path, prepare_file_folder, state::SharedState};
+use crate::{
+    clients::{RedditClient, RedditProviderError},
+    reddit_parser::RedditCrawlerPost,
+};
+use std::{collections::HashSet, fs, path::Path, sync::Arc};
+use tokio::sync::Mutex;
+
+/// Computes the path the comments of a post are written to, next to its files
+pub fn get_comments_file_path(
+    folder_path: &str,
+    post: &RedditCrawlerPost,
+    file_scheme: &str,
+) -> String {
+    get_file_path(
+        folder_path,
+        &RedditCrawlerPost {
+            extension: String::from("comments.json"),
+            index: None,
+            ..post.clone()
+        },
+        file_scheme,
+    )
+}
+
+/// Writes the comment tree of every downloaded post that has none written yet
+pub async fn write_post_comments(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    shared_state: &Arc<Mutex<SharedState>>,
+    folder_path: &str,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> Result<usize, anyhow::Error> {
+    let reddit_client = RedditClient::default();
+    let mut seen = HashSet::new();
+    let mut written = 0;
+
+    for post in posts.iter().filter(|p| seen.insert(&p.id)) {
+        let downloaded = shared_state
+            .lock()
+            .await
+            .file_cache
+            .files
+            .iter()
+            .any(|f| f.id == post.id && f.success);
+        let comments_path = get_comments_file_path(folder_path, post, file_scheme);
+
+        if !downloaded || Path::new(&comments_path).exists() {
+            continue;
+        }
+
+        let comments = match reddit_client
+            .get_post_comments(client, shared_state, &post.id)
+            .await
+        {
+            Ok(comments) => comments,
+            // The post may have been removed since it was downloaded
+            Err(RedditProviderError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        prepare_file_folder(&comments_path)?;
+        fs::write(comments_path, serde_json::to_string_pretty(&comments)?)?;
+        written += 1;
+    }
+
+    Ok(written)
+}
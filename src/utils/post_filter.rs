@@ -0,0 +1,121 @@
+use crate::reddit_parser::{RedditCrawlerPost, RedditMediaProviderType};
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StickiedFilter {
+    #[default]
+    Include,
+    Exclude,
+    Only,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NsfwFilter {
+    #[default]
+    Include,
+    Exclude,
+    Only,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MediaType {
+    Images,
+    Videos,
+    Gifs,
+    Galleries,
+}
+
+impl MediaType {
+    pub fn of(provider: &RedditMediaProviderType) -> Option<Self> {
+        match provider {
+            RedditMediaProviderType::RedditImage
+            | RedditMediaProviderType::ImgurImage
+            | RedditMediaProviderType::RedgifsImage => Some(MediaType::Images),
+            RedditMediaProviderType::RedditVideo
+            | RedditMediaProviderType::YoutubeVideo
+            | RedditMediaProviderType::RedgifsVideo
+            | RedditMediaProviderType::StreamableVideo
+            | RedditMediaProviderType::YtdlpVideo => Some(MediaType::Videos),
+            RedditMediaProviderType::RedditGifVideo => Some(MediaType::Gifs),
+            RedditMediaProviderType::RedditGalleryImage => Some(MediaType::Galleries),
+            RedditMediaProviderType::None => None,
+        }
+    }
+}
+
+/// Selects which parsed posts are handed to the downloader
+#[derive(Default, Debug, Clone)]
+pub struct PostFilter {
+    pub stickied: StickiedFilter,
+    pub exclude_spoilers: bool,
+    pub nsfw: NsfwFilter,
+    pub min_upvotes: Option<i64>,
+    pub max_upvotes: Option<i64>,
+    pub after_date: Option<DateTime<Utc>>,
+    pub before_date: Option<DateTime<Utc>>,
+    // Empty keeps every media type
+    pub only: Vec<MediaType>,
+    pub title_match: Option<Regex>,
+    pub title_exclude: Option<Regex>,
+}
+
+/// Parses an ISO 8601 date like `2024-01-31` or date time like `2024-01-31T12:00:00Z`
+pub fn parse_filter_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| format!("{} is not an ISO 8601 date (e.g. 2024-01-31)", value))
+}
+
+impl PostFilter {
+    /// Whether a post was created before `--after-date`, used to stop paginating early
+    pub fn is_before_cutoff(&self, created_utc: DateTime<Utc>) -> bool {
+        self.after_date.is_some_and(|after| created_utc < after)
+    }
+
+    pub fn matches(&self, post: &RedditCrawlerPost) -> bool {
+        let stickied = match self.stickied {
+            StickiedFilter::Include => true,
+            StickiedFilter::Exclude => !post.stickied,
+            StickiedFilter::Only => post.stickied,
+        };
+
+        let nsfw = match self.nsfw {
+            NsfwFilter::Include => true,
+            NsfwFilter::Exclude => !post.over_18,
+            NsfwFilter::Only => post.over_18,
+        };
+
+        let upvotes = self.min_upvotes.is_none_or(|min| post.upvotes >= min)
+            && self.max_upvotes.is_none_or(|max| post.upvotes <= max);
+
+        let date = !self.is_before_cutoff(post.created_utc)
+            && self
+                .before_date
+                .is_none_or(|before| post.created_utc < before);
+
+        let media_type = self.only.is_empty()
+            || MediaType::of(&post.provider).is_some_and(|t| self.only.contains(&t));
+
+        let title = self
+            .title_match
+            .as_ref()
+            .is_none_or(|r| r.is_match(&post.title))
+            && !self
+                .title_exclude
+                .as_ref()
+                .is_some_and(|r| r.is_match(&post.title));
+
+        stickied
+            && nsfw
+            && title
+            && upvotes
+            && date
+            && media_type
+            && !(self.exclude_spoilers && post.spoiler)
+    }
+}
@@ -0,0 +1,89 @@
+use super::{get_file_name, PostRemoval};
+use crate::{
+    clients::api_types::reddit::submitted_response::{
+        RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    reddit_parser::RedditCrawlerPost,
+};
+use serde::Serialize;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMetadataFile {
+    pub file_name: String,
+    pub provider: String,
+    pub url: String,
+    pub index: Option<usize>,
+    // Set for gallery items
+    pub caption: Option<String>,
+    pub outbound_url: Option<String>,
+}
+
+/// Contents of the `<post id>.json` sidecar of a post
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMetadata<'a> {
+    pub post: &'a RedditSubmittedChildData,
+    // Set for deleted and removed posts
+    pub removal: Option<PostRemoval>,
+    pub files: Vec<PostMetadataFile>,
+}
+
+/// Combines the crawled post with the files it was parsed into
+pub fn get_post_metadata<'a>(
+    data: &'a RedditSubmittedChildData,
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> PostMetadata<'a> {
+    let files = posts
+        .iter()
+        .filter(|p| p.id == data.id)
+        .map(|p| {
+            let item = data
+                .gallery_data
+                .as_ref()
+                .zip(p.index)
+                .and_then(|(gallery_data, index)| gallery_data.items.get(index));
+
+            PostMetadataFile {
+                file_name: get_file_name(p, file_scheme),
+                provider: p.provider.to_string(),
+                url: p.url.clone(),
+                index: p.index,
+                caption: item.and_then(|item| item.caption.clone()),
+                outbound_url: item.and_then(|item| item.outbound_url.clone()),
+            }
+        })
+        .collect();
+
+    PostMetadata {
+        post: data,
+        removal: PostRemoval::of(data),
+        files,
+    }
+}
+
+/// Writes a `<post id>.json` sidecar for every post that has media
+pub fn write_post_metadata(
+    folder_path: &str,
+    responses: &[RedditSubmittedResponse],
+    posts: &[RedditCrawlerPost],
+    file_scheme: &str,
+) -> Result<usize, anyhow::Error> {
+    let mut written = 0;
+
+    for child in responses.iter().flat_map(|r| r.data.children.iter()) {
+        let metadata = get_post_metadata(&child.data, posts, file_scheme);
+        if metadata.files.is_empty() {
+            continue;
+        }
+
+        // Rewritten on every run so upvotes and flair stay current
+        let metadata_path = format!("{}/{}.json", folder_path, child.data.id);
+        fs::write(metadata_path, serde_json::to_string_pretty(&metadata)?)?;
+        written += 1;
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,64 @@
+use crate::clients::api_types::reddit::submitted_response::RedditSubmittedChildData;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Author of posts whose account or the post itself was deleted
+const DELETED_AUTHOR: &str = "[deleted]";
+
+/// Why Reddit no longer shows a post, read from its `removed_by_category` and `[deleted]` author
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PostRemoval {
+    // Deleted by its author
+    Deleted,
+    // Only the account of the author is gone, the post and its media stay up
+    AuthorDeleted,
+    Moderator,
+    Automod,
+    // Removed by the admins e.g. `anti_evil_ops`
+    Reddit,
+    Copyright,
+    // Categories Reddit added later
+    Other,
+    // No longer returned by Reddit at all, only recorded by `diff`
+    Unavailable,
+}
+
+impl PostRemoval {
+    pub fn of(data: &RedditSubmittedChildData) -> Option<Self> {
+        let removal = match data.removed_by_category.as_deref() {
+            Some("deleted" | "author") => PostRemoval::Deleted,
+            Some("moderator") => PostRemoval::Moderator,
+            Some("automod_filtered") => PostRemoval::Automod,
+            Some("reddit" | "anti_evil_ops" | "community_ops" | "content_takedown") => {
+                PostRemoval::Reddit
+            }
+            Some("copyright_takedown") => PostRemoval::Copyright,
+            Some(_) => PostRemoval::Other,
+            None if data.author == DELETED_AUTHOR => PostRemoval::AuthorDeleted,
+            None => return None,
+        };
+        Some(removal)
+    }
+
+    /// Whether the post itself was taken down, its media is then not downloaded anymore
+    pub fn is_removed(&self) -> bool {
+        *self != PostRemoval::AuthorDeleted
+    }
+}
+
+impl fmt::Display for PostRemoval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let removal = match self {
+            PostRemoval::Deleted => "deleted by its author",
+            PostRemoval::AuthorDeleted => "account of the author deleted",
+            PostRemoval::Moderator => "removed by moderators",
+            PostRemoval::Automod => "filtered by AutoModerator",
+            PostRemoval::Reddit => "removed by Reddit",
+            PostRemoval::Copyright => "removed for copyright",
+            PostRemoval::Other => "removed",
+            PostRemoval::Unavailable => "no longer on Reddit",
+        };
+        write!(f, "{}", removal)
+    }
+}
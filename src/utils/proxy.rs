@@ -0,0 +1,86 @@
+use clap::ValueEnum;
+use reqwest::Url;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ProxyError {
+    #[error("Invalid provider proxy {0} - expected PROVIDER=URL")]
+    Format(String),
+    #[error("Unknown proxy provider {0} - expected reddit, redgifs, imgur, streamable or youtube")]
+    Provider(String),
+    #[error("Invalid proxy {0} - expected an http, https, socks5 or socks5h URL")]
+    Url(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProxyProvider {
+    Reddit,
+    Redgifs,
+    Imgur,
+    Streamable,
+    Youtube,
+}
+
+impl ProxyProvider {
+    fn hosts(&self) -> &'static [&'static str] {
+        match self {
+            ProxyProvider::Reddit => &["reddit.com", "redd.it", "redditmedia.com"],
+            ProxyProvider::Redgifs => &["redgifs.com"],
+            ProxyProvider::Imgur => &["imgur.com"],
+            ProxyProvider::Streamable => &["streamable.com"],
+            ProxyProvider::Youtube => &["youtube.com", "youtu.be", "googlevideo.com"],
+        }
+    }
+
+    /// Whether a host or one of its subdomains belongs to the provider
+    pub fn matches_host(&self, host: &str) -> bool {
+        self.hosts()
+            .iter()
+            .any(|h| host == *h || host.ends_with(&format!(".{}", h)))
+    }
+}
+
+/// Proxy for the requests of a single provider, e.g. `redgifs=socks5://127.0.0.1:9050`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderProxy {
+    pub provider: ProxyProvider,
+    pub url: Url,
+}
+
+impl FromStr for ProviderProxy {
+    type Err = ProxyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (provider, url) = s
+            .split_once('=')
+            .ok_or_else(|| ProxyError::Format(s.to_owned()))?;
+
+        Ok(ProviderProxy {
+            provider: ProxyProvider::from_str(provider, true)
+                .map_err(|_| ProxyError::Provider(provider.to_owned()))?,
+            url: parse_proxy_url(url)?,
+        })
+    }
+}
+
+pub fn parse_proxy_url(value: &str) -> Result<Url, ProxyError> {
+    Url::parse(value)
+        .ok()
+        .filter(|url| ["http", "https", "socks5", "socks5h"].contains(&url.scheme()))
+        .ok_or_else(|| ProxyError::Url(value.to_owned()))
+}
+
+/// Picks the proxy of a request, a provider proxy takes precedence over the default proxy
+pub fn select_proxy<'a>(
+    url: &Url,
+    default: Option<&'a Url>,
+    provider_proxies: &'a [ProviderProxy],
+) -> Option<&'a Url> {
+    let host = url.host_str().unwrap_or_default();
+
+    provider_proxies
+        .iter()
+        .find(|p| p.provider.matches_host(host))
+        .map(|p| &p.url)
+        .or(default)
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{self, HeaderMap};
+use spinoff::{spinners, Color, Spinner};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+/// Token bucket filled from the `x-ratelimit-*` headers Reddit sends with every response
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    // Requests left in the current window
+    remaining: f64,
+    reset_at: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Takes a token and returns how long to wait before the request may be sent
+    pub fn acquire(&mut self, now: Instant) -> Duration {
+        match self.reset_at {
+            Some(reset_at) if reset_at > now => {
+                if self.remaining >= 1.0 {
+                    self.remaining -= 1.0;
+                    return Duration::ZERO;
+                }
+                // Every request waiting for the reset is sent with the new window
+                reset_at - now
+            }
+            // The window is unknown until the first response or has been reset
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn update(&mut self, headers: &HeaderMap, now: Instant) {
+        let get_header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<f64>().ok())
+        };
+
+        if let (Some(remaining), Some(reset)) = (
+            get_header("x-ratelimit-remaining"),
+            get_header("x-ratelimit-reset"),
+        ) {
+            self.remaining = remaining;
+            self.reset_at = Some(now + Duration::from_secs_f64(reset.max(0.0)));
+        }
+    }
+}
+
+/// Reads when a rate limit resets from `x-ratelimit-reset` or `Retry-After`, in seconds or as
+/// an HTTP date
+pub fn get_rate_limit_wait(headers: &HeaderMap) -> Option<Duration> {
+    ["x-ratelimit-reset", header::RETRY_AFTER.as_str()]
+        .iter()
+        .find_map(|name| {
+            let value = headers.get(*name)?.to_str().ok()?.trim();
+            match value.parse::<f64>() {
+                Ok(seconds) => Some(Duration::from_secs_f64(seconds.max(0.0))),
+                Err(_) => DateTime::parse_from_rfc2822(value).ok().map(|at| {
+                    (at.with_timezone(&Utc) - Utc::now())
+                        .to_std()
+                        .unwrap_or_default()
+                }),
+            }
+        })
+}
+
+/// Sleeps while counting down the remaining seconds in a spinner
+pub async fn sleep_with_countdown(wait: Duration, message: &str) {
+    let mut spinner = Spinner::new(
+        spinners::Dots,
+        format!("{} {}s", message, wait.as_secs()),
+        Color::TrueColor {
+            r: 237,
+            g: 106,
+            b: 44,
+        },
+    );
+
+    let until = Instant::now() + wait;
+    while let Some(remaining) = until.checked_duration_since(Instant::now()) {
+        if remaining.is_zero() {
+            break;
+        }
+        spinner.update_text(format!("{} {}s", message, remaining.as_secs() + 1));
+        sleep(remaining.min(Duration::from_secs(1))).await;
+    }
+
+    spinner.clear();
+}
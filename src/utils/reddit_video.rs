@@ -0,0 +1,186 @@
+use super::{
+    write_http_response, AudioFormat, BandwidthLimiter, ByteProgress, NoAudioTrackError,
+    TransferTimeouts, VideoProcessingError,
+};
+use std::{
+    fs,
+    process::{Command, Stdio},
+};
+
+// Audio tracks of v.redd.it videos, newer uploads use the bitrate suffixed names
+const AUDIO_TRACK_NAMES: [&str; 4] = [
+    "DASH_AUDIO_128.mp4",
+    "DASH_AUDIO_64.mp4",
+    "DASH_audio.mp4",
+    "audio",
+];
+
+pub enum RedditVideoDownload {
+    /// Video and audio track were muxed into one file
+    Muxed,
+    /// The video has no audio track or ffmpeg is not available
+    VideoOnly,
+    NotFound,
+}
+
+/// Lists the candidate audio tracks next to a fallback video like `v.redd.it/ID/DASH_720.mp4`
+pub fn get_reddit_audio_urls(video_url: &str) -> Vec<String> {
+    let video_url = video_url.split('?').next().unwrap_or(video_url);
+
+    match video_url.rsplit_once('/') {
+        Some((base, _)) => AUDIO_TRACK_NAMES
+            .iter()
+            .map(|name| format!("{}/{}", base, name))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+async fn download_track(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+    file_path: &str,
+    progress: Option<&ByteProgress>,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Result<bool, anyhow::Error> {
+    let response = client.get(url).send().await?;
+
+    // Missing tracks are answered with 403 instead of 404
+    if !response.status().is_success() {
+        return Ok(false);
+    }
+
+    write_http_response(client, response, file_path, progress, bandwidth, timeouts).await?;
+
+    Ok(true)
+}
+
+/// Downloads the fallback video of a Reddit hosted video and muxes in its audio track with ffmpeg
+pub async fn download_reddit_video(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+    file_path: &str,
+    progress: Option<&ByteProgress>,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Result<RedditVideoDownload, anyhow::Error> {
+    let video_path = format!("{}.video", file_path);
+    let audio_path = format!("{}.audio", file_path);
+
+    if !download_track(client, url, &video_path, progress, bandwidth, timeouts).await? {
+        return Ok(RedditVideoDownload::NotFound);
+    }
+
+    let mut has_audio = false;
+    for audio_url in get_reddit_audio_urls(url) {
+        if download_track(
+            client,
+            &audio_url,
+            &audio_path,
+            progress,
+            bandwidth,
+            timeouts,
+        )
+        .await?
+        {
+            has_audio = true;
+            break;
+        }
+    }
+
+    let muxed = has_audio
+        && Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-i",
+                &video_path,
+                "-i",
+                &audio_path,
+            ])
+            .args(["-map", "0:v:0", "-map", "1:a:0", "-c", "copy", file_path])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+
+    match muxed {
+        true => fs::remove_file(&video_path)?,
+        false => fs::rename(&video_path, file_path)?,
+    }
+    if has_audio {
+        fs::remove_file(&audio_path)?;
+    }
+
+    match muxed {
+        true => Ok(RedditVideoDownload::Muxed),
+        false => Ok(RedditVideoDownload::VideoOnly),
+    }
+}
+
+/// Downloads only the audio track of a Reddit hosted video and extracts it with ffmpeg into the
+/// file of the post
+pub async fn download_reddit_audio(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+    file_path: &str,
+    format: AudioFormat,
+    progress: Option<&ByteProgress>,
+    bandwidth: Option<&BandwidthLimiter>,
+    timeouts: TransferTimeouts,
+) -> Result<(), anyhow::Error> {
+    let audio_path = format!("{}.audio", file_path);
+
+    let mut has_audio = false;
+    for audio_url in get_reddit_audio_urls(url) {
+        if download_track(
+            client,
+            &audio_url,
+            &audio_path,
+            progress,
+            bandwidth,
+            timeouts,
+        )
+        .await?
+        {
+            has_audio = true;
+            break;
+        }
+    }
+    if !has_audio {
+        return Err(NoAudioTrackError {
+            url: url.to_owned(),
+        }
+        .into());
+    }
+
+    let result = Command::new("ffmpeg")
+        .args(format.get_ffmpeg_args(&audio_path, file_path))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(VideoProcessingError::Spawn);
+    fs::remove_file(&audio_path)?;
+
+    let result = result?;
+    if !result.status.success() {
+        let _ = fs::remove_file(file_path);
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(VideoProcessingError::Failed {
+            code: result.status.code(),
+            message: stderr
+                .lines()
+                .map(str::trim)
+                .rfind(|l| !l.is_empty())
+                .unwrap_or("no output")
+                .to_owned(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
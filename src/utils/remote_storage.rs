@@ -0,0 +1,348 @@
+use bytes::Bytes;
+use futures_util::StreamExt;
+use object_store::{
+    aws::AmazonS3Builder, buffered::BufWriter, http::HttpBuilder, path::Path as ObjectPath,
+    ObjectStore, PutPayload,
+};
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio::{
+    io::AsyncWriteExt,
+    sync::mpsc::{self, UnboundedSender},
+    task::JoinHandle,
+};
+use tracing::{debug, warn};
+
+#[derive(Error, Debug)]
+pub enum RemoteStorageError {
+    #[error("{0}:// outputs are not supported, use s3:// or webdav://")]
+    Unsupported(String),
+    #[error("Invalid remote output: {0}")]
+    ObjectStore(#[from] object_store::Error),
+}
+
+/// Object storage an `--output` URL points to, e.g. `s3://bucket/prefix`
+///
+/// Plain downloads are streamed to the store as they arrive. Caches and the files external tools
+/// write, e.g. yt-dlp or ffmpeg, go through a local staging folder and are uploaded once complete.
+#[derive(Clone)]
+pub struct RemoteStorage {
+    url: String,
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    // WebDAV servers only accept whole files
+    multipart: bool,
+}
+
+impl fmt::Debug for RemoteStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteStorage")
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl RemoteStorage {
+    /// Storage of a remote output URL, `None` for local paths
+    ///
+    /// S3 reads its credentials, region and endpoint from the usual `AWS_*` variables.
+    pub fn parse(output: &str) -> Result<Option<Self>, RemoteStorageError> {
+        let Some((scheme, location)) = output.split_once("://") else {
+            return Ok(None);
+        };
+
+        let (store, prefix, multipart): (Arc<dyn ObjectStore>, &str, bool) = match scheme {
+            "s3" => {
+                let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+                let store = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?;
+                (Arc::new(store), prefix, true)
+            }
+            // `webdav+http://` for servers without TLS, e.g. on the local network
+            "webdav" | "webdav+http" => {
+                let protocol = match scheme {
+                    "webdav" => "https",
+                    _ => "http",
+                };
+                let store = HttpBuilder::new()
+                    .with_url(format!("{}://{}", protocol, location))
+                    .build()?;
+                (Arc::new(store), "", false)
+            }
+            _ => return Err(RemoteStorageError::Unsupported(scheme.to_owned())),
+        };
+
+        Ok(Some(Self {
+            multipart,
+            ..Self::from_store(output, store, prefix)
+        }))
+    }
+
+    /// Storage writing to any object store below `prefix`, e.g. an in-memory one in tests
+    pub fn from_store(url: &str, store: Arc<dyn ObjectStore>, prefix: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            store,
+            prefix: ObjectPath::from(prefix.trim_matches('/')),
+            multipart: true,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Local folder of the caches and the files tools write before they are uploaded
+    pub fn staging_dir(&self) -> PathBuf {
+        let name = self
+            .url
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '_',
+            })
+            .collect::<String>();
+        env::temp_dir().join("reddit-clawler-staging").join(name)
+    }
+
+    /// Object a file of the staging folder is uploaded to
+    pub fn get_object_path(&self, staging_dir: &Path, file_path: &Path) -> Option<ObjectPath> {
+        let relative = file_path.strip_prefix(staging_dir).ok()?;
+        let parts = self
+            .prefix
+            .parts()
+            .map(|p| p.as_ref().to_owned())
+            .chain(
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string()),
+            )
+            .collect::<Vec<_>>();
+
+        Some(ObjectPath::from_iter(parts))
+    }
+
+    /// Starts streaming a file of the staging folder to the store without writing it locally
+    pub fn start_upload(
+        &self,
+        staging_dir: &Path,
+        file_path: &Path,
+    ) -> Result<RemoteWriter, anyhow::Error> {
+        let object_path = self
+            .get_object_path(staging_dir, file_path)
+            .ok_or_else(|| anyhow::anyhow!("{} is not staged", file_path.display()))?;
+
+        Ok(match self.multipart {
+            // Switches to a multipart upload once more than its buffer is written
+            true => RemoteWriter::Multipart(BufWriter::new(self.store.clone(), object_path)),
+            false => RemoteWriter::Whole {
+                store: self.store.clone(),
+                object_path,
+                chunks: Vec::new(),
+            },
+        })
+    }
+
+    /// Uploads a file of the staging folder, large files in parts
+    pub async fn upload(&self, staging_dir: &Path, file_path: &Path) -> Result<(), anyhow::Error> {
+        let object_path = self
+            .get_object_path(staging_dir, file_path)
+            .ok_or_else(|| anyhow::anyhow!("{} is not staged", file_path.display()))?;
+
+        match self.multipart {
+            true => {
+                // Switches to a multipart upload once more than its buffer is written
+                let mut writer = BufWriter::new(self.store.clone(), object_path);
+                let mut file = tokio::fs::File::open(file_path).await?;
+                tokio::io::copy(&mut file, &mut writer).await?;
+                writer.shutdown().await?;
+            }
+            false => {
+                let data = tokio::fs::read(file_path).await?;
+                self.store.put(&object_path, PutPayload::from(data)).await?;
+            }
+        }
+
+        debug!("Uploaded {} to {}", file_path.display(), self.url);
+        Ok(())
+    }
+
+    /// Fetches the caches of the crawled resources, so crawls skip the posts uploaded by earlier
+    /// runs
+    ///
+    /// The remote cache replaces a staged one, which is left over from a run that may not have
+    /// uploaded its latest state.
+    pub async fn pull_caches(&self, staging_dir: &Path) -> Result<usize, anyhow::Error> {
+        let mut objects = self.store.list(Some(&self.prefix));
+        let mut pulled = 0;
+
+        while let Some(meta) = objects.next().await.transpose()? {
+            if meta.location.filename() != Some("cache.json") {
+                continue;
+            }
+            let relative = meta
+                .location
+                .prefix_match(&self.prefix)
+                .map(|parts| parts.map(|p| p.as_ref().to_owned()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let local_path = relative
+                .iter()
+                .fold(staging_dir.to_path_buf(), |path, part| path.join(part));
+
+            let data = self.store.get(&meta.location).await?.bytes().await?;
+            if let Some(folder) = local_path.parent() {
+                fs::create_dir_all(folder)?;
+            }
+            fs::write(&local_path, data)?;
+            pulled += 1;
+        }
+
+        Ok(pulled)
+    }
+}
+
+/// A file streamed to the store, in parts on S3 and as a whole on WebDAV
+pub enum RemoteWriter {
+    Multipart(BufWriter),
+    // WebDAV servers only accept whole files, so the chunks are held until it is complete
+    Whole {
+        store: Arc<dyn ObjectStore>,
+        object_path: ObjectPath,
+        chunks: Vec<Bytes>,
+    },
+}
+
+impl RemoteWriter {
+    pub async fn write(&mut self, chunk: Bytes) -> Result<(), anyhow::Error> {
+        match self {
+            RemoteWriter::Multipart(writer) => writer.put(chunk).await?,
+            RemoteWriter::Whole { chunks, .. } => chunks.push(chunk),
+        }
+        Ok(())
+    }
+
+    /// Completes the upload, the object only exists once this returned
+    pub async fn finish(self) -> Result<(), anyhow::Error> {
+        match self {
+            RemoteWriter::Multipart(mut writer) => writer.shutdown().await?,
+            RemoteWriter::Whole {
+                store,
+                object_path,
+                chunks,
+            } => {
+                store
+                    .put(&object_path, chunks.into_iter().collect::<PutPayload>())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the parts uploaded so far, e.g. when the download fails halfway
+    pub async fn abort(self) {
+        if let RemoteWriter::Multipart(mut writer) = self {
+            if let Err(e) = writer.abort().await {
+                warn!("[UPLOAD_ABORT_FAILED] {}", e);
+            }
+        }
+    }
+}
+
+/// A staged file to upload, `keep` leaves it in the staging folder afterwards
+#[derive(Debug)]
+pub struct RemoteUpload {
+    pub path: PathBuf,
+    pub keep: bool,
+}
+
+/// Uploads the files of a crawl in the background while it continues downloading
+#[derive(Debug, Clone)]
+pub struct RemoteUploads {
+    tx: UnboundedSender<RemoteUpload>,
+    storage: RemoteStorage,
+    staging_dir: PathBuf,
+}
+
+impl RemoteUploads {
+    /// Starts the uploads, the task finishes once every `RemoteUploads` was dropped
+    pub fn spawn(storage: RemoteStorage, staging_dir: PathBuf) -> (Self, JoinHandle<()>) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<RemoteUpload>();
+
+        let uploads = Self {
+            tx,
+            storage: storage.clone(),
+            staging_dir: staging_dir.clone(),
+        };
+
+        let handle = tokio::spawn(async move {
+            while let Some(upload) = rx.recv().await {
+                match storage.upload(&staging_dir, &upload.path).await {
+                    Ok(()) if !upload.keep => {
+                        let _ = fs::remove_file(&upload.path);
+                    }
+                    Ok(()) => {}
+                    // Staged files are uploaded again when the crawl finishes
+                    Err(e) => warn!("[UPLOAD_FAILED] {} - {}", upload.path.display(), e),
+                }
+            }
+        });
+
+        (uploads, handle)
+    }
+
+    /// Starts streaming a file to its place in the store, instead of staging it
+    pub fn start_upload(&self, file_path: &str) -> Result<RemoteWriter, anyhow::Error> {
+        self.storage
+            .start_upload(&self.staging_dir, Path::new(file_path))
+    }
+
+    /// Queues a staged file, downloads are removed once uploaded while caches stay staged
+    pub fn queue(&self, path: impl Into<PathBuf>, keep: bool) {
+        let _ = self.tx.send(RemoteUpload {
+            path: path.into(),
+            keep,
+        });
+    }
+}
+
+/// Uploads every file left in the staging folder, e.g. metadata and comments, returns how many
+/// were uploaded
+///
+/// Caches stay staged for the next run, the other files are removed once uploaded.
+pub async fn flush_staging_dir(
+    storage: &RemoteStorage,
+    staging_dir: &Path,
+) -> Result<usize, anyhow::Error> {
+    let mut uploaded = 0;
+    let mut folders = vec![staging_dir.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                folders.push(path);
+                continue;
+            }
+            // Unfinished downloads resume from the next run's staging folder instead
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "part" || ext == "aria2")
+            {
+                continue;
+            }
+            storage.upload(staging_dir, &path).await?;
+            if entry.file_name() != "cache.json" {
+                fs::remove_file(&path)?;
+            }
+            uploaded += 1;
+        }
+    }
+
+    Ok(uploaded)
+}
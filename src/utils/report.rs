@@ -0,0 +1,133 @@
+use super::state::FileCacheLatest;
+use std::{
+    borrow::Cow,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+const REPORT_HEADER: [&str; 13] = [
+    "resource",
+    "id",
+    "index",
+    "created_utc",
+    "title",
+    "author",
+    "subreddit",
+    "upvotes",
+    "url",
+    "status",
+    "file_path",
+    "bytes",
+    "spoiler",
+];
+
+/// A cached file of a crawled resource, one line of the report
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    pub resource: String,
+    pub id: String,
+    pub index: Option<usize>,
+    pub created_utc: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub subreddit: String,
+    pub upvotes: Option<i64>,
+    pub url: String,
+    pub status: &'static str,
+    pub file_path: Option<String>,
+    pub bytes: Option<u64>,
+    pub spoiler: bool,
+}
+
+impl ReportRow {
+    fn fields(&self) -> [Cow<'_, str>; 13] {
+        let optional = |value: Option<String>| Cow::Owned(value.unwrap_or_default());
+
+        [
+            Cow::Borrowed(&self.resource),
+            Cow::Borrowed(&self.id),
+            optional(self.index.map(|i| i.to_string())),
+            Cow::Borrowed(&self.created_utc),
+            Cow::Borrowed(&self.title),
+            Cow::Borrowed(self.author.as_deref().unwrap_or_default()),
+            Cow::Borrowed(&self.subreddit),
+            optional(self.upvotes.map(|u| u.to_string())),
+            Cow::Borrowed(&self.url),
+            Cow::Borrowed(self.status),
+            Cow::Borrowed(self.file_path.as_deref().unwrap_or_default()),
+            optional(self.bytes.map(|b| b.to_string())),
+            Cow::Owned(self.spoiler.to_string()),
+        ]
+    }
+}
+
+/// Lists the cached files of the resource in `folder`, paths are relative to `root`
+///
+/// The size is read from disk and falls back to the `Content-Length` of the download.
+pub fn get_report_rows(root: &Path, folder: &Path, cache: &FileCacheLatest) -> Vec<ReportRow> {
+    let relative = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    let resource = relative(folder);
+
+    cache
+        .files
+        .iter()
+        .map(|item| {
+            let file_path = item.file_name.as_ref().map(|f| folder.join(f));
+            let bytes = file_path
+                .as_ref()
+                .and_then(|path| fs::metadata(path).ok())
+                .map(|metadata| metadata.len())
+                .or_else(|| item.provenance.as_ref()?.content_length);
+
+            ReportRow {
+                resource: resource.clone(),
+                id: item.id.clone(),
+                index: item.index,
+                created_utc: item.created_utc.to_rfc3339(),
+                title: item.title.clone(),
+                author: item.author.clone(),
+                subreddit: item.subreddit.clone(),
+                upvotes: item.upvotes,
+                url: item.url.clone(),
+                status: match (item.success, item.skipped) {
+                    (true, _) => "downloaded",
+                    (false, Some(_)) => "skipped",
+                    (false, None) => "failed",
+                },
+                file_path: file_path.as_deref().map(relative),
+                bytes,
+                spoiler: item.spoiler,
+            }
+        })
+        .collect()
+}
+
+/// Quotes a field when it contains a separator, quote or line break (RFC 4180)
+pub fn escape_csv_field(field: &str) -> Cow<'_, str> {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => Cow::Owned(format!("\"{}\"", field.replace('"', "\"\""))),
+        false => Cow::Borrowed(field),
+    }
+}
+
+pub fn write_csv_report<W: Write>(mut writer: W, rows: &[ReportRow]) -> io::Result<()> {
+    writeln!(writer, "{}", REPORT_HEADER.join(","))?;
+
+    for row in rows {
+        let line = row
+            .fields()
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(writer, "{}", line)?;
+    }
+
+    writer.flush()
+}
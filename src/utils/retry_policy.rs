@@ -0,0 +1,134 @@
+use super::get_rate_limit_wait;
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use reqwest_retry::{
+    default_on_request_failure, default_on_request_success, policies::ExponentialBackoff,
+    RetryTransientMiddleware, Retryable, RetryableStrategy,
+};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::warn;
+
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+pub const DEFAULT_RETRY_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Set by `--retries`, `--retry-base-delay` and `--retry-max-delay`, shared by every request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            base_delay: DEFAULT_RETRY_BASE_DELAY,
+            max_delay: DEFAULT_RETRY_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `past_retries + 1`, doubling from the base delay
+    pub fn get_backoff(&self, past_retries: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(past_retries))
+            .min(self.max_delay)
+    }
+
+    /// How long to wait before a rate limited request is sent again, `None` gives up
+    ///
+    /// A reset further away than the max delay is not waited for.
+    pub fn get_rate_limit_delay(
+        &self,
+        headers: &reqwest::header::HeaderMap,
+        past_retries: u32,
+    ) -> Option<Duration> {
+        if past_retries >= self.retries {
+            return None;
+        }
+
+        match get_rate_limit_wait(headers) {
+            Some(wait) if wait > self.max_delay => None,
+            Some(wait) => Some(wait),
+            None => Some(self.get_backoff(past_retries)),
+        }
+    }
+
+    /// Retries connection errors, timeouts and server errors with an exponential backoff
+    pub fn transient_middleware(
+        &self,
+    ) -> RetryTransientMiddleware<ExponentialBackoff, TransientStrategy> {
+        let backoff = ExponentialBackoff::builder()
+            .retry_bounds(self.base_delay, self.max_delay)
+            .build_with_max_retries(self.retries);
+        RetryTransientMiddleware::new_with_policy_and_strategy(backoff, TransientStrategy)
+    }
+
+    pub fn rate_limit_middleware(&self) -> RateLimitMiddleware {
+        RateLimitMiddleware { policy: *self }
+    }
+}
+
+/// The default strategy of `reqwest-retry`, leaving 429 responses to `RateLimitMiddleware`
+pub struct TransientStrategy;
+
+impl RetryableStrategy for TransientStrategy {
+    fn handle(&self, res: &reqwest_middleware::Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(res) if res.status() == StatusCode::TOO_MANY_REQUESTS => Some(Retryable::Fatal),
+            Ok(res) => default_on_request_success(res),
+            Err(e) => default_on_request_failure(e),
+        }
+    }
+}
+
+/// Sends rate limited requests again once `Retry-After` or `x-ratelimit-reset` allows it
+pub struct RateLimitMiddleware {
+    policy: RetryPolicy,
+}
+
+#[async_trait]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut past_retries = 0;
+
+        loop {
+            // Streamed bodies can not be sent twice
+            let Some(attempt) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+
+            let res = next.clone().run(attempt, extensions).await?;
+            if res.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(res);
+            }
+            let Some(wait) = self
+                .policy
+                .get_rate_limit_delay(res.headers(), past_retries)
+            else {
+                return Ok(res);
+            };
+
+            past_retries += 1;
+            warn!(
+                "[RATE_LIMITED] {} - retrying in {:.1}s ({}/{})",
+                req.url().host_str().unwrap_or_default(),
+                wait.as_secs_f64(),
+                past_retries,
+                self.policy.retries
+            );
+            sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use super::{state::SharedState, DownloadPostResult, SkipReason};
+use crate::reddit_parser::RedditCrawlerPost;
+use chrono::{DateTime, Utc};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write, fs, sync::Arc, time::Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Downloads of one provider during a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCounts {
+    pub downloaded: u64,
+    pub failed: u64,
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunFailure {
+    pub id: String,
+    pub url: String,
+    pub reason: String,
+}
+
+/// Contents of `run-report.json`, what the downloads of a crawled resource did
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub resource: String,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_seconds: f64,
+    pub downloaded: u64,
+    pub bytes: u64,
+    // Average over the whole run, including the time spent fetching pages
+    pub bytes_per_second: f64,
+    pub providers: BTreeMap<String, ProviderCounts>,
+    pub skipped: BTreeMap<SkipReason, u64>,
+    // Posts no provider could classify, listed in `unsupported.json`
+    pub unsupported: usize,
+    pub failures: Vec<RunFailure>,
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl Default for RunReport {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl RunReport {
+    pub fn new(resource: &str) -> Self {
+        Self {
+            resource: resource.to_owned(),
+            started_at: Utc::now(),
+            elapsed_seconds: 0.0,
+            downloaded: 0,
+            bytes: 0,
+            bytes_per_second: 0.0,
+            providers: BTreeMap::new(),
+            skipped: BTreeMap::new(),
+            unsupported: 0,
+            failures: Vec::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Counts a finished download of `media`
+    pub fn record(
+        &mut self,
+        media: &RedditCrawlerPost,
+        result: &Result<DownloadPostResult, anyhow::Error>,
+    ) {
+        let reason = match result {
+            Ok(DownloadPostResult::ReceivedBytes(bytes, _, _, _)) => {
+                self.downloaded += 1;
+                self.bytes += *bytes as u64;
+                self.get_provider(media).downloaded += 1;
+                return;
+            }
+            Ok(DownloadPostResult::ReceivedSkipped(reason)) => {
+                *self.skipped.entry(*reason).or_default() += 1;
+                self.get_provider(media).skipped += 1;
+                return;
+            }
+            Ok(DownloadPostResult::ReceivedUnhandled) => return,
+            Ok(DownloadPostResult::ReceivedNotFound) => String::from("not found"),
+            Ok(DownloadPostResult::ReceivedFailed(error)) => error.to_owned(),
+            Err(e) => e.to_string(),
+        };
+
+        self.get_provider(media).failed += 1;
+        self.failures.push(RunFailure {
+            id: media.id.clone(),
+            url: media.url.clone(),
+            reason,
+        });
+    }
+
+    fn get_provider(&mut self, media: &RedditCrawlerPost) -> &mut ProviderCounts {
+        self.providers
+            .entry(media.provider.to_string())
+            .or_default()
+    }
+
+    /// Stops the clock of the run once its downloads are done
+    pub fn finish(&mut self, unsupported: usize) {
+        self.unsupported = unsupported;
+        self.elapsed_seconds = self.started.elapsed().as_secs_f64();
+        self.bytes_per_second = match self.elapsed_seconds > 0.0 {
+            true => self.bytes as f64 / self.elapsed_seconds,
+            false => 0.0,
+        };
+    }
+}
+
+fn format_mb(bytes: f64) -> String {
+    format!("{:.2} MB", bytes / 1024.0 / 1024.0)
+}
+
+/// Summary printed after the progress bar of a run
+pub fn render_run_report(report: &RunReport) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "Downloaded {} files - {} in {:.1}s ({}/s)",
+        report.downloaded,
+        format_mb(report.bytes as f64),
+        report.elapsed_seconds,
+        format_mb(report.bytes_per_second)
+    );
+    let width = report.providers.keys().map(|p| p.len()).max().unwrap_or(0);
+    for (provider, counts) in report.providers.iter() {
+        let _ = writeln!(
+            out,
+            "  {:width$}  {} downloaded, {} failed, {} skipped",
+            provider,
+            counts.downloaded,
+            counts.failed,
+            counts.skipped,
+            width = width
+        );
+    }
+    for (reason, count) in report.skipped.iter() {
+        let _ = writeln!(out, "Skipped {} - {}", count, reason);
+    }
+    if report.unsupported > 0 {
+        let _ = writeln!(
+            out,
+            "Unsupported {} - listed in unsupported.json",
+            report.unsupported
+        );
+    }
+    if !report.failures.is_empty() {
+        let _ = writeln!(out, "Failed {}", report.failures.len());
+    }
+    for failure in report.failures.iter() {
+        let _ = writeln!(out, "  {} {} - {}", failure.id, failure.url, failure.reason);
+    }
+
+    out
+}
+
+pub fn print_run_report(report: &RunReport) {
+    println!("{} {}", "[SUMMARY]".bold(), report.resource.bold());
+    print!("{}", render_run_report(report));
+}
+
+/// Writes `run-report.json`, replacing the report of an earlier run
+pub fn write_run_report(folder_path: &str, report: &RunReport) -> Result<(), anyhow::Error> {
+    fs::write(
+        format!("{}/run-report.json", folder_path),
+        serde_json::to_string_pretty(report)?,
+    )?;
+    Ok(())
+}
+
+/// Prints the report of the run that just finished and writes it into `folder_path`
+pub async fn finish_run_report(
+    shared_state: &Arc<Mutex<SharedState>>,
+    folder_path: &str,
+    unsupported: usize,
+    print: bool,
+) {
+    let report = {
+        let mut ss = shared_state.lock().await;
+        ss.run_report.finish(unsupported);
+        ss.run_report.clone()
+    };
+
+    if print {
+        print_run_report(&report);
+    }
+    if let Err(e) = write_run_report(folder_path, &report) {
+        warn!("[RUN_REPORT_FAILED] {} - {}", folder_path, e);
+    }
+}
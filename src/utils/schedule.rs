@@ -0,0 +1,88 @@
+use super::{parse_interval, BatchTarget, BatchTargetError};
+use chrono::{DateTime, TimeZone};
+use croner::Cron;
+use serde::Deserialize;
+use std::{str::FromStr, time::Duration};
+use thiserror::Error;
+
+/// A batch target with the cron expression it is crawled on, e.g. `0 */6 * * *`
+#[derive(Debug, Clone)]
+pub struct ScheduledTarget {
+    pub target: BatchTarget,
+    pub cron: Cron,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub targets: Vec<ScheduledTarget>,
+    // Minimum pause between two crawls, spreads the requests of targets that are due together
+    pub pause: Duration,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScheduleError {
+    #[error(transparent)]
+    Target(#[from] BatchTargetError),
+    #[error("Invalid schedule {0} of target {1} - {2}")]
+    Cron(String, String, String),
+    #[error("Invalid pause - {0}")]
+    Pause(String),
+    #[error("Invalid TOML schedule file - {0}")]
+    Toml(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ScheduleFile {
+    pause: Option<String>,
+    targets: Vec<ScheduleFileTarget>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScheduleFileTarget {
+    target: String,
+    schedule: String,
+}
+
+impl ScheduledTarget {
+    pub fn next_run<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        self.cron.find_next_occurrence(after, false).ok()
+    }
+}
+
+/// Parses a TOML schedule file:
+///
+/// ```toml
+/// pause = "30s"
+///
+/// [[targets]]
+/// target = "user:spez"
+/// schedule = "0 */6 * * *"
+/// ```
+pub fn parse_schedule_toml(content: &str) -> Result<Schedule, ScheduleError> {
+    let file = toml::from_str::<ScheduleFile>(content)
+        .map_err(|e| ScheduleError::Toml(e.message().to_owned()))?;
+
+    let pause = match file.pause {
+        Some(pause) => parse_interval(&pause).map_err(ScheduleError::Pause)?,
+        None => Duration::ZERO,
+    };
+
+    let targets = file
+        .targets
+        .into_iter()
+        .map(|t| {
+            let cron = Cron::new(&t.schedule).parse().map_err(|e| {
+                ScheduleError::Cron(t.schedule.clone(), t.target.clone(), e.to_string())
+            })?;
+
+            Ok(ScheduledTarget {
+                target: BatchTarget::from_str(&t.target)?,
+                cron,
+            })
+        })
+        .collect::<Result<Vec<_>, ScheduleError>>()?;
+
+    Ok(Schedule { targets, pause })
+}
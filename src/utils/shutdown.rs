@@ -0,0 +1,51 @@
+use crate::error::ExitStatus;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Set by the first Ctrl-C, crawls stop queueing downloads and write their cache
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            requested: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn request(&self) {
+        self.requested.send_replace(true);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Resolves once a shutdown is requested
+    pub async fn wait(&self) {
+        let mut requested = self.requested.subscribe();
+        // The sender lives in `self`, so waiting can not fail
+        let _ = requested.wait_for(|requested| *requested).await;
+    }
+
+    /// Handles Ctrl-C, the first one requests a shutdown and the second one quits immediately
+    pub fn listen(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            warn!("[INTERRUPTED] Finishing running downloads, press Ctrl-C again to quit");
+            shutdown.request();
+
+            if tokio::signal::ctrl_c().await.is_ok() {
+                ExitStatus::Interrupted.exit();
+            }
+        });
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Parses a size like `500M`, `50G` or `1.5GB`, plain numbers are bytes and units are 1024 based
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let number_end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(number_end);
+
+    let amount = amount
+        .parse::<f64>()
+        .map_err(|_| format!("{} is not a valid size (e.g. 500M)", value))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("Unknown size unit {}, use K|M|G|T", unit)),
+    };
+
+    match (amount * multiplier as f64) as u64 {
+        0 => Err(String::from("The size must be greater than 0")),
+        bytes => Ok(bytes),
+    }
+}
+
+/// Set by `--max-file-size` and `--max-total-size`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    pub max_file_size: Option<u64>,
+    pub max_total_size: Option<u64>,
+}
+
+impl SizeLimits {
+    pub fn exceeds_file_size(&self, bytes: u64) -> bool {
+        self.max_file_size.is_some_and(|max| bytes > max)
+    }
+
+    pub fn quota_reached(&self, received_bytes: u64) -> bool {
+        self.max_total_size.is_some_and(|max| received_bytes >= max)
+    }
+}
+
+/// Why a file was not downloaded, recorded in the cache so `retry` can pick it up later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    FileTooLarge,
+    QuotaReached,
+    NoAudioTrack,
+    Removed,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            SkipReason::FileTooLarge => "larger than --max-file-size",
+            SkipReason::QuotaReached => "--max-total-size reached",
+            SkipReason::NoAudioTrack => "no audio track for --audio-only",
+            SkipReason::Removed => "deleted or removed from Reddit",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Aborts a download that turned out larger than `--max-file-size`
+#[derive(Error, Debug)]
+#[error("{file_path} is {bytes} bytes, larger than the limit of {limit} bytes")]
+pub struct FileTooLargeError {
+    pub file_path: String,
+    pub bytes: u64,
+    pub limit: u64,
+}
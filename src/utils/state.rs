@@ -1,9 +1,27 @@
-use std::str::FromStr;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use super::{
+    AboutCache, AudioFormat, BandwidthLimiter, ByteProgress, Downloader, FailureInjection,
+    GlobalCache, ImageConversion, ImportedArchive, LinkMode, OutputMode, PostRemoval,
+    ProviderProxy, RateLimiter, RemoteUploads, RunReport, Shutdown, SizeLimits, SkipReason,
+    SyncProgress, TransferStatus, TransferTimeouts, UserAgentPool, VideoProcessing, YtdlpCookies,
+    DEFAULT_ARIA2C_CONNECTIONS, DEFAULT_VIDEO_TASKS,
+};
+use crate::clients::{RedditAppCredentials, RedditCredentials, RedgifsQuality};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use thiserror::Error;
+use tokio::sync::Semaphore;
+
+pub const DEFAULT_FALLBACK_YTDLP_TASKS: usize = 2;
+pub const DEFAULT_YTDLP_TIMEOUT: Duration = Duration::from_secs(30 * 60);
 
 pub struct DownloadStats {
     pub downloads_failed: u64,
@@ -60,6 +78,23 @@ pub enum ResourceStatus {
     Active,
     Deleted,
     Suspended,
+    Banned,
+    Private,
+    Quarantined,
+}
+
+impl fmt::Display for ResourceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            ResourceStatus::Active => "active",
+            ResourceStatus::Deleted => "deleted",
+            ResourceStatus::Suspended => "suspended",
+            ResourceStatus::Banned => "banned",
+            ResourceStatus::Private => "private",
+            ResourceStatus::Quarantined => "quarantined",
+        };
+        write!(f, "{}", status)
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -97,6 +132,64 @@ pub struct FileCacheItemLatest {
     pub url: String,
     pub success: bool,
     pub index: Option<usize>,
+    #[serde(default)]
+    pub spoiler: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upvotes: Option<i64>,
+    // Name of the downloaded file inside the output folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<FileProvenance>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<PostDuplicate>,
+    // Set for failed entries that were skipped by a size limit instead of failing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<SkipReason>,
+    // Why the download of a failed entry failed, e.g. the error yt-dlp printed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Recorded on success, `cache verify` compares the file on disk against it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<FileChecksum>,
+    // Whether the post was deleted or removed when it was crawled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removal: Option<PostRemoval>,
+}
+
+/// Another submission of the same media
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostDuplicate {
+    pub id: String,
+    pub subreddit: String,
+    pub author: String,
+    pub created_utc: DateTime<Utc>,
+    pub permalink: String,
+}
+
+/// Response headers captured when downloading a file over HTTP
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileProvenance {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_length: Option<u64>,
+    // URL the file was served from after following redirects
+    pub final_url: String,
+}
+
+/// Size and optional hash of a downloaded file
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChecksum {
+    pub size: u64,
+    // Set by `--checksum`, hex encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -158,6 +251,27 @@ impl FromStr for FileCacheLatest {
     }
 }
 
+/// Recursively collects the `cache.json` files of every crawled resource below `root`
+pub fn find_cache_files(root: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut cache_files = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                folders.push(entry.path());
+            } else if file_type.is_file() && entry.file_name() == "cache.json" {
+                cache_files.push(entry.path());
+            }
+        }
+    }
+
+    cache_files.sort();
+    Ok(cache_files)
+}
+
 impl Default for DownloadStats {
     fn default() -> Self {
         Self {
@@ -168,16 +282,101 @@ impl Default for DownloadStats {
     }
 }
 
+/// OAuth2 access token of Reddit, requested again once it expires
+#[derive(Debug, Clone)]
+pub struct RedditToken {
+    pub access_token: String,
+    pub expires_at: Instant,
+}
+
 pub struct SharedState {
     pub redgifs_token: Option<String>,
+    pub reddit_token: Option<RedditToken>,
+    // Set by `--client-id` and `--client-secret` to crawl through the OAuth API
+    pub reddit_app: Option<RedditAppCredentials>,
+    // Set by the saved and upvoted commands, takes precedence over `reddit_app`
+    pub reddit_account: Option<RedditCredentials>,
+    // Kept for yt-dlp, the HTTP client is built with the same proxies
+    pub proxy: Option<reqwest::Url>,
+    pub provider_proxies: Vec<ProviderProxy>,
+    pub user_agents: UserAgentPool,
+    pub redgifs_quality: RedgifsQuality,
     pub file_cache_path: Option<String>,
     pub file_cache: FileCacheLatest,
+    pub failure_injection: Option<FailureInjection>,
+    // Shared with the crawls of concurrent batch targets
+    pub transfer_status: Arc<std::sync::Mutex<TransferStatus>>,
+    pub about_cache: AboutCache,
+    pub last_about_request: Option<std::time::Instant>,
+    pub rate_limiter: Arc<std::sync::Mutex<RateLimiter>>,
+    pub byte_progress: Option<ByteProgress>,
+    // Set by batch and watch runs, crawls draw their bars below its aggregate bar
+    pub sync_progress: Option<Arc<SyncProgress>>,
+    // Set by `--rate-limit`, shared by every download
+    pub bandwidth: Option<BandwidthLimiter>,
+    // Set by `--dry-run`, nothing is written to the output folder
+    pub dry_run: bool,
+    pub output_mode: OutputMode,
+    pub size_limits: SizeLimits,
+    // Bytes downloaded by this run, checked against `--max-total-size`
+    pub received_bytes: u64,
+    // Downloads of this run that failed and resources found unavailable, set the exit code
+    pub downloads_failed: u64,
+    pub resources_gone: u64,
+    // Downloads of the crawled resource, written to `run-report.json`
+    pub run_report: RunReport,
+    // Set by `--crawl-authors`, authors of the posts matched by the subreddit crawl
+    pub matched_authors: Option<Vec<String>>,
+    // Set by `--parallel-targets`, downloads of every crawl share `--tasks`
+    pub download_slots: Option<Arc<Semaphore>>,
+    pub shutdown: Shutdown,
+    // Set by `--embed-metadata`, downloaded images and videos are tagged with their post
+    pub embed_metadata: bool,
+    // Set by `--fallback-ytdlp-tasks`, yt-dlp processes of the fallback running at once
+    pub ytdlp_slots: Arc<Semaphore>,
+    // Set by `--ytdlp-timeout`, yt-dlp is stopped when a download takes longer
+    pub ytdlp_timeout: Duration,
+    // Set by `--download-timeout` and `--stall-timeout`, shared by every HTTP download
+    pub transfer_timeouts: TransferTimeouts,
+    // Set by `--checksum`, downloaded files are hashed into the cache
+    pub checksum: bool,
+    // Set by `--skip-existing`, files already on disk are not downloaded again
+    pub skip_existing: bool,
+    // Set by `--import-archive`, posts downloaded by other tools
+    pub imported_archive: ImportedArchive,
+    // Set by `--global-cache`, posts downloaded by any crawl
+    pub global_cache: Option<GlobalCache>,
+    // Set by `--link-duplicates`, posts of the global cache are linked instead of skipped
+    pub link_duplicates: Option<LinkMode>,
+    // Set by `--downloader` and `--aria2c-connections`
+    pub downloader: Downloader,
+    pub aria2c_connections: u16,
+    // Set for remote `--output` URLs, downloads are streamed and staged files uploaded
+    pub remote_uploads: Option<RemoteUploads>,
+    // Set by `--convert-images`
+    pub convert_images: Option<ImageConversion>,
+    // Set by `--remux` and `--transcode`
+    pub video_processing: VideoProcessing,
+    // Set by `--video-tasks`, ffmpeg processes remuxing or transcoding at once
+    pub video_slots: Arc<Semaphore>,
+    // Set by `--audio-only`
+    pub audio_only: Option<AudioFormat>,
+    // Set by `--cookies` and `--cookies-from-browser`
+    pub ytdlp_cookies: YtdlpCookies,
 }
 
 impl Default for SharedState {
     fn default() -> Self {
         Self {
             redgifs_token: None,
+            reddit_token: None,
+            reddit_app: None,
+            reddit_account: None,
+            proxy: None,
+            provider_proxies: Vec::new(),
+            user_agents: UserAgentPool::default(),
+            output_mode: OutputMode::default(),
+            redgifs_quality: RedgifsQuality::default(),
             file_cache_path: None,
             file_cache: FileCacheLatest {
                 version: FileCacheVersion::Latest,
@@ -187,6 +386,118 @@ impl Default for SharedState {
                 },
                 files: Vec::new(),
             },
+            failure_injection: None,
+            transfer_status: Arc::default(),
+            about_cache: AboutCache::default(),
+            last_about_request: None,
+            rate_limiter: Arc::default(),
+            byte_progress: None,
+            sync_progress: None,
+            bandwidth: None,
+            dry_run: false,
+            size_limits: SizeLimits::default(),
+            received_bytes: 0,
+            downloads_failed: 0,
+            resources_gone: 0,
+            run_report: RunReport::default(),
+            matched_authors: None,
+            download_slots: None,
+            shutdown: Shutdown::default(),
+            embed_metadata: false,
+            ytdlp_slots: Arc::new(Semaphore::new(DEFAULT_FALLBACK_YTDLP_TASKS)),
+            ytdlp_timeout: DEFAULT_YTDLP_TIMEOUT,
+            transfer_timeouts: TransferTimeouts::default(),
+            checksum: false,
+            skip_existing: false,
+            imported_archive: ImportedArchive::default(),
+            global_cache: None,
+            link_duplicates: None,
+            downloader: Downloader::default(),
+            aria2c_connections: DEFAULT_ARIA2C_CONNECTIONS,
+            remote_uploads: None,
+            convert_images: None,
+            video_processing: VideoProcessing::default(),
+            video_slots: Arc::new(Semaphore::new(DEFAULT_VIDEO_TASKS)),
+            audio_only: None,
+            ytdlp_cookies: YtdlpCookies::default(),
+        }
+    }
+}
+
+impl SharedState {
+    /// Whether a post is in the cache, was downloaded by another crawl or by another tool
+    pub fn is_cached(&self, id: &str) -> bool {
+        self.file_cache.files.iter().any(|f| f.id == id)
+            || (self.link_duplicates.is_none()
+                && self.global_cache.as_ref().is_some_and(|c| c.contains(id)))
+            || self.imported_archive.contains(id)
+    }
+
+    /// State of a batch target crawled next to others, the cache and counters are its own while
+    /// tokens, throttling and download slots are shared
+    pub fn fork(&self) -> Self {
+        Self {
+            redgifs_token: self.redgifs_token.clone(),
+            reddit_token: self.reddit_token.clone(),
+            reddit_app: self.reddit_app.clone(),
+            reddit_account: self.reddit_account.clone(),
+            proxy: self.proxy.clone(),
+            provider_proxies: self.provider_proxies.clone(),
+            user_agents: self.user_agents.clone(),
+            redgifs_quality: self.redgifs_quality,
+            failure_injection: self.failure_injection,
+            transfer_status: self.transfer_status.clone(),
+            about_cache: self.about_cache.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            bandwidth: self.bandwidth.clone(),
+            dry_run: self.dry_run,
+            output_mode: self.output_mode,
+            size_limits: self.size_limits,
+            received_bytes: self.received_bytes,
+            shutdown: self.shutdown.clone(),
+            embed_metadata: self.embed_metadata,
+            ytdlp_slots: self.ytdlp_slots.clone(),
+            ytdlp_timeout: self.ytdlp_timeout,
+            transfer_timeouts: self.transfer_timeouts,
+            checksum: self.checksum,
+            skip_existing: self.skip_existing,
+            imported_archive: self.imported_archive.clone(),
+            global_cache: self.global_cache.clone(),
+            link_duplicates: self.link_duplicates,
+            downloader: self.downloader,
+            aria2c_connections: self.aria2c_connections,
+            remote_uploads: self.remote_uploads.clone(),
+            convert_images: self.convert_images,
+            video_processing: self.video_processing,
+            video_slots: self.video_slots.clone(),
+            audio_only: self.audio_only,
+            ytdlp_cookies: self.ytdlp_cookies.clone(),
+            download_slots: self.download_slots.clone(),
+            sync_progress: self.sync_progress.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds the counters of a forked target to this state once it finished
+    ///
+    /// `forked_bytes` are the received bytes at the time of the fork, which the fork started from.
+    pub fn join(&mut self, fork: &SharedState, forked_bytes: u64) {
+        self.downloads_failed += fork.downloads_failed;
+        self.resources_gone += fork.resources_gone;
+        self.received_bytes += fork.received_bytes.saturating_sub(forked_bytes);
+    }
+
+    /// Replaces the cache file at once, a crash while writing leaves the previous cache intact
+    pub fn write_file_cache(&self, file_cache_path: &str) -> Result<(), anyhow::Error> {
+        if !self.dry_run {
+            let partial_path = format!("{}.part", file_cache_path);
+            fs::write(&partial_path, serde_json::to_string(&self.file_cache)?)?;
+            fs::rename(&partial_path, file_cache_path)?;
+            // Stays staged, the crawl keeps reading and updating it
+            if let Some(uploads) = &self.remote_uploads {
+                uploads.queue(file_cache_path, true);
+            }
         }
+        Ok(())
     }
 }
@@ -0,0 +1,52 @@
+use super::state::SharedState;
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::Mutex,
+};
+
+/// Serves the snapshot of the running transfers as JSON on `GET /status`
+pub async fn serve_transfer_status(
+    port: u16,
+    shared_state: Arc<Mutex<SharedState>>,
+) -> Result<(), anyhow::Error> {
+    // Only reachable from the machine running the crawler
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let shared_state = Arc::clone(&shared_state);
+
+        tokio::spawn(async move {
+            let mut buffer = [0; 1024];
+            let read = stream.read(&mut buffer).await.unwrap_or_default();
+            let request = String::from_utf8_lossy(&buffer[..read]);
+
+            let (status, body) = match request.starts_with("GET /status ") {
+                true => {
+                    let snapshot = shared_state
+                        .lock()
+                        .await
+                        .transfer_status
+                        .lock()
+                        .unwrap()
+                        .snapshot();
+                    (
+                        "200 OK",
+                        serde_json::to_string(&snapshot).unwrap_or_default(),
+                    )
+                }
+                false => ("404 Not Found", String::from("{}")),
+            };
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
@@ -0,0 +1,111 @@
+use super::{state::FileCacheLatest, PostRemoval};
+use crate::clients::api_types::reddit::submitted_response::RedditSubmittedChildData;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{collections::HashMap, fmt::Write};
+
+/// An archived post Reddit took down since the cache last recorded it, listed by `diff`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Takedown {
+    pub resource: String,
+    pub id: String,
+    pub title: String,
+    pub subreddit: String,
+    pub author: Option<String>,
+    pub created_utc: DateTime<Utc>,
+    pub removal: PostRemoval,
+    // Archived files of the post inside the resource folder
+    pub files: Vec<String>,
+}
+
+/// IDs of the posts with downloaded files, the ones `diff` fetches again
+///
+/// Redgifs profiles are left out, their posts have no subreddit and are not on Reddit.
+pub fn get_archived_ids(cache: &FileCacheLatest) -> Vec<String> {
+    let mut ids = cache
+        .files
+        .iter()
+        .filter(|item| item.success && !item.subreddit.is_empty())
+        .map(|item| item.id.clone())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Compares the archived posts of a cache with the same posts fetched again and records how
+/// they are shown now in the cache
+///
+/// Posts missing from `posts` are no longer returned by Reddit at all. Only posts taken down
+/// since the cache last recorded them are returned, so every takedown is reported once.
+pub fn diff_takedowns(
+    resource: &str,
+    cache: &mut FileCacheLatest,
+    posts: &[RedditSubmittedChildData],
+) -> Vec<Takedown> {
+    let posts = posts
+        .iter()
+        .map(|post| (post.id.as_str(), post))
+        .collect::<HashMap<_, _>>();
+    let mut takedowns = Vec::<Takedown>::new();
+
+    for id in get_archived_ids(cache) {
+        let removal = match posts.get(id.as_str()) {
+            Some(post) => PostRemoval::of(post),
+            None => Some(PostRemoval::Unavailable),
+        };
+
+        for item in cache.files.iter_mut().filter(|item| item.id == id) {
+            let was_removed = item.removal.is_some_and(|r| r.is_removed());
+            item.removal = removal;
+
+            let Some(removal) = removal.filter(|r| r.is_removed() && !was_removed) else {
+                continue;
+            };
+            let file_name = item.file_name.clone().filter(|_| item.success);
+            match takedowns.iter_mut().find(|t| t.id == id) {
+                Some(takedown) => takedown.files.extend(file_name),
+                None => takedowns.push(Takedown {
+                    resource: resource.to_owned(),
+                    id: id.clone(),
+                    title: item.title.clone(),
+                    subreddit: item.subreddit.clone(),
+                    author: item.author.clone(),
+                    created_utc: item.created_utc,
+                    removal,
+                    files: file_name.into_iter().collect(),
+                }),
+            }
+        }
+    }
+
+    takedowns.sort_by_key(|t| t.created_utc);
+    takedowns
+}
+
+pub fn render_takedowns(takedowns: &[Takedown]) -> String {
+    let mut out = format!(
+        "{} archived posts were taken down since the last check\n",
+        takedowns.len()
+    );
+    let width = takedowns
+        .iter()
+        .map(|t| t.resource.len())
+        .max()
+        .unwrap_or_default();
+
+    for takedown in takedowns {
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {}  {}  {} - {}",
+            takedown.resource,
+            takedown.id,
+            takedown.created_utc.format("%Y-%m-%d"),
+            takedown.removal,
+            takedown.title
+        );
+    }
+
+    out
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+// Window the current transfer rate is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Live view on the running downloads, exposed by long running modes
+#[derive(Debug)]
+pub struct TransferStatus {
+    started_at: Instant,
+    active_downloads: u64,
+    bytes_downloaded: f64,
+    samples: VecDeque<(Instant, f64)>,
+    queue_depth: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferStatusSnapshot {
+    pub uptime_seconds: u64,
+    pub active_downloads: u64,
+    pub bytes_downloaded: f64,
+    pub bytes_per_second: f64,
+    pub files_queued: u64,
+    pub queue_depth: BTreeMap<String, u64>,
+    // Estimated from the average seconds per file of the current rate window
+    pub eta_seconds: Option<u64>,
+}
+
+impl Default for TransferStatus {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            active_downloads: 0,
+            bytes_downloaded: 0.0,
+            samples: VecDeque::new(),
+            queue_depth: BTreeMap::new(),
+        }
+    }
+}
+
+impl TransferStatus {
+    fn prune_samples(&mut self, now: Instant) {
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= RATE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn set_queue_depth(&mut self, resource: &str, depth: u64) {
+        match depth {
+            0 => self.queue_depth.remove(resource),
+            _ => self.queue_depth.insert(resource.to_owned(), depth),
+        };
+    }
+
+    /// Grows the queue of a resource whose posts are still being fetched
+    pub fn add_queue_depth(&mut self, resource: &str, depth: u64) {
+        let current = self.queue_depth.get(resource).copied().unwrap_or(0);
+        self.set_queue_depth(resource, current + depth);
+    }
+
+    pub fn start_download(&mut self) {
+        self.active_downloads += 1;
+    }
+
+    pub fn finish_download(&mut self, resource: &str, bytes: f64) {
+        let now = Instant::now();
+        self.active_downloads = self.active_downloads.saturating_sub(1);
+        self.bytes_downloaded += bytes;
+        self.samples.push_back((now, bytes));
+        self.prune_samples(now);
+
+        if let Some(depth) = self.queue_depth.get(resource) {
+            self.set_queue_depth(resource, depth.saturating_sub(1));
+        }
+    }
+
+    pub fn snapshot(&mut self) -> TransferStatusSnapshot {
+        let now = Instant::now();
+        self.prune_samples(now);
+
+        let window = now
+            .duration_since(self.started_at)
+            .min(RATE_WINDOW)
+            .as_secs_f64();
+        let window_bytes: f64 = self.samples.iter().map(|(_, b)| b).sum();
+        let bytes_per_second = match window > 0.0 {
+            true => window_bytes / window,
+            false => 0.0,
+        };
+
+        let files_queued = self.queue_depth.values().sum::<u64>();
+        let eta_seconds = match self.samples.len() {
+            0 => None,
+            completed => Some((files_queued as f64 * window / completed as f64).ceil() as u64),
+        };
+
+        TransferStatusSnapshot {
+            uptime_seconds: now.duration_since(self.started_at).as_secs(),
+            active_downloads: self.active_downloads,
+            bytes_downloaded: self.bytes_downloaded,
+            bytes_per_second,
+            files_queued,
+            queue_depth: self.queue_depth.clone(),
+            eta_seconds,
+        }
+    }
+}
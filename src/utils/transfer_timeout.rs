@@ -0,0 +1,58 @@
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::Instant;
+
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+// Stalled transfers are requested again this often, resuming their .part file, before failing
+pub const STALL_RETRIES: u32 = 2;
+
+/// Set by `--download-timeout` and `--stall-timeout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferTimeouts {
+    // Longest a file may take including its retries, unlimited when `None`
+    pub download: Option<Duration>,
+    // Longest a transfer may go without receiving a byte
+    pub stall: Duration,
+}
+
+impl Default for TransferTimeouts {
+    fn default() -> Self {
+        Self {
+            download: None,
+            stall: DEFAULT_STALL_TIMEOUT,
+        }
+    }
+}
+
+impl TransferTimeouts {
+    /// When the next chunk of a download started at `started` has to arrive by
+    pub fn chunk_deadline(&self, started: Instant, now: Instant) -> (Instant, TransferTimeout) {
+        let stalled = now + self.stall;
+
+        match self.download.map(|download| started + download) {
+            Some(timed_out) if timed_out <= stalled => (timed_out, TransferTimeout::TimedOut),
+            _ => (stalled, TransferTimeout::Stalled),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferTimeout {
+    Stalled,
+    TimedOut,
+}
+
+/// Aborts a transfer that stopped receiving data or took longer than `--download-timeout`
+#[derive(Error, Debug)]
+pub enum TransferTimeoutError {
+    #[error("{file_path} received no data for {}s", .timeout.as_secs())]
+    Stalled {
+        file_path: String,
+        timeout: Duration,
+    },
+    #[error("{file_path} did not finish within {}s", .timeout.as_secs())]
+    TimedOut {
+        file_path: String,
+        timeout: Duration,
+    },
+}
@@ -0,0 +1,153 @@
+use crate::{
+    clients::api_types::reddit::submitted_response::{
+        RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    reddit_parser::RedditPostParser,
+};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+    fs, io,
+};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnsupportedPostKind {
+    Poll,
+    // Links to an article or a host without a provider
+    Link,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedPost {
+    pub id: String,
+    pub kind: UnsupportedPostKind,
+    pub title: String,
+    pub url: String,
+    pub permalink: String,
+    pub post_hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedDomain {
+    pub domain: String,
+    pub posts: Vec<UnsupportedPost>,
+}
+
+/// Contents of `unsupported.json`, domains with the most posts come first
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedReport {
+    pub total: usize,
+    pub domains: Vec<UnsupportedDomain>,
+}
+
+impl UnsupportedReport {
+    /// Counts per domain for the crawl summary e.g. `example.com (2), reddit.com (1)`
+    pub fn summary(&self) -> String {
+        self.domains
+            .iter()
+            .map(|d| format!("{} ({})", d.domain, d.posts.len()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn get_unsupported_post(data: &RedditSubmittedChildData) -> Option<UnsupportedPost> {
+    let kind = match (&data.poll_data, data.is_self) {
+        (Some(_), _) => UnsupportedPostKind::Poll,
+        // Text posts have no media to miss
+        (None, true) => return None,
+        (None, false) => UnsupportedPostKind::Link,
+    };
+
+    Some(UnsupportedPost {
+        id: data.id.clone(),
+        kind,
+        title: data.title.clone(),
+        url: data.url.clone(),
+        permalink: format!("https://www.reddit.com{}", data.permalink),
+        post_hint: data.post_hint.clone(),
+    })
+}
+
+/// Crawled posts the parser could not classify, grouped by domain
+///
+/// The responses are parsed again, so posts left out by `--filter` are not reported.
+pub fn get_unsupported_report(
+    responses: &[RedditSubmittedResponse],
+    parser: &RedditPostParser,
+) -> UnsupportedReport {
+    let parsed = responses
+        .iter()
+        .flat_map(|r| parser.parse(r))
+        .map(|p| p.id)
+        .collect::<HashSet<_>>();
+    let mut domains = BTreeMap::<String, Vec<UnsupportedPost>>::new();
+
+    for child in responses.iter().flat_map(|r| r.data.children.iter()) {
+        if parsed.contains(&child.data.id) {
+            continue;
+        }
+        if let Some(post) = get_unsupported_post(&child.data) {
+            domains
+                .entry(child.data.domain.clone())
+                .or_default()
+                .push(post);
+        }
+    }
+
+    let mut domains = domains
+        .into_iter()
+        .map(|(domain, posts)| UnsupportedDomain { domain, posts })
+        .collect::<Vec<_>>();
+    domains.sort_by_key(|d| Reverse(d.posts.len()));
+
+    UnsupportedReport {
+        total: domains.iter().map(|d| d.posts.len()).sum(),
+        domains,
+    }
+}
+
+/// Writes `unsupported.json`, a report of an earlier run is removed once nothing is missing
+pub fn write_unsupported_report(
+    folder_path: &str,
+    report: &UnsupportedReport,
+) -> Result<(), anyhow::Error> {
+    let report_path = format!("{}/unsupported.json", folder_path);
+
+    if report.total == 0 {
+        return match fs::remove_file(&report_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+
+    fs::write(report_path, serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+/// Adds the posts that were not downloaded to the crawl summary and `unsupported.json`
+pub fn report_unsupported_posts(
+    folder_path: &str,
+    responses: &[RedditSubmittedResponse],
+    parser: &RedditPostParser,
+) -> usize {
+    let report = get_unsupported_report(responses, parser);
+    if report.total > 0 {
+        info!(
+            "[UNSUPPORTED] {} posts could not be classified - {}",
+            report.total,
+            report.summary()
+        );
+    }
+
+    if let Err(e) = write_unsupported_report(folder_path, &report) {
+        warn!("[UNSUPPORTED_FAILED] {} - {}", folder_path, e);
+    }
+    report.total
+}
@@ -0,0 +1,72 @@
+use reqwest::header::HeaderValue;
+use std::{fs, io};
+use thiserror::Error;
+
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/96.0.4664.110 Safari/537.36";
+
+#[derive(Error, Debug)]
+pub enum UserAgentError {
+    #[error("Failed to read {0}: {1}")]
+    Read(String, io::Error),
+    #[error("{0} does not contain a user agent")]
+    Empty(String),
+    #[error("Invalid user agent {0}")]
+    Invalid(String),
+}
+
+/// User agents sent to Reddit and Redgifs, rotated whenever Reddit rate limits a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserAgentPool {
+    agents: Vec<String>,
+    current: usize,
+}
+
+impl Default for UserAgentPool {
+    fn default() -> Self {
+        Self {
+            agents: vec![String::from(DEFAULT_USER_AGENT)],
+            current: 0,
+        }
+    }
+}
+
+impl UserAgentPool {
+    pub fn new(agents: Vec<String>) -> Result<Self, UserAgentError> {
+        if let Some(agent) = agents.iter().find(|a| HeaderValue::from_str(a).is_err()) {
+            return Err(UserAgentError::Invalid(agent.to_owned()));
+        }
+
+        match agents.is_empty() {
+            true => Ok(Self::default()),
+            false => Ok(Self { agents, current: 0 }),
+        }
+    }
+
+    /// Reads a pool with a user agent per line, empty lines and `#` comments are skipped
+    pub fn from_file(path: &str) -> Result<Self, UserAgentError> {
+        let content =
+            fs::read_to_string(path).map_err(|e| UserAgentError::Read(path.to_owned(), e))?;
+
+        let agents = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        match agents.is_empty() {
+            true => Err(UserAgentError::Empty(path.to_owned())),
+            false => Self::new(agents),
+        }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.agents[self.current]
+    }
+
+    /// Moves on to the next user agent, returns false when the pool only has one
+    pub fn rotate(&mut self) -> bool {
+        self.current = (self.current + 1) % self.agents.len();
+        self.agents.len() > 1
+    }
+}
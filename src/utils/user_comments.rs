@@ -0,0 +1,107 @@
+use crate::clients::api_types::reddit::user_comments::RedditUserCommentData;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader},
+};
+
+/// A line of `comments.ndjson` written by the user-comments command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserComment {
+    pub id: String,
+    pub subreddit: String,
+    pub score: i64,
+    pub permalink: String,
+    pub created_utc: i64,
+    pub link_title: Option<String>,
+    pub body: String,
+}
+
+impl From<RedditUserCommentData> for UserComment {
+    fn from(comment: RedditUserCommentData) -> Self {
+        Self {
+            id: comment.id,
+            subreddit: comment.subreddit,
+            score: comment.score,
+            permalink: format!("https://www.reddit.com{}", comment.permalink),
+            created_utc: comment.created_utc.timestamp(),
+            link_title: comment.link_title,
+            body: comment.body,
+        }
+    }
+}
+
+/// Reads the comments saved by an earlier run, a missing file has none
+pub fn read_user_comments(file_path: &str) -> Result<Vec<UserComment>, anyhow::Error> {
+    let file = match fs::File::open(file_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut comments = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            comments.push(serde_json::from_str(&line)?);
+        }
+    }
+
+    Ok(comments)
+}
+
+/// Adds fetched comments to the saved ones, newest first. Fetched comments replace saved ones
+/// with the same ID, so edits and scores stay current
+pub fn merge_user_comments(saved: Vec<UserComment>, fetched: Vec<UserComment>) -> Vec<UserComment> {
+    let mut comments = saved
+        .into_iter()
+        .map(|c| (c.id.clone(), c))
+        .collect::<HashMap<_, _>>();
+    comments.extend(fetched.into_iter().map(|c| (c.id.clone(), c)));
+
+    let mut comments = comments.into_values().collect::<Vec<_>>();
+    comments.sort_by(|a, b| b.created_utc.cmp(&a.created_utc).then(a.id.cmp(&b.id)));
+    comments
+}
+
+/// One JSON object per line, so archives can be appended to and streamed
+pub fn render_user_comments_ndjson(comments: &[UserComment]) -> Result<String, anyhow::Error> {
+    let mut ndjson = String::new();
+    for comment in comments {
+        ndjson.push_str(&serde_json::to_string(comment)?);
+        ndjson.push('\n');
+    }
+
+    Ok(ndjson)
+}
+
+/// Renders the comments of a user as a readable document, newest first
+pub fn render_user_comments_markdown(user: &str, comments: &[UserComment]) -> String {
+    let mut markdown = format!("# Comments of u/{}\n\n", user);
+
+    for comment in comments {
+        let created = DateTime::from_timestamp(comment.created_utc, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default();
+
+        markdown.push_str(&format!(
+            "## r/{} - {}\n\n",
+            comment.subreddit,
+            comment.link_title.as_deref().unwrap_or(&comment.id)
+        ));
+        markdown.push_str(&format!(
+            "{} points - {} - [permalink]({})\n\n",
+            comment.score, created, comment.permalink
+        ));
+        // Quoted, so headings and rules in the comment do not break the document
+        for line in comment.body.lines() {
+            markdown.push_str(&format!("> {}\n", line).replace("> \n", ">\n"));
+        }
+        markdown.push('\n');
+    }
+
+    markdown
+}
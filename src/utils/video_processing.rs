@@ -0,0 +1,171 @@
+use super::get_long_path;
+use std::{fs, path::Path, process::Stdio};
+use thiserror::Error;
+use tokio::process::Command;
+
+pub const DEFAULT_VIDEO_TASKS: usize = 1;
+
+// Containers of downloaded videos, other files are never passed to ffmpeg
+const VIDEO_EXTENSIONS: [&str; 6] = ["mp4", "m4v", "mkv", "mov", "webm", "ts"];
+
+/// Container videos are remuxed into with `--remux`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum VideoContainer {
+    Mp4,
+    Mkv,
+}
+
+impl VideoContainer {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "mp4",
+            VideoContainer::Mkv => "mkv",
+        }
+    }
+}
+
+/// Codec videos are transcoded to with `--transcode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum VideoCodec {
+    H264,
+    H265,
+}
+
+impl VideoCodec {
+    fn get_args(&self) -> [&'static str; 8] {
+        match self {
+            VideoCodec::H264 => [
+                "-c:v", "libx264", "-crf", "23", "-preset", "medium", "-pix_fmt", "yuv420p",
+            ],
+            // Tagged as hvc1, Apple devices refuse to play the default hev1
+            VideoCodec::H265 => [
+                "-c:v", "libx265", "-crf", "28", "-tag:v", "hvc1", "-pix_fmt", "yuv420p",
+            ],
+        }
+    }
+}
+
+/// Set by `--remux` and `--transcode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VideoProcessing {
+    pub remux: Option<VideoContainer>,
+    pub transcode: Option<VideoCodec>,
+}
+
+#[derive(Error, Debug)]
+pub enum VideoProcessingError {
+    #[error("Spawning ffmpeg failed: {0}")]
+    Spawn(std::io::Error),
+    #[error("ffmpeg exited with code {code:?}: {message}")]
+    Failed { code: Option<i32>, message: String },
+}
+
+impl VideoProcessing {
+    /// Whether `--remux` or `--transcode` were given
+    pub fn is_enabled(&self) -> bool {
+        self.remux.is_some() || self.transcode.is_some()
+    }
+
+    /// Extension of the processed video, transcoded videos are written as mp4 unless remuxed
+    fn get_extension<'a>(&self, extension: &'a str) -> &'a str {
+        match (self.remux, self.transcode) {
+            (Some(container), _) => container.extension(),
+            (None, Some(_)) => "mp4",
+            (None, None) => extension,
+        }
+    }
+
+    /// Whether a downloaded file is processed, videos already in the remux container are kept
+    pub fn processes(&self, file_name: &str) -> bool {
+        let extension = match Path::new(file_name).extension() {
+            Some(extension) => extension.to_string_lossy().to_ascii_lowercase(),
+            None => return false,
+        };
+        if !VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+            return false;
+        }
+
+        self.transcode.is_some() || self.get_extension(&extension) != extension
+    }
+
+    /// Arguments of ffmpeg processing `input` to `output`
+    pub fn get_args(&self, input: &str, output: &str) -> Vec<String> {
+        let mut args = vec!["-y", "-loglevel", "error", "-i", input];
+        match self.transcode {
+            Some(codec) => {
+                args.extend(codec.get_args());
+                args.extend(["-c:a", "aac", "-b:a", "160k"]);
+            }
+            None => args.extend(["-c", "copy"]),
+        }
+        // Players start before the whole file is read
+        if output.ends_with(".mp4") {
+            args.extend(["-movflags", "+faststart"]);
+        }
+        args.push(output);
+
+        args.into_iter().map(String::from).collect()
+    }
+
+    /// Processes the video `file_name` inside `folder_path` and replaces the original, returns
+    /// the name of the processed file
+    pub async fn process(
+        &self,
+        folder_path: &str,
+        file_name: &str,
+    ) -> Result<String, anyhow::Error> {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let extension = self.get_extension(&extension);
+        let processed_name = Path::new(file_name)
+            .with_extension(extension)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let input = format!("{}/{}", folder_path, file_name);
+        let output = format!("{}/{}", folder_path, processed_name);
+        // Named so ffmpeg still picks the container from the extension
+        let partial = format!(
+            "{}/{}",
+            folder_path,
+            Path::new(file_name)
+                .with_extension(format!("part.{}", extension))
+                .to_string_lossy()
+        );
+
+        let long_input = get_long_path(&input).to_string_lossy().to_string();
+        let long_partial = get_long_path(&partial).to_string_lossy().to_string();
+        let result = Command::new("ffmpeg")
+            .args(self.get_args(&long_input, &long_partial))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(VideoProcessingError::Spawn)?;
+
+        if !result.status.success() {
+            let _ = fs::remove_file(get_long_path(&partial));
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(VideoProcessingError::Failed {
+                code: result.status.code(),
+                message: stderr
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|l| !l.is_empty())
+                    .unwrap_or("no output")
+                    .to_owned(),
+            }
+            .into());
+        }
+
+        // Transcoded mp4 files replace their original by the rename
+        fs::rename(get_long_path(&partial), get_long_path(&output))?;
+        if processed_name != file_name {
+            fs::remove_file(get_long_path(&input))?;
+        }
+        Ok(processed_name)
+    }
+}
@@ -0,0 +1,74 @@
+use super::state::FileCacheLatest;
+use chrono::Datelike;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const VIEWS_FOLDER: &str = "views";
+
+#[cfg(unix)]
+pub(super) fn symlink_file(original: &Path, link: &Path) -> Result<(), std::io::Error> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+pub(super) fn symlink_file(original: &Path, link: &Path) -> Result<(), std::io::Error> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+/// Links a downloaded file into `views/<segments>/`, pointing back to it relatively
+fn link_into_view(
+    folder: &Path,
+    segments: &[String],
+    file_name: &str,
+) -> Result<(), anyhow::Error> {
+    let view_folder = segments
+        .iter()
+        .fold(folder.join(VIEWS_FOLDER), |path, segment| {
+            path.join(segment)
+        });
+    fs::create_dir_all(&view_folder)?;
+
+    let original = (0..=segments.len())
+        .fold(PathBuf::new(), |path, _| path.join(".."))
+        .join(file_name);
+    // Files organized into subfolders are linked by their name alone
+    let link = view_folder.join(Path::new(file_name).file_name().unwrap_or_default());
+
+    if fs::symlink_metadata(&link).is_err() {
+        symlink_file(&original, &link)?;
+    }
+    Ok(())
+}
+
+/// Regenerates the human-readable `views` folder of a crawled resource from its cache
+pub fn rebuild_views(folder: &Path, cache: &FileCacheLatest) -> Result<usize, anyhow::Error> {
+    let views_folder = folder.join(VIEWS_FOLDER);
+    if views_folder.exists() {
+        fs::remove_dir_all(&views_folder)?;
+    }
+
+    let mut linked = 0;
+    for item in cache.files.iter().filter(|f| f.success) {
+        let Some(file_name) = &item.file_name else {
+            continue;
+        };
+        if !folder.join(file_name).is_file() {
+            continue;
+        }
+
+        let by_subreddit = [String::from("by-subreddit"), item.subreddit.clone()];
+        let by_date = [
+            String::from("by-date"),
+            format!("{:04}", item.created_utc.year()),
+            format!("{:02}", item.created_utc.month()),
+        ];
+
+        link_into_view(folder, &by_subreddit, file_name)?;
+        link_into_view(folder, &by_date, file_name)?;
+        linked += 1;
+    }
+
+    Ok(linked)
+}
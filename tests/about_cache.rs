@@ -0,0 +1,46 @@
+use chrono::Duration;
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::user_about::{RedditUserAbout, RedditUserAboutData},
+    utils::AboutCache,
+};
+use std::{env, error::Error, fs};
+
+#[test]
+fn it_persists_about_data_across_runs() -> Result<(), Box<dyn Error>> {
+    let path = env::temp_dir().join(format!(
+        "reddit_clawler_about_cache_{}.json",
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+
+    let about = RedditUserAbout {
+        kind: String::from("t2"),
+        data: RedditUserAboutData {
+            name: String::from("spez"),
+            is_suspended: true,
+            ..RedditUserAboutData::default()
+        },
+    };
+
+    let mut cache = AboutCache::load(path.clone());
+    assert_eq!(
+        cache.get::<RedditUserAbout>("user/spez", Duration::hours(1)),
+        None
+    );
+    cache.insert("user/spez", &about)?;
+
+    let cache = AboutCache::load(path.clone());
+    assert_eq!(
+        cache.get::<RedditUserAbout>("user/spez", Duration::hours(1)),
+        Some(about)
+    );
+    // Entries older than the TTL are ignored
+    assert_eq!(
+        cache.get::<RedditUserAbout>("user/spez", Duration::zero()),
+        None
+    );
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::{gen_archive_url, parse_archive_response, Archive, ArchiveQuery, ArchiveResponse},
+    reddit_parser::{RedditMediaProviderType, RedditPostParser},
+};
+use std::{error::Error, fs};
+
+#[test]
+fn it_generates_archive_urls() {
+    assert_eq!(
+        gen_archive_url(
+            Archive::ArcticShift,
+            ArchiveQuery::Subreddit("aww"),
+            None,
+            None
+        ),
+        "https://arctic-shift.photon-reddit.com/api/posts/search?subreddit=aww&limit=100&sort=desc"
+    );
+    assert_eq!(
+        gen_archive_url(
+            Archive::Pushshift,
+            ArchiveQuery::Author("spez"),
+            Some(1702072273),
+            Some(1201233600)
+        ),
+        "https://api.pushshift.io/reddit/search/submission?author=spez&size=100&sort=desc&sort_type=created_utc&before=1702072273&after=1201233600"
+    );
+}
+
+#[test]
+fn it_parses_archived_posts() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/archive/arctic_shift_response.json")?;
+    let response: ArchiveResponse = serde_json::from_str(&data)?;
+    assert_eq!(response.data.len(), 2);
+
+    // The post without media fields is skipped
+    let listing = parse_archive_response(response);
+    assert_eq!(listing.data.children.len(), 1);
+
+    let posts = RedditPostParser::default().parse(&listing);
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].id, "18dxopo");
+    assert_eq!(posts[0].provider, RedditMediaProviderType::RedditImage);
+
+    Ok(())
+}
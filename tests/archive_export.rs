@@ -0,0 +1,69 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{write_archive, ArchiveFormat};
+use std::{env, error::Error, fs, io::Read, path::Path};
+
+fn prepare_folder(name: &str) -> Result<String, Box<dyn Error>> {
+    let folder = env::temp_dir()
+        .join(format!("{}_{}", name, std::process::id()))
+        .join("user")
+        .join("spez");
+    let _ = fs::remove_dir_all(folder.parent().unwrap());
+    fs::create_dir_all(folder.join("aww"))?;
+    fs::write(folder.join("cache.json"), "{}")?;
+    fs::write(folder.join("aww").join("18dxopo.jpg"), "image")?;
+    fs::write(folder.join("18dxopo.json"), "metadata")?;
+
+    Ok(folder.to_string_lossy().to_string())
+}
+
+#[test]
+fn it_archives_to_zip_and_deletes_files() -> Result<(), Box<dyn Error>> {
+    let folder = prepare_folder("reddit_clawler_archive_zip")?;
+
+    let summary = write_archive(&folder, ArchiveFormat::Zip, true)?.ok_or("Expected an archive")?;
+    assert!(summary.path.starts_with(&format!("{}-", folder)));
+    assert!(summary.path.ends_with(".zip"));
+    assert_eq!(summary.files, 3);
+
+    let mut zip = zip::ZipArchive::new(fs::File::open(&summary.path)?)?;
+    let mut names = zip.file_names().map(String::from).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["18dxopo.json", "aww/18dxopo.jpg", "cache.json"]);
+
+    let mut image = String::new();
+    zip.by_name("aww/18dxopo.jpg")?.read_to_string(&mut image)?;
+    assert_eq!(image, "image");
+
+    // Only the cache stays behind
+    assert!(Path::new(&folder).join("cache.json").exists());
+    assert!(!Path::new(&folder).join("aww").exists());
+    assert!(!Path::new(&folder).join("18dxopo.json").exists());
+
+    // Nothing new to archive
+    assert_eq!(write_archive(&folder, ArchiveFormat::Zip, true)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn it_archives_to_tar_zst() -> Result<(), Box<dyn Error>> {
+    let folder = prepare_folder("reddit_clawler_archive_tar")?;
+
+    let summary =
+        write_archive(&folder, ArchiveFormat::TarZst, false)?.ok_or("Expected an archive")?;
+    assert!(summary.path.ends_with(".tar.zst"));
+
+    let decoder = zstd::Decoder::new(fs::File::open(&summary.path)?)?;
+    let mut tar = tar::Archive::new(decoder);
+    let mut names = tar
+        .entries()?
+        .map(|e| Ok(e?.path()?.to_string_lossy().to_string()))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    names.sort();
+    assert_eq!(names, vec!["18dxopo.json", "aww/18dxopo.jpg", "cache.json"]);
+
+    // The loose files are kept
+    assert!(Path::new(&folder).join("aww").join("18dxopo.jpg").exists());
+
+    Ok(())
+}
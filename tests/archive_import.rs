@@ -0,0 +1,49 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{get_gallery_dl_post_id, read_id_file, ImportedArchive};
+use std::{collections::HashSet, env, error::Error, fs};
+
+#[test]
+fn it_reads_post_ids_of_other_tools() {
+    assert_eq!(
+        get_gallery_dl_post_id("reddit18f0e01_01 Sleepy cat"),
+        Some(String::from("18f0e01"))
+    );
+    assert_eq!(get_gallery_dl_post_id("twitter1234567890"), None);
+
+    assert_eq!(
+        read_id_file("18f0e01\nt3_18f0e02\n\nnot an id\n"),
+        HashSet::from([String::from("18f0e01"), String::from("18f0e02")])
+    );
+}
+
+#[test]
+fn it_imports_archives() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_import_{}", std::process::id()));
+    fs::create_dir_all(folder.join("bdfr"))?;
+
+    let sqlite_path = folder.join("archive.sqlite3");
+    let connection = rusqlite::Connection::open(&sqlite_path)?;
+    connection.execute_batch(
+        "CREATE TABLE archive (entry TEXT PRIMARY KEY) WITHOUT ROWID;
+        INSERT INTO archive VALUES ('reddit18f0e01_01 Sleepy cat'), ('imgurabc1234');",
+    )?;
+    drop(connection);
+
+    let archive = ImportedArchive::load(&sqlite_path.to_string_lossy())?;
+    assert_eq!(archive.len(), 1);
+    assert!(archive.contains("18f0e01"));
+
+    fs::write(
+        folder.join("bdfr/18f0e03.json"),
+        r#"{"id": "18f0e03", "title": "Cat"}"#,
+    )?;
+    let archive = ImportedArchive::load(&folder.join("bdfr").to_string_lossy())?;
+    assert!(archive.contains("18f0e03"));
+
+    fs::write(folder.join("ids.txt"), "18f0e04")?;
+    let archive = ImportedArchive::load(&folder.join("ids.txt").to_string_lossy())?;
+    assert!(archive.contains("18f0e04"));
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
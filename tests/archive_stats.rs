@@ -0,0 +1,73 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{get_archive_stats, render_archive_stats, ReportRow, StatsGroup};
+
+fn mock_row(resource: &str, id: &str, author: &str, subreddit: &str, bytes: u64) -> ReportRow {
+    ReportRow {
+        resource: resource.to_owned(),
+        id: id.to_owned(),
+        created_utc: String::from("2023-11-14T22:13:20+00:00"),
+        author: Some(author.to_owned()),
+        subreddit: subreddit.to_owned(),
+        status: "downloaded",
+        bytes: Some(bytes),
+        ..ReportRow::default()
+    }
+}
+
+#[test]
+fn it_aggregates_the_caches_of_an_output_directory() {
+    let mut rows = vec![
+        mock_row("user/spez", "a1", "spez", "pics", 1024),
+        mock_row("user/spez", "a2", "spez", "pics", 2048),
+        mock_row("user/kn0thing", "b1", "kn0thing", "aww", 8192),
+        // Downloaded by a second resource
+        mock_row("subreddit/aww", "b1", "kn0thing", "aww", 8192),
+        ReportRow {
+            created_utc: String::from("2024-01-02T10:00:00+00:00"),
+            ..mock_row("user/spez", "a3", "spez", "pics", 512)
+        },
+        ReportRow {
+            status: "failed",
+            ..mock_row("user/spez", "a4", "spez", "pics", 0)
+        },
+        ReportRow {
+            status: "skipped",
+            ..mock_row("user/spez", "a5", "spez", "pics", 0)
+        },
+    ];
+    rows[0].author = None;
+
+    let stats = get_archive_stats(&rows, 1);
+    assert_eq!(stats.resources, 3);
+    assert_eq!(stats.posts, 6);
+    assert_eq!(stats.files, 5);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(stats.bytes, 19968);
+    // Posters by files, subreddits by size
+    assert_eq!(
+        stats.top_authors,
+        vec![StatsGroup {
+            name: String::from("kn0thing"),
+            files: 2,
+            bytes: 16384
+        }]
+    );
+    assert_eq!(stats.top_subreddits[0].name, "aww");
+    assert_eq!(
+        stats
+            .growth
+            .iter()
+            .map(|g| (g.name.as_str(), g.files))
+            .collect::<Vec<_>>(),
+        vec![("2023-11", 4), ("2024-01", 1)]
+    );
+
+    let rendered = render_archive_stats(&stats);
+    assert!(rendered
+        .starts_with("3 resources, 6 posts - 5 files downloaded (0.02 MB), 1 failed, 1 skipped\n"));
+    assert!(rendered.contains(concat!(
+        "SUBREDDIT   FILES          SIZE\n",
+        "aww             2       0.02 MB\n"
+    )));
+}
@@ -0,0 +1,49 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    get_aria2c_args, Aria2cSettings, BandwidthLimiter, ProviderProxy, TransferTimeouts,
+};
+use std::str::FromStr;
+
+#[test]
+fn it_builds_aria2c_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Aria2cSettings {
+        connections: 4,
+        proxy: None,
+        provider_proxies: vec![ProviderProxy::from_str("redgifs=socks5://127.0.0.1:9050")?],
+    };
+    let args = get_aria2c_args(
+        "https://media.redgifs.com/Cat.mp4",
+        "output/spez/1_spez_18f0f01_2023-12-09.mp4",
+        &settings,
+        Some(&BandwidthLimiter::new(1024)),
+        TransferTimeouts::default(),
+    );
+
+    for arg in [
+        "--dir=output/spez",
+        "--out=1_spez_18f0f01_2023-12-09.mp4.part",
+        "--split=4",
+        "--timeout=60",
+        "--all-proxy=socks5://127.0.0.1:9050",
+        "--max-download-limit=1024",
+    ] {
+        assert!(args.contains(&String::from(arg)), "{}", arg);
+    }
+    assert_eq!(
+        args.last().map(String::as_str),
+        Some("https://media.redgifs.com/Cat.mp4")
+    );
+
+    // Hosts without a proxy are requested directly
+    let args = get_aria2c_args(
+        "https://i.redd.it/cat.jpg",
+        "cat.jpg",
+        &settings,
+        None,
+        TransferTimeouts::default(),
+    );
+    assert!(!args.iter().any(|a| a.starts_with("--all-proxy")));
+    assert!(args.contains(&String::from("--dir=.")));
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::RedditMediaProviderType,
+    utils::{extracts_audio, AudioFormat},
+};
+
+#[test]
+fn it_extracts_the_audio_of_video_posts_only() {
+    assert!(extracts_audio(&RedditMediaProviderType::RedditVideo));
+    assert!(extracts_audio(&RedditMediaProviderType::YoutubeVideo));
+    assert!(extracts_audio(&RedditMediaProviderType::YtdlpVideo));
+
+    assert!(!extracts_audio(&RedditMediaProviderType::RedditImage));
+    assert!(!extracts_audio(&RedditMediaProviderType::RedditGifVideo));
+    assert!(!extracts_audio(&RedditMediaProviderType::RedgifsVideo));
+}
+
+#[test]
+fn it_builds_ytdlp_args_per_format() {
+    assert_eq!(
+        AudioFormat::M4a.get_ytdlp_args(),
+        [
+            "-f",
+            "bestaudio[ext=m4a]/bestaudio",
+            "-x",
+            "--audio-format",
+            "m4a"
+        ]
+    );
+    assert_eq!(
+        AudioFormat::Mp3.get_ytdlp_args(),
+        ["-f", "bestaudio", "-x", "--audio-format", "mp3"]
+    );
+}
+
+#[test]
+fn it_copies_m4a_and_encodes_mp3() {
+    assert_eq!(
+        AudioFormat::M4a.get_ffmpeg_args("a.m4a.audio", "a.m4a"),
+        [
+            "-y",
+            "-loglevel",
+            "error",
+            "-i",
+            "a.m4a.audio",
+            "-map",
+            "0:a:0",
+            "-vn",
+            "-c:a",
+            "copy",
+            "a.m4a"
+        ]
+    );
+
+    let args = AudioFormat::Mp3.get_ffmpeg_args("a.mp3.audio", "a.mp3");
+    assert_eq!(
+        args[args.len() - 5..],
+        ["-c:a", "libmp3lame", "-q:a", "2", "a.mp3"]
+    );
+}
@@ -0,0 +1,27 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::get_expanded_authors;
+
+#[test]
+fn it_expands_to_the_most_active_authors() {
+    let authors = [
+        "spez",
+        "kn0thing",
+        "[deleted]",
+        "spez",
+        "",
+        "redpanda",
+        "kn0thing",
+        "spez",
+    ]
+    .map(String::from);
+
+    assert_eq!(
+        get_expanded_authors(&authors, None),
+        vec!["spez", "kn0thing", "redpanda"]
+    );
+    assert_eq!(
+        get_expanded_authors(&authors, Some(2)),
+        vec!["spez", "kn0thing"]
+    );
+    assert!(get_expanded_authors(&[], Some(2)).is_empty());
+}
@@ -0,0 +1,79 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::RedditCrawlerPost,
+    utils::{get_author_ranks, write_author_list, AuthorListFormat, AuthorRank},
+};
+use std::error::Error;
+
+fn mock_post(id: &str, author: &str, upvotes: i64) -> RedditCrawlerPost {
+    RedditCrawlerPost {
+        author: String::from(author),
+        id: String::from(id),
+        upvotes,
+        ..RedditCrawlerPost::default()
+    }
+}
+
+fn mock_ranks() -> Vec<AuthorRank> {
+    let posts = [
+        mock_post("a", "spez", 10),
+        // Second item of the gallery a
+        mock_post("a", "spez", 10),
+        mock_post("b", "kn0thing", 50),
+        mock_post("c", "[deleted]", 99),
+        mock_post("d", "spez", 5),
+        mock_post("e", "kn0thing", 1),
+        mock_post("f", "redpanda, the", 7),
+    ];
+    get_author_ranks(&posts)
+}
+
+#[test]
+fn it_ranks_authors_by_posts_and_upvotes() {
+    assert_eq!(
+        mock_ranks(),
+        vec![
+            AuthorRank {
+                author: String::from("kn0thing"),
+                posts: 2,
+                upvotes: 51,
+            },
+            AuthorRank {
+                author: String::from("spez"),
+                posts: 2,
+                upvotes: 15,
+            },
+            AuthorRank {
+                author: String::from("redpanda, the"),
+                posts: 1,
+                upvotes: 7,
+            },
+        ]
+    );
+    assert!(get_author_ranks(&[]).is_empty());
+}
+
+#[test]
+fn it_writes_author_lists() -> Result<(), Box<dyn Error>> {
+    let ranks = mock_ranks();
+
+    let mut csv = Vec::new();
+    write_author_list(&mut csv, &ranks, AuthorListFormat::Csv)?;
+    assert_eq!(
+        String::from_utf8(csv)?,
+        "author,posts,upvotes\nkn0thing,2,51\nspez,2,15\n\"redpanda, the\",1,7\n"
+    );
+
+    let mut batch = Vec::new();
+    write_author_list(&mut batch, &ranks[..2], AuthorListFormat::Batch)?;
+    assert_eq!(String::from_utf8(batch)?, "user:kn0thing\nuser:spez\n");
+
+    let mut json = Vec::new();
+    write_author_list(&mut json, &ranks[..1], AuthorListFormat::Json)?;
+    let json = serde_json::from_slice::<serde_json::Value>(&json)?;
+    assert_eq!(
+        json,
+        serde_json::json!([{ "author": "kn0thing", "posts": 2, "upvotes": 51 }])
+    );
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::BandwidthBucket;
+use std::time::{Duration, Instant};
+
+#[test]
+fn it_throttles_to_the_bandwidth_limit() {
+    let now = Instant::now();
+    let mut bucket = BandwidthBucket::new(1000);
+
+    // A full second worth of bytes passes right away
+    assert_eq!(bucket.reserve(1000, now), Duration::ZERO);
+    // Concurrent downloads share the debt of the bucket
+    assert_eq!(bucket.reserve(500, now), Duration::from_millis(500));
+    assert_eq!(bucket.reserve(500, now), Duration::from_secs(1));
+
+    assert_eq!(
+        bucket.reserve(0, now + Duration::from_secs(1)),
+        Duration::ZERO
+    );
+    // Idle time only refills up to a second worth of bytes
+    assert_eq!(
+        bucket.reserve(2000, now + Duration::from_secs(60)),
+        Duration::from_secs(1)
+    );
+}
@@ -0,0 +1,112 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    parse_batch_targets, parse_batch_targets_toml,
+    state::{FileCacheItemLatest, SharedState},
+    BatchTarget, BatchTargetError, BatchTargetKind,
+};
+use std::{error::Error, sync::Arc};
+use tokio::sync::Semaphore;
+
+#[test]
+fn it_parses_batch_targets() -> Result<(), Box<dyn Error>> {
+    let lines =
+        "# accounts to mirror\nuser:spez\n\nsubreddit:aww\nsearch: olympics \ndomain:imgur.com\n";
+    let toml = r#"targets = ["user:spez", "subreddit:aww", "search:olympics", "domain:imgur.com"]"#;
+
+    let expected = vec![
+        BatchTarget {
+            kind: BatchTargetKind::User,
+            resource: String::from("spez"),
+        },
+        BatchTarget {
+            kind: BatchTargetKind::Subreddit,
+            resource: String::from("aww"),
+        },
+        BatchTarget {
+            kind: BatchTargetKind::Search,
+            resource: String::from("olympics"),
+        },
+        BatchTarget {
+            kind: BatchTargetKind::Domain,
+            resource: String::from("imgur.com"),
+        },
+    ];
+
+    assert_eq!(parse_batch_targets(lines)?, expected);
+    assert_eq!(parse_batch_targets_toml(toml)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn it_normalizes_combined_subreddit_targets() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        parse_batch_targets("r:pics+aww\nsubreddit:All\n")?,
+        vec![
+            BatchTarget {
+                kind: BatchTargetKind::Subreddit,
+                resource: String::from("aww+pics"),
+            },
+            BatchTarget {
+                kind: BatchTargetKind::Subreddit,
+                resource: String::from("all"),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_batch_targets() {
+    assert_eq!(
+        parse_batch_targets("user:spez\nmultireddit:foo"),
+        Err(BatchTargetError::Target(String::from("multireddit:foo")))
+    );
+    assert_eq!(
+        parse_batch_targets("user:"),
+        Err(BatchTargetError::Target(String::from("user:")))
+    );
+    assert!(matches!(
+        parse_batch_targets_toml("targets = 42"),
+        Err(BatchTargetError::Toml(_))
+    ));
+}
+
+#[test]
+fn it_forks_the_state_of_concurrent_targets() {
+    let mut parent = SharedState {
+        download_slots: Some(Arc::new(Semaphore::new(4))),
+        received_bytes: 100,
+        ..SharedState::default()
+    };
+    parent.file_cache.files.push(FileCacheItemLatest::default());
+
+    let mut fork = parent.fork();
+    // The cache belongs to the target, throttling and slots to the whole batch
+    assert!(fork.file_cache.files.is_empty());
+    assert!(Arc::ptr_eq(&fork.rate_limiter, &parent.rate_limiter));
+    assert!(Arc::ptr_eq(&fork.transfer_status, &parent.transfer_status));
+    assert!(Arc::ptr_eq(
+        fork.download_slots.as_ref().unwrap(),
+        parent.download_slots.as_ref().unwrap()
+    ));
+    fork.transfer_status.lock().unwrap().start_download();
+    assert_eq!(
+        parent
+            .transfer_status
+            .lock()
+            .unwrap()
+            .snapshot()
+            .active_downloads,
+        1
+    );
+
+    fork.downloads_failed = 2;
+    fork.resources_gone = 1;
+    fork.received_bytes = 150;
+    parent.join(&fork, 100);
+    assert_eq!(parent.downloads_failed, 2);
+    assert_eq!(parent.resources_gone, 1);
+    assert_eq!(parent.received_bytes, 150);
+}
@@ -0,0 +1,241 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    get_cache_item_from_file_name, get_cache_stats, get_file_checksum, prune_cache, rebuild_cache,
+    state::{
+        FileCacheItemLatest, FileCacheLatest, FileCacheVersion, FileChecksum, FileProvenance,
+        ResourceStatus,
+    },
+    verify_cache, CacheStats,
+};
+use std::{env, error::Error, fs, str::FromStr};
+
+#[test]
+fn it_upgrades_v1_cache() -> Result<(), Box<dyn Error>> {
+    let data = r#"{
+        "version": 1,
+        "files": [
+            {
+                "id": "MOCK_ID",
+                "createdUtc": "2023-12-09T20:26:26Z",
+                "title": "MOCK_TITLE",
+                "subreddit": "MOCK_SUBREDDIT",
+                "url": "https://i.redd.it/mock.jpg",
+                "success": true,
+                "index": null
+            }
+        ]
+    }"#;
+
+    let cache = FileCacheLatest::from_str(data)?;
+    assert_eq!(cache.version, FileCacheVersion::Latest);
+    assert_eq!(cache.files.len(), 1);
+    assert_eq!(cache.files[0].provenance, None);
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_file_provenance() -> Result<(), Box<dyn Error>> {
+    let data = r#"{
+        "version": 2,
+        "status": { "resource": "active", "lastDownload": "success" },
+        "files": [
+            {
+                "id": "MOCK_ID",
+                "createdUtc": "2023-12-09T20:26:26Z",
+                "title": "MOCK_TITLE",
+                "subreddit": "MOCK_SUBREDDIT",
+                "url": "https://i.redd.it/mock.jpg",
+                "success": true,
+                "index": null,
+                "provenance": {
+                    "etag": "\"abc\"",
+                    "lastModified": "Sat, 09 Dec 2023 20:26:26 GMT",
+                    "contentLength": 1024,
+                    "finalUrl": "https://i.redd.it/mock.jpg"
+                }
+            }
+        ]
+    }"#;
+
+    let cache = FileCacheLatest::from_str(data)?;
+    assert_eq!(
+        cache.files[0].provenance,
+        Some(FileProvenance {
+            etag: Some(String::from("\"abc\"")),
+            last_modified: Some(String::from("Sat, 09 Dec 2023 20:26:26 GMT")),
+            content_length: Some(1024),
+            final_url: String::from("https://i.redd.it/mock.jpg"),
+        })
+    );
+
+    let serialized = serde_json::to_string(&cache)?;
+    assert_eq!(FileCacheLatest::from_str(&serialized)?, cache);
+
+    Ok(())
+}
+
+#[test]
+fn it_reads_unavailable_subreddit_status() -> Result<(), Box<dyn Error>> {
+    let data = r#"{
+        "version": 2,
+        "status": { "resource": "quarantined", "lastDownload": "success" },
+        "files": []
+    }"#;
+
+    let cache = FileCacheLatest::from_str(data)?;
+    assert_eq!(cache.status.resource, ResourceStatus::Quarantined);
+    assert_eq!(cache.status.resource.to_string(), "quarantined");
+
+    Ok(())
+}
+
+#[test]
+fn it_verifies_and_prunes_cache() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_cache_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join("present.jpg"), "image")?;
+
+    let item = |id: &str, success: bool, file_name: Option<&str>| FileCacheItemLatest {
+        id: String::from(id),
+        success,
+        file_name: file_name.map(String::from),
+        ..FileCacheItemLatest::default()
+    };
+    let mut cache = FileCacheLatest {
+        files: vec![
+            item("present", true, Some("present.jpg")),
+            item("missing", true, Some("missing.jpg")),
+            item("failed", false, None),
+        ],
+        ..FileCacheLatest::default()
+    };
+
+    assert_eq!(
+        get_cache_stats(&folder, &cache),
+        CacheStats {
+            entries: 3,
+            downloaded: 2,
+            failed: 1,
+            missing: 1,
+            bytes: 5,
+        }
+    );
+
+    assert_eq!(verify_cache(&folder, &mut cache, false), 1);
+    assert!(!cache.files[1].success);
+
+    assert_eq!(prune_cache(&mut cache), 2);
+    assert_eq!(
+        cache
+            .files
+            .iter()
+            .map(|f| f.id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["present"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_marks_corrupt_files_as_failed() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_checksum_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let path = |name: &str| folder.join(name).to_string_lossy().to_string();
+
+    fs::write(path("intact.mp4"), "video")?;
+    fs::write(path("truncated.mp4"), "video")?;
+    fs::write(path("flipped.mp4"), "video")?;
+    let checksum = get_file_checksum(&path("intact.mp4"), true).await?;
+    assert_eq!(checksum.size, 5);
+    assert_eq!(
+        get_file_checksum(&path("intact.mp4"), false).await?,
+        FileChecksum {
+            size: 5,
+            blake3: None
+        }
+    );
+
+    fs::write(path("truncated.mp4"), "vid")?;
+    // Same size, different content
+    fs::write(path("flipped.mp4"), "vidoe")?;
+
+    let item = |id: &str| FileCacheItemLatest {
+        id: String::from(id),
+        success: true,
+        file_name: Some(format!("{}.mp4", id)),
+        checksum: Some(checksum.clone()),
+        ..FileCacheItemLatest::default()
+    };
+    let mut cache = FileCacheLatest {
+        files: vec![item("intact"), item("truncated"), item("flipped")],
+        ..FileCacheLatest::default()
+    };
+
+    assert_eq!(verify_cache(&folder, &mut cache, false), 1);
+    assert_eq!(
+        cache.files[1].error.as_deref(),
+        Some("File has 3 bytes, 5 were downloaded")
+    );
+    assert!(cache.files[2].success);
+
+    assert_eq!(verify_cache(&folder, &mut cache, true), 1);
+    assert_eq!(
+        cache.files.iter().map(|f| f.success).collect::<Vec<_>>(),
+        vec![true, false, false]
+    );
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
+#[test]
+fn it_rebuilds_cache_from_file_names() -> Result<(), Box<dyn Error>> {
+    let item = get_cache_item_from_file_name("1024_some_user_18f0d01_2023-12-09_2.jpg")
+        .ok_or("Expected the default file scheme to be parsed")?;
+    assert_eq!(item.id, "18f0d01");
+    assert_eq!(item.author.as_deref(), Some("some_user"));
+    assert_eq!(item.upvotes, Some(1024));
+    assert_eq!(item.index, Some(2));
+    assert_eq!(item.created_utc.to_rfc3339(), "2023-12-09T00:00:00+00:00");
+    assert!(get_cache_item_from_file_name("-3_spez_18f0d02_2023-12-09.mp4.part").is_none());
+    assert!(get_cache_item_from_file_name("gallery-dl_123.jpg").is_none());
+
+    let folder = env::temp_dir().join(format!("reddit_clawler_rebuild_{}", std::process::id()));
+    fs::create_dir_all(folder.join("aww"))?;
+    fs::create_dir_all(folder.join("other"))?;
+    fs::write(folder.join("-3_spez_18f0d02_2023-12-09.mp4"), "video")?;
+    fs::write(folder.join("aww/12_spez_18f0d03_2023-12-10.jpg"), "image")?;
+    fs::write(folder.join("12_spez_18f0d04_2023-12-10.jpg"), "image")?;
+    // Another resource with its own cache
+    fs::write(folder.join("other/cache.json"), "{}")?;
+    fs::write(folder.join("other/1_spez_18f0d05_2023-12-11.jpg"), "image")?;
+
+    let mut cache = FileCacheLatest {
+        files: vec![FileCacheItemLatest {
+            id: String::from("18f0d04"),
+            success: true,
+            ..FileCacheItemLatest::default()
+        }],
+        ..FileCacheLatest::default()
+    };
+    assert_eq!(rebuild_cache(&folder, &mut cache)?, 2);
+
+    let mut file_names = cache
+        .files
+        .iter()
+        .filter_map(|f| f.file_name.as_deref())
+        .collect::<Vec<_>>();
+    file_names.sort();
+    assert_eq!(
+        file_names,
+        vec![
+            "-3_spez_18f0d02_2023-12-09.mp4",
+            "aww/12_spez_18f0d03_2023-12-10.jpg"
+        ]
+    );
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use reddit_clawler::utils::CachedPageStreak;
+
+#[test]
+fn it_stops_after_a_streak_of_cached_pages() {
+    let mut streak = CachedPageStreak::new(Some(3));
+    assert!(!streak.observe(25, 0));
+    assert!(!streak.observe(25, 0));
+    assert!(streak.observe(25, 0));
+}
+
+#[test]
+fn it_resets_the_streak_on_new_posts() {
+    let mut streak = CachedPageStreak::new(Some(2));
+    assert!(!streak.observe(25, 0));
+    assert!(!streak.observe(25, 1));
+    assert!(!streak.observe(25, 0));
+    // An empty page is not a cached one
+    assert!(!streak.observe(0, 0));
+    assert!(!streak.observe(25, 0));
+    assert!(streak.observe(25, 0));
+}
+
+#[test]
+fn it_stops_on_the_first_cached_page_with_a_threshold_of_one() {
+    let mut streak = CachedPageStreak::new(Some(1));
+    assert!(!streak.observe(25, 25));
+    assert!(streak.observe(25, 0));
+}
+
+#[test]
+fn it_never_stops_without_a_threshold() {
+    let mut streak = CachedPageStreak::new(None);
+    for _ in 0..100 {
+        assert!(!streak.observe(25, 0));
+    }
+}
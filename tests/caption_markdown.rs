@@ -0,0 +1,47 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedResponse,
+    reddit_parser::RedditPostParser,
+    utils::{render_caption_markdown, DEFAULT_FILE_SCHEME},
+};
+use std::{error::Error, fs};
+
+#[test]
+fn it_renders_gallery_captions() -> Result<(), Box<dyn Error>> {
+    let data =
+        fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery_captions.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+    let child = &res.data.children[0].data;
+
+    let posts = RedditPostParser::default().parse(res);
+    let markdown = render_caption_markdown(child, &posts, DEFAULT_FILE_SCHEME)
+        .ok_or("Expected captions to render")?;
+
+    assert!(markdown.starts_with(&format!("# {}\n\nAdopted them last week!\n\n", child.title)));
+    assert!(markdown.contains("![Sleeping on the couch]("));
+    assert!(markdown.contains("_0.jpg)\n\nSleeping on the couch\n\n"));
+    assert!(markdown.contains("![](") && markdown.contains("_1.jpg)"));
+    assert_eq!(markdown.matches("![").count(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_galleries_without_captions() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+
+    let posts = RedditPostParser::default().parse(res);
+    assert_eq!(
+        render_caption_markdown(&res.data.children[0].data, &posts, DEFAULT_FILE_SCHEME),
+        None
+    );
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    state::{FileCacheItemLatest, FileCacheLatest, SharedState},
+    CheckpointSettings,
+};
+use std::{env, error::Error, fs, path::Path, str::FromStr, time::Duration};
+
+#[test]
+fn it_checkpoints_after_downloads_or_interval() {
+    let settings = CheckpointSettings {
+        every: 100,
+        interval: Duration::from_secs(30),
+    };
+
+    assert!(!settings.is_due(0, Duration::from_secs(60)));
+    assert!(!settings.is_due(99, Duration::from_secs(29)));
+    assert!(settings.is_due(100, Duration::ZERO));
+    assert!(settings.is_due(1, Duration::from_secs(30)));
+}
+
+#[test]
+fn it_replaces_the_cache_file() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_checkpoint_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let cache_path = folder.join("cache.json").to_string_lossy().to_string();
+    fs::write(&cache_path, "{}")?;
+
+    let ss = SharedState {
+        file_cache: FileCacheLatest {
+            files: vec![FileCacheItemLatest {
+                id: String::from("abc123"),
+                success: true,
+                ..FileCacheItemLatest::default()
+            }],
+            ..FileCacheLatest::default()
+        },
+        ..SharedState::default()
+    };
+    ss.write_file_cache(&cache_path)?;
+
+    let written = FileCacheLatest::from_str(&fs::read_to_string(&cache_path)?)?;
+    assert_eq!(written.files.len(), 1);
+    assert!(!Path::new(&format!("{}.part", cache_path)).exists());
+
+    fs::remove_dir_all(folder)?;
+    Ok(())
+}
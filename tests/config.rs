@@ -0,0 +1,77 @@
+use clap::{Arg, Command};
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::RedgifsQuality,
+    config::{resolve_flag, Config, ConfigError},
+};
+use std::{error::Error, str::FromStr};
+
+fn mock_command() -> Command {
+    Command::new("reddit-clawler").arg(
+        Arg::new("tasks")
+            .long("tasks")
+            .env("REDDIT_CLAWLER_CONFIG_TEST_TASKS")
+            .value_parser(clap::value_parser!(u16))
+            .default_value("10"),
+    )
+}
+
+#[test]
+fn it_parses_config_files() -> Result<(), Box<dyn Error>> {
+    let config = Config::from_str(
+        r#"
+        output = "~/archive"
+        tasks = 50
+        file-scheme = "{DATE}_{POSTID}"
+        quality = "sd"
+        "#,
+    )?;
+
+    assert_eq!(
+        config,
+        Config {
+            output: Some(String::from("~/archive")),
+            tasks: Some(50),
+            file_scheme: Some(String::from("{DATE}_{POSTID}")),
+            quality: Some(RedgifsQuality::SD),
+            ..Config::default()
+        }
+    );
+
+    assert!(matches!(
+        Config::from_str("taks = 50"),
+        Err(ConfigError::Toml(_))
+    ));
+    assert!(matches!(
+        Config::from_str("tasks = 500"),
+        Err(ConfigError::Tasks(500))
+    ));
+    assert!(matches!(
+        Config::from_str(r#"retry-max-delay = "5 minutes""#),
+        Err(ConfigError::Interval("retry-max-delay", _))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_prefers_flags_over_env_over_config() -> Result<(), Box<dyn Error>> {
+    let config = Config {
+        tasks: Some(50),
+        ..Config::default()
+    };
+
+    let m = mock_command().try_get_matches_from(["reddit-clawler"])?;
+    assert_eq!(resolve_flag(&m, "tasks", config.tasks.as_ref()), Some(50));
+    assert_eq!(resolve_flag::<u16>(&m, "tasks", None), Some(10));
+
+    std::env::set_var("REDDIT_CLAWLER_CONFIG_TEST_TASKS", "25");
+    let m = mock_command().try_get_matches_from(["reddit-clawler"])?;
+    assert_eq!(resolve_flag(&m, "tasks", config.tasks.as_ref()), Some(25));
+
+    let m = mock_command().try_get_matches_from(["reddit-clawler", "--tasks", "5"])?;
+    assert_eq!(resolve_flag(&m, "tasks", config.tasks.as_ref()), Some(5));
+    std::env::remove_var("REDDIT_CLAWLER_CONFIG_TEST_TASKS");
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    providers::requires_sign_in,
+    utils::{CookieFileError, CookieJar, YtdlpCookies},
+};
+use reqwest::Url;
+use std::error::Error;
+
+const COOKIES: &str = "# Netscape HTTP Cookie File\n\
+    \n\
+    .reddit.com\tTRUE\t/\tTRUE\t0\tover18\t1\n\
+    #HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t1900000000\tSID\tabc\n\
+    www.example.com\tFALSE\t/private\tFALSE\t1600000000\told\tgone\n\
+    www.example.com\tFALSE\t/\tFALSE\t0\tlang\ten\n";
+
+#[test]
+fn it_parses_netscape_cookie_files() -> Result<(), Box<dyn Error>> {
+    let jar = CookieJar::parse(COOKIES)?;
+    assert_eq!(jar.len(), 4);
+
+    assert!(matches!(
+        CookieJar::parse("reddit.com\tTRUE\t/\tover18"),
+        Err(CookieFileError::Invalid(1))
+    ));
+    assert!(matches!(
+        CookieJar::parse("# comment\nreddit.com\tTRUE\t/\tTRUE\tnever\tover18\t1"),
+        Err(CookieFileError::Invalid(2))
+    ));
+    Ok(())
+}
+
+#[test]
+fn it_sends_cookies_to_their_domains() -> Result<(), Box<dyn Error>> {
+    let jar = CookieJar::parse(COOKIES)?;
+    let now = 1700000000;
+
+    assert_eq!(
+        jar.get_header(&Url::parse("https://old.reddit.com/r/aww.json")?, now),
+        Some(String::from("over18=1"))
+    );
+    assert_eq!(
+        jar.get_header(&Url::parse("https://www.youtube.com/watch?v=id")?, now),
+        Some(String::from("SID=abc"))
+    );
+    // Secure cookies are not sent over http
+    assert_eq!(
+        jar.get_header(&Url::parse("http://www.reddit.com/")?, now),
+        None
+    );
+    // Expired cookies are left out and subdomains only match with the flag
+    assert_eq!(
+        jar.get_header(&Url::parse("http://www.example.com/private/a")?, now),
+        Some(String::from("lang=en"))
+    );
+    assert_eq!(
+        jar.get_header(&Url::parse("http://cdn.www.example.com/")?, now),
+        None
+    );
+    assert_eq!(
+        jar.get_header(&Url::parse("https://notreddit.com/")?, now),
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn it_passes_cookies_to_ytdlp() {
+    assert!(!YtdlpCookies::default().is_set());
+
+    let cookies = YtdlpCookies {
+        file: Some(String::from("/home/spez/cookies.txt")),
+        from_browser: Some(String::from("firefox")),
+    };
+    assert_eq!(
+        cookies.get_args(),
+        [
+            "--cookies",
+            "/home/spez/cookies.txt",
+            "--cookies-from-browser",
+            "firefox"
+        ]
+    );
+}
+
+#[test]
+fn it_detects_videos_requiring_sign_in() {
+    assert!(requires_sign_in(
+        "ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users."
+    ));
+    assert!(!requires_sign_in(
+        "ERROR: Unsupported URL: https://www.example.com/cats-sleep"
+    ));
+}
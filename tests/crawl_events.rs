@@ -0,0 +1,28 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::CrawlEvent;
+use std::error::Error;
+
+#[test]
+fn it_serializes_crawl_events() -> Result<(), Box<dyn Error>> {
+    let event = CrawlEvent::FileDownloaded {
+        id: "MOCK_ID",
+        index: Some(1),
+        url: "https://i.redd.it/mock.jpg",
+        file_name: "MOCK_ID_1.jpg",
+        bytes: 1024.0,
+    };
+    assert_eq!(
+        serde_json::to_string(&event)?,
+        r#"{"event":"fileDownloaded","id":"MOCK_ID","index":1,"url":"https://i.redd.it/mock.jpg","fileName":"MOCK_ID_1.jpg","bytes":1024.0}"#
+    );
+
+    let event = CrawlEvent::CrawlStarted {
+        resource: "user/spez",
+    };
+    assert_eq!(
+        serde_json::to_string(&event)?,
+        r#"{"event":"crawlStarted","resource":"user/spez"}"#
+    );
+
+    Ok(())
+}
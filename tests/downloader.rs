@@ -0,0 +1,140 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType},
+    utils::{
+        download_crawler_post, get_content_range_total, get_extension_from_content_type,
+        get_file_path, get_part_file_path, get_reddit_audio_urls,
+        state::{FileChecksum, SharedState},
+        DownloadPostResult, SkipReason,
+    },
+};
+use reqwest_middleware::ClientBuilder;
+use std::{env, fs, sync::Arc};
+use tokio::sync::Mutex;
+
+#[test]
+fn it_detects_extensions_from_content_types() {
+    assert_eq!(get_extension_from_content_type("image/jpeg"), Some("jpg"));
+    assert_eq!(get_extension_from_content_type("image/PNG"), Some("png"));
+    assert_eq!(
+        get_extension_from_content_type("video/mp4; charset=binary"),
+        Some("mp4")
+    );
+    assert_eq!(get_extension_from_content_type("text/html"), None);
+}
+
+#[test]
+fn it_lists_reddit_audio_tracks() {
+    let urls =
+        get_reddit_audio_urls("https://v.redd.it/7kxjendogp4c1/DASH_480.mp4?source=fallback");
+
+    assert_eq!(
+        urls.first().map(String::as_str),
+        Some("https://v.redd.it/7kxjendogp4c1/DASH_AUDIO_128.mp4")
+    );
+    assert!(urls.contains(&String::from(
+        "https://v.redd.it/7kxjendogp4c1/DASH_audio.mp4"
+    )));
+}
+
+#[test]
+fn it_parses_content_range_totals() {
+    assert_eq!(get_content_range_total("bytes 100-199/200"), Some(200));
+    assert_eq!(get_content_range_total("bytes */1024"), Some(1024));
+    assert_eq!(get_content_range_total("bytes 0-99/*"), None);
+    assert_eq!(
+        get_part_file_path("output/post.mp4"),
+        "output/post.mp4.part"
+    );
+}
+
+#[tokio::test]
+async fn it_skips_existing_files() -> Result<(), Box<dyn std::error::Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_existing_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let folder_path = folder.to_string_lossy().to_string();
+
+    let data = RedditSubmittedChildData {
+        id: String::from("18f0c01"),
+        url: String::from("https://i.redd.it/existing.jpg"),
+        ..Default::default()
+    };
+    // Never requested, the file is found first
+    let media = RedditCrawlerPost::new(
+        &data,
+        RedditMediaProviderType::RedditImage,
+        "jpg",
+        "http://127.0.0.1:9/existing.jpg",
+    );
+    fs::write(get_file_path(&folder_path, &media, "{POSTID}"), "image")?;
+
+    let shared_state = Arc::new(Mutex::new(SharedState {
+        skip_existing: true,
+        ..Default::default()
+    }));
+    let client = ClientBuilder::new(reqwest::Client::new()).build();
+    let result =
+        download_crawler_post(&client, &shared_state, &folder_path, &media, "{POSTID}").await?;
+
+    match result {
+        DownloadPostResult::ReceivedBytes(bytes, provenance, file_name, checksum) => {
+            assert_eq!(bytes, 0.0);
+            assert!(provenance.is_none());
+            assert_eq!(file_name, "18f0c01.jpg");
+            assert_eq!(checksum.size, 5);
+        }
+        _ => panic!("Expected the existing file to be recorded as downloaded"),
+    }
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
+#[test]
+fn it_records_download_results_in_the_cache() {
+    let post = RedditCrawlerPost {
+        id: String::from("18dxopo"),
+        subreddit: String::from("aww"),
+        author: String::from("spez"),
+        upvotes: 42,
+        index: Some(1),
+        url: String::from("https://i.redd.it/mock.jpg"),
+        ..RedditCrawlerPost::default()
+    };
+
+    let checksum = FileChecksum {
+        size: 7,
+        ..FileChecksum::default()
+    };
+    let item = DownloadPostResult::ReceivedBytes(7.0, None, String::from("mock.jpg"), checksum)
+        .into_cache_item(&post)
+        .unwrap();
+    assert!(item.success);
+    assert_eq!(item.id, "18dxopo");
+    assert_eq!(item.index, Some(1));
+    assert_eq!(item.author.as_deref(), Some("spez"));
+    assert_eq!(item.upvotes, Some(42));
+    assert_eq!(item.file_name.as_deref(), Some("mock.jpg"));
+    assert_eq!(item.checksum.map(|c| c.size), Some(7));
+
+    let item = DownloadPostResult::ReceivedFailed(String::from("timed out"))
+        .into_cache_item(&post)
+        .unwrap();
+    assert!(!item.success);
+    assert_eq!(item.error.as_deref(), Some("timed out"));
+    assert_eq!(item.file_name, None);
+
+    let item = DownloadPostResult::ReceivedSkipped(SkipReason::FileTooLarge)
+        .into_cache_item(&post)
+        .unwrap();
+    assert!(!item.success);
+    assert_eq!(item.skipped, Some(SkipReason::FileTooLarge));
+
+    assert!(DownloadPostResult::ReceivedNotFound
+        .into_cache_item(&post)
+        .is_some_and(|item| !item.success));
+    assert!(DownloadPostResult::ReceivedUnhandled
+        .into_cache_item(&post)
+        .is_none());
+}
@@ -0,0 +1,35 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{render_dry_run_table, DryRunItem};
+
+#[test]
+fn it_renders_dry_run_table() {
+    let items = vec![
+        DryRunItem {
+            id: String::from("MOCK_ID"),
+            index: None,
+            provider: String::from("reddit_image"),
+            file_name: String::from("MOCK_ID.jpg"),
+            url: String::from("https://i.redd.it/mock.jpg"),
+            size: Some(3 * 1024 * 1024),
+        },
+        DryRunItem {
+            id: String::from("MOCK_ID_2"),
+            index: None,
+            provider: String::from("youtube_video"),
+            file_name: String::from("MOCK_ID_2.mp4"),
+            url: String::from("https://youtu.be/mock"),
+            size: None,
+        },
+    ];
+
+    assert_eq!(
+        render_dry_run_table(&items),
+        [
+            "PROVIDER             SIZE  URL",
+            "reddit_image      3.00 MB  https://i.redd.it/mock.jpg",
+            "youtube_video           ?  https://youtu.be/mock",
+            "",
+        ]
+        .join("\n")
+    );
+}
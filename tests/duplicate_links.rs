@@ -0,0 +1,63 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::RedditCrawlerPost,
+    utils::{get_link_file_name, GlobalCache, LinkMode},
+};
+use std::{env, error::Error, fs, path::Path};
+
+#[test]
+fn it_names_links_after_the_linked_file() {
+    let original = Path::new("output/user/spez/42_spez_18dxopo.webp");
+
+    assert_eq!(
+        get_link_file_name("7_spez_18dxopo.jpg", "jpg", original),
+        "7_spez_18dxopo.webp"
+    );
+    assert_eq!(
+        get_link_file_name("7_spez_18dxopo.webp", "webp", original),
+        "7_spez_18dxopo.webp"
+    );
+    assert_eq!(
+        get_link_file_name("7_spez_18dxopo.jpg", "jpg", Path::new("18dxopo")),
+        "7_spez_18dxopo"
+    );
+}
+
+#[test]
+fn it_links_files_of_the_global_cache() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("clawler_link_duplicates_{}", std::process::id()));
+    let user_folder = folder.join("user");
+    let subreddit_folder = folder.join("subreddit");
+    fs::create_dir_all(&user_folder)?;
+    fs::create_dir_all(&subreddit_folder)?;
+
+    let original = user_folder.join("18dxopo.jpg");
+    fs::write(&original, b"jpg")?;
+
+    let post = RedditCrawlerPost {
+        id: String::from("18dxopo"),
+        ..RedditCrawlerPost::default()
+    };
+    let mut cache = GlobalCache::open(&folder.join("global.db").to_string_lossy())?;
+    assert_eq!(cache.get_file_path(&post), None);
+    cache.record(&post, "user/spez", &original.to_string_lossy())?;
+    assert_eq!(
+        cache.get_file_path(&post),
+        Some(original.to_string_lossy().to_string())
+    );
+
+    let hardlink = subreddit_folder.join("hardlink.jpg");
+    LinkMode::Hardlink.link(&original, &hardlink)?;
+    assert_eq!(fs::read(&hardlink)?, b"jpg");
+
+    #[cfg(unix)]
+    {
+        let symlink = subreddit_folder.join("symlink.jpg");
+        LinkMode::Symlink.link(&original, &symlink)?;
+        assert!(fs::symlink_metadata(&symlink)?.file_type().is_symlink());
+        assert_eq!(fs::read(&symlink)?, b"jpg");
+    }
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{embed_image_metadata, get_exif, get_xmp_packet, EmbeddedMetadata};
+use std::{env, error::Error, fs};
+
+// SOI, a JFIF APP0 segment, a start of scan with a byte of image data and EOI
+const JPEG: &[u8] = &[
+    0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x00, 0x00, 0x01,
+    0x00, 0x01, 0x00, 0x00, 0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3f, 0x00, 0x00, 0xff,
+    0xd9,
+];
+
+// Signature, a 1x1 IHDR chunk and IEND
+const PNG: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, b'I', b'H', b'D', b'R',
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90, 0x77, 0x53,
+    0xde, 0x00, 0x00, 0x00, 0x00, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82,
+];
+
+fn get_metadata() -> EmbeddedMetadata {
+    EmbeddedMetadata {
+        title: String::from("Sunset <over> the \"bay\" & hills"),
+        author: String::from("spez"),
+        permalink: String::from("https://www.reddit.com/r/pics/comments/abc123"),
+        created_utc: Utc.with_ymd_and_hms(2023, 5, 17, 8, 30, 5).unwrap(),
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[test]
+fn it_escapes_the_xmp_packet() {
+    let xmp = get_xmp_packet(&get_metadata());
+
+    assert!(xmp.contains("Sunset &lt;over&gt; the &quot;bay&quot; &amp; hills"));
+    assert!(xmp.contains("<rdf:li>spez</rdf:li>"));
+    assert!(xmp.contains("<dc:source>https://www.reddit.com/r/pics/comments/abc123</dc:source>"));
+    assert!(xmp.contains("<xmp:CreateDate>2023-05-17T08:30:05Z</xmp:CreateDate>"));
+}
+
+#[test]
+fn it_encodes_exif() -> Result<(), Box<dyn Error>> {
+    let exif = get_exif(&get_metadata());
+
+    assert_eq!(&exif[..8], b"II\x2a\x00\x08\x00\x00\x00");
+    // IFD0 holds the title, date, artist and the Exif directory pointer
+    assert_eq!(u16::from_le_bytes([exif[8], exif[9]]), 4);
+    assert!(contains(&exif, b"Sunset <over> the \"bay\" & hills\0"));
+    assert!(contains(&exif, b"2023:05:17 08:30:05\0"));
+    assert!(contains(&exif, b"spez\0"));
+    assert!(contains(
+        &exif,
+        b"ASCII\0\0\0https://www.reddit.com/r/pics/comments/abc123"
+    ));
+
+    // The pointer entry is the last one of IFD0 and leads to the two Exif entries
+    let pointer_entry = 10 + 3 * 12;
+    assert_eq!(&exif[pointer_entry..pointer_entry + 2], &[0x69, 0x87]);
+    let offset =
+        u32::from_le_bytes(exif[pointer_entry + 8..pointer_entry + 12].try_into()?) as usize;
+    assert_eq!(u16::from_le_bytes([exif[offset], exif[offset + 1]]), 2);
+    assert_eq!(&exif[offset + 2..offset + 4], &[0x03, 0x90]);
+    Ok(())
+}
+
+#[test]
+fn it_embeds_metadata_into_images() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("embed_metadata_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+
+    for (name, image) in [("image.jpg", JPEG), ("image.png", PNG)] {
+        let file_path = folder.join(name);
+        fs::write(&file_path, image)?;
+
+        assert!(embed_image_metadata(&file_path, &get_metadata())?);
+        // Embedding again replaces the XMP packet instead of adding another one
+        assert!(embed_image_metadata(&file_path, &get_metadata())?);
+
+        let bytes = fs::read(&file_path)?;
+        assert!(contains(&bytes, b"spez\0"));
+        assert_eq!(
+            bytes
+                .windows(b"<x:xmpmeta".len())
+                .filter(|window| *window == b"<x:xmpmeta")
+                .count(),
+            1
+        );
+    }
+
+    let file_path = folder.join("notes.txt");
+    fs::write(&file_path, "not an image")?;
+    assert!(!embed_image_metadata(&file_path, &get_metadata())?);
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use http::Extensions;
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    cli::{handle_account_command, CliAccountCommand, CliSharedOptions, RedditAccountListing},
+    clients::{
+        Archive, ArchiveClientError, RedditCredentials, RedditProviderError, RedgifsClientError,
+    },
+    error::{ClawlerError, ExitStatus},
+    utils::state::SharedState,
+};
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, Middleware, Next};
+use std::{env, error::Error, fs, io, sync::Arc};
+use tokio::sync::Mutex;
+
+/// Answers every request with a 401, like Reddit does for wrong app or account credentials
+struct RejectedCredentials;
+
+#[async_trait]
+impl Middleware for RejectedCredentials {
+    async fn handle(
+        &self,
+        _req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut response = http::Response::new(String::from("{}"));
+        *response.status_mut() = http::StatusCode::UNAUTHORIZED;
+        Ok(Response::from(response))
+    }
+}
+
+fn exit_status(e: impl Into<Box<dyn Error>>) -> ExitStatus {
+    ClawlerError::classify(e).exit_status()
+}
+
+#[test]
+fn it_classifies_errors_of_commands() {
+    assert_eq!(
+        exit_status(RedditProviderError::TooManyRequests),
+        ExitStatus::RateLimited
+    );
+    assert_eq!(
+        exit_status(ArchiveClientError::TooManyRequests(Archive::ArcticShift)),
+        ExitStatus::RateLimited
+    );
+    assert_eq!(
+        exit_status(RedditProviderError::Suspended),
+        ExitStatus::ResourceGone
+    );
+    assert_eq!(
+        exit_status(RedgifsClientError::NotFound),
+        ExitStatus::ResourceGone
+    );
+    assert_eq!(
+        exit_status(RedditProviderError::Unauthorized),
+        ExitStatus::AuthFailed
+    );
+    assert_eq!(
+        exit_status(io::Error::other("disk full")),
+        ExitStatus::Error
+    );
+}
+
+#[test]
+fn it_keeps_rejected_credentials_of_crawls() {
+    // Commands return their errors boxed, `main` converts them with `?`
+    let crawled: Box<dyn Error> = Box::new(RedditProviderError::Unauthorized);
+    let e = ClawlerError::from(crawled);
+
+    assert_eq!(e.exit_status(), ExitStatus::AuthFailed);
+    assert_eq!(e.exit_status().code(), 5);
+    assert_eq!(e.to_string(), RedditProviderError::Unauthorized.to_string());
+}
+
+#[test]
+fn it_keeps_the_exit_codes() {
+    let codes = [
+        (ExitStatus::Success, 0),
+        (ExitStatus::Error, 1),
+        (ExitStatus::PartialFailure, 2),
+        (ExitStatus::RateLimited, 3),
+        (ExitStatus::ResourceGone, 4),
+        (ExitStatus::AuthFailed, 5),
+        (ExitStatus::InvalidInput, 6),
+        (ExitStatus::MissingDependency, 7),
+        (ExitStatus::Interrupted, 130),
+    ];
+
+    for (status, code) in codes {
+        assert_eq!(status.code(), code);
+    }
+    assert_eq!(
+        ClawlerError::PartialFailure(3).to_string(),
+        "3 downloads failed, `retry` downloads them again"
+    );
+}
+
+#[tokio::test]
+async fn it_fails_account_crawls_with_rejected_credentials() -> Result<(), Box<dyn Error>> {
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(RejectedCredentials)
+        .build();
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    let output = env::temp_dir().join(format!("reddit_clawler_auth_{}", std::process::id()));
+
+    let result = handle_account_command(
+        CliAccountCommand {
+            listing: RedditAccountListing::Saved,
+            credentials: RedditCredentials {
+                client_id: String::from("MOCK_CLIENT_ID"),
+                client_secret: String::from("MOCK_CLIENT_SECRET"),
+                username: String::from("spez"),
+                password: String::from("MOCK_PASSWORD"),
+            },
+            options: CliSharedOptions {
+                output: output.to_string_lossy().to_string(),
+                ..CliSharedOptions::default()
+            },
+        },
+        &client,
+        &shared_state,
+    )
+    .await;
+
+    let _ = fs::remove_dir_all(&output);
+    let e = result.err().ok_or("Expected the crawl to fail")?;
+    assert_eq!(
+        ClawlerError::classify(e).exit_status(),
+        ExitStatus::AuthFailed
+    );
+    Ok(())
+}
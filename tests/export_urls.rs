@@ -0,0 +1,39 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedResponse,
+    reddit_parser::RedditPostParser,
+    utils::{export_media_urls, state::SharedState},
+};
+use reqwest_middleware::ClientBuilder;
+use std::{env, error::Error, fs, sync::Arc};
+use tokio::sync::Mutex;
+
+#[tokio::test]
+async fn it_exports_media_urls() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+    let posts = RedditPostParser::default().parse(res);
+    assert!(posts.len() > 1);
+
+    let file_path = env::temp_dir()
+        .join(format!("reddit_clawler_urls_{}.txt", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    let client = ClientBuilder::new(reqwest::Client::new()).build();
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+
+    // Gallery images are served directly, nothing is requested to resolve them
+    export_media_urls(&client, &shared_state, &posts, &file_path, 4).await?;
+    export_media_urls(&client, &shared_state, &posts[..1], &file_path, 4).await?;
+
+    let exported = fs::read_to_string(&file_path)?;
+    let mut expected = posts.iter().map(|p| p.url.as_str()).collect::<Vec<_>>();
+    expected.push(&posts[0].url);
+    assert_eq!(exported.lines().collect::<Vec<_>>(), expected);
+
+    fs::remove_file(&file_path)?;
+    Ok(())
+}
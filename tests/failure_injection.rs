@@ -0,0 +1,86 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::reddit_parser::{RedditCrawlerPost, RedditMediaProviderType};
+use reddit_clawler::utils::{
+    download_crawler_post, state::SharedState, DownloadPostResult, FailureInjection,
+    FailureInjectionError, InjectedFailureKind, DEFAULT_FILE_SCHEME,
+};
+use std::{error::Error, sync::Arc};
+use tokio::sync::Mutex;
+
+#[test]
+fn it_parses_failure_injection() -> Result<(), Box<dyn Error>> {
+    let injection: FailureInjection = "rate=0.1,kind=timeout".parse()?;
+    assert_eq!(
+        injection,
+        FailureInjection {
+            rate: 0.1,
+            kind: InjectedFailureKind::Timeout
+        }
+    );
+
+    let injection: FailureInjection = "kind=notfound".parse()?;
+    assert_eq!(injection.rate, 0.0);
+    assert_eq!(injection.kind, InjectedFailureKind::NotFound);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_failure_injection() {
+    assert_eq!(
+        "rate=1.5".parse::<FailureInjection>(),
+        Err(FailureInjectionError::Rate(String::from("1.5")))
+    );
+    assert_eq!(
+        "kind=explode".parse::<FailureInjection>(),
+        Err(FailureInjectionError::Kind(String::from("explode")))
+    );
+    assert_eq!(
+        "seed=42".parse::<FailureInjection>(),
+        Err(FailureInjectionError::Option(String::from("seed=42")))
+    );
+}
+
+#[test]
+fn it_rolls_failures_by_rate() {
+    let never = FailureInjection {
+        rate: 0.0,
+        kind: InjectedFailureKind::Error,
+    };
+    let always = FailureInjection {
+        rate: 1.0,
+        kind: InjectedFailureKind::Error,
+    };
+
+    for _ in 0..100 {
+        assert_eq!(never.roll(), None);
+        assert_eq!(always.roll(), Some(InjectedFailureKind::Error));
+    }
+}
+
+#[tokio::test]
+async fn it_injects_download_failures() -> Result<(), Box<dyn Error>> {
+    let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+    let shared_state = Arc::new(Mutex::new(SharedState {
+        failure_injection: Some("rate=1,kind=notfound".parse()?),
+        ..SharedState::default()
+    }));
+    let post = RedditCrawlerPost {
+        id: String::from("MOCK_ID"),
+        extension: String::from("jpg"),
+        provider: RedditMediaProviderType::RedditImage,
+        url: String::from("http://127.0.0.1:0/unreachable.jpg"),
+        ..RedditCrawlerPost::default()
+    };
+
+    let result =
+        download_crawler_post(&client, &shared_state, "output", &post, DEFAULT_FILE_SCHEME).await?;
+    assert!(matches!(result, DownloadPostResult::ReceivedNotFound));
+
+    shared_state.lock().await.failure_injection = Some("rate=1,kind=timeout".parse()?);
+    let result =
+        download_crawler_post(&client, &shared_state, "output", &post, DEFAULT_FILE_SCHEME).await;
+    assert!(result.is_err());
+
+    Ok(())
+}
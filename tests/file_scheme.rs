@@ -0,0 +1,128 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType},
+    utils::{
+        get_file_name, get_verbatim_path, sanitize_file_name_part, sanitize_file_path, Organize,
+        DEFAULT_FILE_SCHEME,
+    },
+};
+
+fn mock_post() -> RedditCrawlerPost {
+    RedditCrawlerPost {
+        author: String::from("MOCK_AUTHOR"),
+        created_utc: Utc.with_ymd_and_hms(2023, 12, 9, 20, 26, 26).unwrap(),
+        extension: String::from("webp"),
+        id: String::from("MOCK_ID"),
+        index: Some(1),
+        provider: RedditMediaProviderType::RedditGalleryImage,
+        subreddit: String::from("aww"),
+        title: String::from("Is this a cat?  No: it's a <dog>"),
+        upvotes: 42,
+        ..RedditCrawlerPost::default()
+    }
+}
+
+#[test]
+fn it_applies_file_schemes() {
+    let post = mock_post();
+
+    assert_eq!(
+        get_file_name(&post, DEFAULT_FILE_SCHEME),
+        "42_MOCK_AUTHOR_MOCK_ID_2023-12-09_1.webp"
+    );
+    assert_eq!(
+        get_file_name(&post, "{DATE}_{SUBREDDIT}_{TITLE}_{POSTID}"),
+        "2023-12-09_aww_Is_this_a_cat_No_it's_a_dog_MOCK_ID_1.webp"
+    );
+    assert_eq!(
+        get_file_name(&post, "{PROVIDER}-{POSTID}-{INDEX}.{EXT}"),
+        "reddit_gallery_image-MOCK_ID-1.webp"
+    );
+}
+
+#[test]
+fn it_sanitizes_file_name_parts() {
+    assert_eq!(
+        sanitize_file_name_part("a/b\\c:d*e?f\"g<h>i|j"),
+        "a_b_c_d_e_f_g_h_i_j"
+    );
+    assert_eq!(
+        sanitize_file_name_part("  ..hidden title.. "),
+        "hidden_title"
+    );
+    assert_eq!(
+        sanitize_file_name_part("tabs\tand\nnewlines"),
+        "tabs_and_newlines"
+    );
+}
+
+#[test]
+fn it_sanitizes_file_paths() {
+    assert_eq!(
+        sanitize_file_path("aww/what: a <cat>?.jpg"),
+        "aww/what_ a _cat__.jpg"
+    );
+    assert_eq!(
+        sanitize_file_path("/trailing dots../file. "),
+        "trailing dots/file"
+    );
+    assert_eq!(sanitize_file_path("../../etc/passwd"), "_/_/etc/passwd");
+    assert_eq!(sanitize_file_path("CON.jpg"), "CON_.jpg");
+    assert_eq!(sanitize_file_path("lpt1/com9"), "lpt1_/com9_");
+    assert_eq!(sanitize_file_path("CONSOLE.jpg"), "CONSOLE.jpg");
+
+    let long_name = sanitize_file_path(&format!("{}.webm", "ü".repeat(200)));
+    assert_eq!(long_name.len(), 255);
+    assert!(long_name.ends_with("ü.webm"));
+}
+
+#[test]
+fn it_prefixes_long_windows_paths() {
+    assert_eq!(
+        get_verbatim_path("C:/downloads/user/foo/file.jpg"),
+        r"\\?\C:\downloads\user\foo\file.jpg"
+    );
+    assert_eq!(
+        get_verbatim_path(r"\\nas\share\file.jpg"),
+        r"\\?\UNC\nas\share\file.jpg"
+    );
+    assert_eq!(get_verbatim_path(r"\\?\C:\file.jpg"), r"\\?\C:\file.jpg");
+}
+
+#[test]
+fn it_keeps_file_names_valid_on_windows() {
+    let post = RedditCrawlerPost {
+        title: "a".repeat(300),
+        ..mock_post()
+    };
+
+    assert_eq!(
+        get_file_name(&post, "{SUBREDDIT}: {TITLE}"),
+        format!("aww_ {}_1.webp", "a".repeat(100))
+    );
+    assert_eq!(get_file_name(&post, "{YEAR}/../con"), "2023/_/con_1.webp");
+}
+
+#[test]
+fn it_organizes_files_into_subfolders() {
+    let post = mock_post();
+    let file_name = |organize: Organize| get_file_name(&post, &organize.apply(DEFAULT_FILE_SCHEME));
+
+    assert_eq!(
+        file_name(Organize::Flat),
+        "42_MOCK_AUTHOR_MOCK_ID_2023-12-09_1.webp"
+    );
+    assert_eq!(
+        file_name(Organize::BySubreddit),
+        "aww/42_MOCK_AUTHOR_MOCK_ID_2023-12-09_1.webp"
+    );
+    assert_eq!(
+        file_name(Organize::ByDate),
+        "2023/12/42_MOCK_AUTHOR_MOCK_ID_2023-12-09_1.webp"
+    );
+    assert_eq!(
+        file_name(Organize::ByProvider),
+        "reddit_gallery_image/42_MOCK_AUTHOR_MOCK_ID_2023-12-09_1.webp"
+    );
+}
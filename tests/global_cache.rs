@@ -0,0 +1,38 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{reddit_parser::RedditCrawlerPost, utils::GlobalCache};
+use std::{env, error::Error, fs};
+
+#[test]
+fn it_remembers_downloads_across_crawls() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("clawler_global_cache_{}", std::process::id()));
+    let path = folder.join("nested").join("global.db");
+    let path = path.to_string_lossy();
+
+    let mut cache = GlobalCache::open(&path)?;
+    assert!(cache.is_empty());
+
+    let post = RedditCrawlerPost {
+        id: String::from("18dxopo"),
+        index: Some(1),
+        ..RedditCrawlerPost::default()
+    };
+    cache.record(&post, "user/spez", "output/user/spez/18dxopo_1.jpg")?;
+    let second_item = RedditCrawlerPost {
+        index: Some(2),
+        ..post.clone()
+    };
+    cache.record(&second_item, "user/spez", "output/user/spez/18dxopo_2.jpg")?;
+    // Recording a file again keeps the first record
+    cache.record(&post, "subreddit/aww", "output/subreddit/aww/18dxopo_1.jpg")?;
+    assert!(cache.contains("18dxopo"));
+    drop(cache);
+
+    // Another crawl opening the database sees the downloads of the first one
+    let cache = GlobalCache::open(&path)?;
+    assert_eq!(cache.len(), 1);
+    assert!(cache.contains("18dxopo"));
+    assert!(!cache.contains("18dxopp"));
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{ImageConversion, ImageFormat};
+
+#[test]
+fn it_only_converts_still_images() {
+    let conversion = ImageConversion {
+        format: ImageFormat::Avif,
+        quality: 80,
+    };
+
+    assert!(conversion.converts("1700000000_cats_18f0b01_2023-11-14.jpg"));
+    assert!(conversion.converts("1700000000_cats_18f0b01_2023-11-14_1.PNG"));
+    assert!(conversion.converts("1700000000_cats_18f0b01_2023-11-14.webp"));
+    // Already in the target format
+    assert!(!conversion.converts("1700000000_cats_18f0b01_2023-11-14.avif"));
+    assert!(!conversion.converts("1700000000_cats_18f0b01_2023-11-14.gif"));
+    assert!(!conversion.converts("1700000000_cats_18f0b01_2023-11-14.mp4"));
+
+    let webp = ImageConversion {
+        format: ImageFormat::Webp,
+        ..conversion
+    };
+    assert!(!webp.converts("1700000000_cats_18f0b01_2023-11-14.webp"));
+}
+
+#[test]
+fn it_gets_the_encoder_args() {
+    let avif = ImageConversion {
+        format: ImageFormat::Avif,
+        quality: 60,
+    };
+    assert_eq!(
+        avif.get_args("in.jpg", "out.part.avif"),
+        vec!["-q", "60", "in.jpg", "out.part.avif"]
+    );
+
+    let webp = ImageConversion {
+        format: ImageFormat::Webp,
+        quality: 80,
+    };
+    assert_eq!(
+        webp.get_args("in.png", "out.part.webp"),
+        vec![
+            "-y",
+            "-loglevel",
+            "error",
+            "-i",
+            "in.png",
+            "-quality",
+            "80",
+            "out.part.webp"
+        ]
+    );
+}
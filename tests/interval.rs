@@ -0,0 +1,15 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::parse_interval;
+use std::time::Duration;
+
+#[test]
+fn it_parses_intervals() {
+    assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_interval("15m"), Ok(Duration::from_secs(15 * 60)));
+    assert_eq!(parse_interval("6h"), Ok(Duration::from_secs(6 * 60 * 60)));
+    assert_eq!(parse_interval("1d"), Ok(Duration::from_secs(24 * 60 * 60)));
+
+    assert!(parse_interval("0m").is_err());
+    assert!(parse_interval("15w").is_err());
+    assert!(parse_interval("m").is_err());
+}
@@ -0,0 +1,11 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::get_log_level;
+
+#[test]
+fn it_maps_verbosity_to_log_levels() {
+    assert_eq!(get_log_level(0, false), "info");
+    assert_eq!(get_log_level(1, false), "debug");
+    assert_eq!(get_log_level(2, false), "trace");
+    assert_eq!(get_log_level(5, false), "trace");
+    assert_eq!(get_log_level(0, true), "warn");
+}
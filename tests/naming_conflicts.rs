@@ -0,0 +1,62 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::RedditCrawlerPost,
+    utils::{find_naming_conflicts, NamingConflict, DEFAULT_FILE_SCHEME},
+};
+
+#[test]
+fn it_detects_file_name_collisions() {
+    let post = RedditCrawlerPost {
+        id: String::from("MOCK_ID"),
+        author: String::from("MOCK_AUTHOR"),
+        extension: String::from("jpg"),
+        ..RedditCrawlerPost::default()
+    };
+    let gallery_item = RedditCrawlerPost {
+        index: Some(0),
+        ..post.clone()
+    };
+
+    assert_eq!(
+        find_naming_conflicts(
+            "output",
+            &[post.clone(), gallery_item, post.clone()],
+            DEFAULT_FILE_SCHEME
+        ),
+        vec![]
+    );
+
+    // Placeholders are joined by underscores, so values containing them can collide
+    let first = RedditCrawlerPost {
+        id: String::from("B_C"),
+        author: String::from("A"),
+        ..post.clone()
+    };
+    let second = RedditCrawlerPost {
+        id: String::from("C"),
+        author: String::from("A_B"),
+        ..post
+    };
+
+    let conflicts = find_naming_conflicts("output", &[first, second], DEFAULT_FILE_SCHEME);
+    assert!(matches!(
+        conflicts.as_slice(),
+        [NamingConflict::Collision { post_ids, .. }] if post_ids == &["B_C", "C"]
+    ));
+}
+
+#[test]
+fn it_detects_path_length_violations() {
+    let post = RedditCrawlerPost {
+        id: String::from("MOCK_ID"),
+        author: "A".repeat(300),
+        extension: String::from("jpg"),
+        ..RedditCrawlerPost::default()
+    };
+
+    let conflicts = find_naming_conflicts("output", &[post], DEFAULT_FILE_SCHEME);
+    assert!(matches!(
+        conflicts.as_slice(),
+        [NamingConflict::PathTooLong { post_id, .. }] if post_id == "MOCK_ID"
+    ));
+}
@@ -0,0 +1,29 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{get_webhook_payload, CrawlSummary};
+use serde_json::json;
+use std::error::Error;
+
+#[test]
+fn it_builds_a_discord_and_slack_payload() -> Result<(), Box<dyn Error>> {
+    let summary = CrawlSummary {
+        resource: String::from("/u/spez"),
+        downloaded: 12,
+        failed: 1,
+        bytes: 3.0 * 1024.0 * 1024.0,
+    };
+    let message = "Finished crawling /u/spez - downloaded 12 files (3.00 MB), 1 failed";
+
+    assert_eq!(
+        get_webhook_payload(&summary),
+        json!({
+            "content": message,
+            "text": message,
+            "resource": "/u/spez",
+            "downloaded": 12,
+            "failed": 1,
+            "bytes": 3145728.0,
+        })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{expand_path, resolve_output_path, OutputPathError};
+use std::{env, error::Error, path::Path};
+
+#[test]
+fn it_expands_tilde() -> Result<(), Box<dyn Error>> {
+    let home = env::var("HOME")?;
+
+    assert_eq!(expand_path("~")?, home);
+    assert_eq!(expand_path("~/archive")?, format!("{}/archive", home));
+    // Only a leading tilde refers to the home directory
+    assert_eq!(expand_path("archive/~")?, "archive/~");
+    assert_eq!(expand_path("~archive")?, "~archive");
+
+    Ok(())
+}
+
+#[test]
+fn it_expands_environment_variables() -> Result<(), Box<dyn Error>> {
+    env::set_var("REDDIT_CLAWLER_TEST_DIR", "/mnt/archive");
+
+    assert_eq!(
+        expand_path("$REDDIT_CLAWLER_TEST_DIR/reddit")?,
+        "/mnt/archive/reddit"
+    );
+    assert_eq!(
+        expand_path("${REDDIT_CLAWLER_TEST_DIR}_reddit")?,
+        "/mnt/archive_reddit"
+    );
+    assert_eq!(expand_path("cost$")?, "cost$");
+    assert_eq!(
+        expand_path("$REDDIT_CLAWLER_UNSET_DIR/reddit"),
+        Err(OutputPathError::EnvironmentVariable(String::from(
+            "REDDIT_CLAWLER_UNSET_DIR"
+        )))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_resolves_output_path() -> Result<(), Box<dyn Error>> {
+    assert_eq!(resolve_output_path("output/", false)?, "output");
+    assert_eq!(resolve_output_path("/", false)?, "/");
+
+    let absolute = resolve_output_path("output", true)?;
+    assert!(Path::new(&absolute).is_absolute());
+    assert_eq!(Path::new(&absolute), env::current_dir()?.join("output"));
+
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use pretty_assertions::assert_eq;
 use reddit_clawler::{
     self,
-    clients::api_types::reddit::submitted_response::RedditSubmittedResponse,
-    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    clients::{
+        api_types::reddit::submitted_response::RedditSubmittedResponse, extract_redgifs_id,
+        extract_streamable_id, RedgifsUserResponse,
+    },
+    reddit_parser::{PreviewSize, RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
 };
 use std::{error::Error, fs};
 
@@ -20,8 +23,13 @@ fn it_detects_reddit_image() -> Result<(), Box<dyn Error>> {
     assert_eq!(parsed_posts.len(), 1);
 
     for mt in parsed_posts.iter() {
-        let RedditCrawlerPost { provider, .. } = mt;
+        let RedditCrawlerPost {
+            provider,
+            extension,
+            ..
+        } = mt;
         assert_eq!(provider, &RedditMediaProviderType::RedditImage);
+        assert_eq!(extension, "jpg");
     }
 
     Ok(())
@@ -48,6 +56,103 @@ fn it_detects_reddit_gallery() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn it_selects_preview_resolutions() {
+    let resolutions = [(108, "108"), (216, "216"), (640, "640"), (1080, "1080")];
+
+    assert_eq!(
+        PreviewSize::Thumbnail.select(4032, &resolutions),
+        Some("108")
+    );
+    assert_eq!(
+        PreviewSize::MaxResolution(1080).select(4032, &resolutions),
+        Some("1080")
+    );
+    assert_eq!(
+        PreviewSize::MaxResolution(700).select(4032, &resolutions),
+        Some("640")
+    );
+    // Falls back to the smallest preview, and keeps sources within the limit
+    assert_eq!(
+        PreviewSize::MaxResolution(50).select(4032, &resolutions),
+        Some("108")
+    );
+    assert_eq!(
+        PreviewSize::MaxResolution(1080).select(960, &resolutions),
+        None
+    );
+    assert_eq!(PreviewSize::Thumbnail.select(4032, &[]), None);
+}
+
+#[test]
+fn it_downloads_previews_of_images() -> Result<(), Box<dyn Error>> {
+    let post_parser = RedditPostParser {
+        preview_size: Some(PreviewSize::MaxResolution(640)),
+        ..Default::default()
+    };
+
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_image.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let parsed_posts = post_parser.parse(responses.first().ok_or("Expected a response")?);
+    assert_eq!(parsed_posts.len(), 1);
+    assert!(parsed_posts[0].url.contains("width=640"));
+
+    let post_parser = RedditPostParser {
+        preview_size: Some(PreviewSize::Thumbnail),
+        ..Default::default()
+    };
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let parsed_posts = post_parser.parse(responses.first().ok_or("Expected a response")?);
+    assert_eq!(parsed_posts.len(), 3);
+    assert!(parsed_posts
+        .iter()
+        .all(|post| post.url.contains("width=108")));
+
+    Ok(())
+}
+
+#[test]
+fn it_detects_gallery_extensions() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery.json")?;
+    let mut value: serde_json::Value = serde_json::from_str(&data)?;
+    let media_metadata = &mut value[0]["data"]["children"][0]["data"]["media_metadata"];
+    media_metadata["dll626nzl55c1"]["m"] = serde_json::json!("image/png");
+    media_metadata["43vpj6nzl55c1"] = serde_json::json!({
+        "status": "valid",
+        "e": "AnimatedImage",
+        "m": "image/gif",
+        "s": {
+            "y": 480,
+            "x": 270,
+            "gif": "https://i.redd.it/43vpj6nzl55c1.gif",
+            "mp4": "https://preview.redd.it/43vpj6nzl55c1.gif?format=mp4&s=abc"
+        },
+        "id": "43vpj6nzl55c1"
+    });
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_value(value)?;
+
+    let parsed_posts = RedditPostParser::default().parse(&responses[0]);
+    let files = parsed_posts
+        .iter()
+        .map(|p| (p.extension.as_str(), p.url.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        files,
+        vec![
+            ("jpg", "https://i.redd.it/0w3ha6nzl55c1.jpg"),
+            ("png", "https://i.redd.it/dll626nzl55c1.png"),
+            (
+                "mp4",
+                "https://preview.redd.it/43vpj6nzl55c1.gif?format=mp4&s=abc"
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
 #[test]
 fn it_detects_reddit_video() -> Result<(), Box<dyn Error>> {
     let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_video.json")?;
@@ -62,8 +167,12 @@ fn it_detects_reddit_video() -> Result<(), Box<dyn Error>> {
     assert_eq!(parsed_posts.len(), 1);
 
     for mt in parsed_posts.iter() {
-        let RedditCrawlerPost { provider, .. } = mt;
+        let RedditCrawlerPost { provider, url, .. } = mt;
         assert_eq!(provider, &RedditMediaProviderType::RedditVideo);
+        assert_eq!(
+            url,
+            "https://v.redd.it/7kxjendogp4c1/DASH_480.mp4?source=fallback"
+        );
     }
 
     Ok(())
@@ -174,3 +283,133 @@ fn it_detects_redgifs_video_iframe() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn it_explains_parser_branches() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/redgifs_video.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let child = responses
+        .first()
+        .and_then(|res| res.data.children.first())
+        .ok_or("Expected mockfile to contain a post")?;
+
+    let post_parser = RedditPostParser::default();
+    let (parsed_posts, trace) = post_parser.explain(child);
+
+    assert_eq!(parsed_posts.len(), 1);
+    assert_eq!(
+        trace,
+        vec![
+            String::from("is_reddit_media_domain = false"),
+            String::from("Matched Redgifs video"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_detects_streamable_video() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/streamable_video.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+
+    let post_parser = RedditPostParser::default();
+    let parsed_posts = post_parser.parse(res);
+
+    assert_eq!(parsed_posts.len(), 1);
+
+    for mt in parsed_posts.iter() {
+        let RedditCrawlerPost { provider, url, .. } = mt;
+        assert_eq!(provider, &RedditMediaProviderType::StreamableVideo);
+        assert_eq!(extract_streamable_id(url), Some("mock"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_detects_gfycat_video() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/gfycat_video.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+
+    let post_parser = RedditPostParser::default();
+    let parsed_posts = post_parser.parse(res);
+
+    assert_eq!(parsed_posts.len(), 1);
+
+    for mt in parsed_posts.iter() {
+        let RedditCrawlerPost { provider, url, .. } = mt;
+        assert_eq!(provider, &RedditMediaProviderType::RedgifsVideo);
+        assert_eq!(url, "https://www.redgifs.com/watch/mockgiganticalpinegoat");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_parses_redgifs_user_gifs() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/redgifs/user_response.json")?;
+    let responses: Vec<RedgifsUserResponse> = serde_json::from_str(&data)?;
+
+    let post_parser = RedditPostParser::default();
+    let parsed_posts = post_parser.parse_redgifs_user(&responses);
+
+    // The second gif of the gallery is expanded by the downloader instead
+    let parsed = parsed_posts
+        .iter()
+        .map(|p| (p.id.as_str(), p.provider.clone(), p.extension.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parsed,
+        vec![
+            (
+                "mockgiganticalpinegoat",
+                RedditMediaProviderType::RedgifsVideo,
+                "mp4"
+            ),
+            (
+                "mocktinyredpanda",
+                RedditMediaProviderType::RedgifsImage,
+                "jpg"
+            ),
+            (
+                "mockquietotter",
+                RedditMediaProviderType::RedgifsImage,
+                "jpg"
+            ),
+        ]
+    );
+
+    let RedditCrawlerPost {
+        author,
+        title,
+        upvotes,
+        url,
+        ..
+    } = &parsed_posts[0];
+    assert_eq!(author, "mockuser");
+    assert_eq!(title, "Mock video");
+    assert_eq!(upvotes, &42);
+    assert_eq!(url, "https://www.redgifs.com/watch/mockgiganticalpinegoat");
+
+    Ok(())
+}
+
+#[test]
+fn it_extracts_redgifs_ids() {
+    assert_eq!(
+        extract_redgifs_id("https://www.redgifs.com/watch/mockgiganticalpinegoat").ok(),
+        Some("mockgiganticalpinegoat")
+    );
+    assert_eq!(
+        extract_redgifs_id("https://i.redgifs.com/i/mocktinyredpanda.jpg").ok(),
+        Some("mocktinyredpanda")
+    );
+    assert!(extract_redgifs_id("https://www.redgifs.com/users/mockuser").is_err());
+}
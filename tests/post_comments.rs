@@ -0,0 +1,24 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::RedditCrawlerPost,
+    utils::{get_comments_file_path, DEFAULT_FILE_SCHEME},
+};
+
+#[test]
+fn it_places_comments_next_to_the_files() {
+    let post = RedditCrawlerPost {
+        author: String::from("MOCK_AUTHOR"),
+        created_utc: Utc.with_ymd_and_hms(2023, 12, 9, 20, 26, 26).unwrap(),
+        extension: String::from("webp"),
+        id: String::from("MOCK_ID"),
+        index: Some(2),
+        upvotes: 42,
+        ..RedditCrawlerPost::default()
+    };
+
+    assert_eq!(
+        get_comments_file_path("output/user/MOCK_AUTHOR", &post, DEFAULT_FILE_SCHEME),
+        "output/user/MOCK_AUTHOR/42_MOCK_AUTHOR_MOCK_ID_2023-12-09.comments.json"
+    );
+}
@@ -0,0 +1,162 @@
+use reddit_clawler::{
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType},
+    utils::{parse_filter_date, MediaType, NsfwFilter, PostFilter, StickiedFilter},
+};
+use regex::Regex;
+use std::error::Error;
+
+#[test]
+fn it_filters_stickied_posts() {
+    let stickied = RedditCrawlerPost {
+        stickied: true,
+        ..RedditCrawlerPost::default()
+    };
+    let regular = RedditCrawlerPost::default();
+
+    let filter = PostFilter::default();
+    assert!(filter.matches(&stickied));
+    assert!(filter.matches(&regular));
+
+    let filter = PostFilter {
+        stickied: StickiedFilter::Exclude,
+        ..PostFilter::default()
+    };
+    assert!(!filter.matches(&stickied));
+    assert!(filter.matches(&regular));
+
+    let filter = PostFilter {
+        stickied: StickiedFilter::Only,
+        ..PostFilter::default()
+    };
+    assert!(filter.matches(&stickied));
+    assert!(!filter.matches(&regular));
+}
+
+#[test]
+fn it_filters_spoiler_posts() {
+    let spoiler = RedditCrawlerPost {
+        spoiler: true,
+        ..RedditCrawlerPost::default()
+    };
+    let regular = RedditCrawlerPost::default();
+
+    let filter = PostFilter::default();
+    assert!(filter.matches(&spoiler));
+
+    let filter = PostFilter {
+        exclude_spoilers: true,
+        ..PostFilter::default()
+    };
+    assert!(!filter.matches(&spoiler));
+    assert!(filter.matches(&regular));
+}
+
+#[test]
+fn it_filters_posts_by_upvotes() {
+    let post = |upvotes| RedditCrawlerPost {
+        upvotes,
+        ..RedditCrawlerPost::default()
+    };
+
+    let filter = PostFilter {
+        min_upvotes: Some(100),
+        max_upvotes: Some(1000),
+        ..PostFilter::default()
+    };
+    assert!(!filter.matches(&post(99)));
+    assert!(filter.matches(&post(100)));
+    assert!(filter.matches(&post(1000)));
+    assert!(!filter.matches(&post(1001)));
+
+    let filter = PostFilter {
+        min_upvotes: Some(100),
+        ..PostFilter::default()
+    };
+    assert!(filter.matches(&post(i64::MAX)));
+}
+
+#[test]
+fn it_filters_posts_by_date() -> Result<(), Box<dyn Error>> {
+    let post = |date: &str| -> Result<RedditCrawlerPost, String> {
+        Ok(RedditCrawlerPost {
+            created_utc: parse_filter_date(date)?,
+            ..RedditCrawlerPost::default()
+        })
+    };
+
+    let filter = PostFilter {
+        after_date: Some(parse_filter_date("2024-01-01")?),
+        before_date: Some(parse_filter_date("2024-02-01T00:00:00Z")?),
+        ..PostFilter::default()
+    };
+    assert!(!filter.matches(&post("2023-12-31T23:59:59Z")?));
+    assert!(filter.matches(&post("2024-01-01")?));
+    assert!(filter.matches(&post("2024-01-31T23:59:59+00:00")?));
+    assert!(!filter.matches(&post("2024-02-01")?));
+
+    assert!(filter.is_before_cutoff(parse_filter_date("2023-12-31")?));
+    assert!(parse_filter_date("31.01.2024").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn it_filters_posts_by_media_type() {
+    let post = |provider| RedditCrawlerPost {
+        provider,
+        ..RedditCrawlerPost::default()
+    };
+
+    let filter = PostFilter {
+        only: vec![MediaType::Images, MediaType::Galleries],
+        ..PostFilter::default()
+    };
+    assert!(filter.matches(&post(RedditMediaProviderType::ImgurImage)));
+    assert!(filter.matches(&post(RedditMediaProviderType::RedditGalleryImage)));
+    assert!(!filter.matches(&post(RedditMediaProviderType::RedditVideo)));
+    assert!(!filter.matches(&post(RedditMediaProviderType::RedditGifVideo)));
+
+    assert!(PostFilter::default().matches(&post(RedditMediaProviderType::YoutubeVideo)));
+}
+
+#[test]
+fn it_filters_nsfw_posts() {
+    let nsfw = RedditCrawlerPost {
+        over_18: true,
+        ..RedditCrawlerPost::default()
+    };
+    let sfw = RedditCrawlerPost::default();
+
+    let filter = PostFilter {
+        nsfw: NsfwFilter::Exclude,
+        ..PostFilter::default()
+    };
+    assert!(!filter.matches(&nsfw));
+    assert!(filter.matches(&sfw));
+
+    let filter = PostFilter {
+        nsfw: NsfwFilter::Only,
+        ..PostFilter::default()
+    };
+    assert!(filter.matches(&nsfw));
+    assert!(!filter.matches(&sfw));
+}
+
+#[test]
+fn it_filters_posts_by_title() -> Result<(), Box<dyn Error>> {
+    let post = |title: &str| RedditCrawlerPost {
+        title: String::from(title),
+        ..RedditCrawlerPost::default()
+    };
+
+    let filter = PostFilter {
+        title_match: Some(Regex::new(r"(?i)\[oc\]")?),
+        title_exclude: Some(Regex::new("repost")?),
+        ..PostFilter::default()
+    };
+    assert!(filter.matches(&post("My cat [OC]")));
+    assert!(!filter.matches(&post("My cat")));
+    assert!(!filter.matches(&post("My cat [OC] repost")));
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedResponse,
+    reddit_parser::RedditPostParser,
+    utils::{get_post_metadata, DEFAULT_FILE_SCHEME},
+};
+use std::{error::Error, fs};
+
+#[test]
+fn it_serializes_post_metadata() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+    let child = &res.data.children[0].data;
+
+    let posts = RedditPostParser::default().parse(res);
+    let metadata = get_post_metadata(child, &posts, DEFAULT_FILE_SCHEME);
+    assert_eq!(metadata.files.len(), posts.len());
+    assert_eq!(metadata.files[0].provider, "reddit_gallery_image");
+    assert_eq!(metadata.files[0].index, Some(0));
+
+    let value = serde_json::to_value(&metadata)?;
+    assert_eq!(value["post"]["id"], child.id.as_str());
+    assert_eq!(value["post"]["permalink"], child.permalink.as_str());
+    assert_eq!(
+        value["files"][0]["fileName"],
+        metadata.files[0].file_name.as_str()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_gallery_captions_into_post_metadata() -> Result<(), Box<dyn Error>> {
+    let data =
+        fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_gallery_captions.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+    let child = &res.data.children[0].data;
+
+    let posts = RedditPostParser::default().parse(res);
+    let metadata = get_post_metadata(child, &posts, "{POSTID}_{INDEX}_{CAPTION}");
+
+    let captions = metadata
+        .files
+        .iter()
+        .map(|f| (f.caption.as_deref(), f.outbound_url.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        captions,
+        vec![
+            (Some("Sleeping on the couch"), None),
+            (None, None),
+            (
+                Some("First walk outside"),
+                Some("https://example.com/adopt")
+            ),
+        ]
+    );
+    assert!(metadata.files[0]
+        .file_name
+        .ends_with("_0_Sleeping_on_the_couch.jpg"));
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::{
+        RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+    utils::{
+        download_crawler_post, get_post_metadata, state::SharedState, DownloadPostResult,
+        PostRemoval, SkipReason,
+    },
+};
+use reqwest_middleware::ClientBuilder;
+use std::{env, error::Error, fs, sync::Arc};
+use tokio::sync::Mutex;
+
+fn mock_post(removed_by_category: Option<&str>, author: &str) -> RedditSubmittedChildData {
+    RedditSubmittedChildData {
+        id: String::from("18f0d01"),
+        author: author.to_owned(),
+        removed_by_category: removed_by_category.map(String::from),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn it_reads_why_posts_were_removed() {
+    let cases = [
+        (None, "spez", None),
+        (None, "[deleted]", Some(PostRemoval::AuthorDeleted)),
+        (Some("deleted"), "[deleted]", Some(PostRemoval::Deleted)),
+        (Some("moderator"), "spez", Some(PostRemoval::Moderator)),
+        (Some("automod_filtered"), "spez", Some(PostRemoval::Automod)),
+        (Some("anti_evil_ops"), "spez", Some(PostRemoval::Reddit)),
+        (
+            Some("copyright_takedown"),
+            "spez",
+            Some(PostRemoval::Copyright),
+        ),
+        (Some("something_new"), "spez", Some(PostRemoval::Other)),
+    ];
+
+    for (category, author, removal) in cases {
+        assert_eq!(
+            PostRemoval::of(&mock_post(category, author)),
+            removal,
+            "{:?}",
+            category
+        );
+    }
+
+    // Posts of deleted accounts are still up
+    assert!(!PostRemoval::AuthorDeleted.is_removed());
+    assert!(PostRemoval::Moderator.is_removed());
+}
+
+#[test]
+fn it_records_the_removal_of_parsed_posts() -> Result<(), Box<dyn Error>> {
+    let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_image.json")?
+        .replace(
+            r#""removed_by_category": null"#,
+            r#""removed_by_category": "moderator""#,
+        );
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses.first().ok_or("Expected a response")?;
+
+    let posts = RedditPostParser::default().parse(res);
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].removal, Some(PostRemoval::Moderator));
+
+    let metadata = get_post_metadata(&res.data.children[0].data, &posts, "{POSTID}");
+    assert_eq!(metadata.removal, Some(PostRemoval::Moderator));
+    assert!(serde_json::to_string(&metadata)?.contains(r#""removal":"moderator""#));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_skips_the_media_of_removed_posts() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_removed_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let folder_path = folder.to_string_lossy().to_string();
+
+    // Never requested, the post is skipped first
+    let media = RedditCrawlerPost::new(
+        &mock_post(Some("deleted"), "[deleted]"),
+        RedditMediaProviderType::RedditImage,
+        "jpg",
+        "http://127.0.0.1:9/removed.jpg",
+    );
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    let client = ClientBuilder::new(reqwest::Client::new()).build();
+    let result =
+        download_crawler_post(&client, &shared_state, &folder_path, &media, "{POSTID}").await?;
+
+    assert!(matches!(
+        result,
+        DownloadPostResult::ReceivedSkipped(SkipReason::Removed)
+    ));
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::{
+        RedditSubmittedChildData, RedditSubmittedResponse,
+    },
+    providers::{
+        get_ytdlp_error, MediaProvider, ProviderContext, ProviderDownload, ProviderRegistry,
+        RedditProvider, PROVIDERS,
+    },
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType, RedditPostParser},
+};
+use std::{error::Error, fs};
+
+fn read_post(mock: &str) -> Result<RedditSubmittedChildData, Box<dyn Error>> {
+    let data = fs::read_to_string(format!("./tests/mocks/reddit/submitted_response/{}", mock))?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let child = responses
+        .into_iter()
+        .flat_map(|r| r.data.children)
+        .next()
+        .ok_or("Expected mockfile to contain a post")?;
+    Ok(child.data)
+}
+
+/// Stands in for a host added as its own module
+struct CatboxProvider;
+
+#[async_trait]
+impl MediaProvider for CatboxProvider {
+    fn provider_types(&self) -> &'static [RedditMediaProviderType] {
+        &[RedditMediaProviderType::None]
+    }
+
+    fn detect(&self, data: &RedditSubmittedChildData) -> bool {
+        data.url.contains("files.catbox.moe/")
+    }
+
+    fn resolve(
+        &self,
+        data: &RedditSubmittedChildData,
+        _parser: &RedditPostParser,
+        trace: &mut Vec<String>,
+    ) -> Vec<RedditCrawlerPost> {
+        trace.push(String::from("Matched Catbox file"));
+        vec![RedditCrawlerPost::new(
+            data,
+            RedditMediaProviderType::None,
+            "png",
+            &data.url,
+        )]
+    }
+
+    async fn download(
+        &self,
+        _ctx: &ProviderContext<'_>,
+        _media: &RedditCrawlerPost,
+    ) -> Result<ProviderDownload, anyhow::Error> {
+        Ok(ProviderDownload::NotFound)
+    }
+}
+
+#[test]
+fn it_detects_the_provider_of_posts() -> Result<(), Box<dyn Error>> {
+    let cases = [
+        ("reddit_image.json", RedditMediaProviderType::RedditImage),
+        (
+            "reddit_gallery.json",
+            RedditMediaProviderType::RedditGalleryImage,
+        ),
+        ("youtube_video.json", RedditMediaProviderType::YoutubeVideo),
+        ("redgifs_video.json", RedditMediaProviderType::RedgifsVideo),
+        ("gfycat_video.json", RedditMediaProviderType::RedgifsVideo),
+        (
+            "streamable_video.json",
+            RedditMediaProviderType::StreamableVideo,
+        ),
+        ("imgur_image.json", RedditMediaProviderType::ImgurImage),
+        ("imgur_gifv.json", RedditMediaProviderType::ImgurImage),
+    ];
+
+    for (mock, provider_type) in cases {
+        let data = read_post(mock)?;
+        let provider = PROVIDERS.detect(&data).ok_or("Expected a provider")?;
+        assert!(
+            provider.provider_types().contains(&provider_type),
+            "{}",
+            mock
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_rewrites_imgur_gifv_links_to_mp4() -> Result<(), Box<dyn Error>> {
+    let data = read_post("imgur_gifv.json")?;
+    let mut trace = Vec::new();
+    let posts = PROVIDERS.resolve(&data, &RedditPostParser::default(), &mut trace);
+
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].url, "https://i.imgur.com/mock_gif.mp4");
+    assert_eq!(posts[0].extension, "mp4");
+    assert_eq!(
+        trace,
+        vec![
+            "Matched Imgur embed",
+            "Rewrote gifv to https://i.imgur.com/mock_gif.mp4"
+        ]
+    );
+
+    // Other links keep their URL, the extension ignores the query
+    let data = RedditSubmittedChildData {
+        url: String::from("https://i.imgur.com/mock_img.JPG?1"),
+        ..read_post("imgur_image.json")?
+    };
+    let posts = PROVIDERS.resolve(&data, &RedditPostParser::default(), &mut Vec::new());
+    assert_eq!(posts[0].url, "https://i.imgur.com/mock_img.JPG?1");
+    assert_eq!(posts[0].extension, "jpg");
+
+    Ok(())
+}
+
+#[test]
+fn it_gets_the_provider_downloading_a_type() {
+    let provider = PROVIDERS.get(&RedditMediaProviderType::RedditVideo);
+    assert!(provider.is_some_and(|p| p
+        .provider_types()
+        .contains(&RedditMediaProviderType::RedditGalleryImage)));
+
+    assert!(PROVIDERS.get(&RedditMediaProviderType::None).is_none());
+}
+
+#[test]
+fn it_resolves_posts_with_registered_providers() {
+    let mut registry = ProviderRegistry::new();
+    registry.register(Box::new(RedditProvider));
+    registry.register(Box::new(CatboxProvider));
+
+    let data = RedditSubmittedChildData {
+        id: String::from("18f0b01"),
+        title: String::from("Catbox"),
+        url: String::from("https://files.catbox.moe/abc123.png"),
+        ..Default::default()
+    };
+
+    let mut trace = Vec::new();
+    let posts = registry.resolve(&data, &RedditPostParser::default(), &mut trace);
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].url, "https://files.catbox.moe/abc123.png");
+    assert_eq!(trace, vec!["Matched Catbox file"]);
+
+    // The built-in providers do not know the host
+    let mut trace = Vec::new();
+    let posts = PROVIDERS.resolve(&data, &RedditPostParser::default(), &mut trace);
+    assert!(posts.is_empty());
+    assert_eq!(
+        trace,
+        vec!["yt-dlp fallback is disabled", "No parser branch matched"]
+    );
+}
+
+#[test]
+fn it_falls_back_to_ytdlp_for_unsupported_links() -> Result<(), Box<dyn Error>> {
+    let data =
+        fs::read_to_string("./tests/mocks/reddit/submitted_response/unsupported_posts.json")?;
+    let responses: Vec<RedditSubmittedResponse> = serde_json::from_str(&data)?;
+    let res = responses
+        .first()
+        .ok_or("Expected mockfile to contain a RedditUserSubmittedResponse")?;
+
+    assert!(RedditPostParser::default().parse(res).is_empty());
+
+    let post_parser = RedditPostParser {
+        fallback_ytdlp: true,
+        ..Default::default()
+    };
+    let parsed_posts = post_parser.parse(res);
+
+    // The poll and the text post are still skipped
+    assert_eq!(
+        parsed_posts
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["18e0a02", "18e0a03", "18e0a05"]
+    );
+    for post in parsed_posts.iter() {
+        assert_eq!(post.provider, RedditMediaProviderType::YtdlpVideo);
+        assert_eq!(post.extension, "mp4");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_reads_ytdlp_errors() {
+    let stderr = "WARNING: [generic] Falling back on generic information extractor\n\
+        ERROR: Unsupported URL: https://www.example.com/cats-sleep\n";
+    assert_eq!(
+        get_ytdlp_error(Some(1), stderr),
+        "yt-dlp exited with code 1: Unsupported URL: https://www.example.com/cats-sleep"
+    );
+
+    // Without an error line the last line is kept
+    assert_eq!(
+        get_ytdlp_error(Some(2), "Usage: yt-dlp [OPTIONS] URL\n\n"),
+        "yt-dlp exited with code 2: Usage: yt-dlp [OPTIONS] URL"
+    );
+    assert_eq!(
+        get_ytdlp_error(None, ""),
+        "yt-dlp was terminated: no output"
+    );
+}
@@ -0,0 +1,52 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    parse_proxy_url, select_proxy, ProviderProxy, ProxyError, ProxyProvider,
+};
+use reqwest::Url;
+use std::error::Error;
+
+#[test]
+fn it_parses_provider_proxies() -> Result<(), Box<dyn Error>> {
+    let proxy: ProviderProxy = "redgifs=socks5h://127.0.0.1:9050".parse()?;
+    assert_eq!(proxy.provider, ProxyProvider::Redgifs);
+    assert_eq!(proxy.url, Url::parse("socks5h://127.0.0.1:9050")?);
+
+    assert_eq!(
+        "redgifs".parse::<ProviderProxy>(),
+        Err(ProxyError::Format(String::from("redgifs")))
+    );
+    assert_eq!(
+        "vimeo=http://127.0.0.1:8080".parse::<ProviderProxy>(),
+        Err(ProxyError::Provider(String::from("vimeo")))
+    );
+    assert_eq!(
+        parse_proxy_url("ftp://127.0.0.1"),
+        Err(ProxyError::Url(String::from("ftp://127.0.0.1")))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_selects_provider_proxies() -> Result<(), Box<dyn Error>> {
+    let default = parse_proxy_url("http://127.0.0.1:8080")?;
+    let provider_proxies = vec!["redgifs=socks5://127.0.0.1:9050".parse::<ProviderProxy>()?];
+
+    let redgifs = Url::parse("https://api.redgifs.com/v2/gifs/mock")?;
+    let reddit = Url::parse("https://i.redd.it/mock.jpg")?;
+    assert_eq!(
+        select_proxy(&redgifs, Some(&default), &provider_proxies),
+        Some(&provider_proxies[0].url)
+    );
+    assert_eq!(
+        select_proxy(&reddit, Some(&default), &provider_proxies),
+        Some(&default)
+    );
+    assert_eq!(select_proxy(&reddit, None, &provider_proxies), None);
+
+    // Only subdomains match, not hosts that merely end with the same name
+    let lookalike = Url::parse("https://notredgifs.com/mock")?;
+    assert_eq!(select_proxy(&lookalike, None, &provider_proxies), None);
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{get_rate_limit_wait, RateLimiter};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::{Duration, Instant};
+
+#[test]
+fn it_throttles_by_rate_limit_headers() {
+    let now = Instant::now();
+    let mut limiter = RateLimiter::default();
+    // Nothing is known before the first response
+    assert_eq!(limiter.acquire(now), Duration::ZERO);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-ratelimit-remaining", HeaderValue::from_static("2.0"));
+    headers.insert("x-ratelimit-reset", HeaderValue::from_static("30"));
+    limiter.update(&headers, now);
+
+    assert_eq!(limiter.acquire(now), Duration::ZERO);
+    assert_eq!(limiter.acquire(now), Duration::ZERO);
+    assert_eq!(limiter.acquire(now), Duration::from_secs(30));
+    assert_eq!(
+        limiter.acquire(now + Duration::from_secs(10)),
+        Duration::from_secs(20)
+    );
+    assert_eq!(
+        limiter.acquire(now + Duration::from_secs(30)),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn it_reads_rate_limit_waits() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(get_rate_limit_wait(&headers), None);
+
+    headers.insert("retry-after", HeaderValue::from_static("12"));
+    assert_eq!(get_rate_limit_wait(&headers), Some(Duration::from_secs(12)));
+
+    headers.insert("x-ratelimit-reset", HeaderValue::from_static("42"));
+    assert_eq!(get_rate_limit_wait(&headers), Some(Duration::from_secs(42)));
+}
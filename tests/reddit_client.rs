@@ -0,0 +1,489 @@
+use async_trait::async_trait;
+use http::Extensions;
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    cli::{
+        CliRedditCommand, CliUserCommentsCommand, RedditAccountListing, RedditCategoryFilter,
+        RedditTimeframeFilter,
+    },
+    clients::{
+        api_types::reddit::{
+            access_token::RedditAccessTokenResponse, submitted_response::RedditSubmittedResponse,
+        },
+        extract_post_id, is_combined_subreddit, normalize_subreddit, DeepWindow,
+        RedditAppCredentials, RedditClient, RedditCredentials, RedditProviderError,
+    },
+    utils::state::SharedState,
+};
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, Middleware, Next};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, Mutex},
+    time::{sleep, timeout},
+};
+
+// Same as the crawl engine, pages fetched ahead of the downloader
+const PAGE_BUFFER: usize = 4;
+
+/// Serves a listing of `pages` copies of a mock post, every page links the next with `after`
+struct MockListing {
+    pages: usize,
+    template: Value,
+}
+
+impl MockListing {
+    fn new(pages: usize) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read_to_string("./tests/mocks/reddit/submitted_response/reddit_image.json")?;
+        let template = serde_json::from_str::<Value>(&data)?[0].take();
+        Ok(Self { pages, template })
+    }
+}
+
+#[async_trait]
+impl Middleware for MockListing {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let page = req
+            .url()
+            .query_pairs()
+            .find(|(key, _)| key == "after")
+            .and_then(|(_, after)| after.strip_prefix("t3_page")?.parse::<usize>().ok())
+            .map_or(0, |previous| previous + 1);
+
+        let mut response = self.template.clone();
+        response["data"]["after"] = match page + 1 < self.pages {
+            true => Value::from(format!("t3_page{}", page)),
+            false => Value::Null,
+        };
+        response["data"]["children"][0]["data"]["id"] = Value::from(format!("page{}", page));
+
+        Ok(Response::from(http::Response::new(response.to_string())))
+    }
+}
+
+/// Rejects the first `rejected` requests with a 401, like Reddit does for a revoked token
+struct RevokedToken {
+    rejected: AtomicUsize,
+}
+
+#[async_trait]
+impl Middleware for RevokedToken {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if self
+            .rejected
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |r| r.checked_sub(1))
+            .is_err()
+        {
+            return next.run(req, extensions).await;
+        }
+
+        let mut response = http::Response::new(String::from("{}"));
+        *response.status_mut() = http::StatusCode::UNAUTHORIZED;
+        Ok(Response::from(response))
+    }
+}
+
+/// Serves the first page of the mock user comments
+struct MockUserComments {
+    page: String,
+}
+
+impl MockUserComments {
+    fn new() -> Result<Self, Box<dyn Error>> {
+        let data =
+            fs::read_to_string("./tests/mocks/reddit/user_comments_response/user_comments.json")?;
+        let responses: Vec<Value> = serde_json::from_str(&data)?;
+
+        Ok(Self {
+            page: responses[0].to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Middleware for MockUserComments {
+    async fn handle(
+        &self,
+        _req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        Ok(Response::from(http::Response::new(self.page.clone())))
+    }
+}
+
+/// Issues app tokens and records whether the shared state was free while one was requested
+struct TokenEndpoint {
+    shared_state: Arc<Mutex<SharedState>>,
+    state_locked: AtomicBool,
+}
+
+#[async_trait]
+impl Middleware for TokenEndpoint {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if !req.url().path().ends_with("/access_token") {
+            return next.run(req, extensions).await;
+        }
+
+        if self.shared_state.try_lock().is_err() {
+            self.state_locked.store(true, Ordering::Relaxed);
+        }
+        let token = RedditAccessTokenResponse {
+            access_token: String::from("MOCK_TOKEN"),
+            token_type: String::from("bearer"),
+            expires_in: 86400,
+            scope: String::from("*"),
+        };
+        Ok(Response::from(http::Response::new(
+            serde_json::to_string(&token).unwrap(),
+        )))
+    }
+}
+
+#[test]
+fn it_extracts_post_ids() {
+    assert_eq!(
+        extract_post_id("https://www.reddit.com/r/aww/comments/18dxopo/i_took_my_2_year_old/"),
+        Some("18dxopo")
+    );
+    assert_eq!(
+        extract_post_id("https://old.reddit.com/comments/18dxopo?context=3"),
+        Some("18dxopo")
+    );
+    assert_eq!(extract_post_id("https://redd.it/18dxopo"), Some("18dxopo"));
+    assert_eq!(extract_post_id("t3_18dxopo"), Some("18dxopo"));
+    assert_eq!(extract_post_id("18dxopo"), Some("18dxopo"));
+    assert_eq!(extract_post_id("https://www.reddit.com/r/aww/"), None);
+}
+
+#[test]
+fn it_hides_credentials_in_debug_output() {
+    let credentials = RedditCredentials {
+        client_id: String::from("MOCK_CLIENT_ID"),
+        client_secret: String::from("MOCK_CLIENT_SECRET"),
+        username: String::from("MOCK_USERNAME"),
+        password: String::from("MOCK_PASSWORD"),
+    };
+
+    let debug = format!("{:?}", credentials);
+    assert!(debug.contains("MOCK_CLIENT_ID"));
+    assert!(!debug.contains("MOCK_CLIENT_SECRET"));
+    assert!(!debug.contains("MOCK_PASSWORD"));
+}
+
+#[test]
+fn it_narrows_truncated_deep_windows() {
+    let day = 24 * 60 * 60;
+    let window = DeepWindow::new(100 * day, 0);
+    assert_eq!(
+        window,
+        DeepWindow {
+            start: 70 * day,
+            end: 100 * day
+        }
+    );
+
+    // A full window continues below its oldest post with half the span
+    assert_eq!(
+        window.next(0, 250, Some(90 * day)),
+        Some(DeepWindow {
+            start: 75 * day,
+            end: 90 * day
+        })
+    );
+    // A sparse window moves on with twice the span, down to the floor
+    assert_eq!(
+        window.next(50 * day, 10, Some(80 * day)),
+        Some(DeepWindow {
+            start: 50 * day,
+            end: 70 * day
+        })
+    );
+    assert_eq!(window.next(70 * day, 100, Some(80 * day)), None);
+}
+
+#[test]
+fn it_normalizes_subreddits() {
+    assert_eq!(normalize_subreddit("pics"), "pics");
+    assert_eq!(normalize_subreddit("/r/pics/"), "pics");
+    assert_eq!(normalize_subreddit("All"), "all");
+    assert_eq!(normalize_subreddit("r/popular"), "popular");
+    // Combinations crawl into the same folder regardless of their order
+    assert_eq!(
+        normalize_subreddit("pics+aww+EarthPorn"),
+        "aww+EarthPorn+pics"
+    );
+    assert_eq!(normalize_subreddit("aww + pics+AWW+"), "aww+pics");
+
+    assert!(is_combined_subreddit("all"));
+    assert!(is_combined_subreddit("popular"));
+    assert!(is_combined_subreddit("all-pics-aww"));
+    assert!(is_combined_subreddit("aww+pics"));
+    assert!(!is_combined_subreddit("allthingsdogs"));
+    assert!(!is_combined_subreddit("pics"));
+}
+
+/// Listings paging through `MockListing`
+#[derive(Debug, Clone, Copy)]
+enum MockListingKind {
+    User,
+    Subreddit,
+    Search,
+    Domain,
+    Saved,
+}
+
+/// Pages through a mock listing while falling behind the fetcher, returns the IDs received
+async fn stream_mock_listing(
+    kind: MockListingKind,
+    pages: usize,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(MockListing::new(pages)?)
+        .build();
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    let cmd = CliRedditCommand {
+        resource: String::from("spez"),
+        category: RedditCategoryFilter::New,
+        timeframe: RedditTimeframeFilter::All,
+        deep: false,
+        crawl_authors: false,
+        max_authors: None,
+        options: Default::default(),
+    };
+
+    let (page_tx, mut page_rx) = mpsc::channel::<RedditSubmittedResponse>(PAGE_BUFFER);
+    let fetch = async {
+        let page_tx = page_tx;
+        let reddit_client = RedditClient::default();
+        match kind {
+            MockListingKind::User => {
+                reddit_client
+                    .get_user_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+                    .await
+            }
+            MockListingKind::Subreddit => {
+                reddit_client
+                    .get_subreddit_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+                    .await
+            }
+            MockListingKind::Search => {
+                reddit_client
+                    .get_search_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+                    .await
+            }
+            MockListingKind::Domain => {
+                reddit_client
+                    .get_domain_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+                    .await
+            }
+            MockListingKind::Saved => {
+                reddit_client
+                    .get_account_submissions(
+                        &client,
+                        &shared_state,
+                        &cmd,
+                        &RedditAccountListing::Saved,
+                        &cmd.options,
+                        &page_tx,
+                    )
+                    .await
+            }
+        }
+    };
+    let download = async {
+        let mut ids = Vec::new();
+        loop {
+            // Falls behind the fetcher and needs the state for every page, like the downloader
+            sleep(Duration::from_millis(10)).await;
+            drop(shared_state.lock().await);
+
+            let Some(page) = page_rx.recv().await else {
+                break;
+            };
+            ids.extend(page.data.children.into_iter().map(|c| c.data.id));
+        }
+        ids
+    };
+
+    let (fetched, ids) = timeout(Duration::from_secs(10), async {
+        tokio::join!(fetch, download)
+    })
+    .await
+    .map_err(|_| format!("the {:?} fetcher deadlocked with the downloader", kind))?;
+    fetched?;
+
+    Ok(ids)
+}
+
+#[tokio::test]
+async fn it_streams_more_pages_than_the_buffer_holds() -> Result<(), Box<dyn Error>> {
+    let pages = PAGE_BUFFER * 3;
+
+    for kind in [
+        MockListingKind::User,
+        MockListingKind::Subreddit,
+        MockListingKind::Search,
+        MockListingKind::Domain,
+        MockListingKind::Saved,
+    ] {
+        assert_eq!(
+            stream_mock_listing(kind, pages).await?,
+            (0..pages).map(|p| format!("page{}", p)).collect::<Vec<_>>()
+        );
+    }
+
+    Ok(())
+}
+
+async fn fetch_user_with_revoked_token(
+    rejected: usize,
+) -> Result<Vec<String>, RedditProviderError> {
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(RevokedToken {
+            rejected: AtomicUsize::new(rejected),
+        })
+        .with(MockListing::new(2).expect("Failed to read the mock listing"))
+        .build();
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    let cmd = CliRedditCommand {
+        resource: String::from("spez"),
+        category: RedditCategoryFilter::New,
+        timeframe: RedditTimeframeFilter::All,
+        deep: false,
+        crawl_authors: false,
+        max_authors: None,
+        options: Default::default(),
+    };
+
+    let (page_tx, mut page_rx) = mpsc::channel::<RedditSubmittedResponse>(PAGE_BUFFER);
+    RedditClient::default()
+        .get_user_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+        .await?;
+    drop(page_tx);
+
+    let mut ids = Vec::new();
+    while let Some(page) = page_rx.recv().await {
+        ids.extend(page.data.children.into_iter().map(|c| c.data.id));
+    }
+    Ok(ids)
+}
+
+#[tokio::test]
+async fn it_requests_a_page_again_after_a_revoked_token() {
+    assert_eq!(
+        fetch_user_with_revoked_token(1).await.ok(),
+        Some(vec![String::from("page0"), String::from("page1")])
+    );
+    assert!(matches!(
+        fetch_user_with_revoked_token(2).await,
+        Err(RedditProviderError::Unauthorized)
+    ));
+}
+
+async fn fetch_user_comments_with_revoked_token(
+    rejected: usize,
+) -> Result<Vec<String>, RedditProviderError> {
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(RevokedToken {
+            rejected: AtomicUsize::new(rejected),
+        })
+        .with(MockUserComments::new().expect("Failed to read the mock comments"))
+        .build();
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    let cmd = CliUserCommentsCommand {
+        resource: String::from("spez"),
+        category: RedditCategoryFilter::New,
+        timeframe: RedditTimeframeFilter::All,
+        limit: None,
+        output: String::new(),
+        markdown: false,
+        mock: None,
+    };
+
+    let comments = RedditClient::default()
+        .get_user_comments(&client, &shared_state, &cmd, &HashSet::new())
+        .await?;
+    Ok(comments.into_iter().map(|c| c.id).collect())
+}
+
+#[tokio::test]
+async fn it_requests_user_comments_again_after_a_revoked_token() {
+    assert_eq!(
+        fetch_user_comments_with_revoked_token(1).await.ok(),
+        Some(vec![String::from("kc8x2mf"), String::from("kc1a9zq")])
+    );
+    assert!(matches!(
+        fetch_user_comments_with_revoked_token(2).await,
+        Err(RedditProviderError::Unauthorized)
+    ));
+}
+
+#[tokio::test]
+async fn it_releases_the_state_while_authenticating() -> Result<(), Box<dyn Error>> {
+    let shared_state = Arc::new(Mutex::new(SharedState::default()));
+    shared_state.lock().await.reddit_app = Some(RedditAppCredentials {
+        client_id: String::from("MOCK_CLIENT_ID"),
+        client_secret: String::from("MOCK_CLIENT_SECRET"),
+    });
+    let token_endpoint = Arc::new(TokenEndpoint {
+        shared_state: Arc::clone(&shared_state),
+        state_locked: AtomicBool::new(false),
+    });
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with_arc(token_endpoint.clone())
+        .with(MockListing::new(1)?)
+        .build();
+    let cmd = CliRedditCommand {
+        resource: String::from("spez"),
+        category: RedditCategoryFilter::New,
+        timeframe: RedditTimeframeFilter::All,
+        deep: false,
+        crawl_authors: false,
+        max_authors: None,
+        options: Default::default(),
+    };
+
+    let (page_tx, mut page_rx) = mpsc::channel::<RedditSubmittedResponse>(PAGE_BUFFER);
+    RedditClient::default()
+        .get_user_submissions(&client, &shared_state, &cmd, &cmd.options, &page_tx)
+        .await?;
+
+    assert!(page_rx.recv().await.is_some());
+    assert!(!token_endpoint.state_locked.load(Ordering::Relaxed));
+    assert_eq!(
+        shared_state
+            .lock()
+            .await
+            .reddit_token
+            .as_ref()
+            .map(|t| t.access_token.clone()),
+        Some(String::from("MOCK_TOKEN"))
+    );
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use bytes::Bytes;
+use object_store::{memory::InMemory, path::Path as ObjectPath, ObjectStore};
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{flush_staging_dir, RemoteStorage, RemoteStorageError, RemoteUploads};
+use std::{env, error::Error, fs, sync::Arc};
+
+#[test]
+fn it_parses_remote_outputs() -> Result<(), Box<dyn Error>> {
+    assert!(RemoteStorage::parse("./output")?.is_none());
+    assert!(RemoteStorage::parse("C:\\Users\\spez\\output")?.is_none());
+    assert!(matches!(
+        RemoteStorage::parse("sftp://host/output"),
+        Err(RemoteStorageError::Unsupported(scheme)) if scheme == "sftp"
+    ));
+
+    let remote = RemoteStorage::parse("s3://media/reddit/")?.ok_or("Expected a remote")?;
+    let staging_dir = remote.staging_dir();
+    assert_eq!(
+        remote
+            .get_object_path(&staging_dir, &staging_dir.join("user/spez/cache.json"))
+            .map(|p| p.to_string()),
+        Some(String::from("reddit/user/spez/cache.json"))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_uploads_staged_files() -> Result<(), Box<dyn Error>> {
+    let store = Arc::new(InMemory::new());
+    let remote = RemoteStorage::from_store("memory://reddit", store.clone(), "reddit");
+    let staging_dir = env::temp_dir().join(format!("reddit_clawler_remote_{}", std::process::id()));
+    let folder = staging_dir.join("user/spez");
+    fs::create_dir_all(&folder)?;
+
+    fs::write(folder.join("1_spez_18f1a01_2023-12-09.jpg"), "image")?;
+    fs::write(folder.join("cache.json"), "{}")?;
+    fs::write(folder.join("1_spez_18f1a02_2023-12-09.mp4.part"), "vid")?;
+
+    let (uploads, handle) = RemoteUploads::spawn(remote.clone(), staging_dir.clone());
+    uploads.queue(folder.join("1_spez_18f1a01_2023-12-09.jpg"), false);
+    drop(uploads);
+    handle.await?;
+    // Downloads are removed once uploaded
+    assert!(!folder.join("1_spez_18f1a01_2023-12-09.jpg").exists());
+
+    assert_eq!(flush_staging_dir(&remote, &staging_dir).await?, 1);
+    assert!(folder.join("cache.json").exists());
+
+    let uploaded = store
+        .get(&ObjectPath::from(
+            "reddit/user/spez/1_spez_18f1a01_2023-12-09.jpg",
+        ))
+        .await?
+        .bytes()
+        .await?;
+    assert_eq!(uploaded.as_ref(), b"image");
+
+    // A new staging folder gets the caches of earlier runs
+    fs::remove_dir_all(&staging_dir)?;
+    assert_eq!(remote.pull_caches(&staging_dir).await?, 1);
+    assert_eq!(fs::read_to_string(folder.join("cache.json"))?, "{}");
+
+    // A staged cache left by an earlier run is replaced by the remote one
+    fs::write(folder.join("cache.json"), "stale")?;
+    assert_eq!(remote.pull_caches(&staging_dir).await?, 1);
+    assert_eq!(fs::read_to_string(folder.join("cache.json"))?, "{}");
+
+    fs::remove_dir_all(&staging_dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_streams_files_without_staging_them() -> Result<(), Box<dyn Error>> {
+    let store = Arc::new(InMemory::new());
+    let remote = RemoteStorage::from_store("memory://reddit", store.clone(), "reddit");
+    let staging_dir = env::temp_dir().join(format!("reddit_clawler_stream_{}", std::process::id()));
+    let file_path = staging_dir.join("user/spez/1_spez_18f1a01_2023-12-09.mp4");
+
+    let (uploads, handle) = RemoteUploads::spawn(remote, staging_dir.clone());
+    let mut writer = uploads.start_upload(&file_path.to_string_lossy())?;
+    writer.write(Bytes::from("vid")).await?;
+    writer.write(Bytes::from("eo")).await?;
+    writer.finish().await?;
+    drop(uploads);
+    handle.await?;
+
+    assert!(!staging_dir.exists());
+    let uploaded = store
+        .get(&ObjectPath::from(
+            "reddit/user/spez/1_spez_18f1a01_2023-12-09.mp4",
+        ))
+        .await?
+        .bytes()
+        .await?;
+    assert_eq!(uploaded.as_ref(), b"video");
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    escape_csv_field, get_report_rows, state::FileCacheItemLatest, state::FileCacheLatest,
+    write_csv_report,
+};
+use std::{env, error::Error, fs};
+
+#[test]
+fn it_escapes_csv_fields() {
+    assert_eq!(escape_csv_field("Pixel"), "Pixel");
+    assert_eq!(escape_csv_field("Cats, dogs"), "\"Cats, dogs\"");
+    assert_eq!(
+        escape_csv_field("A \"red\" panda"),
+        "\"A \"\"red\"\" panda\""
+    );
+    assert_eq!(escape_csv_field("Two\nlines"), "\"Two\nlines\"");
+}
+
+#[test]
+fn it_writes_csv_reports() -> Result<(), Box<dyn Error>> {
+    let root = env::temp_dir().join(format!("reddit_clawler_report_{}", std::process::id()));
+    let folder = root.join("user").join("spez");
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join("18dxopo.jpg"), "image")?;
+
+    let created_utc = Utc.with_ymd_and_hms(2023, 12, 8, 21, 51, 13).unwrap();
+    let cache = FileCacheLatest {
+        files: vec![
+            FileCacheItemLatest {
+                id: String::from("18dxopo"),
+                created_utc,
+                title: String::from("Pixel, the kitty"),
+                subreddit: String::from("aww"),
+                url: String::from("https://i.redd.it/22plaimq655c1.jpg"),
+                success: true,
+                author: Some(String::from("spez")),
+                upvotes: Some(1538),
+                file_name: Some(String::from("18dxopo.jpg")),
+                ..FileCacheItemLatest::default()
+            },
+            FileCacheItemLatest {
+                id: String::from("18dxopp"),
+                created_utc,
+                title: String::from("Gone"),
+                subreddit: String::from("aww"),
+                url: String::from("https://i.imgur.com/gone.jpg"),
+                ..FileCacheItemLatest::default()
+            },
+        ],
+        ..FileCacheLatest::default()
+    };
+
+    let rows = get_report_rows(&root, &folder, &cache);
+    let mut report = Vec::new();
+    write_csv_report(&mut report, &rows)?;
+
+    assert_eq!(
+        String::from_utf8(report)?,
+        "resource,id,index,created_utc,title,author,subreddit,upvotes,url,status,file_path,bytes,spoiler\n\
+         user/spez,18dxopo,,2023-12-08T21:51:13+00:00,\"Pixel, the kitty\",spez,aww,1538,https://i.redd.it/22plaimq655c1.jpg,downloaded,user/spez/18dxopo.jpg,5,false\n\
+         user/spez,18dxopp,,2023-12-08T21:51:13+00:00,Gone,,aww,,https://i.imgur.com/gone.jpg,failed,,,false\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{get_rate_limit_wait, RetryPolicy};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest_middleware::ClientBuilder;
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+fn mock_policy() -> RetryPolicy {
+    RetryPolicy {
+        retries: 3,
+        base_delay: Duration::from_secs(2),
+        max_delay: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn it_doubles_the_delay_up_to_the_max_delay() {
+    let policy = mock_policy();
+
+    assert_eq!(policy.get_backoff(0), Duration::from_secs(2));
+    assert_eq!(policy.get_backoff(2), Duration::from_secs(8));
+    assert_eq!(policy.get_backoff(10), Duration::from_secs(60));
+}
+
+#[test]
+fn it_respects_retry_after() {
+    let policy = mock_policy();
+    let mut headers = HeaderMap::new();
+
+    // Without a header the backoff is used
+    assert_eq!(
+        policy.get_rate_limit_delay(&headers, 1),
+        Some(Duration::from_secs(4))
+    );
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+    assert_eq!(
+        policy.get_rate_limit_delay(&headers, 0),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(policy.get_rate_limit_delay(&headers, 3), None);
+
+    // Resets further away than the max delay are not waited for
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+    assert_eq!(policy.get_rate_limit_delay(&headers, 0), None);
+
+    headers.insert(
+        RETRY_AFTER,
+        HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    assert_eq!(get_rate_limit_wait(&headers), Some(Duration::ZERO));
+}
+
+#[tokio::test]
+async fn it_retries_rate_limited_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/submitted.json", listener.local_addr()?);
+    let requests = Arc::new(AtomicU32::new(0));
+
+    // Rate limits the first two requests
+    let server_requests = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buffer = [0; 1024];
+            let _ = socket.read(&mut buffer).await;
+            let response: &[u8] = match server_requests.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                _ => b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+            };
+            let _ = socket.write_all(response).await;
+        }
+    });
+
+    let policy = mock_policy();
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(policy.transient_middleware())
+        .with(policy.rate_limit_middleware())
+        .build();
+    let res = client.get(&url).send().await?;
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // Giving up returns the last 429 response
+    let no_retries = RetryPolicy {
+        retries: 0,
+        ..policy
+    };
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(no_retries.transient_middleware())
+        .with(no_retries.rate_limit_middleware())
+        .build();
+    requests.store(0, Ordering::SeqCst);
+    let res = client.get(&url).send().await?;
+    assert_eq!(res.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
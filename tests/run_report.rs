@@ -0,0 +1,87 @@
+use anyhow::anyhow;
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    reddit_parser::{RedditCrawlerPost, RedditMediaProviderType},
+    utils::{
+        render_run_report, state::FileChecksum, DownloadPostResult, RunFailure, RunReport,
+        SkipReason,
+    },
+};
+
+fn mock_post(id: &str, provider: RedditMediaProviderType) -> RedditCrawlerPost {
+    RedditCrawlerPost {
+        id: id.to_owned(),
+        provider,
+        url: format!("https://i.redd.it/{}.jpg", id),
+        ..RedditCrawlerPost::default()
+    }
+}
+
+#[test]
+fn it_counts_the_downloads_of_a_run() {
+    let mut report = RunReport::new("user/spez");
+    let downloaded = Ok(DownloadPostResult::ReceivedBytes(
+        2048.0,
+        None,
+        String::from("file.jpg"),
+        FileChecksum::default(),
+    ));
+
+    report.record(
+        &mock_post("a1", RedditMediaProviderType::RedditImage),
+        &downloaded,
+    );
+    report.record(
+        &mock_post("a2", RedditMediaProviderType::RedditImage),
+        &Ok(DownloadPostResult::ReceivedSkipped(
+            SkipReason::FileTooLarge,
+        )),
+    );
+    report.record(
+        &mock_post("a3", RedditMediaProviderType::ImgurImage),
+        &Ok(DownloadPostResult::ReceivedNotFound),
+    );
+    report.record(
+        &mock_post("a4", RedditMediaProviderType::ImgurImage),
+        &Err(anyhow!("connection reset")),
+    );
+    report.finish(3);
+
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(report.bytes, 2048);
+    assert_eq!(report.unsupported, 3);
+    assert_eq!(report.skipped.get(&SkipReason::FileTooLarge), Some(&1));
+    assert_eq!(report.providers["reddit_image"].downloaded, 1);
+    assert_eq!(report.providers["reddit_image"].skipped, 1);
+    assert_eq!(report.providers["imgur_image"].failed, 2);
+    assert_eq!(
+        report.failures,
+        vec![
+            RunFailure {
+                id: String::from("a3"),
+                url: String::from("https://i.redd.it/a3.jpg"),
+                reason: String::from("not found"),
+            },
+            RunFailure {
+                id: String::from("a4"),
+                url: String::from("https://i.redd.it/a4.jpg"),
+                reason: String::from("connection reset"),
+            },
+        ]
+    );
+
+    let rendered = render_run_report(&report);
+    let lines = rendered.lines().skip(1).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "  imgur_image   0 downloaded, 2 failed, 0 skipped",
+            "  reddit_image  1 downloaded, 0 failed, 1 skipped",
+            "Skipped 1 - larger than --max-file-size",
+            "Unsupported 3 - listed in unsupported.json",
+            "Failed 2",
+            "  a3 https://i.redd.it/a3.jpg - not found",
+            "  a4 https://i.redd.it/a4.jpg - connection reset",
+        ]
+    );
+}
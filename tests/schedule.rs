@@ -0,0 +1,58 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{parse_schedule_toml, BatchTargetKind, ScheduleError};
+use std::{error::Error, time::Duration};
+
+#[test]
+fn it_parses_schedules() -> Result<(), Box<dyn Error>> {
+    let schedule = parse_schedule_toml(
+        r#"
+        pause = "30s"
+
+        [[targets]]
+        target = "user:spez"
+        schedule = "0 */6 * * *"
+
+        [[targets]]
+        target = "subreddit:aww"
+        schedule = "30 8 * * 1"
+        "#,
+    )?;
+
+    assert_eq!(schedule.pause, Duration::from_secs(30));
+    assert_eq!(schedule.targets.len(), 2);
+    assert_eq!(schedule.targets[0].target.kind, BatchTargetKind::User);
+
+    let now = Utc.with_ymd_and_hms(2024, 1, 1, 7, 15, 0).unwrap();
+    assert_eq!(
+        schedule.targets[0].next_run(&now),
+        Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap())
+    );
+    // 2024-01-01 is a monday
+    assert_eq!(
+        schedule.targets[1].next_run(&now),
+        Some(Utc.with_ymd_and_hms(2024, 1, 1, 8, 30, 0).unwrap())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_schedules() {
+    let invalid_cron = parse_schedule_toml(
+        r#"
+        [[targets]]
+        target = "user:spez"
+        schedule = "every day"
+        "#,
+    );
+    assert!(matches!(invalid_cron, Err(ScheduleError::Cron(..))));
+
+    let missing_schedule = parse_schedule_toml(
+        r#"
+        [[targets]]
+        target = "user:spez"
+        "#,
+    );
+    assert!(matches!(missing_schedule, Err(ScheduleError::Toml(_))));
+}
@@ -0,0 +1,20 @@
+use reddit_clawler::utils::Shutdown;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn it_shares_a_requested_shutdown() {
+    let shutdown = Shutdown::default();
+    let clone = shutdown.clone();
+    assert!(!clone.is_requested());
+
+    let waiting = tokio::spawn(async move { clone.wait().await });
+    shutdown.request();
+
+    assert!(shutdown.is_requested());
+    assert!(timeout(Duration::from_secs(1), waiting).await.is_ok());
+    // Resolves right away once the shutdown has been requested
+    assert!(timeout(Duration::from_secs(1), shutdown.wait())
+        .await
+        .is_ok());
+}
@@ -0,0 +1,57 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    parse_size,
+    state::{FileCacheItemLatest, FileCacheLatest},
+    SizeLimits, SkipReason,
+};
+use std::{error::Error, str::FromStr};
+
+#[test]
+fn it_parses_sizes() {
+    assert_eq!(parse_size("1024"), Ok(1024));
+    assert_eq!(parse_size("500M"), Ok(500 * 1024 * 1024));
+    assert_eq!(parse_size("50G"), Ok(50 * 1024 * 1024 * 1024));
+    assert_eq!(parse_size("1.5gb"), Ok(1536 * 1024 * 1024));
+    assert_eq!(parse_size("2 KB"), Ok(2048));
+
+    assert!(parse_size("0M").is_err());
+    assert!(parse_size("10X").is_err());
+    assert!(parse_size("M").is_err());
+}
+
+#[test]
+fn it_checks_size_limits() {
+    let limits = SizeLimits {
+        max_file_size: Some(100),
+        max_total_size: Some(1000),
+    };
+
+    assert!(!limits.exceeds_file_size(100));
+    assert!(limits.exceeds_file_size(101));
+    assert!(!limits.quota_reached(999));
+    assert!(limits.quota_reached(1000));
+
+    let unlimited = SizeLimits::default();
+    assert!(!unlimited.exceeds_file_size(u64::MAX));
+    assert!(!unlimited.quota_reached(u64::MAX));
+}
+
+#[test]
+fn it_records_skipped_files_in_the_cache() -> Result<(), Box<dyn Error>> {
+    let cache = FileCacheLatest {
+        files: vec![FileCacheItemLatest {
+            id: String::from("abc123"),
+            skipped: Some(SkipReason::QuotaReached),
+            error: None,
+            checksum: None,
+            ..FileCacheItemLatest::default()
+        }],
+        ..FileCacheLatest::default()
+    };
+
+    let json = serde_json::to_string(&cache)?;
+    assert!(json.contains(r#""skipped":"quotaReached""#));
+    assert_eq!(FileCacheLatest::from_str(&json)?, cache);
+
+    Ok(())
+}
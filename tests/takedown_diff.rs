@@ -0,0 +1,99 @@
+use chrono::{TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedChildData,
+    utils::{
+        diff_takedowns, get_archived_ids, render_takedowns,
+        state::{FileCacheItemLatest, FileCacheLatest},
+        PostRemoval,
+    },
+};
+
+fn mock_item(id: &str, index: Option<usize>, success: bool) -> FileCacheItemLatest {
+    FileCacheItemLatest {
+        id: id.to_owned(),
+        created_utc: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+        title: format!("Post {}", id),
+        subreddit: String::from("aww"),
+        success,
+        index,
+        author: Some(String::from("spez")),
+        file_name: success.then(|| format!("{}_{}.jpg", id, index.unwrap_or_default())),
+        ..FileCacheItemLatest::default()
+    }
+}
+
+fn mock_post(id: &str, removed_by_category: Option<&str>) -> RedditSubmittedChildData {
+    RedditSubmittedChildData {
+        id: id.to_owned(),
+        author: String::from("spez"),
+        removed_by_category: removed_by_category.map(String::from),
+        ..Default::default()
+    }
+}
+
+fn mock_cache() -> FileCacheLatest {
+    FileCacheLatest {
+        files: vec![
+            mock_item("kept", None, true),
+            mock_item("removed", Some(0), true),
+            mock_item("removed", Some(1), true),
+            mock_item("deleted", None, true),
+            mock_item("failed", None, false),
+            // Redgifs profiles are not on Reddit
+            FileCacheItemLatest {
+                subreddit: String::new(),
+                ..mock_item("redgifs", None, true)
+            },
+        ],
+        ..FileCacheLatest::default()
+    }
+}
+
+#[test]
+fn it_lists_the_archived_posts() {
+    assert_eq!(
+        get_archived_ids(&mock_cache()),
+        vec!["deleted", "kept", "removed"]
+    );
+}
+
+#[test]
+fn it_reports_takedowns_once() {
+    let mut cache = mock_cache();
+    // `deleted` is not returned anymore
+    let posts = vec![
+        mock_post("kept", None),
+        mock_post("removed", Some("moderator")),
+    ];
+
+    let takedowns = diff_takedowns("subreddit/aww", &mut cache, &posts);
+    assert_eq!(
+        takedowns
+            .iter()
+            .map(|t| (t.id.as_str(), t.removal, t.files.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "deleted",
+                PostRemoval::Unavailable,
+                vec![String::from("deleted_0.jpg")]
+            ),
+            (
+                "removed",
+                PostRemoval::Moderator,
+                vec![String::from("removed_0.jpg"), String::from("removed_1.jpg")]
+            ),
+        ]
+    );
+    assert_eq!(cache.files[1].removal, Some(PostRemoval::Moderator));
+    assert_eq!(cache.files[0].removal, None);
+
+    let rendered = render_takedowns(&takedowns);
+    assert!(rendered.starts_with("2 archived posts were taken down since the last check\n"));
+    assert!(rendered
+        .contains("subreddit/aww  removed  2024-03-01  removed by moderators - Post removed"));
+
+    // Recorded in the cache, the next diff does not report them again
+    assert!(diff_takedowns("subreddit/aww", &mut cache, &posts).is_empty());
+}
@@ -0,0 +1,27 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::TransferStatus;
+
+#[test]
+fn it_tracks_active_downloads_and_queue_depth() {
+    let mut status = TransferStatus::default();
+    status.set_queue_depth("user/spez", 2);
+    status.set_queue_depth("subreddit/redpandas", 1);
+
+    status.start_download();
+    status.start_download();
+    let snapshot = status.snapshot();
+    assert_eq!(snapshot.active_downloads, 2);
+    assert_eq!(snapshot.files_queued, 3);
+    assert_eq!(snapshot.eta_seconds, None);
+
+    status.finish_download("user/spez", 1024.0);
+    status.finish_download("subreddit/redpandas", 1024.0);
+    let snapshot = status.snapshot();
+    assert_eq!(snapshot.active_downloads, 0);
+    assert_eq!(snapshot.bytes_downloaded, 2048.0);
+    assert_eq!(snapshot.files_queued, 1);
+    assert!(snapshot.queue_depth.contains_key("user/spez"));
+    assert!(!snapshot.queue_depth.contains_key("subreddit/redpandas"));
+    assert!(snapshot.bytes_per_second > 0.0);
+    assert!(snapshot.eta_seconds.is_some());
+}
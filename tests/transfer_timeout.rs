@@ -0,0 +1,87 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    write_http_response, TransferTimeout, TransferTimeoutError, TransferTimeouts, STALL_RETRIES,
+};
+use reqwest_middleware::ClientBuilder;
+use std::{
+    env, fs,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{io::AsyncWriteExt, net::TcpListener, time::Instant};
+
+#[test]
+fn it_picks_the_earlier_chunk_deadline() {
+    let started = Instant::now();
+    let timeouts = TransferTimeouts {
+        download: Some(Duration::from_secs(300)),
+        stall: Duration::from_secs(60),
+    };
+
+    assert_eq!(
+        timeouts.chunk_deadline(started, started),
+        (started + Duration::from_secs(60), TransferTimeout::Stalled)
+    );
+    // Close to the end of the download its timeout comes first
+    let now = started + Duration::from_secs(280);
+    assert_eq!(
+        timeouts.chunk_deadline(started, now),
+        (
+            started + Duration::from_secs(300),
+            TransferTimeout::TimedOut
+        )
+    );
+
+    let unlimited = TransferTimeouts::default();
+    assert_eq!(
+        unlimited.chunk_deadline(started, now).1,
+        TransferTimeout::Stalled
+    );
+}
+
+#[tokio::test]
+async fn it_requests_stalled_transfers_again() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/video.mp4", listener.local_addr()?);
+    let requests = Arc::new(AtomicU32::new(0));
+
+    // Sends the first bytes of the body and then nothing, like a dead connection
+    let server_requests = requests.clone();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((mut socket, _)) = listener.accept().await {
+            server_requests.fetch_add(1, Ordering::SeqCst);
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc")
+                .await;
+            connections.push(socket);
+        }
+    });
+
+    let folder = env::temp_dir().join(format!("clawler_stalled_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let file_path = folder.join("video.mp4").to_string_lossy().to_string();
+
+    let client = ClientBuilder::new(reqwest::Client::new()).build();
+    let timeouts = TransferTimeouts {
+        download: None,
+        stall: Duration::from_millis(200),
+    };
+    let response = client.get(&url).send().await?;
+    let written = write_http_response(&client, response, &file_path, None, None, timeouts).await;
+
+    let e = written.expect_err("Expected the stalled transfer to fail");
+    assert!(matches!(
+        e.downcast_ref(),
+        Some(TransferTimeoutError::Stalled { .. })
+    ));
+    assert_eq!(requests.load(Ordering::SeqCst), 1 + STALL_RETRIES);
+    // The received bytes are kept to resume from
+    assert_eq!(fs::read(format!("{}.part", file_path))?, b"abc");
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}
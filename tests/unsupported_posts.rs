@@ -0,0 +1,77 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::submitted_response::RedditSubmittedResponse,
+    reddit_parser::RedditPostParser,
+    utils::{get_unsupported_report, write_unsupported_report, UnsupportedPostKind},
+};
+use std::{env, error::Error, fs};
+
+fn read_responses(mock: &str) -> Result<Vec<RedditSubmittedResponse>, Box<dyn Error>> {
+    let data = fs::read_to_string(format!("./tests/mocks/reddit/submitted_response/{}", mock))?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+#[test]
+fn it_groups_unsupported_posts_by_domain() -> Result<(), Box<dyn Error>> {
+    let mut responses = read_responses("unsupported_posts.json")?;
+    // Parsed posts are not reported
+    responses.extend(read_responses("reddit_image.json")?);
+
+    let report = get_unsupported_report(&responses, &RedditPostParser::default());
+    // The text post has no media to miss
+    assert_eq!(report.total, 4);
+    assert_eq!(
+        report
+            .domains
+            .iter()
+            .map(|d| d.domain.as_str())
+            .collect::<Vec<_>>(),
+        vec!["example.com", "news.example.org", "self.AskReddit"]
+    );
+    assert_eq!(
+        report.summary(),
+        "example.com (2), news.example.org (1), self.AskReddit (1)"
+    );
+
+    let poll = &report.domains[2].posts[0];
+    assert_eq!(poll.id, "18e0a01");
+    assert_eq!(poll.kind, UnsupportedPostKind::Poll);
+
+    let link = &report.domains[0].posts[0];
+    assert_eq!(link.kind, UnsupportedPostKind::Link);
+    assert_eq!(link.post_hint.as_deref(), Some("link"));
+    assert_eq!(
+        link.permalink,
+        "https://www.reddit.com/r/aww/comments/18e0a02/why_cats_sleep_so_mu/"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_and_clears_unsupported_json() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("clawler_unsupported_{}", std::process::id()));
+    fs::create_dir_all(&folder)?;
+    let folder_path = folder.to_string_lossy().to_string();
+    let report_path = folder.join("unsupported.json");
+
+    let responses = read_responses("unsupported_posts.json")?;
+    let report = get_unsupported_report(&responses, &RedditPostParser::default());
+    write_unsupported_report(&folder_path, &report)?;
+
+    let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report_path)?)?;
+    assert_eq!(value["total"], 4);
+    assert_eq!(value["domains"][0]["domain"], "example.com");
+    assert_eq!(value["domains"][2]["posts"][0]["kind"], "poll");
+
+    // Nothing is missing anymore
+    let report = get_unsupported_report(
+        &read_responses("reddit_image.json")?,
+        &RedditPostParser::default(),
+    );
+    write_unsupported_report(&folder_path, &report)?;
+    assert!(!report_path.exists());
+
+    fs::remove_dir_all(folder)?;
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{UserAgentError, UserAgentPool, DEFAULT_USER_AGENT};
+use std::{env, error::Error, fs};
+
+#[test]
+fn it_rotates_user_agents_from_file() -> Result<(), Box<dyn Error>> {
+    let path = env::temp_dir().join(format!(
+        "reddit_clawler_user_agents_{}.txt",
+        std::process::id()
+    ));
+    fs::write(&path, "# Desktop\nMOCK_AGENT_1\n\n  MOCK_AGENT_2  \n")?;
+
+    let mut pool = UserAgentPool::from_file(&path.to_string_lossy())?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(pool.current(), "MOCK_AGENT_1");
+    assert!(pool.rotate());
+    assert_eq!(pool.current(), "MOCK_AGENT_2");
+    assert!(pool.rotate());
+    assert_eq!(pool.current(), "MOCK_AGENT_1");
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_a_single_user_agent() -> Result<(), Box<dyn Error>> {
+    let mut pool = UserAgentPool::new(Vec::new())?;
+    assert_eq!(pool.current(), DEFAULT_USER_AGENT);
+    assert!(!pool.rotate());
+    assert_eq!(pool.current(), DEFAULT_USER_AGENT);
+
+    assert!(matches!(
+        UserAgentPool::new(vec![String::from("MOCK\nAGENT")]),
+        Err(UserAgentError::Invalid(_))
+    ));
+
+    Ok(())
+}
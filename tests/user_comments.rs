@@ -0,0 +1,97 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::{
+    clients::api_types::reddit::user_comments::RedditUserCommentsResponse,
+    utils::{
+        merge_user_comments, read_user_comments, render_user_comments_markdown,
+        render_user_comments_ndjson, UserComment,
+    },
+};
+use std::{env, error::Error, fs};
+
+fn get_mock_comments() -> Result<Vec<UserComment>, Box<dyn Error>> {
+    let data =
+        fs::read_to_string("./tests/mocks/reddit/user_comments_response/user_comments.json")?;
+    let responses: Vec<RedditUserCommentsResponse> = serde_json::from_str(&data)?;
+
+    Ok(responses
+        .into_iter()
+        .flat_map(|r| r.data.children.into_iter().map(|c| c.data))
+        .map(UserComment::from)
+        .collect())
+}
+
+#[test]
+fn it_parses_user_comments() -> Result<(), Box<dyn Error>> {
+    let comments = get_mock_comments()?;
+
+    assert_eq!(comments.len(), 2);
+    assert_eq!(
+        comments[0],
+        UserComment {
+            id: String::from("kc8x2mf"),
+            subreddit: String::from("redpandas"),
+            score: 42,
+            permalink: String::from(
+                "https://www.reddit.com/r/redpandas/comments/18dxopo/look_at_him/kc8x2mf/"
+            ),
+            created_utc: 1702108921,
+            link_title: Some(String::from("Look at him")),
+            body: String::from("Red pandas are the best pandas.\n\nFight me."),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_merges_user_comments() -> Result<(), Box<dyn Error>> {
+    let comments = get_mock_comments()?;
+    let saved = vec![
+        UserComment {
+            score: 1,
+            ..comments[0].clone()
+        },
+        UserComment {
+            id: String::from("older"),
+            created_utc: 1600000000,
+            ..comments[1].clone()
+        },
+    ];
+
+    let merged = merge_user_comments(saved, comments);
+    let ids = merged.iter().map(|c| c.id.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(ids, vec!["kc8x2mf", "kc1a9zq", "older"]);
+    // The fetched score replaces the saved one
+    assert_eq!(merged[0].score, 42);
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_and_reads_ndjson() -> Result<(), Box<dyn Error>> {
+    let comments = get_mock_comments()?;
+    let file_path = env::temp_dir().join(format!("user_comments_{}.ndjson", std::process::id()));
+
+    let ndjson = render_user_comments_ndjson(&comments)?;
+    assert_eq!(ndjson.lines().count(), 2);
+
+    fs::write(&file_path, ndjson)?;
+    assert_eq!(read_user_comments(&file_path.to_string_lossy())?, comments);
+    fs::remove_file(&file_path)?;
+
+    assert_eq!(read_user_comments(&file_path.to_string_lossy())?, vec![]);
+
+    Ok(())
+}
+
+#[test]
+fn it_renders_user_comments_markdown() -> Result<(), Box<dyn Error>> {
+    let markdown = render_user_comments_markdown("spez", &get_mock_comments()?);
+
+    assert!(markdown.starts_with("# Comments of u/spez\n\n## r/redpandas - Look at him\n\n"));
+    assert!(markdown.contains("42 points - 2023-12-09 08:02 UTC - [permalink](https://www.reddit.com/r/redpandas/comments/18dxopo/look_at_him/kc8x2mf/)"));
+    assert!(markdown.contains("> Red pandas are the best pandas.\n>\n> Fight me.\n"));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{VideoCodec, VideoContainer, VideoProcessing};
+
+#[test]
+fn it_only_processes_videos() {
+    let remux = VideoProcessing {
+        remux: Some(VideoContainer::Mp4),
+        transcode: None,
+    };
+    assert!(remux.processes("1700000000_cats_18f0b01_2023-11-14.webm"));
+    assert!(remux.processes("1700000000_cats_18f0b01_2023-11-14.MKV"));
+    // Already in the container
+    assert!(!remux.processes("1700000000_cats_18f0b01_2023-11-14.mp4"));
+    assert!(!remux.processes("1700000000_cats_18f0b01_2023-11-14.jpg"));
+
+    let transcode = VideoProcessing {
+        remux: None,
+        transcode: Some(VideoCodec::H264),
+    };
+    assert!(transcode.processes("1700000000_cats_18f0b01_2023-11-14.mp4"));
+    assert!(!transcode.processes("1700000000_cats_18f0b01_2023-11-14.gif"));
+
+    assert!(!VideoProcessing::default().processes("1700000000_cats_18f0b01_2023-11-14.webm"));
+}
+
+#[test]
+fn it_gets_the_ffmpeg_args() {
+    let remux = VideoProcessing {
+        remux: Some(VideoContainer::Mkv),
+        transcode: None,
+    };
+    assert_eq!(
+        remux.get_args("in.webm", "out.part.mkv"),
+        vec![
+            "-y",
+            "-loglevel",
+            "error",
+            "-i",
+            "in.webm",
+            "-c",
+            "copy",
+            "out.part.mkv"
+        ]
+    );
+
+    let transcode = VideoProcessing {
+        remux: None,
+        transcode: Some(VideoCodec::H264),
+    };
+    assert_eq!(
+        transcode.get_args("in.webm", "out.part.mp4"),
+        vec![
+            "-y",
+            "-loglevel",
+            "error",
+            "-i",
+            "in.webm",
+            "-c:v",
+            "libx264",
+            "-crf",
+            "23",
+            "-preset",
+            "medium",
+            "-pix_fmt",
+            "yuv420p",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "160k",
+            "-movflags",
+            "+faststart",
+            "out.part.mp4"
+        ]
+    );
+}
@@ -0,0 +1,54 @@
+#![cfg(unix)]
+use pretty_assertions::assert_eq;
+use reddit_clawler::utils::{
+    rebuild_views,
+    state::{find_cache_files, FileCacheItemLatest, FileCacheLatest},
+};
+use std::{env, error::Error, fs};
+
+#[test]
+fn it_rebuilds_views_from_cache() -> Result<(), Box<dyn Error>> {
+    let folder = env::temp_dir().join(format!("reddit_clawler_views_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join("1_MOCK_AUTHOR_MOCK_ID_2023-12-09.jpg"), b"mock")?;
+
+    let created_utc = "2023-12-09T20:26:26Z".parse()?;
+    let cache = FileCacheLatest {
+        files: vec![
+            FileCacheItemLatest {
+                id: String::from("MOCK_ID"),
+                created_utc,
+                subreddit: String::from("aww"),
+                success: true,
+                file_name: Some(String::from("1_MOCK_AUTHOR_MOCK_ID_2023-12-09.jpg")),
+                ..FileCacheItemLatest::default()
+            },
+            // Files missing on disk are not linked
+            FileCacheItemLatest {
+                id: String::from("MISSING_ID"),
+                created_utc,
+                subreddit: String::from("aww"),
+                success: true,
+                file_name: Some(String::from("missing.jpg")),
+                ..FileCacheItemLatest::default()
+            },
+        ],
+        ..FileCacheLatest::default()
+    };
+    fs::write(folder.join("cache.json"), serde_json::to_string(&cache)?)?;
+
+    assert_eq!(rebuild_views(&folder, &cache)?, 1);
+    // Running it again regenerates the same links
+    assert_eq!(rebuild_views(&folder, &cache)?, 1);
+
+    let by_subreddit = folder.join("views/by-subreddit/aww/1_MOCK_AUTHOR_MOCK_ID_2023-12-09.jpg");
+    let by_date = folder.join("views/by-date/2023/12/1_MOCK_AUTHOR_MOCK_ID_2023-12-09.jpg");
+    assert_eq!(fs::read(by_subreddit)?, b"mock");
+    assert_eq!(fs::read(by_date)?, b"mock");
+
+    assert_eq!(find_cache_files(&folder)?, vec![folder.join("cache.json")]);
+
+    fs::remove_dir_all(&folder)?;
+    Ok(())
+}